@@ -254,6 +254,16 @@ pub async fn main() {
                                     let _ = autostart_manager.disable();
                                 }
                             }
+
+                            {
+                                use tauri_plugin_listener::ListenerPluginExt;
+                                if let Err(e) = app_clone
+                                    .set_pre_roll_seconds(config.general.pre_roll_seconds)
+                                    .await
+                                {
+                                    tracing::error!("failed_to_start_pre_roll: {}", e);
+                                }
+                            }
                         }
 
                         tauri_plugin_sentry::sentry::configure_scope(|scope| {
@@ -32,6 +32,8 @@ import { TextSelectionPopover } from "./text-selection-popover";
 import { prepareContextText } from "./utils/summary-prepare";
 
 const TIPS_MODAL_SHOWN_KEY = "hypr-tips-modal-shown-v1";
+// Leaves room in the local model's 16k input window for the rest of the prompt.
+const LOCAL_TRANSCRIPT_TOKEN_BUDGET = 10000;
 
 async function shouldShowTipsModal(
   userId: string,
@@ -71,9 +73,12 @@ async function generateTitleDirect(
   sessions: Record<string, any>,
   queryClient: QueryClient,
 ) {
-  const title = await localLlmCommands.generateTitle({
-    enhanced_note: enhancedContent,
-  });
+  const config = await dbCommands.getConfig();
+  const title = await localLlmCommands.generateTitle(
+    { enhanced_note: enhancedContent },
+    config.ai?.title_max_words ?? null,
+    config.ai?.title_sentence_case ?? false,
+  );
 
   const session = await dbCommands.getSession({ id: targetSessionId });
   if (!session?.title && sessions[targetSessionId]?.getState) {
@@ -550,6 +555,11 @@ export function useEnhanceMutation({
           type,
           editor: finalInput,
           words: JSON.stringify(words),
+          redactPii: config.ai?.redact_pii ?? false,
+          ...(config.ai?.strip_filler_words
+            ? { fillerWords: { mode: "strip", language: config.general?.spoken_languages?.[0] ?? "en" } }
+            : {}),
+          ...(type === "HyprLocal" ? { transcriptTokenBudget: LOCAL_TRANSCRIPT_TOKEN_BUDGET } : {}),
           participants,
           ...((contextText !== "" || contextText !== undefined || contextText !== null) ? { contextText } : {}),
         },
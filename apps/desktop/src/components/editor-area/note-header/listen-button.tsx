@@ -434,7 +434,7 @@ function MicrophoneSelector({
   });
 
   const handleSelectDevice = (device: string) => {
-    listenerCommands.setMicrophoneDevice(device).then(() => {
+    listenerCommands.setMicrophoneDevice(device, true).then(() => {
       currentDeviceQuery.refetch();
 
       if (userId) {
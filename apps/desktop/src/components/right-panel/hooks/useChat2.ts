@@ -8,6 +8,7 @@ interface UseChat2Props {
   sessionId: string | null;
   userId: string | null;
   conversationId: string | null;
+  systemPrompt?: string | null;
   sessionData?: any;
   selectionData?: any;
   sessions?: any;
@@ -18,6 +19,7 @@ export function useChat2({
   sessionId,
   userId,
   conversationId,
+  systemPrompt,
   sessionData,
   selectionData,
   sessions,
@@ -35,6 +37,7 @@ export function useChat2({
     transportRef.current = new CustomChatTransport({
       sessionId,
       userId,
+      systemPrompt,
       sessionData,
       selectionData,
       sessions,
@@ -47,13 +50,14 @@ export function useChat2({
       transportRef.current.updateOptions({
         sessionId,
         userId,
+        systemPrompt,
         sessionData,
         selectionData,
         sessions,
         getLicense: getLicense as any,
       });
     }
-  }, [sessionId, userId, sessionData, selectionData, sessions, getLicense]);
+  }, [sessionId, userId, systemPrompt, sessionData, selectionData, sessions, getLicense]);
 
   useEffect(() => {
     return () => {
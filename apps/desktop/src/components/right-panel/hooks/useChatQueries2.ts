@@ -146,6 +146,7 @@ export function useChatQueries2({
       name: null,
       created_at: new Date().toISOString(),
       updated_at: new Date().toISOString(),
+      system_prompt: null,
     });
 
     setCurrentConversationId(conversation.id);
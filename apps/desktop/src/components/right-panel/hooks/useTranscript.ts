@@ -1,7 +1,7 @@
-import { useEffect, useMemo, useState } from "react";
+import { useEffect, useMemo, useRef, useState } from "react";
 
 import { commands as dbCommands } from "@hypr/plugin-db";
-import { events as listenerEvents, type Word2 as Word } from "@hypr/plugin-listener";
+import { events as listenerEvents, type Word2 as Word, type WordOp } from "@hypr/plugin-listener";
 import { useOngoingSession, useSession } from "@hypr/utils/contexts";
 import { useQuery } from "@tanstack/react-query";
 
@@ -22,6 +22,8 @@ export function useTranscript(sessionId: string | null) {
 
   const [finalWords, setFinalWords] = useState<Word[]>([]);
   const [partialWords, setPartialWords] = useState<Word[]>([]);
+  // Partials by channel, which `wordsPatch` ops index into.
+  const partialChannels = useRef<Record<number, Word[]>>({});
   const [selectedLanguage, setSelectedLanguage] = useState<string>("en");
 
   const words = useMemo(() => {
@@ -48,6 +50,7 @@ export function useTranscript(sessionId: string | null) {
   useEffect(() => {
     setFinalWords(existingWords.data ?? []);
     setPartialWords([]);
+    partialChannels.current = {};
   }, [existingWords.data]);
 
   useEffect(() => {
@@ -64,6 +67,17 @@ export function useTranscript(sessionId: string | null) {
       } else if (payload.type === "partialWords") {
         const words = Object.values(payload.words).flat().filter((v) => !!v);
         setPartialWords(words);
+      } else if (payload.type === "wordsPatch") {
+        // Finalized words only ever get appended.
+        const added = payload.final_ops.flatMap((op) => op.op === "add" ? [op.word] : []);
+        if (added.length > 0) {
+          setFinalWords((existing) => [...existing, ...added]);
+        }
+
+        if (payload.partial_ops.length > 0) {
+          partialChannels.current = applyWordOps(partialChannels.current, payload.partial_ops);
+          setPartialWords(Object.values(partialChannels.current).flat());
+        }
       }
     }).then((fn) => {
       unlisten = fn;
@@ -89,3 +103,28 @@ export function useTranscript(sessionId: string | null) {
     handleLanguageChange,
   };
 }
+
+// Ops come in the order they were computed, so each index refers to the list as left by the
+// ones before it.
+function applyWordOps(channels: Record<number, Word[]>, ops: WordOp[]): Record<number, Word[]> {
+  const next = { ...channels };
+  const copied = new Set<number>();
+
+  for (const op of ops) {
+    if (!copied.has(op.channel)) {
+      next[op.channel] = [...(next[op.channel] ?? [])];
+      copied.add(op.channel);
+    }
+    const words = next[op.channel];
+
+    if (op.op === "add") {
+      words.splice(op.index, 0, op.word);
+    } else if (op.op === "replace") {
+      words[op.index] = op.word;
+    } else {
+      words.splice(op.index, 1);
+    }
+  }
+
+  return next;
+}
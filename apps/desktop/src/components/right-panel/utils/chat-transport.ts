@@ -28,6 +28,7 @@ import { createSearchSessionTool } from "./tools/search_session_multi_keywords";
 interface CustomChatTransportOptions {
   sessionId: string | null;
   userId: string | null;
+  systemPrompt?: string | null;
   sessionData?: any;
   selectionData?: any;
   sessions?: any;
@@ -203,6 +204,7 @@ export class CustomChatTransport implements ChatTransport<UIMessage> {
         sessionData: this.options.sessionData,
         selectionData: this.options.selectionData,
         mentionedContent: this.options.mentionedContent,
+        systemPrompt: this.options.systemPrompt,
       });
 
       const result = streamText({
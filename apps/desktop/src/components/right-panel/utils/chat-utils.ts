@@ -87,9 +87,10 @@ export const prepareMessagesForAI = async (
     sessionData?: any;
     selectionData?: SelectionData;
     mentionedContent?: Array<{ id: string; type: string; label: string }>;
+    systemPrompt?: string | null;
   },
 ) => {
-  const { sessionId, userId, sessionData, selectionData, mentionedContent } = options;
+  const { sessionId, userId, sessionData, selectionData, mentionedContent, systemPrompt } = options;
 
   // sessionData is already the data object from the query, not the query itself
   // It doesn't have a refetch method - it's just the plain data
@@ -127,6 +128,7 @@ export const prepareMessagesForAI = async (
   const calendarEvent = sessionId
     ? await dbCommands.sessionGetEvent(sessionId)
     : null;
+  const config = await dbCommands.getConfig();
 
   // Format current date/time
   const currentDateTime = new Date().toLocaleString("en-US", {
@@ -170,6 +172,10 @@ export const prepareMessagesForAI = async (
   const systemContent = await templateCommands.render("chat.system", {
     session: freshSessionData,
     words: JSON.stringify(freshSessionData?.words || []),
+    redactPii: config.ai?.redact_pii ?? false,
+    ...(config.ai?.strip_filler_words
+      ? { fillerWords: { mode: "strip", language: config.general?.spoken_languages?.[0] ?? "en" } }
+      : {}),
     title: freshSessionData?.title,
     enhancedContent: freshSessionData?.enhancedContent,
     rawContent: freshSessionData?.rawContent,
@@ -180,6 +186,7 @@ export const prepareMessagesForAI = async (
     event: eventInfo,
     toolEnabled: toolEnabled,
     mcpTools: mcpToolsArray,
+    systemPrompt: systemPrompt,
   });
 
   // Clean UIMessages to remove problematic tool states before conversion
@@ -67,6 +67,7 @@ export function ChatView() {
     sessionId,
     userId,
     conversationId: currentConversationId,
+    systemPrompt: conversations.find((c) => c.id === currentConversationId)?.system_prompt ?? null,
     sessionData: sessionData,
     selectionData: pendingSelection,
     onError: (err: Error) => {
@@ -141,6 +141,13 @@ export default function General() {
         save_recordings: v.saveRecordings ?? true,
         selected_template_id: config.data.general.selected_template_id,
         summary_language: v.summaryLanguage,
+        recordings_dir: config.data.general.recordings_dir,
+        max_recording_seconds: config.data.general.max_recording_seconds,
+        transcript_sink_path: config.data.general.transcript_sink_path,
+        pre_roll_seconds: config.data.general.pre_roll_seconds,
+        incremental_word_events: config.data.general.incremental_word_events,
+        min_free_disk_mb: config.data.general.min_free_disk_mb,
+        restore_punctuation_languages: config.data.general.restore_punctuation_languages,
       };
 
       await dbCommands.setConfig({
@@ -10,6 +10,11 @@ const extractHashtags = (text: string): string[] => {
   return Array.from(text.matchAll(hashtagRegex), match => match[1]);
 };
 
+const tagsSchema = z.preprocess(
+  (val) => (typeof val === "string" ? JSON.parse(val) : val),
+  z.array(z.object({ name: z.string().min(1), confidence: z.number().min(0).max(1) })).min(1).max(4),
+);
+
 export async function generateTagsForSession(sessionId: string): Promise<string[]> {
   try {
     const { type: connectionType } = await connectorCommands.getLlmConnection();
@@ -63,13 +68,8 @@ export async function generateTagsForSession(sessionId: string): Promise<string[
       },
     });
 
-    const schema = z.preprocess(
-      (val) => (typeof val === "string" ? JSON.parse(val) : val),
-      z.array(z.string().min(1)).min(1).max(5),
-    );
-
-    const parsed = schema.safeParse(result.text);
-    return parsed.success ? parsed.data : [];
+    const parsed = tagsSchema.safeParse(result.text);
+    return parsed.success ? parsed.data.map(tag => tag.name) : [];
   } catch (error) {
     console.error("Tag generation failed:", error);
     return [];
@@ -129,13 +129,8 @@ export async function autoTagGeneration(sessionId: string): Promise<string[]> {
       },
     });
 
-    const schema = z.preprocess(
-      (val) => (typeof val === "string" ? JSON.parse(val) : val),
-      z.array(z.string().min(1)).min(1).max(5),
-    );
-
-    const parsed = schema.safeParse(result.text);
-    return parsed.success ? parsed.data : [];
+    const parsed = tagsSchema.safeParse(result.text);
+    return parsed.success ? parsed.data.map(tag => tag.name) : [];
   } catch (error) {
     console.error("Tag generation failed:", error);
     return [];
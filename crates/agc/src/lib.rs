@@ -2,27 +2,72 @@ use std::ops::{Deref, DerefMut};
 
 use dagc::MonoAgc;
 
+// Seeds outside this range are more likely a bad save than a real device level.
+const MIN_SEED_GAIN: f32 = 0.1;
+const MAX_SEED_GAIN: f32 = 10.0;
+// Blocks quieter than this say nothing about the device's level, so the gain estimate skips them.
+const SILENCE_RMS: f32 = 0.0001;
+// How far each block moves the gain estimate.
+const GAIN_SMOOTHING: f32 = 0.05;
+
 #[derive(Debug)]
 pub struct Agc {
     agc: MonoAgc,
+    // Applied ahead of `agc`, so a seeded instance starts close to where the last one settled.
+    seed_gain: f32,
+    gain: Option<f32>,
 }
 
 impl Agc {
     pub fn new(desired_output_rms: f32, distortion_factor: f32) -> Self {
         Self {
             agc: MonoAgc::new(desired_output_rms, distortion_factor).expect("failed_to_create_agc"),
+            seed_gain: 1.0,
+            gain: None,
+        }
+    }
+
+    /// Starts from a gain previously read off `gain()`, instead of from unity.
+    pub fn with_seed(mut self, gain: f32) -> Self {
+        if gain.is_finite() {
+            self.seed_gain = gain.clamp(MIN_SEED_GAIN, MAX_SEED_GAIN);
+        }
+        self
+    }
+
+    /// The overall gain being applied, for saving and seeding the next instance with.
+    pub fn gain(&self) -> f32 {
+        self.gain.unwrap_or(self.seed_gain)
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let input_rms = rms(samples);
+
+        for sample in samples.iter_mut() {
+            *sample *= self.seed_gain;
+        }
+        self.agc.process(samples);
+
+        if input_rms > SILENCE_RMS {
+            let block_gain = rms(samples) / input_rms;
+            self.gain = Some(match self.gain {
+                Some(gain) => gain + (block_gain - gain) * GAIN_SMOOTHING,
+                None => block_gain,
+            });
         }
     }
 }
 
 impl Default for Agc {
     fn default() -> Self {
-        Self {
-            agc: MonoAgc::new(0.1, 0.000001).expect("failed_to_create_agc"),
-        }
+        Self::new(0.1, 0.000001)
     }
 }
 
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+}
+
 impl Deref for Agc {
     type Target = MonoAgc;
 
@@ -95,4 +140,48 @@ mod tests {
             output_audio.finalize().unwrap();
         }
     }
+
+    #[test]
+    fn test_seeded_agc_settles_faster() {
+        // A quiet 440Hz tone, well under the target level.
+        let tone = (0..16000 * 20)
+            .map(|i| 0.02 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin())
+            .collect::<Vec<_>>();
+
+        let output_levels = |agc: &mut Agc| {
+            tone.chunks(512)
+                .map(|chunk| {
+                    let mut target = chunk.to_vec();
+                    agc.process(&mut target);
+                    rms(&target)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut fresh = Agc::new(0.1, 0.0001);
+        let fresh_levels = output_levels(&mut fresh);
+        let steady = *fresh_levels.last().unwrap();
+
+        let mut seeded = Agc::new(0.1, 0.0001).with_seed(fresh.gain());
+        let seeded_levels = output_levels(&mut seeded);
+
+        let blocks_to_settle = |levels: &[f32]| {
+            levels
+                .iter()
+                .position(|level| (level - steady).abs() < steady * 0.1)
+                .unwrap_or(levels.len())
+        };
+        let (fresh_blocks, seeded_blocks) = (
+            blocks_to_settle(&fresh_levels),
+            blocks_to_settle(&seeded_levels),
+        );
+        assert!(
+            seeded_blocks < fresh_blocks,
+            "{seeded_blocks} vs {fresh_blocks}"
+        );
+
+        // Out-of-range seeds are clamped rather than blowing the level up.
+        assert_eq!(Agc::default().with_seed(1000.0).gain(), MAX_SEED_GAIN);
+        assert_eq!(Agc::default().with_seed(f32::NAN).gain(), 1.0);
+    }
 }
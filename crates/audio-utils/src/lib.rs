@@ -5,6 +5,9 @@ use kalosm_sound::AsyncSource;
 mod error;
 pub use error::*;
 
+mod opus_encoder;
+pub use opus_encoder::*;
+
 const I16_SCALE: f32 = 32768.0;
 
 impl<T: AsyncSource> AudioFormatExt for T {}
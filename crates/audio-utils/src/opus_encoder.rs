@@ -0,0 +1,127 @@
+use bytes::Bytes;
+
+pub const OPUS_SAMPLE_RATE: u32 = 16000;
+// 20ms, the frame size Opus is tuned for.
+const OPUS_FRAME_SAMPLES: usize = OPUS_SAMPLE_RATE as usize / 50;
+const OPUS_MAX_PACKET_BYTES: usize = 4000;
+
+// Encodes interleaved 16kHz PCM into Opus packets.
+//
+// Opus only takes whole frames, so samples are held back until a frame is complete.
+pub struct OpusEncoder {
+    encoder: opus::Encoder,
+    channels: usize,
+    pending: Vec<i16>,
+}
+
+impl OpusEncoder {
+    pub fn new(channels: u8) -> Result<Self, crate::Error> {
+        let (opus_channels, channels) = match channels {
+            1 => (opus::Channels::Mono, 1),
+            _ => (opus::Channels::Stereo, 2),
+        };
+
+        let encoder = opus::Encoder::new(OPUS_SAMPLE_RATE, opus_channels, opus::Application::Voip)?;
+
+        Ok(Self {
+            encoder,
+            channels,
+            pending: Vec::with_capacity(OPUS_FRAME_SAMPLES * channels),
+        })
+    }
+
+    // Returns one packet per complete frame, which may be none.
+    pub fn encode(&mut self, samples: &[i16]) -> Result<Vec<Bytes>, crate::Error> {
+        self.pending.extend_from_slice(samples);
+
+        let frame_len = OPUS_FRAME_SAMPLES * self.channels;
+        let mut packets = Vec::with_capacity(self.pending.len() / frame_len);
+
+        let mut frames = self.pending.chunks_exact(frame_len);
+        for frame in &mut frames {
+            let packet = self.encoder.encode_vec(frame, OPUS_MAX_PACKET_BYTES)?;
+            packets.push(packet.into());
+        }
+
+        let remainder = frames.remainder().len();
+        let consumed = self.pending.len() - remainder;
+        self.pending.drain(..consumed);
+
+        Ok(packets)
+    }
+
+    // Pads whatever is left of the last frame with silence and encodes it, so the tail of the
+    // stream isn't lost.
+    pub fn finish(&mut self) -> Result<Option<Bytes>, crate::Error> {
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+
+        self.pending.resize(OPUS_FRAME_SAMPLES * self.channels, 0);
+        let packet = self.encoder.encode_vec(&self.pending, OPUS_MAX_PACKET_BYTES)?;
+        self.pending.clear();
+
+        Ok(Some(packet.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opus_roundtrip() {
+        let samples = (0..OPUS_SAMPLE_RATE as usize)
+            .map(|i| {
+                let t = i as f32 / OPUS_SAMPLE_RATE as f32;
+                ((t * 440.0 * 2.0 * std::f32::consts::PI).sin() * 0.5 * i16::MAX as f32) as i16
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = OpusEncoder::new(1).unwrap();
+        let mut packets = vec![];
+        for chunk in samples.chunks(512) {
+            packets.extend(encoder.encode(chunk).unwrap());
+        }
+        assert_eq!(packets.len(), samples.len() / OPUS_FRAME_SAMPLES);
+
+        let mut decoder = opus::Decoder::new(OPUS_SAMPLE_RATE, opus::Channels::Mono).unwrap();
+        let mut decoded = vec![];
+        for packet in &packets {
+            let mut frame = vec![0i16; OPUS_FRAME_SAMPLES];
+            let n = decoder.decode(packet, &mut frame, false).unwrap();
+            decoded.extend_from_slice(&frame[..n]);
+        }
+        assert_eq!(decoded.len(), samples.len());
+
+        // Opus is lossy and delays its output a little, so compare energy rather than samples.
+        let rms = |s: &[i16]| {
+            (s.iter().map(|&x| (x as f64).powi(2)).sum::<f64>() / s.len() as f64).sqrt()
+        };
+        let (original, reconstructed) = (rms(&samples[1600..]), rms(&decoded[1600..]));
+        assert!((original - reconstructed).abs() / original < 0.1);
+    }
+
+    #[test]
+    fn test_opus_finish_encodes_partial_frame() {
+        // Two and a half frames.
+        let samples = vec![1000i16; OPUS_FRAME_SAMPLES * 5 / 2];
+
+        let mut encoder = OpusEncoder::new(1).unwrap();
+        let mut packets = encoder.encode(&samples).unwrap();
+        assert_eq!(packets.len(), 2);
+
+        packets.extend(encoder.finish().unwrap());
+        assert_eq!(packets.len(), 3);
+        assert!(encoder.finish().unwrap().is_none());
+
+        let mut decoder = opus::Decoder::new(OPUS_SAMPLE_RATE, opus::Channels::Mono).unwrap();
+        let mut decoded = 0;
+        for packet in &packets {
+            let mut frame = vec![0i16; OPUS_FRAME_SAMPLES];
+            decoded += decoder.decode(packet, &mut frame, false).unwrap();
+        }
+        assert_eq!(decoded, OPUS_FRAME_SAMPLES * 3);
+        assert!(decoded >= samples.len());
+    }
+}
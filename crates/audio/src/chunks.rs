@@ -0,0 +1,112 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use kalosm_sound::AsyncSource;
+
+// Groups samples into blocks of exactly `size` samples.
+//
+// Unlike `StreamExt::chunks`, the last block is never shorter: when the source ends in the middle
+// of one, it is zero-padded to `size` by default, or dropped with `drop_remainder`.
+pub struct ExactChunks<S: AsyncSource> {
+    source: S,
+    size: usize,
+    pad_remainder: bool,
+    chunk: Vec<f32>,
+    ended: bool,
+}
+
+pub trait ExactChunksExt<S: AsyncSource> {
+    fn exact_chunks(self, size: usize) -> ExactChunks<S>;
+}
+
+impl<S: AsyncSource> ExactChunksExt<S> for S {
+    fn exact_chunks(self, size: usize) -> ExactChunks<S> {
+        let size = size.max(1);
+
+        ExactChunks {
+            source: self,
+            size,
+            pad_remainder: true,
+            chunk: Vec::with_capacity(size),
+            ended: false,
+        }
+    }
+}
+
+impl<S: AsyncSource> ExactChunks<S> {
+    pub fn drop_remainder(mut self) -> Self {
+        self.pad_remainder = false;
+        self
+    }
+}
+
+impl<S: AsyncSource + Unpin> Stream for ExactChunks<S> {
+    type Item = Vec<f32>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.ended {
+            return Poll::Ready(None);
+        }
+
+        let mut inner = std::pin::pin!(this.source.as_stream());
+
+        loop {
+            match inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(sample)) => {
+                    this.chunk.push(sample);
+
+                    if this.chunk.len() == this.size {
+                        let chunk =
+                            std::mem::replace(&mut this.chunk, Vec::with_capacity(this.size));
+                        return Poll::Ready(Some(chunk));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => {
+                    this.ended = true;
+
+                    if this.chunk.is_empty() || !this.pad_remainder {
+                        return Poll::Ready(None);
+                    }
+
+                    let mut chunk = std::mem::take(&mut this.chunk);
+                    chunk.resize(this.size, 0.0);
+                    return Poll::Ready(Some(chunk));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_exact_chunks() {
+        let samples = (1..=1100).map(|i| i as f32).collect::<Vec<_>>();
+
+        let source = rodio::buffer::SamplesBuffer::new(1, 16000, samples.clone());
+        let chunks = source.exact_chunks(512).collect::<Vec<_>>().await;
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.len() == 512));
+        assert_eq!(chunks.concat()[..1100], samples[..]);
+        assert!(chunks[2][1100 - 1024..].iter().all(|s| *s == 0.0));
+
+        let source = rodio::buffer::SamplesBuffer::new(1, 16000, samples.clone());
+        let chunks = source
+            .exact_chunks(512)
+            .drop_remainder()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| c.len() == 512));
+        assert_eq!(chunks.concat(), samples[..1024]);
+    }
+}
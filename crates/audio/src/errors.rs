@@ -2,4 +2,12 @@
 pub enum Error {
     #[error("no input device found")]
     NoInputDevice,
+    #[error("audio host not available: {0}")]
+    HostUnavailable(String),
+    #[error("unsupported media format")]
+    UnsupportedFormat,
+    #[error("malformed media header: {0}")]
+    MalformedHeader(&'static str),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
@@ -1,17 +1,27 @@
+mod chunks;
 mod device_monitor;
 mod errors;
 mod mic;
+mod mono;
 mod norm;
+mod probe;
+mod rate_tagged;
 mod resampler;
 mod speaker;
+mod tee;
 mod utils;
 
+pub use chunks::*;
 pub use device_monitor::*;
 pub use errors::*;
 pub use mic::*;
+pub use mono::*;
 pub use norm::*;
+pub use probe::*;
+pub use rate_tagged::*;
 pub use resampler::*;
 pub use speaker::*;
+pub use tee::*;
 pub use utils::*;
 
 pub use cpal;
@@ -23,15 +33,55 @@ pub use kalosm_sound::AsyncSource;
 pub struct AudioOutput {}
 
 impl AudioOutput {
+    pub fn list_output_devices() -> Vec<String> {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+        rodio::cpal::default_host()
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    // Falls back to the default output when the named one is gone.
+    fn open_stream(
+        device_name: Option<&str>,
+    ) -> Option<(rodio::OutputStream, rodio::OutputStreamHandle)> {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+        let device = device_name.and_then(|name| {
+            rodio::cpal::default_host()
+                .output_devices()
+                .ok()?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+        });
+
+        match device {
+            Some(device) => rodio::OutputStream::try_from_device(&device).ok(),
+            None => {
+                if let Some(name) = device_name {
+                    tracing::warn!(device = name, "output_device_not_found_using_default");
+                }
+                rodio::OutputStream::try_default().ok()
+            }
+        }
+    }
+
     pub fn to_speaker(bytes: &'static [u8]) -> std::sync::mpsc::Sender<()> {
-        use rodio::{Decoder, OutputStream, Sink};
+        Self::to_speaker_on(None, bytes)
+    }
+
+    pub fn to_speaker_on(
+        device_name: Option<String>,
+        bytes: &'static [u8],
+    ) -> std::sync::mpsc::Sender<()> {
+        use rodio::{Decoder, Sink};
         let (tx, rx) = std::sync::mpsc::channel();
 
         std::thread::spawn(move || {
-            if let Ok((_, stream)) = OutputStream::try_default() {
+            if let Some((_stream, handle)) = Self::open_stream(device_name.as_deref()) {
                 let file = std::io::Cursor::new(bytes);
                 if let Ok(source) = Decoder::new(file) {
-                    let sink = Sink::try_new(&stream).unwrap();
+                    let sink = Sink::try_new(&handle).unwrap();
                     sink.append(source);
 
                     let _ = rx.recv_timeout(std::time::Duration::from_secs(3600));
@@ -44,20 +94,24 @@ impl AudioOutput {
     }
 
     pub fn silence() -> std::sync::mpsc::Sender<()> {
+        Self::silence_on(None)
+    }
+
+    pub fn silence_on(device_name: Option<String>) -> std::sync::mpsc::Sender<()> {
         use rodio::{
             source::{Source, Zero},
-            OutputStream, Sink,
+            Sink,
         };
 
         let (tx, rx) = std::sync::mpsc::channel();
 
         std::thread::spawn(move || {
-            if let Ok((_, stream)) = OutputStream::try_default() {
+            if let Some((_stream, handle)) = Self::open_stream(device_name.as_deref()) {
                 let silence = Zero::<f32>::new(2, 48_000)
                     .take_duration(std::time::Duration::from_secs(1))
                     .repeat_infinite();
 
-                let sink = Sink::try_new(&stream).unwrap();
+                let sink = Sink::try_new(&handle).unwrap();
                 sink.append(silence);
 
                 let _ = rx.recv();
@@ -75,6 +129,21 @@ pub enum AudioSource {
     Recorded,
 }
 
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HostInfo {
+    // cpal's name for the host (e.g. "CoreAudio", "WASAPI", "ALSA", "JACK").
+    pub id: String,
+    pub is_default: bool,
+}
+
+pub(crate) fn find_host(id: &str) -> Result<cpal::Host, crate::Error> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|host_id| host_id.name() == id)
+        .and_then(|host_id| cpal::host_from_id(host_id).ok())
+        .ok_or_else(|| crate::Error::HostUnavailable(id.to_string()))
+}
+
 pub struct AudioInput {
     source: AudioSource,
     mic: Option<MicInput>,
@@ -94,8 +163,14 @@ impl AudioInput {
     }
 
     pub fn list_mic_devices() -> Vec<String> {
-        let host = cpal::default_host();
+        Self::list_mic_devices_on_host(&cpal::default_host())
+    }
 
+    pub fn list_mic_devices_of_host(host: &str) -> Result<Vec<String>, crate::Error> {
+        Ok(Self::list_mic_devices_on_host(&find_host(host)?))
+    }
+
+    fn list_mic_devices_on_host(host: &cpal::Host) -> Vec<String> {
         let devices: Vec<cpal::Device> = host
             .input_devices()
             .map(|devices| devices.collect())
@@ -108,8 +183,28 @@ impl AudioInput {
             .collect()
     }
 
+    // Hosts that are usable on this machine right now, e.g. JACK only while its server runs.
+    pub fn list_hosts() -> Vec<HostInfo> {
+        let default_id = cpal::default_host().id();
+
+        cpal::available_hosts()
+            .into_iter()
+            .map(|host_id| HostInfo {
+                id: host_id.name().to_string(),
+                is_default: host_id == default_id,
+            })
+            .collect()
+    }
+
     pub fn from_mic(device_name: Option<String>) -> Result<Self, crate::Error> {
-        let mic = MicInput::new(device_name)?;
+        Self::from_mic_on_host(device_name, None)
+    }
+
+    pub fn from_mic_on_host(
+        device_name: Option<String>,
+        host: Option<String>,
+    ) -> Result<Self, crate::Error> {
+        let mic = MicInput::new(device_name, host)?;
 
         Ok(Self {
             source: AudioSource::RealtimeMic,
@@ -119,11 +214,16 @@ impl AudioInput {
         })
     }
 
+    // Where speaker capture isn't available, the stream ends right away instead of failing.
     pub fn from_speaker() -> Self {
+        let speaker = SpeakerInput::new()
+            .inspect_err(|e| tracing::warn!("speaker_input_unavailable: {:?}", e))
+            .ok();
+
         Self {
             source: AudioSource::RealtimeSpeaker,
             mic: None,
-            speaker: Some(SpeakerInput::new().unwrap()),
+            speaker,
             data: None,
         }
     }
@@ -142,7 +242,16 @@ impl AudioInput {
                 mic: self.mic.as_ref().unwrap().stream(),
             },
             AudioSource::RealtimeSpeaker => AudioStream::RealtimeSpeaker {
-                speaker: self.speaker.take().unwrap().stream().unwrap(),
+                speaker: self
+                    .speaker
+                    .take()
+                    .and_then(|speaker| {
+                        speaker
+                            .stream()
+                            .inspect_err(|e| tracing::warn!("speaker_stream_failed: {:?}", e))
+                            .ok()
+                    })
+                    .unwrap_or_else(SpeakerStream::unsupported),
             },
             AudioSource::Recorded => AudioStream::Recorded {
                 data: self.data.as_ref().unwrap().clone(),
@@ -241,3 +350,45 @@ pub(crate) fn play_sine_for_sec(seconds: u64) -> std::thread::JoinHandle<()> {
         sleep(Duration::from_secs(seconds));
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[tokio::test]
+    async fn test_from_speaker_unsupported_ends() {
+        use futures_util::StreamExt;
+        use kalosm_sound::AsyncSource;
+
+        let mut input = AudioInput::from_speaker();
+        let mut stream = input.stream();
+        assert_eq!(stream.sample_rate(), 0);
+        assert_eq!(stream.next().await, None);
+    }
+
+    #[test]
+    fn test_silence_on_chosen_output() {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+        let default = rodio::cpal::default_host()
+            .default_output_device()
+            .and_then(|d| d.name().ok());
+        let outputs = AudioOutput::list_output_devices();
+
+        // Nothing to choose with only the default output.
+        let Some(chosen) = outputs
+            .into_iter()
+            .find(|name| Some(name) != default.as_ref())
+        else {
+            return;
+        };
+
+        assert!(AudioOutput::open_stream(Some(&chosen)).is_some());
+
+        let stop = AudioOutput::silence_on(Some(chosen));
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        // Still playing, so the stream opened.
+        stop.send(()).unwrap();
+    }
+}
@@ -5,7 +5,6 @@ use cpal::{
 use dasp::sample::ToSample;
 use futures_channel::mpsc;
 use futures_util::{Stream, StreamExt};
-use std::pin::Pin;
 
 use crate::AsyncSource;
 
@@ -31,8 +30,12 @@ impl MicInput {
             .collect()
     }
 
-    pub fn new(device_name: Option<String>) -> Result<Self, crate::Error> {
-        let host = cpal::default_host();
+    // `host` is a name from `AudioInput::list_hosts`; the platform default host when `None`.
+    pub fn new(device_name: Option<String>, host: Option<String>) -> Result<Self, crate::Error> {
+        let host = match host {
+            None => cpal::default_host(),
+            Some(name) => crate::find_host(&name)?,
+        };
 
         let default_input_device = host.default_input_device();
         let input_devices: Vec<cpal::Device> = host
@@ -67,9 +70,22 @@ impl MicInput {
     }
 }
 
+// How often the device is checked for a new format, e.g. after a Bluetooth headset switches
+// from A2DP to HFP.
+const FORMAT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+fn format_changed(
+    current: &cpal::SupportedStreamConfig,
+    latest: &cpal::SupportedStreamConfig,
+) -> bool {
+    current.sample_rate() != latest.sample_rate()
+        || current.channels() != latest.channels()
+        || current.sample_format() != latest.sample_format()
+}
+
 impl MicInput {
     pub fn stream(&self) -> MicStream {
-        let (tx, rx) = mpsc::unbounded::<Vec<f32>>();
+        let (tx, rx) = mpsc::unbounded::<(u32, Vec<f32>)>();
 
         let config = self.config.clone();
         let device = self.device.clone();
@@ -79,18 +95,20 @@ impl MicInput {
             fn build_stream<S: ToSample<f32> + SizedSample>(
                 device: &cpal::Device,
                 config: &cpal::SupportedStreamConfig,
-                mut tx: mpsc::UnboundedSender<Vec<f32>>,
+                mut tx: mpsc::UnboundedSender<(u32, Vec<f32>)>,
             ) -> Result<cpal::Stream, cpal::BuildStreamError> {
                 let channels = config.channels() as usize;
+                let sample_rate = config.sample_rate().0;
                 device.build_input_stream::<S, _, _>(
                     &config.config(),
                     move |data: &[S], _input_callback_info: &_| {
-                        let _ = tx.start_send(
+                        let _ = tx.start_send((
+                            sample_rate,
                             data.iter()
                                 .step_by(channels)
                                 .map(|&x| x.to_sample())
                                 .collect(),
-                        );
+                        ));
                     },
                     |err| {
                         tracing::error!("an error occurred on stream: {}", err);
@@ -99,12 +117,13 @@ impl MicInput {
                 )
             }
 
-            let start_stream = || {
+            let start_stream = |config: &cpal::SupportedStreamConfig| {
+                let tx = tx.clone();
                 let stream = match config.sample_format() {
-                    cpal::SampleFormat::I8 => build_stream::<i8>(&device, &config, tx),
-                    cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, tx),
-                    cpal::SampleFormat::I32 => build_stream::<i32>(&device, &config, tx),
-                    cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, tx),
+                    cpal::SampleFormat::I8 => build_stream::<i8>(&device, config, tx),
+                    cpal::SampleFormat::I16 => build_stream::<i16>(&device, config, tx),
+                    cpal::SampleFormat::I32 => build_stream::<i32>(&device, config, tx),
+                    cpal::SampleFormat::F32 => build_stream::<f32>(&device, config, tx),
                     sample_format => {
                         tracing::error!(sample_format = ?sample_format, "unsupported");
                         return None;
@@ -126,40 +145,77 @@ impl MicInput {
                 Some(stream)
             };
 
-            let stream = match start_stream() {
+            let mut config = config;
+            let mut stream = match start_stream(&config) {
                 Some(stream) => stream,
                 None => {
                     return;
                 }
             };
 
-            // Wait for the stream to be dropped
-            drop_rx.recv().unwrap();
+            // Until the stream is dropped, rebuild it whenever the device changes its format.
+            // Every chunk carries its rate, so `MicStream` reports the new one once it gets there.
+            loop {
+                if !matches!(
+                    drop_rx.recv_timeout(FORMAT_CHECK_INTERVAL),
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+                ) {
+                    break;
+                }
+
+                let Ok(latest) = device.default_input_config() else {
+                    continue;
+                };
+                if !format_changed(&config, &latest) {
+                    continue;
+                }
+
+                tracing::info!(from = ?config, to = ?latest, "mic_format_changed");
+                drop(stream);
+                config = latest;
+                stream = match start_stream(&config) {
+                    Some(stream) => stream,
+                    None => {
+                        return;
+                    }
+                };
+            }
 
-            // Then drop the stream
             drop(stream);
         });
 
-        let receiver = rx.map(futures_util::stream::iter).flatten();
-        MicStream {
-            drop_tx,
-            config: self.config.clone(),
-            receiver: Box::pin(receiver),
-            read_data: Vec::new(),
-        }
+        MicStream::new(drop_tx, rx, self.config.sample_rate().0)
     }
 }
 
 pub struct MicStream {
     drop_tx: std::sync::mpsc::Sender<()>,
-    config: cpal::SupportedStreamConfig,
+    // Rate of the chunk being read.
+    sample_rate: u32,
+    chunk: std::vec::IntoIter<f32>,
     read_data: Vec<f32>,
-    receiver: Pin<Box<dyn Stream<Item = f32> + Send + Sync>>,
+    receiver: mpsc::UnboundedReceiver<(u32, Vec<f32>)>,
+}
+
+impl MicStream {
+    fn new(
+        drop_tx: std::sync::mpsc::Sender<()>,
+        receiver: mpsc::UnboundedReceiver<(u32, Vec<f32>)>,
+        sample_rate: u32,
+    ) -> Self {
+        Self {
+            drop_tx,
+            sample_rate,
+            chunk: Vec::new().into_iter(),
+            read_data: Vec::new(),
+            receiver,
+        }
+    }
 }
 
 impl Drop for MicStream {
     fn drop(&mut self) {
-        self.drop_tx.send(()).unwrap();
+        let _ = self.drop_tx.send(());
     }
 }
 
@@ -170,13 +226,20 @@ impl Stream for MicStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        match self.receiver.as_mut().poll_next_unpin(cx) {
-            std::task::Poll::Ready(Some(data_chunk)) => {
-                self.read_data.push(data_chunk);
-                std::task::Poll::Ready(Some(data_chunk))
+        loop {
+            if let Some(sample) = self.chunk.next() {
+                self.read_data.push(sample);
+                return std::task::Poll::Ready(Some(sample));
+            }
+
+            match self.receiver.poll_next_unpin(cx) {
+                std::task::Poll::Ready(Some((sample_rate, data))) => {
+                    self.sample_rate = sample_rate;
+                    self.chunk = data.into_iter();
+                }
+                std::task::Poll::Ready(None) => return std::task::Poll::Ready(None),
+                std::task::Poll::Pending => return std::task::Poll::Pending,
             }
-            std::task::Poll::Ready(None) => std::task::Poll::Ready(None),
-            std::task::Poll::Pending => std::task::Poll::Pending,
         }
     }
 }
@@ -187,7 +250,7 @@ impl AsyncSource for MicStream {
     }
 
     fn sample_rate(&self) -> u32 {
-        self.config.sample_rate().0
+        self.sample_rate
     }
 }
 
@@ -198,7 +261,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_mic() {
-        let mic = MicInput::new(None).unwrap();
+        let mic = MicInput::new(None, None).unwrap();
         let mut stream = mic.stream();
 
         let mut buffer = Vec::new();
@@ -211,4 +274,51 @@ mod tests {
 
         assert!(buffer.iter().any(|x| *x != 0.0));
     }
+
+    #[test]
+    fn test_mic_on_each_host() {
+        let hosts = crate::AudioInput::list_hosts();
+        assert_eq!(hosts.iter().filter(|h| h.is_default).count(), 1);
+
+        for host in hosts {
+            match MicInput::new(None, Some(host.id.clone())) {
+                Ok(mic) => assert!(!mic.device_name().is_empty()),
+                // Hosts can be up without any input device, e.g. JACK with no ports.
+                Err(crate::Error::NoInputDevice) => {}
+                Err(e) => panic!("{}: {}", host.id, e),
+            }
+        }
+
+        assert!(matches!(
+            MicInput::new(None, Some("no-such-host".to_string())),
+            Err(crate::Error::HostUnavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_format_change_mid_stream() {
+        let (tx, rx) = mpsc::unbounded();
+        let (drop_tx, _drop_rx) = std::sync::mpsc::channel();
+        let stream = MicStream::new(drop_tx, rx, 48000);
+
+        // A Bluetooth headset drops from A2DP at 48kHz to HFP at 16kHz, a second each.
+        for (rate, chunks) in [(48000, 100), (16000, 100)] {
+            for _ in 0..chunks {
+                tx.unbounded_send((rate, vec![0.5; rate as usize / 100]))
+                    .unwrap();
+            }
+        }
+        drop(tx);
+
+        let resampled = crate::ResampledAsyncSource::new(stream, 16000)
+            .collect::<Vec<_>>()
+            .await;
+
+        // Two seconds at 16kHz. Keeping the 48kHz ratio would leave a third of the second one.
+        assert!(
+            resampled.len().abs_diff(32000) <= 4,
+            "{} samples",
+            resampled.len()
+        );
+    }
 }
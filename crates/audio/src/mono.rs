@@ -0,0 +1,120 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use kalosm_sound::AsyncSource;
+
+// Averages each frame of interleaved samples into a single one.
+//
+// With one channel (or zero), samples are passed through as is. A trailing frame that the source
+// ends in the middle of is dropped, since it has no sample for some of the channels.
+pub struct MonoSource<S: AsyncSource> {
+    source: S,
+    channels: usize,
+    frame: Vec<f32>,
+}
+
+pub trait MonoExt<S: AsyncSource> {
+    fn to_mono(self, channels: u16) -> MonoSource<S>;
+}
+
+impl<S: AsyncSource> MonoExt<S> for S {
+    fn to_mono(self, channels: u16) -> MonoSource<S> {
+        let channels = channels.max(1) as usize;
+
+        MonoSource {
+            source: self,
+            channels,
+            frame: Vec::with_capacity(channels),
+        }
+    }
+}
+
+impl<S: AsyncSource + Unpin> Stream for MonoSource<S> {
+    type Item = f32;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut inner = std::pin::pin!(this.source.as_stream());
+
+        loop {
+            match inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(sample)) if this.channels == 1 => {
+                    return Poll::Ready(Some(sample));
+                }
+                Poll::Ready(Some(sample)) => {
+                    this.frame.push(sample);
+
+                    if this.frame.len() == this.channels {
+                        let mono = this.frame.iter().sum::<f32>() / this.channels as f32;
+                        this.frame.clear();
+                        return Poll::Ready(Some(mono));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<S: AsyncSource + Unpin> AsyncSource for MonoSource<S> {
+    fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+        self
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_to_mono_stereo() {
+        let left = (0..1000)
+            .map(|i| (i as f32 * 0.01).sin())
+            .collect::<Vec<_>>();
+        let right = (0..1000)
+            .map(|i| (i as f32 * 0.03).cos())
+            .collect::<Vec<_>>();
+        let interleaved = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(l, r)| [*l, *r])
+            .collect::<Vec<_>>();
+
+        let source = rodio::buffer::SamplesBuffer::new(2, 16000, interleaved).to_mono(2);
+        assert_eq!(source.sample_rate(), 16000);
+
+        let mono = source.collect::<Vec<_>>().await;
+        let expected = left
+            .iter()
+            .zip(&right)
+            .map(|(l, r)| (l + r) / 2.0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(mono, expected);
+    }
+
+    #[tokio::test]
+    async fn test_to_mono_passthrough() {
+        let samples = (0..1000)
+            .map(|i| (i as f32 * 0.01).sin())
+            .collect::<Vec<_>>();
+        let source = rodio::buffer::SamplesBuffer::new(1, 16000, samples.clone()).to_mono(1);
+
+        assert_eq!(source.collect::<Vec<_>>().await, samples);
+    }
+
+    #[tokio::test]
+    async fn test_to_mono_drops_partial_frame() {
+        let source =
+            rodio::buffer::SamplesBuffer::new(1, 16000, vec![0.25, 0.5, 0.75, 1.0, 0.0]).to_mono(3);
+
+        assert_eq!(source.collect::<Vec<_>>().await, vec![0.5]);
+    }
+}
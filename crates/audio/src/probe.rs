@@ -0,0 +1,226 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::Error;
+
+// Ogg pages are at most this long, so the last one is always within this much of the end.
+const MAX_OGG_PAGE_BYTES: u64 = 65_307;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaFormat {
+    Wav,
+    Ogg,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MediaInfo {
+    pub duration: Duration,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: MediaFormat,
+}
+
+/// Reads a recording's duration and format from its headers, without decoding the audio.
+pub fn probe_file(path: impl AsRef<Path>) -> Result<MediaInfo, Error> {
+    let mut file = File::open(path)?;
+
+    let mut magic = [0u8; 4];
+    file.read_exact(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    match &magic {
+        b"RIFF" => probe_wav(&mut file),
+        b"OggS" => probe_ogg(&mut file),
+        _ => Err(Error::UnsupportedFormat),
+    }
+}
+
+fn probe_wav(file: &mut File) -> Result<MediaInfo, Error> {
+    let file_len = file.metadata()?.len();
+
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff)?;
+    if &riff[8..12] != b"WAVE" {
+        return Err(Error::MalformedHeader("missing WAVE id"));
+    }
+
+    let mut fmt = None;
+    loop {
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header)
+            .map_err(|_| Error::MalformedHeader("missing data chunk"))?;
+        let size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as u64;
+
+        match &header[0..4] {
+            b"fmt " => {
+                if size < 16 {
+                    return Err(Error::MalformedHeader("short fmt chunk"));
+                }
+                let mut body = [0u8; 16];
+                file.read_exact(&mut body)?;
+                file.seek(SeekFrom::Current(size as i64 - 16 + (size % 2) as i64))?;
+
+                let channels = u16::from_le_bytes(body[2..4].try_into().unwrap());
+                let sample_rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                let block_align = u16::from_le_bytes(body[12..14].try_into().unwrap());
+                fmt = Some((channels, sample_rate, block_align));
+            }
+            b"data" => {
+                let (channels, sample_rate, block_align) =
+                    fmt.ok_or(Error::MalformedHeader("data before fmt chunk"))?;
+                if sample_rate == 0 || block_align == 0 {
+                    return Err(Error::MalformedHeader("empty fmt chunk"));
+                }
+
+                // A WAV still being written hasn't had its size filled in yet.
+                let available = file_len.saturating_sub(file.stream_position()?);
+                let size = match size {
+                    0 | 0xFFFF_FFFF => available,
+                    size => size.min(available),
+                };
+                let frames = size / block_align as u64;
+
+                return Ok(MediaInfo {
+                    duration: frames_to_duration(frames, sample_rate),
+                    sample_rate,
+                    channels,
+                    format: MediaFormat::Wav,
+                });
+            }
+            _ => {
+                file.seek(SeekFrom::Current(size as i64 + (size % 2) as i64))?;
+            }
+        }
+    }
+}
+
+fn probe_ogg(file: &mut File) -> Result<MediaInfo, Error> {
+    // The first page carries the Vorbis identification header.
+    let mut first_page = [0u8; 58];
+    file.read_exact(&mut first_page)
+        .map_err(|_| Error::MalformedHeader("truncated first page"))?;
+
+    let segments = first_page[26] as usize;
+    let packet = first_page.get(27 + segments..).unwrap_or_default();
+    if packet.len() < 16 || &packet[0..7] != b"\x01vorbis" {
+        return Err(Error::UnsupportedFormat);
+    }
+    let channels = packet[11] as u16;
+    let sample_rate = u32::from_le_bytes(packet[12..16].try_into().unwrap());
+    if sample_rate == 0 {
+        return Err(Error::MalformedHeader("zero sample rate"));
+    }
+
+    // For Vorbis, the granule position of the last page is the total number of frames.
+    let file_len = file.metadata()?.len();
+    let tail_start = file_len.saturating_sub(MAX_OGG_PAGE_BYTES);
+    file.seek(SeekFrom::Start(tail_start))?;
+    let mut tail = Vec::with_capacity((file_len - tail_start) as usize);
+    file.read_to_end(&mut tail)?;
+
+    let frames = (0..tail.len().saturating_sub(14))
+        .rev()
+        .filter(|&i| &tail[i..i + 4] == b"OggS")
+        .map(|i| i64::from_le_bytes(tail[i + 6..i + 14].try_into().unwrap()))
+        .find(|&granule| granule >= 0)
+        .ok_or(Error::MalformedHeader("no granule position"))?;
+
+    Ok(MediaInfo {
+        duration: frames_to_duration(frames as u64, sample_rate),
+        sample_rate,
+        channels,
+        format: MediaFormat::Ogg,
+    })
+}
+
+fn frames_to_duration(frames: u64, sample_rate: u32) -> Duration {
+    Duration::from_secs(frames / sample_rate as u64)
+        + Duration::from_nanos((frames % sample_rate as u64) * 1_000_000_000 / sample_rate as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::num::{NonZeroU32, NonZeroU8};
+
+    const SAMPLE_RATE: u32 = 16000;
+    // 2.5s, which isn't a whole number of Vorbis blocks.
+    const FRAMES: usize = SAMPLE_RATE as usize * 5 / 2;
+
+    fn tone() -> Vec<f32> {
+        (0..FRAMES)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn assert_within_a_frame(duration: Duration) {
+        let expected = Duration::from_millis(2500);
+        let frame = Duration::from_secs(1) / SAMPLE_RATE;
+        assert!(
+            duration.abs_diff(expected) <= frame,
+            "{duration:?} vs {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_probe_wav_and_ogg() {
+        let dir = std::env::temp_dir().join(format!("probe_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let samples = tone();
+
+        // What the recorder writes while a session is running.
+        let wav_path = dir.join("audio.wav");
+        let mut writer = hound::WavWriter::create(
+            &wav_path,
+            hound::WavSpec {
+                channels: 1,
+                sample_rate: SAMPLE_RATE,
+                bits_per_sample: 32,
+                sample_format: hound::SampleFormat::Float,
+            },
+        )
+        .unwrap();
+        for sample in &samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let info = probe_file(&wav_path).unwrap();
+        assert_eq!(info.format, MediaFormat::Wav);
+        assert_eq!((info.sample_rate, info.channels), (SAMPLE_RATE, 1));
+        assert_within_a_frame(info.duration);
+
+        // And what it transcodes that into once the session ends.
+        let ogg_path = dir.join("audio.ogg");
+        let mut encoder = vorbis_rs::VorbisEncoderBuilder::new(
+            NonZeroU32::new(SAMPLE_RATE).unwrap(),
+            NonZeroU8::new(1).unwrap(),
+            std::fs::File::create(&ogg_path).unwrap(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        for block in samples.chunks(4096) {
+            encoder.encode_audio_block([block]).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let info = probe_file(&ogg_path).unwrap();
+        assert_eq!(info.format, MediaFormat::Ogg);
+        assert_eq!((info.sample_rate, info.channels), (SAMPLE_RATE, 1));
+        assert_within_a_frame(info.duration);
+
+        let other_path = dir.join("audio.txt");
+        std::fs::write(&other_path, b"not audio").unwrap();
+        assert!(matches!(
+            probe_file(&other_path),
+            Err(Error::UnsupportedFormat)
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use ringbuf::{
+    traits::{Consumer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+
+// Most devices change rate a handful of times per session, if at all.
+const MAX_PENDING_RATE_CHANGES: usize = 64;
+
+// A sample buffer that remembers the rate each sample was captured at.
+//
+// A device can switch rates while older samples are still buffered. Reporting the device's
+// current rate for those would resample them with the wrong ratio, so the reported rate only
+// changes once the first sample captured at the new rate is next in line.
+pub fn rate_tagged_buffer(
+    capacity: usize,
+    initial_rate: u32,
+) -> (RateTaggedProducer, RateTaggedConsumer) {
+    let (samples_prod, samples_cons) = HeapRb::<f32>::new(capacity).split();
+    let (changes_prod, changes_cons) = HeapRb::<(u64, u32)>::new(MAX_PENDING_RATE_CHANGES).split();
+
+    (
+        RateTaggedProducer {
+            samples: samples_prod,
+            changes: changes_prod,
+            overflow: None,
+            pushed: 0,
+            rate: initial_rate,
+        },
+        RateTaggedConsumer {
+            samples: samples_cons,
+            changes: changes_cons,
+            popped: 0,
+            rate: initial_rate,
+        },
+    )
+}
+
+pub struct RateTaggedProducer {
+    samples: HeapProd<f32>,
+    // (index of the first sample at the new rate, new rate)
+    changes: HeapProd<(u64, u32)>,
+    // The newest change that didn't fit in `changes`, queued once the consumer frees a slot.
+    overflow: Option<(u64, u32)>,
+    pushed: u64,
+    rate: u32,
+}
+
+impl RateTaggedProducer {
+    // Returns how many samples fit, like `push_slice`.
+    pub fn push_slice(&mut self, rate: u32, data: &[f32]) -> usize {
+        if let Some(change) = self.overflow {
+            if self.changes.try_push(change).is_ok() {
+                self.overflow = None;
+            }
+        }
+
+        let pushed = self.samples.push_slice(data);
+
+        // Only recorded once samples at the new rate are actually buffered.
+        if pushed > 0 && rate != self.rate {
+            let change = (self.pushed, rate);
+            if self.overflow.is_some() || self.changes.try_push(change).is_err() {
+                // Replaces any change still waiting, so the latest rate always lands. Samples
+                // between the two are read at the rate before them.
+                if self.overflow.replace(change).is_none() {
+                    tracing::warn!(rate, "rate_changes_full");
+                }
+            }
+            self.rate = rate;
+        }
+
+        self.pushed += pushed as u64;
+        pushed
+    }
+}
+
+pub struct RateTaggedConsumer {
+    samples: HeapCons<f32>,
+    changes: HeapCons<(u64, u32)>,
+    popped: u64,
+    rate: u32,
+}
+
+impl RateTaggedConsumer {
+    pub fn try_pop(&mut self) -> Option<f32> {
+        self.apply_changes();
+
+        let sample = self.samples.try_pop()?;
+        self.popped += 1;
+        self.apply_changes();
+        Some(sample)
+    }
+
+    // Rate of the next sample to be popped.
+    pub fn sample_rate(&self) -> u32 {
+        match self.changes.try_peek() {
+            Some(&(at, rate)) if at <= self.popped => rate,
+            _ => self.rate,
+        }
+    }
+
+    fn apply_changes(&mut self) {
+        while let Some(&(at, rate)) = self.changes.try_peek() {
+            if at > self.popped {
+                break;
+            }
+            self.rate = rate;
+            self.changes.try_pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_util::{Stream, StreamExt};
+    use kalosm_sound::AsyncSource;
+
+    struct TaggedSource {
+        consumer: RateTaggedConsumer,
+    }
+
+    impl AsyncSource for TaggedSource {
+        fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+            futures_util::stream::poll_fn(move |_| std::task::Poll::Ready(self.consumer.try_pop()))
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.consumer.sample_rate()
+        }
+    }
+
+    fn sine(rate: u32, seconds: f32) -> Vec<f32> {
+        (0..(rate as f32 * seconds) as usize)
+            .map(|i| (i as f32 * 440.0 * 2.0 * std::f32::consts::PI / rate as f32).sin())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rate_change_with_buffered_samples() {
+        let (mut producer, consumer) = rate_tagged_buffer(48000 * 3, 48000);
+
+        // The device switches to 44.1kHz before any of the 48kHz second was read.
+        assert_eq!(producer.push_slice(48000, &sine(48000, 1.0)), 48000);
+        assert_eq!(producer.push_slice(44100, &sine(44100, 1.0)), 44100);
+        assert_eq!(consumer.sample_rate(), 48000);
+
+        let resampled = crate::ResampledAsyncSource::new(TaggedSource { consumer }, 16000)
+            .collect::<Vec<_>>()
+            .await;
+
+        // Two seconds at 16kHz. Reading the 48kHz second as 44.1kHz would add ~1400 samples,
+        // lowering its pitch.
+        assert!(
+            resampled.len().abs_diff(32000) <= 4,
+            "{} samples",
+            resampled.len()
+        );
+    }
+
+    #[test]
+    fn test_latest_rate_lands_when_changes_are_full() {
+        let (mut producer, mut consumer) = rate_tagged_buffer(1024, 8000);
+
+        for i in 0..MAX_PENDING_RATE_CHANGES as u32 {
+            producer.push_slice(16000 + i, &[0.0]);
+        }
+        // The two changes that don't fit collapse into the newest.
+        producer.push_slice(44100, &[1.0]);
+        producer.push_slice(48000, &[2.0]);
+
+        for _ in 0..MAX_PENDING_RATE_CHANGES {
+            consumer.try_pop();
+        }
+        producer.push_slice(48000, &[3.0]);
+
+        // The dropped change's sample is read at the rate before it.
+        assert_eq!(
+            consumer.sample_rate(),
+            16000 + MAX_PENDING_RATE_CHANGES as u32 - 1
+        );
+        assert_eq!(consumer.try_pop(), Some(1.0));
+        assert_eq!(consumer.sample_rate(), 48000);
+        assert_eq!(consumer.try_pop(), Some(2.0));
+        assert_eq!(consumer.sample_rate(), 48000);
+        assert_eq!(consumer.try_pop(), Some(3.0));
+    }
+
+    #[test]
+    fn test_rate_follows_the_samples() {
+        let (mut producer, mut consumer) = rate_tagged_buffer(16, 48000);
+
+        producer.push_slice(48000, &[0.0; 2]);
+        producer.push_slice(44100, &[1.0; 2]);
+        // Nothing was pushed, so it doesn't count as a change.
+        producer.push_slice(16000, &[]);
+
+        assert_eq!(consumer.sample_rate(), 48000);
+        assert_eq!(consumer.try_pop(), Some(0.0));
+        assert_eq!(consumer.sample_rate(), 48000);
+        assert_eq!(consumer.try_pop(), Some(0.0));
+        assert_eq!(consumer.sample_rate(), 44100);
+        assert_eq!(consumer.try_pop(), Some(1.0));
+        assert_eq!(consumer.try_pop(), Some(1.0));
+        assert_eq!(consumer.try_pop(), None);
+        assert_eq!(consumer.sample_rate(), 44100);
+
+        // A change while the buffer is empty applies to the next sample.
+        producer.push_slice(16000, &[2.0]);
+        assert_eq!(consumer.sample_rate(), 16000);
+        assert_eq!(consumer.try_pop(), Some(2.0));
+    }
+}
@@ -15,38 +15,56 @@ pub struct ResampledAsyncSource<S: AsyncSource> {
 
     interp: dasp::interpolate::linear::Linear<f32>,
     last_sample: f32,
-    seeded: bool,
+    seeded_frames: usize,
 }
 
 impl<S: AsyncSource> ResampledAsyncSource<S> {
+    // A source may report a rate of 0 until it is initialized. Its samples are then taken to be at
+    // the target rate, and passed through as-is until it reports a real one.
     pub fn new(source: S, target_sample_rate: u32) -> Self {
         let initial_rate = source.sample_rate();
         Self {
             source,
             target_sample_rate,
             last_source_rate: initial_rate,
-            ratio: initial_rate as f64 / target_sample_rate as f64,
+            ratio: ratio(initial_rate, target_sample_rate),
             phase: 0.0,
             interp: dasp::interpolate::linear::Linear::new(0.0, 0.0),
             last_sample: 0.0,
-            seeded: false,
+            seeded_frames: 0,
         }
     }
 
     #[inline]
     fn handle_rate_change(&mut self) {
         let new_rate = self.source.sample_rate();
-        if new_rate == self.last_source_rate {
+        // An unknown rate keeps the last known one.
+        if new_rate == 0 || new_rate == self.last_source_rate {
             return;
         }
 
+        let was_unknown = self.last_source_rate == 0;
         self.last_source_rate = new_rate;
-        self.ratio = new_rate as f64 / self.target_sample_rate as f64;
+        self.ratio = ratio(new_rate, self.target_sample_rate);
+
+        // Passed-through samples are already where the interpolator is, so only the step changes.
+        if was_unknown {
+            return;
+        }
+
         self.phase = 0.0;
         self.interp = dasp::interpolate::linear::Linear::new(self.last_sample, self.last_sample);
     }
 }
 
+fn ratio(source_rate: u32, target_sample_rate: u32) -> f64 {
+    if source_rate == 0 {
+        1.0
+    } else {
+        source_rate as f64 / target_sample_rate as f64
+    }
+}
+
 impl<S: AsyncSource + Unpin> Stream for ResampledAsyncSource<S> {
     type Item = f32;
 
@@ -58,12 +76,19 @@ impl<S: AsyncSource + Unpin> Stream for ResampledAsyncSource<S> {
         let inner = me.source.as_stream();
         pin_mut!(inner);
 
-        if !me.seeded {
+        // Seeded with the first frame. A source without a rate yet passes its samples through, so
+        // the interpolator also takes the second one, or the first would be returned twice.
+        let frames_to_seed = if me.last_source_rate == 0 { 2 } else { 1 };
+        while me.seeded_frames < frames_to_seed {
             match inner.as_mut().poll_next(cx) {
                 Poll::Ready(Some(frame)) => {
                     me.last_sample = frame;
-                    me.interp = dasp::interpolate::linear::Linear::new(frame, frame);
-                    me.seeded = true;
+                    if me.seeded_frames == 0 {
+                        me.interp = dasp::interpolate::linear::Linear::new(frame, frame);
+                    } else {
+                        me.interp.next_source_frame(frame);
+                    }
+                    me.seeded_frames += 1;
                 }
                 Poll::Ready(None) => return Poll::Ready(None),
                 Poll::Pending => return Poll::Pending,
@@ -176,6 +201,52 @@ mod tests {
         }
     }
 
+    // Reports a rate of 0 until `pending` samples were read.
+    struct LateRateSource {
+        samples: Vec<f32>,
+        position: usize,
+        pending: usize,
+        rate: u32,
+    }
+
+    impl AsyncSource for LateRateSource {
+        fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+            futures_util::stream::poll_fn(move |_| {
+                let sample = self.samples.get(self.position).copied();
+                self.position += 1;
+                Poll::Ready(sample)
+            })
+        }
+
+        fn sample_rate(&self) -> u32 {
+            if self.position < self.pending {
+                0
+            } else {
+                self.rate
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_late_sample_rate() {
+        let samples = (0..1000).map(|i| i as f32).collect::<Vec<_>>();
+        let source = LateRateSource {
+            samples: samples.clone(),
+            position: 0,
+            pending: 100,
+            rate: 16000,
+        };
+        assert_eq!(source.sample_rate(), 0);
+
+        let resampled = ResampledAsyncSource::new(source, 16000)
+            .collect::<Vec<_>>()
+            .await;
+
+        // Samples read before the rate was known pass through, and each appears once. The last one
+        // is never stepped past, so it isn't returned.
+        assert_eq!(resampled, &samples[..999]);
+    }
+
     #[tokio::test]
     async fn test_existing_resampler() {
         let source = DynamicRateSource::new(vec![
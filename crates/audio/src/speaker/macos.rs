@@ -5,10 +5,7 @@ use std::task::{Poll, Waker};
 use anyhow::Result;
 use futures_util::Stream;
 
-use ringbuf::{
-    traits::{Consumer, Producer, Split},
-    HeapCons, HeapProd, HeapRb,
-};
+use crate::{rate_tagged_buffer, RateTaggedConsumer, RateTaggedProducer};
 
 use ca::aggregate_device_keys as agg_keys;
 use cidre::{arc, av, cat, cf, core_audio as ca, ns, os};
@@ -24,29 +21,35 @@ struct WakerState {
 }
 
 pub struct SpeakerStream {
-    consumer: HeapCons<f32>,
+    consumer: RateTaggedConsumer,
     _device: ca::hardware::StartedDevice<ca::AggregateDevice>,
     _ctx: Box<Ctx>,
     _tap: ca::TapGuard,
     waker_state: Arc<Mutex<WakerState>>,
-    current_sample_rate: Arc<AtomicU32>,
 }
 
 impl SpeakerStream {
+    // The rate of the samples about to be read, which lags the device's while older ones drain.
     pub fn sample_rate(&self) -> u32 {
-        self.current_sample_rate.load(Ordering::Acquire)
+        self.consumer.sample_rate()
     }
 }
 
 struct Ctx {
     format: arc::R<av::AudioFormat>,
-    producer: HeapProd<f32>,
+    producer: RateTaggedProducer,
     waker_state: Arc<Mutex<WakerState>>,
     current_sample_rate: Arc<AtomicU32>,
     consecutive_drops: Arc<AtomicU32>,
     should_terminate: Arc<AtomicBool>,
+    mixed: Vec<f32>,
+    malformed_buffers: u32,
 }
 
+// The IO proc runs every few milliseconds, so a driver that keeps sending malformed buffers is
+// only logged on the first one and every this many after it.
+const MALFORMED_LOG_EVERY: u32 = 500;
+
 impl SpeakerInput {
     pub fn new() -> Result<Self> {
         let output_device = ca::System::default_output_device()?;
@@ -116,23 +119,26 @@ impl SpeakerInput {
                 tracing::info!(before = before, after = after, "sample_rate",);
             }
 
+            if let Err(error) = channel_frames(input_data) {
+                ctx.malformed_buffers += 1;
+                if ctx.malformed_buffers % MALFORMED_LOG_EVERY == 1 {
+                    tracing::warn!(?error, skipped = ctx.malformed_buffers, "malformed_buffer");
+                }
+                return os::Status::NO_ERR;
+            }
+
             if let Some(view) =
                 av::AudioPcmBuf::with_buf_list_no_copy(&ctx.format, input_data, None)
             {
                 if let Some(data) = view.data_f32_at(0) {
-                    process_audio_data(ctx, data);
+                    process_audio_data(ctx, after, data);
                 }
             } else if ctx.format.common_format() == av::audio::CommonFormat::PcmF32 {
-                let first_buffer = &input_data.buffers[0];
-                let byte_count = first_buffer.data_bytes_size as usize;
-                let float_count = byte_count / std::mem::size_of::<f32>();
-
-                if float_count > 0 && first_buffer.data != std::ptr::null_mut() {
-                    let data = unsafe {
-                        std::slice::from_raw_parts(first_buffer.data as *const f32, float_count)
-                    };
-                    process_audio_data(ctx, data);
+                let mut mixed = std::mem::take(&mut ctx.mixed);
+                if mix_channels(input_data, &mut mixed).is_ok() && !mixed.is_empty() {
+                    process_audio_data(ctx, after, &mixed);
                 }
+                ctx.mixed = mixed;
             }
 
             os::Status::NO_ERR
@@ -151,8 +157,7 @@ impl SpeakerInput {
         let format = av::AudioFormat::with_asbd(&asbd).unwrap();
 
         let buffer_size = 1024 * 128;
-        let rb = HeapRb::<f32>::new(buffer_size);
-        let (producer, consumer) = rb.split();
+        let (producer, consumer) = rate_tagged_buffer(buffer_size, asbd.sample_rate as u32);
 
         let waker_state = Arc::new(Mutex::new(WakerState {
             waker: None,
@@ -166,9 +171,11 @@ impl SpeakerInput {
             format,
             producer,
             waker_state: waker_state.clone(),
-            current_sample_rate: current_sample_rate.clone(),
+            current_sample_rate,
             consecutive_drops: Arc::new(AtomicU32::new(0)),
             should_terminate: Arc::new(AtomicBool::new(false)),
+            mixed: Vec::new(),
+            malformed_buffers: 0,
         });
 
         let device = self.start_device(&mut ctx).unwrap();
@@ -179,14 +186,111 @@ impl SpeakerInput {
             _ctx: ctx,
             _tap: self.tap,
             waker_state,
-            current_sample_rate,
         }
     }
 }
 
-fn process_audio_data(ctx: &mut Ctx, data: &[f32]) {
+#[derive(Debug, PartialEq)]
+enum MalformedBuffer {
+    NullData {
+        buffer: usize,
+    },
+    NoChannels {
+        buffer: usize,
+    },
+    PartialFrame {
+        buffer: usize,
+    },
+    FrameMismatch {
+        buffer: usize,
+        expected: usize,
+        got: usize,
+    },
+}
+
+// Each buffer holds one or more interleaved channels of the same frames. Returns the shared frame
+// count, or why the list can't be mixed: a list whose channels disagree would otherwise be mixed
+// with some channels cut short. A trailing partial sample is left out.
+fn channel_frames<const N: usize>(
+    input_data: &cat::AudioBufList<N>,
+) -> Result<usize, MalformedBuffer> {
+    let mut frames = None;
+
+    for (i, buf) in buffers(input_data).iter().enumerate() {
+        let float_count = buf.data_bytes_size as usize / std::mem::size_of::<f32>();
+        let channels = buf.number_channels as usize;
+
+        if float_count > 0 && buf.data.is_null() {
+            return Err(MalformedBuffer::NullData { buffer: i });
+        }
+        if channels == 0 {
+            return Err(MalformedBuffer::NoChannels { buffer: i });
+        }
+        if float_count % channels != 0 {
+            return Err(MalformedBuffer::PartialFrame { buffer: i });
+        }
+
+        let got = float_count / channels;
+        match frames {
+            None => frames = Some(got),
+            Some(expected) if expected != got => {
+                return Err(MalformedBuffer::FrameMismatch {
+                    buffer: i,
+                    expected,
+                    got,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(frames.unwrap_or(0))
+}
+
+// Averages every channel of every buffer into `out`, which is left empty if the list is malformed.
+fn mix_channels<const N: usize>(
+    input_data: &cat::AudioBufList<N>,
+    out: &mut Vec<f32>,
+) -> Result<(), MalformedBuffer> {
+    out.clear();
+    let frames = channel_frames(input_data)?;
+    if frames == 0 {
+        return Ok(());
+    }
+
+    out.resize(frames, 0.0);
+    let mut total_channels = 0;
+
+    for buf in buffers(input_data) {
+        let channels = buf.number_channels as usize;
+        let data = unsafe { std::slice::from_raw_parts(buf.data as *const f32, frames * channels) };
+
+        for (frame, sum) in data.chunks_exact(channels).zip(out.iter_mut()) {
+            *sum += frame.iter().sum::<f32>();
+        }
+        total_channels += channels;
+    }
+
+    let scale = 1.0 / total_channels as f32;
+    out.iter_mut().for_each(|s| *s *= scale);
+
+    Ok(())
+}
+
+// CoreAudio allocates the list with `number_buffers` entries, past the one the proc's type
+// declares, so they are read through the pointer rather than the typed array.
+fn buffers<const N: usize>(input_data: &cat::AudioBufList<N>) -> &[cat::AudioBuf] {
+    unsafe {
+        std::slice::from_raw_parts(
+            input_data.buffers.as_ptr(),
+            input_data.number_buffers as usize,
+        )
+    }
+}
+
+fn process_audio_data(ctx: &mut Ctx, sample_rate: u32, data: &[f32]) {
     let buffer_size = data.len();
-    let pushed = ctx.producer.push_slice(data);
+    let pushed = ctx.producer.push_slice(sample_rate, data);
 
     if pushed < buffer_size {
         let consecutive = ctx.consecutive_drops.fetch_add(1, Ordering::AcqRel) + 1;
@@ -250,3 +354,108 @@ impl Drop for SpeakerStream {
         self._ctx.should_terminate.store(true, Ordering::Release);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buf(data: &mut [f32], number_channels: u32, data_bytes_size: u32) -> cat::AudioBuf {
+        cat::AudioBuf {
+            number_channels,
+            data_bytes_size,
+            data: data.as_mut_ptr() as _,
+        }
+    }
+
+    fn bytes(samples: usize) -> u32 {
+        (samples * std::mem::size_of::<f32>()) as u32
+    }
+
+    #[test]
+    fn test_mix_channels() {
+        let mut left = vec![0.2f32, -0.4, 0.6];
+        let mut right = vec![0.4f32, 0.0, -0.2];
+
+        let list = cat::AudioBufList {
+            number_buffers: 2,
+            buffers: [buf(&mut left, 1, bytes(3)), buf(&mut right, 1, bytes(3))],
+        };
+
+        let mut out = Vec::new();
+        mix_channels(&list, &mut out).unwrap();
+        assert_eq!(out.len(), 3);
+        for (got, expected) in out.iter().zip([0.3f32, -0.2, 0.2]) {
+            assert!((got - expected).abs() < 1e-6);
+        }
+
+        // A size that isn't a whole number of samples never reads past the buffer.
+        let mut mono = vec![0.1f32, -0.2, 0.3];
+        let list = cat::AudioBufList {
+            number_buffers: 1,
+            buffers: [buf(&mut mono, 1, bytes(3) - 2)],
+        };
+        mix_channels(&list, &mut out).unwrap();
+        assert_eq!(out, vec![0.1f32, -0.2]);
+    }
+
+    #[test]
+    fn test_mismatched_channels_rejected() {
+        let mut left = vec![0.5f32; 4];
+        let mut right = vec![0.5f32; 2];
+
+        let list = cat::AudioBufList {
+            number_buffers: 2,
+            buffers: [buf(&mut left, 1, bytes(4)), buf(&mut right, 1, bytes(2))],
+        };
+
+        let mut out = vec![1.0f32; 8];
+        assert_eq!(
+            mix_channels(&list, &mut out),
+            Err(MalformedBuffer::FrameMismatch {
+                buffer: 1,
+                expected: 4,
+                got: 2
+            })
+        );
+        assert!(out.is_empty());
+
+        // The IO proc's list type declares a single buffer, and the rest still get checked.
+        let list = cat::AudioBufList {
+            number_buffers: 2,
+            buffers: [buf(&mut left, 1, bytes(4)), buf(&mut right, 1, bytes(2))],
+        };
+        let as_proc_list =
+            unsafe { &*(&list as *const cat::AudioBufList<2> as *const cat::AudioBufList<1>) };
+        assert_eq!(
+            channel_frames(as_proc_list),
+            Err(MalformedBuffer::FrameMismatch {
+                buffer: 1,
+                expected: 4,
+                got: 2
+            })
+        );
+
+        let mut interleaved = vec![0.5f32; 3];
+        let list = cat::AudioBufList {
+            number_buffers: 1,
+            buffers: [buf(&mut interleaved, 2, bytes(3))],
+        };
+        assert_eq!(
+            channel_frames(&list),
+            Err(MalformedBuffer::PartialFrame { buffer: 0 })
+        );
+
+        let list = cat::AudioBufList {
+            number_buffers: 1,
+            buffers: [cat::AudioBuf {
+                number_channels: 1,
+                data_bytes_size: bytes(3),
+                data: std::ptr::null_mut(),
+            }],
+        };
+        assert_eq!(
+            channel_frames(&list),
+            Err(MalformedBuffer::NullData { buffer: 0 })
+        );
+    }
+}
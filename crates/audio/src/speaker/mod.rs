@@ -45,20 +45,26 @@ impl SpeakerInput {
     #[cfg(any(target_os = "macos", target_os = "windows"))]
     pub fn stream(self) -> Result<SpeakerStream> {
         let inner = self.inner.stream();
-        Ok(SpeakerStream { inner })
+        Ok(SpeakerStream { inner: Some(inner) })
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     pub fn stream(self) -> Result<SpeakerStream> {
-        Err(anyhow::anyhow!(
-            "'SpeakerInput::stream' is not supported on this platform"
-        ))
+        Ok(SpeakerStream::unsupported())
     }
 }
 
 // https://github.com/floneum/floneum/blob/50afe10/interfaces/kalosm-sound/src/source/mic.rs#L140
 pub struct SpeakerStream {
-    inner: PlatformSpeakerStream,
+    // `None` where speaker capture is not supported.
+    inner: Option<PlatformSpeakerStream>,
+}
+
+impl SpeakerStream {
+    // Ends right away, so consumers stop waiting on it and carry on without speaker audio.
+    pub(crate) fn unsupported() -> Self {
+        Self { inner: None }
+    }
 }
 
 impl Stream for SpeakerStream {
@@ -68,14 +74,19 @@ impl Stream for SpeakerStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
+        let Some(_inner) = self.inner.as_mut() else {
+            return std::task::Poll::Ready(None);
+        };
+
         #[cfg(any(target_os = "macos", target_os = "windows"))]
         {
-            self.inner.poll_next_unpin(cx)
+            _inner.poll_next_unpin(cx)
         }
 
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         {
-            std::task::Poll::Pending
+            let _ = cx;
+            std::task::Poll::Ready(None)
         }
     }
 }
@@ -87,7 +98,7 @@ impl kalosm_sound::AsyncSource for SpeakerStream {
 
     #[cfg(any(target_os = "macos", target_os = "windows"))]
     fn sample_rate(&self) -> u32 {
-        self.inner.sample_rate()
+        self.inner.as_ref().map_or(0, |inner| inner.sample_rate())
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
@@ -103,6 +114,18 @@ mod tests {
 
     use serial_test::serial;
 
+    #[tokio::test]
+    async fn test_unsupported_stream_ends() {
+        use kalosm_sound::AsyncSource;
+
+        let mut stream = SpeakerStream::unsupported();
+        assert_eq!(stream.sample_rate(), 0);
+        assert_eq!(stream.next().await, None);
+
+        let mut resampled = crate::ResampledAsyncSource::new(SpeakerStream::unsupported(), 16000);
+        assert_eq!(resampled.next().await, None);
+    }
+
     #[cfg(target_os = "macos")]
     #[tokio::test]
     #[serial]
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures_util::Stream;
+use kalosm_sound::AsyncSource;
+
+const DEFAULT_TEE_BUFFER_SECS: usize = 5;
+
+// Both branches read from a single buffer; samples are dropped once every live branch has seen them.
+//
+// Whichever branch is polled drives the underlying source. When one branch lags by more than
+// `capacity` samples, the oldest samples are discarded for the lagging branch instead of
+// stalling the other one, since live audio can not wait. Dropped samples are counted per branch.
+struct TeeShared<S: AsyncSource> {
+    source: S,
+    buffer: VecDeque<f32>,
+    // Absolute index of `buffer[0]`.
+    offset: u64,
+    // Absolute index of the next sample for each branch. `None` once the branch is dropped.
+    cursors: [Option<u64>; 2],
+    wakers: [Option<Waker>; 2],
+    dropped: [u64; 2],
+    capacity: usize,
+    ended: bool,
+}
+
+impl<S: AsyncSource> TeeShared<S> {
+    fn trim(&mut self) {
+        let Some(min) = self.cursors.iter().flatten().min().copied() else {
+            self.offset += self.buffer.len() as u64;
+            self.buffer.clear();
+            return;
+        };
+
+        while self.offset < min && self.buffer.pop_front().is_some() {
+            self.offset += 1;
+        }
+    }
+
+    fn wake(&mut self, branch: usize) {
+        if let Some(waker) = self.wakers[branch].take() {
+            waker.wake();
+        }
+    }
+}
+
+pub struct TeeSource<S: AsyncSource> {
+    shared: Arc<Mutex<TeeShared<S>>>,
+    branch: usize,
+}
+
+impl<S: AsyncSource> TeeSource<S> {
+    // Number of samples this branch missed because it fell too far behind the other one.
+    pub fn dropped_samples(&self) -> u64 {
+        self.shared.lock().unwrap().dropped[self.branch]
+    }
+}
+
+pub trait TeeExt<S: AsyncSource> {
+    fn tee(self) -> (TeeSource<S>, TeeSource<S>);
+    fn tee_with_capacity(self, capacity: usize) -> (TeeSource<S>, TeeSource<S>);
+}
+
+impl<S: AsyncSource> TeeExt<S> for S {
+    fn tee(self) -> (TeeSource<S>, TeeSource<S>) {
+        let capacity = self.sample_rate() as usize * DEFAULT_TEE_BUFFER_SECS;
+        self.tee_with_capacity(capacity)
+    }
+
+    fn tee_with_capacity(self, capacity: usize) -> (TeeSource<S>, TeeSource<S>) {
+        let shared = Arc::new(Mutex::new(TeeShared {
+            source: self,
+            buffer: VecDeque::with_capacity(capacity),
+            offset: 0,
+            cursors: [Some(0), Some(0)],
+            wakers: [None, None],
+            dropped: [0, 0],
+            capacity: capacity.max(1),
+            ended: false,
+        }));
+
+        (
+            TeeSource {
+                shared: shared.clone(),
+                branch: 0,
+            },
+            TeeSource { shared, branch: 1 },
+        )
+    }
+}
+
+impl<S: AsyncSource + Unpin> Stream for TeeSource<S> {
+    type Item = f32;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+        let branch = me.branch;
+        let other = 1 - branch;
+
+        let mut guard = me.shared.lock().unwrap();
+        let shared = &mut *guard;
+
+        let Some(cursor) = shared.cursors[branch] else {
+            return Poll::Ready(None);
+        };
+
+        let end = shared.offset + shared.buffer.len() as u64;
+        if cursor < end {
+            let sample = shared.buffer[(cursor - shared.offset) as usize];
+            shared.cursors[branch] = Some(cursor + 1);
+            shared.trim();
+            return Poll::Ready(Some(sample));
+        }
+
+        if shared.ended {
+            return Poll::Ready(None);
+        }
+
+        let polled = {
+            let mut inner = std::pin::pin!(shared.source.as_stream());
+            inner.as_mut().poll_next(cx)
+        };
+
+        match polled {
+            Poll::Ready(Some(sample)) => {
+                shared.buffer.push_back(sample);
+                shared.cursors[branch] = Some(cursor + 1);
+
+                if shared.buffer.len() > shared.capacity {
+                    shared.buffer.pop_front();
+                    shared.offset += 1;
+
+                    if let Some(other_cursor) = shared.cursors[other] {
+                        if other_cursor < shared.offset {
+                            shared.cursors[other] = Some(shared.offset);
+                            shared.dropped[other] += 1;
+                        }
+                    }
+                }
+
+                shared.trim();
+                shared.wake(other);
+                Poll::Ready(Some(sample))
+            }
+            Poll::Ready(None) => {
+                shared.ended = true;
+                shared.wake(other);
+                Poll::Ready(None)
+            }
+            Poll::Pending => {
+                shared.wakers[branch] = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<S: AsyncSource + Unpin> AsyncSource for TeeSource<S> {
+    fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+        self
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.shared.lock().unwrap().source.sample_rate()
+    }
+}
+
+impl<S: AsyncSource> Drop for TeeSource<S> {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.cursors[self.branch] = None;
+            shared.trim();
+            // The remaining branch may be waiting on a waker that only this branch would have fired.
+            shared.wake(1 - self.branch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn synthetic_source(len: usize) -> (rodio::buffer::SamplesBuffer<f32>, Vec<f32>) {
+        let samples = (0..len)
+            .map(|i| ((i as f32) * 0.01).sin())
+            .collect::<Vec<_>>();
+        (
+            rodio::buffer::SamplesBuffer::new(1, 16000, samples.clone()),
+            samples,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tee_identical() {
+        let (source, expected) = synthetic_source(16000);
+        let (a, b) = source.tee();
+
+        assert_eq!(a.sample_rate(), 16000);
+        assert_eq!(b.sample_rate(), 16000);
+
+        let (a_samples, b_samples) =
+            futures_util::future::join(a.collect::<Vec<_>>(), b.collect::<Vec<_>>()).await;
+
+        assert_eq!(a_samples, expected);
+        assert_eq!(b_samples, expected);
+    }
+
+    #[tokio::test]
+    async fn test_tee_sequential_within_capacity() {
+        let (source, expected) = synthetic_source(1000);
+        let (mut a, mut b) = source.tee_with_capacity(1000);
+
+        let a_samples = a.as_stream().collect::<Vec<_>>().await;
+        let b_samples = b.as_stream().collect::<Vec<_>>().await;
+
+        assert_eq!(a_samples, expected);
+        assert_eq!(b_samples, expected);
+        assert_eq!(b.dropped_samples(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tee_lagging_branch_drops_oldest() {
+        let (source, expected) = synthetic_source(1000);
+        let (mut a, mut b) = source.tee_with_capacity(100);
+
+        let a_samples = a.as_stream().collect::<Vec<_>>().await;
+        let b_samples = b.as_stream().collect::<Vec<_>>().await;
+
+        assert_eq!(a_samples, expected);
+        assert_eq!(b_samples, expected[900..]);
+        assert_eq!(a.dropped_samples(), 0);
+        assert_eq!(b.dropped_samples(), 900);
+    }
+}
@@ -0,0 +1,58 @@
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ComputeBackend {
+    #[default]
+    Gpu,
+    Cpu,
+}
+
+/// Loads with `preferred`, retrying on the CPU if loading on the GPU fails.
+///
+/// Returns the model along with where it ended up.
+pub fn load_with_fallback<M, E: std::fmt::Debug>(
+    preferred: ComputeBackend,
+    load: impl Fn(ComputeBackend) -> Result<M, E>,
+) -> Result<(M, ComputeBackend), E> {
+    match load(preferred) {
+        Ok(model) => Ok((model, preferred)),
+        Err(e) if preferred == ComputeBackend::Gpu => {
+            tracing::warn!(error = ?e, "gpu_load_failed_falling_back_to_cpu");
+            load(ComputeBackend::Cpu).map(|model| (model, ComputeBackend::Cpu))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpu_failure_falls_back_to_cpu() {
+        let attempts = std::sync::Mutex::new(Vec::new());
+        let load = |backend: ComputeBackend| {
+            attempts.lock().unwrap().push(backend);
+            match backend {
+                // Stands in for the GPU failing to initialize.
+                ComputeBackend::Gpu => Err("no gpu"),
+                ComputeBackend::Cpu => Ok("model"),
+            }
+        };
+
+        let (model, backend) = load_with_fallback(ComputeBackend::Gpu, load).unwrap();
+        assert_eq!(model, "model");
+        assert_eq!(backend, ComputeBackend::Cpu);
+        assert_eq!(
+            *attempts.lock().unwrap(),
+            vec![ComputeBackend::Gpu, ComputeBackend::Cpu]
+        );
+
+        // Preferring the CPU never touches the GPU.
+        attempts.lock().unwrap().clear();
+        let (_, backend) = load_with_fallback(ComputeBackend::Cpu, load).unwrap();
+        assert_eq!(backend, ComputeBackend::Cpu);
+        assert_eq!(*attempts.lock().unwrap(), vec![ComputeBackend::Cpu]);
+    }
+}
@@ -1,4 +1,4 @@
-use owhisper_interface::{SpeakerIdentity, Word2};
+use owhisper_interface::{SpeakerIdentity, Word2, WordKind};
 
 fn run(name: &str) {
     let raw_path = format!("src/{}/raw.json", name);
@@ -19,6 +19,9 @@ fn run(name: &str) {
             start_ms: Some((v["start"].as_f64().unwrap() * 1000.0) as u64),
             end_ms: Some((v["end"].as_f64().unwrap() * 1000.0) as u64),
             confidence: Some(1.0),
+            recording_start_s: None,
+            recording_end_s: None,
+            kind: WordKind::Speech,
         })
         .collect();
 
@@ -17,6 +17,9 @@ pub mod conversation_to_words {
                         confidence: transcript.confidence,
                         start_ms: None,
                         end_ms: None,
+                        recording_start_s: None,
+                        recording_end_s: None,
+                        kind: owhisper_interface::WordKind::classify(word),
                     })
                     .collect::<Vec<_>>()
             })
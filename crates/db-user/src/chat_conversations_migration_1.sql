@@ -0,0 +1,4 @@
+ALTER TABLE
+  chat_conversations
+ADD
+  COLUMN system_prompt TEXT;
@@ -11,16 +11,20 @@ impl UserDatabase {
             .query(
                 "INSERT INTO chat_conversations (
                     id, session_id, user_id, name, created_at, 
-updated_at
-                ) VALUES (?, ?, ?, ?, ?, ?)
+updated_at, system_prompt
+                ) VALUES (?, ?, ?, ?, ?, ?, ?)
                 RETURNING *",
                 vec![
-                    conversation.id,
-                    conversation.session_id,
-                    conversation.user_id,
-                    conversation.name.unwrap_or_default(),
-                    conversation.created_at.to_rfc3339(),
-                    conversation.updated_at.to_rfc3339(),
+                    libsql::Value::Text(conversation.id),
+                    libsql::Value::Text(conversation.session_id),
+                    libsql::Value::Text(conversation.user_id),
+                    libsql::Value::Text(conversation.name.unwrap_or_default()),
+                    libsql::Value::Text(conversation.created_at.to_rfc3339()),
+                    libsql::Value::Text(conversation.updated_at.to_rfc3339()),
+                    conversation
+                        .system_prompt
+                        .map(libsql::Value::Text)
+                        .unwrap_or(libsql::Value::Null),
                 ],
             )
             .await?;
@@ -73,4 +77,90 @@ updated_at
             Ok(None)
         }
     }
+
+    pub async fn update_conversation_system_prompt(
+        &self,
+        id: impl Into<String>,
+        system_prompt: Option<String>,
+    ) -> Result<(), crate::Error> {
+        let conn = self.conn()?;
+
+        conn.execute(
+            "UPDATE chat_conversations 
+            SET system_prompt = ?, updated_at = ? 
+            WHERE id = ?",
+            vec![
+                system_prompt
+                    .map(libsql::Value::Text)
+                    .unwrap_or(libsql::Value::Null),
+                libsql::Value::Text(chrono::Utc::now().to_rfc3339()),
+                libsql::Value::Text(id.into()),
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::setup_db, ChatConversation, Human, Session};
+
+    #[tokio::test]
+    async fn test_conversation_system_prompt() {
+        let db = setup_db().await;
+
+        let user = db.upsert_human(Human::default()).await.unwrap();
+        let session = db
+            .upsert_session(Session {
+                user_id: user.id.clone(),
+                title: "Test Session".to_string(),
+                ..Session::default()
+            })
+            .await
+            .unwrap();
+
+        let conversation = db
+            .create_conversation(ChatConversation {
+                id: uuid::Uuid::new_v4().to_string(),
+                session_id: session.id.clone(),
+                user_id: user.id.clone(),
+                name: None,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                system_prompt: Some("Answer tersely.".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            conversation.system_prompt,
+            Some("Answer tersely.".to_string())
+        );
+
+        db.update_conversation_system_prompt(&conversation.id, Some("Answer in French.".into()))
+            .await
+            .unwrap();
+
+        let conversation = db
+            .get_conversation(&conversation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            conversation.system_prompt,
+            Some("Answer in French.".to_string())
+        );
+
+        db.update_conversation_system_prompt(&conversation.id, None)
+            .await
+            .unwrap();
+
+        let conversation = db
+            .get_conversation(&conversation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(conversation.system_prompt, None);
+    }
 }
@@ -8,5 +8,6 @@ user_common_derives! {
         pub name: Option<String>,
         pub created_at: chrono::DateTime<chrono::Utc>,
         pub updated_at: chrono::DateTime<chrono::Utc>,
+        pub system_prompt: Option<String>,
     }
 }
@@ -1,4 +1,4 @@
-use super::{ChatMessageV2, UserDatabase};
+use super::{ChatConversation, ChatMessageV2, ChatMessageV2Role, UserDatabase};
 
 impl UserDatabase {
     pub async fn create_message_v2(
@@ -71,4 +71,218 @@ created_at, updated_at
 
         Ok(())
     }
+
+    // `None` if there is no such conversation.
+    pub async fn export_conversation(
+        &self,
+        conversation_id: impl Into<String>,
+    ) -> Result<Option<String>, crate::Error> {
+        let conversation_id = conversation_id.into();
+
+        let Some(conversation) = self.get_conversation(&conversation_id).await? else {
+            return Ok(None);
+        };
+        let messages = self.list_messages_v2(&conversation_id).await?;
+
+        Ok(Some(conversation_to_markdown(&conversation, &messages)))
+    }
+}
+
+fn conversation_to_markdown(conversation: &ChatConversation, messages: &[ChatMessageV2]) -> String {
+    let mut out = format!(
+        "# {}\n",
+        conversation.name.as_deref().unwrap_or("Conversation")
+    );
+
+    for message in messages.iter().filter(|m| !is_deleted(m)) {
+        let role = match message.role {
+            ChatMessageV2Role::System => "System",
+            ChatMessageV2Role::User => "User",
+            ChatMessageV2Role::Assistant => "Assistant",
+        };
+        out.push_str(&format!("\n## {}\n", role));
+
+        let parts: Vec<serde_json::Value> =
+            serde_json::from_str(&message.parts).unwrap_or_default();
+        let mut text = String::new();
+
+        for part in &parts {
+            if let Some(t) = part.get("text").and_then(|t| t.as_str()) {
+                if part.get("type").and_then(|t| t.as_str()) == Some("text") {
+                    text.push_str(t);
+                }
+                continue;
+            }
+
+            let Some(name) = tool_name(part) else {
+                continue;
+            };
+            flush_text(&mut out, &mut text);
+
+            let mut call = part.clone();
+            if let Some(call) = call.as_object_mut() {
+                call.remove("type");
+            }
+            out.push_str(&format!(
+                "\n```json tool:{}\n{}\n```\n",
+                name,
+                serde_json::to_string_pretty(&call).unwrap_or_default()
+            ));
+        }
+        flush_text(&mut out, &mut text);
+    }
+
+    out
+}
+
+fn flush_text(out: &mut String, text: &mut String) {
+    let trimmed = text.trim();
+    if !trimmed.is_empty() {
+        out.push_str(&format!("\n{}\n", trimmed));
+    }
+    text.clear();
+}
+
+// Tool parts are `tool-<name>` or `dynamic-tool` in the current message format, and
+// `tool-invocation` in the one before.
+fn tool_name(part: &serde_json::Value) -> Option<String> {
+    let kind = part.get("type")?.as_str()?;
+
+    match kind {
+        "tool-invocation" => part
+            .pointer("/toolInvocation/toolName")
+            .and_then(|n| n.as_str())
+            .map(str::to_string),
+        "dynamic-tool" => part
+            .get("toolName")
+            .and_then(|n| n.as_str())
+            .map(str::to_string),
+        _ => kind.strip_prefix("tool-").map(str::to_string),
+    }
+}
+
+// Messages have no delete column; the chat marks removed ones in their metadata instead.
+fn is_deleted(message: &ChatMessageV2) -> bool {
+    message
+        .metadata
+        .as_deref()
+        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+        .and_then(|m| m.get("deleted").and_then(|d| d.as_bool()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::setup_db, ChatConversation, ChatMessageV2, ChatMessageV2Role, Human, Session,
+    };
+
+    #[tokio::test]
+    async fn test_export_conversation() {
+        let db = setup_db().await;
+
+        let user = db.upsert_human(Human::default()).await.unwrap();
+        let session = db
+            .upsert_session(Session {
+                user_id: user.id.clone(),
+                title: "Test Session".to_string(),
+                ..Session::default()
+            })
+            .await
+            .unwrap();
+
+        let conversation = db
+            .create_conversation(ChatConversation {
+                id: uuid::Uuid::new_v4().to_string(),
+                session_id: session.id.clone(),
+                user_id: user.id.clone(),
+                name: Some("Planning".to_string()),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                system_prompt: None,
+            })
+            .await
+            .unwrap();
+
+        let messages = [
+            (
+                ChatMessageV2Role::System,
+                r#"[{"type":"text","text":"Be brief."}]"#,
+                None,
+            ),
+            (
+                ChatMessageV2Role::User,
+                r#"[{"type":"text","text":"When do we ship?"}]"#,
+                None,
+            ),
+            (
+                ChatMessageV2Role::User,
+                r#"[{"type":"text","text":"ignore this"}]"#,
+                Some(r#"{"deleted":true}"#),
+            ),
+            (
+                ChatMessageV2Role::Assistant,
+                r#"[
+                    {"type":"step-start"},
+                    {"type":"text","text":"Let me "},
+                    {"type":"text","text":"check."},
+                    {"type":"tool-search_notes","input":{"query":"ship"},"output":"Friday"},
+                    {"type":"text","text":"On Friday."}
+                ]"#,
+                None,
+            ),
+        ];
+        for (i, (role, parts, metadata)) in messages.into_iter().enumerate() {
+            db.create_message_v2(ChatMessageV2 {
+                id: uuid::Uuid::new_v4().to_string(),
+                conversation_id: conversation.id.clone(),
+                role,
+                parts: parts.to_string(),
+                metadata: metadata.map(str::to_string),
+                created_at: chrono::Utc::now() + chrono::Duration::seconds(i as i64),
+                updated_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+        }
+
+        let markdown = db
+            .export_conversation(&conversation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            markdown,
+            [
+                "# Planning",
+                "",
+                "## System",
+                "",
+                "Be brief.",
+                "",
+                "## User",
+                "",
+                "When do we ship?",
+                "",
+                "## Assistant",
+                "",
+                "Let me check.",
+                "",
+                "```json tool:search_notes",
+                "{",
+                "  \"input\": {",
+                "    \"query\": \"ship\"",
+                "  },",
+                "  \"output\": \"Friday\"",
+                "}",
+                "```",
+                "",
+                "On Friday.",
+                "",
+            ]
+            .join("\n")
+        );
+
+        assert_eq!(db.export_conversation("missing").await.unwrap(), None);
+    }
 }
@@ -49,6 +49,42 @@ user_common_derives! {
         #[schemars(with = "String", regex(pattern = "^[a-zA-Z]{2}$"))]
         #[serde(default)]
         pub summary_language: hypr_language::Language,
+        #[serde(default)]
+        pub recordings_dir: Option<String>,
+        #[serde(default)]
+        pub max_recording_seconds: Option<u64>,
+        // Live transcript diffs are appended to this file as JSON lines, for external tools.
+        #[serde(default)]
+        pub transcript_sink_path: Option<String>,
+        // Seconds of microphone audio kept before a recording starts. Off unless set.
+        #[serde(default)]
+        pub pre_roll_seconds: Option<u64>,
+        // Live words are sent as add/replace/remove ops instead of full lists per response.
+        #[serde(default)]
+        pub incremental_word_events: bool,
+        // Recording won't start, and warns while running, below this much free space. 0 disables.
+        #[serde(default)]
+        pub min_free_disk_mb: Option<u64>,
+        // Final words in these languages get capitalization and sentence-ending punctuation
+        // before they are saved, for STT backends that return neither.
+        #[specta(type = Vec<String>)]
+        #[serde(default)]
+        pub restore_punctuation_languages: Vec<hypr_language::Language>,
+        // How long muting fades out and unmuting fades in, in ms. 50ms when unset, and 0 switches
+        // at once.
+        #[serde(default)]
+        pub mute_ramp_ms: Option<u64>,
+        // Transcripts stay in this language while the STT is unsure which one it's hearing.
+        #[specta(type = Option<String>)]
+        #[schemars(with = "Option<String>")]
+        #[serde(default)]
+        pub fallback_language: Option<hypr_language::Language>,
+        // Sessions start without the STT when false, only recording. Live when unset.
+        #[serde(default)]
+        pub transcribe_live: Option<bool>,
+        // Large sessions' words are stored gzip-compressed. On when unset.
+        #[serde(default)]
+        pub compress_words: Option<bool>,
     }
 }
 
@@ -63,10 +99,30 @@ impl Default for ConfigGeneral {
             save_recordings: Some(false),
             selected_template_id: None,
             summary_language: hypr_language::ISO639::En.into(),
+            recordings_dir: None,
+            max_recording_seconds: None,
+            transcript_sink_path: None,
+            pre_roll_seconds: None,
+            incremental_word_events: false,
+            min_free_disk_mb: None,
+            restore_punctuation_languages: vec![],
+            mute_ramp_ms: None,
+            fallback_language: None,
+            transcribe_live: None,
+            compress_words: None,
         }
     }
 }
 
+impl ConfigGeneral {
+    // Size above which session words are compressed, or `None` if they never are.
+    pub fn words_compression_threshold(&self) -> Option<usize> {
+        self.compress_words
+            .unwrap_or(true)
+            .then_some(crate::DEFAULT_WORDS_COMPRESSION_THRESHOLD)
+    }
+}
+
 user_common_derives! {
     pub struct ConfigNotification {
         pub before: bool,
@@ -92,6 +148,64 @@ user_common_derives! {
         pub api_key: Option<String>,
         pub ai_specificity: Option<u8>,
         pub redemption_time_ms: Option<u32>,
+        // Redact emails, phone numbers, etc. from transcripts before they are sent to the LLM.
+        #[serde(default)]
+        pub redact_pii: bool,
+        // Strip filler words like "um" from transcripts before they are sent to the LLM.
+        #[serde(default)]
+        pub strip_filler_words: bool,
+        // Tried in order when the primary STT backend can't be reached, e.g. local, then cloud.
+        #[serde(default)]
+        pub stt_fallbacks: Vec<ConfigSttBackend>,
+        // Rate the primary STT backend wants its audio at, unless it says so itself. 16kHz when unset.
+        #[serde(default)]
+        pub stt_sample_rate: Option<u32>,
+        // Wait for a slow STT backend instead of dropping audio, at the cost of latency.
+        #[serde(default)]
+        pub stt_block_on_backpressure: bool,
+        // How long a dropped STT stream may take to reconnect, with audio held back meanwhile.
+        // Dropping ends transcription right away when unset.
+        #[serde(default)]
+        pub stt_reconnect_grace_seconds: Option<u64>,
+        // Audio is sent to the STT backend in frames of about this length instead of as captured,
+        // e.g. 100-200ms for cloud backends. Unset sends every chunk right away.
+        #[serde(default)]
+        pub stt_aggregation_window_ms: Option<u64>,
+        // What the primary STT backend charges per minute of audio, and the most a single meeting
+        // should cost. Only used for estimates; nothing stops at the cap.
+        #[serde(default)]
+        pub stt_price_per_minute: Option<f64>,
+        #[serde(default)]
+        pub stt_budget_cap: Option<f64>,
+        // Send the primary STT backend Opus instead of PCM. Only for backends known to take it.
+        #[serde(default)]
+        pub stt_opus: bool,
+        // Silence, in ms, before the STT backend ends an utterance, by ISO 639-1 code. Languages
+        // not listed use the built-in defaults.
+        #[serde(default)]
+        pub stt_redemption_time_ms: std::collections::HashMap<String, u32>,
+        // Style of generated titles. Any length, in title case, unless set.
+        #[serde(default)]
+        pub title_max_words: Option<u8>,
+        #[serde(default)]
+        pub title_sentence_case: bool,
+    }
+}
+
+user_common_derives! {
+    pub struct ConfigSttBackend {
+        pub base_url: String,
+        pub api_key: Option<String>,
+        pub model: Option<String>,
+        // Rate the backend wants its audio at. 16kHz when unset.
+        #[serde(default)]
+        pub sample_rate: Option<u32>,
+        // What the backend charges per minute of audio.
+        #[serde(default)]
+        pub price_per_minute: Option<f64>,
+        // Send this backend Opus instead of PCM.
+        #[serde(default)]
+        pub opus: bool,
     }
 }
 
@@ -102,6 +216,19 @@ impl Default for ConfigAI {
             api_key: None,
             ai_specificity: Some(3),
             redemption_time_ms: Some(500),
+            redact_pii: false,
+            strip_filler_words: false,
+            stt_fallbacks: vec![],
+            stt_sample_rate: None,
+            stt_block_on_backpressure: false,
+            stt_reconnect_grace_seconds: None,
+            stt_aggregation_window_ms: None,
+            stt_price_per_minute: None,
+            stt_budget_cap: None,
+            stt_opus: false,
+            stt_redemption_time_ms: Default::default(),
+            title_max_words: None,
+            title_sentence_case: false,
         }
     }
 }
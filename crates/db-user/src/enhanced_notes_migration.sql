@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS enhanced_notes (
+  id TEXT PRIMARY KEY,
+  session_id TEXT NOT NULL,
+  template_variant TEXT,
+  model TEXT NOT NULL,
+  content TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
@@ -0,0 +1,130 @@
+use super::{EnhancedNote, UserDatabase};
+
+impl UserDatabase {
+    pub async fn create_enhanced_note(
+        &self,
+        note: EnhancedNote,
+    ) -> Result<EnhancedNote, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query(
+                "INSERT INTO enhanced_notes (
+                    id, session_id, template_variant, model, content, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?)
+                RETURNING *",
+                vec![
+                    libsql::Value::Text(note.id),
+                    libsql::Value::Text(note.session_id),
+                    note.template_variant
+                        .map(libsql::Value::Text)
+                        .unwrap_or(libsql::Value::Null),
+                    libsql::Value::Text(note.model),
+                    libsql::Value::Text(note.content),
+                    libsql::Value::Text(note.created_at.to_rfc3339()),
+                ],
+            )
+            .await?;
+
+        let row = rows.next().await?.unwrap();
+        let note: EnhancedNote = libsql::de::from_row(&row)?;
+        Ok(note)
+    }
+
+    // Newest first.
+    pub async fn list_enhanced_notes(
+        &self,
+        session_id: impl Into<String>,
+    ) -> Result<Vec<EnhancedNote>, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query(
+                "SELECT * FROM enhanced_notes
+                WHERE session_id = ?
+                ORDER BY created_at DESC",
+                vec![session_id.into()],
+            )
+            .await?;
+
+        let mut notes = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let note: EnhancedNote = libsql::de::from_row(&row)?;
+            notes.push(note);
+        }
+        Ok(notes)
+    }
+
+    pub async fn delete_enhanced_note(&self, id: impl Into<String>) -> Result<(), crate::Error> {
+        let conn = self.conn()?;
+
+        conn.execute("DELETE FROM enhanced_notes WHERE id = ?", vec![id.into()])
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::setup_db, EnhancedNote, Human, Session};
+
+    #[tokio::test]
+    async fn test_enhanced_notes() {
+        let db = setup_db().await;
+
+        let human = db
+            .upsert_human(Human {
+                full_name: Some("test".to_string()),
+                ..Human::default()
+            })
+            .await
+            .unwrap();
+
+        let session = db
+            .upsert_session(Session {
+                user_id: human.id.clone(),
+                title: "test".to_string(),
+                raw_memo_html: "raw".to_string(),
+                enhanced_memo_html: Some("original".to_string()),
+                ..Session::default()
+            })
+            .await
+            .unwrap();
+
+        let created_at = chrono::Utc::now();
+        for (i, model) in ["hypr-llm", "llama-3.2", "gemma-3"].iter().enumerate() {
+            db.create_enhanced_note(EnhancedNote {
+                id: format!("note-{}", i),
+                session_id: session.id.clone(),
+                template_variant: (i == 1).then(|| "template-1".to_string()),
+                model: model.to_string(),
+                content: format!("# Version {}", i),
+                created_at: created_at + chrono::Duration::seconds(i as i64),
+            })
+            .await
+            .unwrap();
+        }
+
+        let notes = db.list_enhanced_notes(&session.id).await.unwrap();
+        assert_eq!(
+            notes.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(),
+            ["note-2", "note-1", "note-0"]
+        );
+        assert_eq!(notes[1].template_variant.as_deref(), Some("template-1"));
+        assert_eq!(notes[2].template_variant, None);
+        assert_eq!(notes[0].content, "# Version 2");
+
+        assert!(db.list_enhanced_notes("other").await.unwrap().is_empty());
+
+        db.delete_enhanced_note("note-1").await.unwrap();
+        let notes = db.list_enhanced_notes(&session.id).await.unwrap();
+        assert_eq!(notes.len(), 2);
+
+        let session = db
+            .get_session(crate::GetSessionFilter::Id(session.id.clone()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.enhanced_memo_html.as_deref(), Some("original"));
+    }
+}
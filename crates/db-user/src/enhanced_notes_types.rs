@@ -0,0 +1,15 @@
+use crate::user_common_derives;
+
+user_common_derives! {
+    // One generated version of a session's enhanced note. `sessions.enhanced_memo_html` stays the
+    // one being shown.
+    pub struct EnhancedNote {
+        pub id: String,
+        pub session_id: String,
+        // `None` for the default structure.
+        pub template_variant: Option<String>,
+        pub model: String,
+        pub content: String,
+        pub created_at: chrono::DateTime<chrono::Utc>,
+    }
+}
@@ -10,6 +10,8 @@ mod chat_messages_v2_ops;
 mod chat_messages_v2_types;
 mod config_ops;
 mod config_types;
+mod enhanced_notes_ops;
+mod enhanced_notes_types;
 mod events_ops;
 mod events_types;
 mod extensions_ops;
@@ -50,6 +52,10 @@ pub use config_ops::*;
 #[allow(unused)]
 pub use config_types::*;
 #[allow(unused)]
+pub use enhanced_notes_ops::*;
+#[allow(unused)]
+pub use enhanced_notes_types::*;
+#[allow(unused)]
 pub use events_ops::*;
 #[allow(unused)]
 pub use events_types::*;
@@ -122,14 +128,32 @@ macro_rules! user_common_derives {
     };
 }
 
+// Sessions with more than this much word JSON, in bytes, store it compressed.
+pub const DEFAULT_WORDS_COMPRESSION_THRESHOLD: usize = 64 * 1024;
+
 #[derive(Clone)]
 pub struct UserDatabase {
     db: hypr_db_core::Database,
+    compress_words_above: Option<usize>,
 }
 
 impl UserDatabase {
     pub fn from(db: hypr_db_core::Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            compress_words_above: None,
+        }
+    }
+
+    // Words are still read back either way, so this can be turned on for an existing database.
+    pub fn with_words_compression(mut self, threshold_bytes: usize) -> Self {
+        self.compress_words_above = Some(threshold_bytes);
+        self
+    }
+
+    // Applies to sessions written from now on; `None` stores all words as plain JSON.
+    pub fn set_words_compression(&mut self, threshold_bytes: Option<usize>) {
+        self.compress_words_above = threshold_bytes;
     }
 }
 
@@ -142,7 +166,7 @@ impl std::ops::Deref for UserDatabase {
 }
 
 // Append only. Do not reorder.
-const MIGRATIONS: [&str; 27] = [
+const MIGRATIONS: [&str; 31] = [
     include_str!("./calendars_migration.sql"),
     include_str!("./configs_migration.sql"),
     include_str!("./events_migration.sql"),
@@ -170,6 +194,10 @@ const MIGRATIONS: [&str; 27] = [
     include_str!("./templates_migration_1.sql"),
     include_str!("./chat_conversations_migration.sql"),
     include_str!("./chat_messages_v2_migration.sql"),
+    include_str!("./sessions_migration_5.sql"),
+    include_str!("./chat_conversations_migration_1.sql"),
+    include_str!("./enhanced_notes_migration.sql"),
+    include_str!("./sessions_migration_6.sql"),
 ];
 
 pub async fn migrate(db: &UserDatabase) -> Result<(), crate::Error> {
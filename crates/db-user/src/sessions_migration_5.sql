@@ -0,0 +1,4 @@
+ALTER TABLE
+  sessions
+ADD
+  COLUMN partial_words TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,4 @@
+ALTER TABLE
+  sessions
+ADD
+  COLUMN words_gz BLOB;
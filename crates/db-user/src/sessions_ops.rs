@@ -1,4 +1,5 @@
 use super::{
+    sessions_types::{decode_words, encode_words},
     Event, GetSessionFilter, Human, ListSessionFilter, ListSessionFilterCommon,
     ListSessionFilterSpecific, Session, UserDatabase,
 };
@@ -45,17 +46,14 @@ impl UserDatabase {
         let conn = self.conn()?;
         let mut rows = conn
             .query(
-                "SELECT words FROM sessions WHERE id = ?",
+                "SELECT words, words_gz FROM sessions WHERE id = ?",
                 vec![session_id.into()],
             )
             .await?;
 
         match rows.next().await? {
             None => Ok(vec![]),
-            Some(row) => {
-                let words_str: String = row.get(0)?;
-                Ok(serde_json::from_str(&words_str).unwrap_or_default())
-            }
+            Some(row) => decode_words(row.get_value(0)?, row.get_value(1)?),
         }
     }
 
@@ -116,6 +114,117 @@ impl UserDatabase {
         Ok(())
     }
 
+    // Deletes the session and every row that belongs to it, all or nothing.
+    // A session that doesn't exist is not an error, so this can be retried.
+    pub async fn purge_session(&self, id: impl Into<String>) -> Result<(), crate::Error> {
+        let session_id = id.into();
+        let conn = self.conn()?;
+
+        let tx = conn.transaction().await?;
+        for sql in [
+            "DELETE FROM chat_messages_v2 WHERE conversation_id IN (SELECT id FROM chat_conversations WHERE session_id = ?)",
+            "DELETE FROM chat_conversations WHERE session_id = ?",
+            "DELETE FROM chat_messages WHERE group_id IN (SELECT id FROM chat_groups WHERE session_id = ?)",
+            "DELETE FROM chat_groups WHERE session_id = ?",
+            "DELETE FROM session_participants WHERE session_id = ?",
+            "DELETE FROM tags_sessions WHERE session_id = ?",
+            "DELETE FROM enhanced_notes WHERE session_id = ?",
+            "DELETE FROM sessions WHERE id = ?",
+        ] {
+            tx.execute(sql, vec![session_id.clone()]).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // Appends the secondary session's words to the primary, moves everything else that belongs
+    // to it (participants, tags, chats and notes) to the primary, then deletes the secondary.
+    //
+    // Secondary words are shifted to start where the primary ended, as if both were recorded
+    // back to back, keeping the merged timeline monotonic. `primary_recording_s` is the length
+    // of the primary's recording, which recording-relative times are shifted by.
+    pub async fn merge_sessions(
+        &self,
+        primary_id: impl Into<String>,
+        secondary_id: impl Into<String>,
+        primary_recording_s: f64,
+    ) -> Result<Session, crate::Error> {
+        let (primary_id, secondary_id) = (primary_id.into(), secondary_id.into());
+        if primary_id == secondary_id {
+            return Err(crate::Error::InvalidInput(
+                "cannot merge a session into itself".to_string(),
+            ));
+        }
+
+        let mut primary = self
+            .get_session(GetSessionFilter::Id(primary_id.clone()))
+            .await?
+            .ok_or_else(|| crate::Error::InvalidInput(format!("no session: {}", primary_id)))?;
+        let secondary = self
+            .get_session(GetSessionFilter::Id(secondary_id.clone()))
+            .await?
+            .ok_or_else(|| crate::Error::InvalidInput(format!("no session: {}", secondary_id)))?;
+
+        let primary_words_end = primary.words.iter().filter_map(|w| w.end_ms).max();
+        let primary_end = primary
+            .record_end
+            .map(|t| t.timestamp_millis() as u64)
+            .or(primary_words_end);
+        let secondary_words_start = secondary.words.iter().filter_map(|w| w.start_ms).min();
+        let secondary_start = secondary
+            .record_start
+            .map(|t| t.timestamp_millis() as u64)
+            .or(secondary_words_start);
+
+        let mut shift_ms = match (primary_end, secondary_start) {
+            (Some(end), Some(start)) => end as i64 - start as i64,
+            _ => 0,
+        };
+        // Words can run past the recorded end, so the gap alone doesn't guarantee ordering.
+        if let (Some(end), Some(start)) = (primary_words_end, secondary_words_start) {
+            shift_ms = shift_ms.max(end as i64 - start as i64);
+        }
+
+        let shift = |ms: u64| (ms as i64 + shift_ms).max(0) as u64;
+        primary
+            .words
+            .extend(secondary.words.into_iter().map(|mut w| {
+                w.start_ms = w.start_ms.map(shift);
+                w.end_ms = w.end_ms.map(shift);
+                w.recording_start_s = w.recording_start_s.map(|s| s + primary_recording_s);
+                w.recording_end_s = w.recording_end_s.map(|s| s + primary_recording_s);
+                w
+            }));
+        primary.record_end = secondary.record_end.or(primary.record_end);
+
+        // Both or neither, so a failure can't leave the words in two sessions.
+        let conn = self.conn()?;
+        let tx = conn.transaction().await?;
+        let merged = self.upsert_session_with(&tx, primary).await?;
+        // The same tables as `purge_session`. Messages follow their chat group or conversation.
+        for sql in [
+            "UPDATE chat_conversations SET session_id = ? WHERE session_id = ?",
+            "UPDATE chat_groups SET session_id = ? WHERE session_id = ?",
+            "INSERT OR IGNORE INTO session_participants (session_id, human_id, deleted) SELECT ?, human_id, deleted FROM session_participants WHERE session_id = ?",
+            "INSERT OR IGNORE INTO tags_sessions (session_id, tag_id) SELECT ?, tag_id FROM tags_sessions WHERE session_id = ?",
+            "UPDATE enhanced_notes SET session_id = ? WHERE session_id = ?",
+        ] {
+            tx.execute(sql, vec![primary_id.clone(), secondary_id.clone()])
+                .await?;
+        }
+        for sql in [
+            "DELETE FROM session_participants WHERE session_id = ?",
+            "DELETE FROM tags_sessions WHERE session_id = ?",
+            "DELETE FROM sessions WHERE id = ?",
+        ] {
+            tx.execute(sql, vec![secondary_id.clone()]).await?;
+        }
+        tx.commit().await?;
+
+        Ok(merged)
+    }
+
     pub async fn list_sessions(
         &self,
         filter: Option<ListSessionFilter>,
@@ -265,6 +374,15 @@ impl UserDatabase {
 
     pub async fn upsert_session(&self, session: Session) -> Result<Session, crate::Error> {
         let conn = self.conn()?;
+        self.upsert_session_with(&conn, session).await
+    }
+
+    async fn upsert_session_with(
+        &self,
+        conn: &libsql::Connection,
+        session: Session,
+    ) -> Result<Session, crate::Error> {
+        let (words, words_gz) = encode_words(&session.words, self.compress_words_above)?;
 
         let mut rows = conn
             .query(
@@ -281,7 +399,9 @@ impl UserDatabase {
                     words,
                     record_start,
                     record_end,
-                    pre_meeting_memo_html
+                    pre_meeting_memo_html,
+                    partial_words,
+                    words_gz
                 ) VALUES (
                     :id,
                     :created_at,
@@ -295,7 +415,9 @@ impl UserDatabase {
                     :words,
                     :record_start,
                     :record_end,
-                    :pre_meeting_memo_html
+                    :pre_meeting_memo_html,
+                    :partial_words,
+                    :words_gz
                 )
                 ON CONFLICT(id) DO UPDATE SET
                     created_at = :created_at,
@@ -309,7 +431,9 @@ impl UserDatabase {
                     words = :words,
                     record_start = :record_start,
                     record_end = :record_end,
-                    pre_meeting_memo_html = :pre_meeting_memo_html
+                    pre_meeting_memo_html = :pre_meeting_memo_html,
+                    partial_words = :partial_words,
+                    words_gz = :words_gz
                 RETURNING *",
                 libsql::named_params! {
                     ":id": session.id.clone(),
@@ -321,10 +445,12 @@ impl UserDatabase {
                     ":raw_memo_html": session.raw_memo_html.clone(),
                     ":enhanced_memo_html": session.enhanced_memo_html.clone(),
                     ":conversations": "[]",
-                    ":words": serde_json::to_string(&session.words).unwrap(),
+                    ":words": words,
                     ":record_start": session.record_start.map(|dt| dt.to_rfc3339()),
                     ":record_end": session.record_end.map(|dt| dt.to_rfc3339()),
                     ":pre_meeting_memo_html": session.pre_meeting_memo_html.clone(),
+                    ":partial_words": serde_json::to_string(&session.partial_words).unwrap(),
+                    ":words_gz": words_gz,
                 },
             )
             .await?;
@@ -434,7 +560,10 @@ impl UserDatabase {
 
 #[cfg(test)]
 mod tests {
-    use crate::{tests::setup_db, Human, Session};
+    use crate::{
+        tests::setup_db, ChatConversation, ChatGroup, ChatMessageV2, ChatMessageV2Role,
+        EnhancedNote, GetSessionFilter, Human, Session, Tag, UserDatabase,
+    };
 
     #[tokio::test]
     async fn test_sessions() {
@@ -452,25 +581,21 @@ mod tests {
             .unwrap();
 
         let session = Session {
-            id: uuid::Uuid::new_v4().to_string(),
             user_id: user.id.clone(),
-            created_at: chrono::Utc::now(),
-            visited_at: chrono::Utc::now(),
-            calendar_event_id: None,
             title: "test".to_string(),
             raw_memo_html: "raw_memo_html_1".to_string(),
-            enhanced_memo_html: None,
-            conversations: vec![],
             words: vec![owhisper_interface::Word2 {
                 text: "hello 1".to_string(),
                 start_ms: None,
                 end_ms: None,
                 speaker: None,
                 confidence: None,
+                recording_start_s: None,
+                recording_end_s: None,
+                kind: owhisper_interface::WordKind::Speech,
             }],
-            record_start: None,
-            record_end: None,
             pre_meeting_memo_html: Some("pre_meeting_memo_html_1".to_string()),
+            ..Session::default()
         };
 
         let mut session = db.upsert_session(session).await.unwrap();
@@ -502,4 +627,440 @@ mod tests {
 
         assert_eq!(db.session_get_event(&session.id).await.unwrap(), None);
     }
+
+    #[tokio::test]
+    async fn test_merge_sessions() {
+        let db = setup_db().await;
+
+        let user = db
+            .upsert_human(Human {
+                full_name: Some("John Doe".to_string()),
+                ..Human::default()
+            })
+            .await
+            .unwrap();
+
+        let word = |text: &str, start_ms: u64, recording_start_s: f64| owhisper_interface::Word2 {
+            text: text.to_string(),
+            speaker: None,
+            confidence: None,
+            start_ms: Some(start_ms),
+            end_ms: Some(start_ms + 500),
+            recording_start_s: Some(recording_start_s),
+            recording_end_s: Some(recording_start_s + 0.5),
+            kind: owhisper_interface::WordKind::Speech,
+        };
+        let at = |ms: i64| chrono::DateTime::from_timestamp_millis(ms).unwrap();
+
+        let session = |title: &str, words, record_start, record_end| Session {
+            user_id: user.id.clone(),
+            title: title.to_string(),
+            words,
+            record_start: Some(record_start),
+            record_end: Some(record_end),
+            ..Session::default()
+        };
+
+        // Recorded from t=10s to t=20s, then again from t=60s to t=70s after an app restart.
+        let primary = db
+            .upsert_session(session(
+                "primary",
+                vec![word("one", 11_000, 1.0), word("two", 19_000, 9.0)],
+                at(10_000),
+                at(20_000),
+            ))
+            .await
+            .unwrap();
+        let secondary = db
+            .upsert_session(session(
+                "secondary",
+                vec![word("three", 61_000, 1.0), word("four", 65_000, 5.0)],
+                at(60_000),
+                at(70_000),
+            ))
+            .await
+            .unwrap();
+
+        // The user was in both sessions, the other participant only in the secondary.
+        let other = db
+            .upsert_human(Human {
+                full_name: Some("Jane Doe".to_string()),
+                ..Human::default()
+            })
+            .await
+            .unwrap();
+        for (session_id, human_id) in [
+            (&primary.id, &user.id),
+            (&secondary.id, &user.id),
+            (&secondary.id, &other.id),
+        ] {
+            db.session_add_participant(session_id, human_id)
+                .await
+                .unwrap();
+        }
+
+        let tag = db
+            .upsert_tag(Tag {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: "tag".to_string(),
+            })
+            .await
+            .unwrap();
+        db.assign_tag_to_session(&tag.id, &secondary.id)
+            .await
+            .unwrap();
+
+        db.create_chat_group(ChatGroup {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            name: None,
+            created_at: chrono::Utc::now(),
+            session_id: secondary.id.clone(),
+        })
+        .await
+        .unwrap();
+        let conversation = db
+            .create_conversation(ChatConversation {
+                id: uuid::Uuid::new_v4().to_string(),
+                session_id: secondary.id.clone(),
+                user_id: user.id.clone(),
+                name: None,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                system_prompt: None,
+            })
+            .await
+            .unwrap();
+        db.create_message_v2(ChatMessageV2 {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: conversation.id.clone(),
+            role: ChatMessageV2Role::User,
+            parts: "[]".to_string(),
+            metadata: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        })
+        .await
+        .unwrap();
+        db.create_enhanced_note(EnhancedNote {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: secondary.id.clone(),
+            template_variant: None,
+            model: "model".to_string(),
+            content: "content".to_string(),
+            created_at: chrono::Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        let merged = db
+            .merge_sessions(&primary.id, &secondary.id, 10.0)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            merged
+                .words
+                .iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["one", "two", "three", "four"]
+        );
+        assert_eq!(
+            merged
+                .words
+                .iter()
+                .map(|w| w.start_ms.unwrap())
+                .collect::<Vec<_>>(),
+            vec![11_000, 19_000, 21_000, 25_000]
+        );
+        assert_eq!(
+            merged
+                .words
+                .iter()
+                .map(|w| w.recording_start_s.unwrap())
+                .collect::<Vec<_>>(),
+            vec![1.0, 9.0, 11.0, 15.0]
+        );
+        assert_eq!(merged.record_end, Some(at(70_000)));
+
+        let sessions = db.list_sessions(None).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, primary.id);
+
+        let conn = db.conn().unwrap();
+        for table in [
+            "session_participants",
+            "tags_sessions",
+            "chat_groups",
+            "chat_conversations",
+            "enhanced_notes",
+        ] {
+            let mut rows = conn
+                .query(
+                    &format!("SELECT COUNT(*) FROM {} WHERE session_id = ?", table),
+                    vec![secondary.id.clone()],
+                )
+                .await
+                .unwrap();
+            let count: i64 = rows.next().await.unwrap().unwrap().get(0).unwrap();
+            assert_eq!(count, 0, "{}", table);
+        }
+
+        assert_eq!(
+            db.session_list_participants(&primary.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(db.list_session_tags(&primary.id).await.unwrap().len(), 1);
+        assert_eq!(db.list_chat_groups(&primary.id).await.unwrap().len(), 1);
+        assert_eq!(db.list_conversations(&primary.id).await.unwrap().len(), 1);
+        assert_eq!(
+            db.list_messages_v2(&conversation.id).await.unwrap().len(),
+            1
+        );
+        assert_eq!(db.list_enhanced_notes(&primary.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_session() {
+        let db = setup_db().await;
+
+        let user = db
+            .upsert_human(Human {
+                full_name: Some("John Doe".to_string()),
+                ..Human::default()
+            })
+            .await
+            .unwrap();
+
+        let session = db
+            .upsert_session(Session {
+                user_id: user.id.clone(),
+                title: "test".to_string(),
+                raw_memo_html: "raw_memo_html".to_string(),
+                ..Session::default()
+            })
+            .await
+            .unwrap();
+
+        db.session_add_participant(&session.id, &user.id)
+            .await
+            .unwrap();
+
+        let tag = db
+            .upsert_tag(Tag {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: "tag".to_string(),
+            })
+            .await
+            .unwrap();
+        db.assign_tag_to_session(&tag.id, &session.id)
+            .await
+            .unwrap();
+
+        let conversation = db
+            .create_conversation(ChatConversation {
+                id: uuid::Uuid::new_v4().to_string(),
+                session_id: session.id.clone(),
+                user_id: user.id.clone(),
+                name: None,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                system_prompt: None,
+            })
+            .await
+            .unwrap();
+        db.create_message_v2(ChatMessageV2 {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: conversation.id.clone(),
+            role: ChatMessageV2Role::User,
+            parts: "[]".to_string(),
+            metadata: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        db.purge_session(&session.id).await.unwrap();
+
+        assert!(db
+            .get_session(GetSessionFilter::Id(session.id.clone()))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            db.session_list_participants(&session.id)
+                .await
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_eq!(db.list_session_tags(&session.id).await.unwrap().len(), 0);
+        assert_eq!(db.list_conversations(&session.id).await.unwrap().len(), 0);
+        assert_eq!(
+            db.list_messages_v2(&conversation.id).await.unwrap().len(),
+            0
+        );
+
+        // Purging again, or purging an unknown session, is a no-op.
+        db.purge_session(&session.id).await.unwrap();
+        db.purge_session("missing").await.unwrap();
+    }
+
+    // The compressed column's storage type and size, as it is on disk.
+    async fn stored_words_gz(db: &UserDatabase, id: &str) -> (String, i64) {
+        let conn = db.conn().unwrap();
+        let mut rows = conn
+            .query(
+                "SELECT typeof(words_gz), ifnull(length(words_gz), 0) FROM sessions WHERE id = ?",
+                vec![id.to_string()],
+            )
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        (row.get(0).unwrap(), row.get(1).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_large_words_are_stored_compressed() {
+        let db = setup_db().await.with_words_compression(1024);
+
+        let user = db
+            .upsert_human(Human {
+                full_name: Some("John Doe".to_string()),
+                ..Human::default()
+            })
+            .await
+            .unwrap();
+
+        let words = (0..5000)
+            .map(|i| owhisper_interface::Word2 {
+                text: format!("word{}", i % 50),
+                speaker: Some(owhisper_interface::SpeakerIdentity::Unassigned {
+                    index: (i % 2) as u8,
+                }),
+                confidence: Some(0.9),
+                start_ms: Some(i * 300),
+                end_ms: Some(i * 300 + 250),
+                recording_start_s: None,
+                recording_end_s: None,
+                kind: owhisper_interface::WordKind::Speech,
+            })
+            .collect::<Vec<_>>();
+
+        let session = |words| Session {
+            user_id: user.id.clone(),
+            words,
+            ..Session::default()
+        };
+
+        let large = db.upsert_session(session(words.clone())).await.unwrap();
+        let small = db
+            .upsert_session(session(words[..3].to_vec()))
+            .await
+            .unwrap();
+
+        assert_eq!(large.words, words);
+        assert_eq!(db.get_words(&large.id).await.unwrap(), words);
+        assert_eq!(
+            db.get_session(GetSessionFilter::Id(large.id.clone()))
+                .await
+                .unwrap()
+                .unwrap()
+                .words,
+            words
+        );
+        assert_eq!(small.words, words[..3].to_vec());
+
+        let (kind, size) = stored_words_gz(&db, &large.id).await;
+        assert_eq!(kind, "blob");
+        assert!(size < serde_json::to_string(&words).unwrap().len() as i64 / 4);
+
+        // Small sessions stay plain JSON.
+        let (kind, _) = stored_words_gz(&db, &small.id).await;
+        assert_eq!(kind, "null");
+
+        // With compression off, large sessions are plain JSON too.
+        let mut db = db;
+        db.set_words_compression(None);
+        let uncompressed = db.upsert_session(session(words.clone())).await.unwrap();
+        let (kind, _) = stored_words_gz(&db, &uncompressed.id).await;
+        assert_eq!(kind, "null");
+        assert_eq!(db.get_words(&uncompressed.id).await.unwrap(), words);
+        // Rows compressed earlier still read back.
+        assert_eq!(db.get_words(&large.id).await.unwrap(), words);
+
+        // Rows written uncompressed read back once compression is turned on, and are
+        // compressed the next time they are saved.
+        db.set_words_compression(Some(1024));
+        assert_eq!(db.get_words(&uncompressed.id).await.unwrap(), words);
+        let resaved = db
+            .get_session(GetSessionFilter::Id(uncompressed.id.clone()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resaved.words, words);
+        db.upsert_session(resaved).await.unwrap();
+        let (kind, _) = stored_words_gz(&db, &uncompressed.id).await;
+        assert_eq!(kind, "blob");
+        assert_eq!(db.get_words(&uncompressed.id).await.unwrap(), words);
+
+        // A corrupt row is an error for that row, not a panic.
+        db.conn()
+            .unwrap()
+            .execute(
+                "UPDATE sessions SET words_gz = ? WHERE id = ?",
+                (
+                    libsql::Value::Blob(b"not gzip".to_vec()),
+                    libsql::Value::Text(large.id.clone()),
+                ),
+            )
+            .await
+            .unwrap();
+        assert!(db.get_words(&large.id).await.is_err());
+        assert!(db
+            .get_session(GetSessionFilter::Id(large.id.clone()))
+            .await
+            .is_err());
+        assert_eq!(db.get_words(&small.id).await.unwrap(), words[..3].to_vec());
+
+        // Partial words are decoded the same way.
+        db.conn()
+            .unwrap()
+            .execute(
+                "UPDATE sessions SET partial_words = ? WHERE id = ?",
+                (
+                    libsql::Value::Text("not json".to_string()),
+                    libsql::Value::Text(small.id.clone()),
+                ),
+            )
+            .await
+            .unwrap();
+        assert!(db
+            .get_session(GetSessionFilter::Id(small.id.clone()))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_words_compression_config() {
+        let general = |compress_words| crate::ConfigGeneral {
+            compress_words,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            general(None).words_compression_threshold(),
+            Some(crate::DEFAULT_WORDS_COMPRESSION_THRESHOLD)
+        );
+        assert_eq!(
+            general(Some(true)).words_compression_threshold(),
+            Some(crate::DEFAULT_WORDS_COMPRESSION_THRESHOLD)
+        );
+        assert_eq!(general(Some(false)).words_compression_threshold(), None);
+    }
 }
@@ -1,3 +1,5 @@
+use std::io::{Read, Write};
+
 use chrono::{DateTime, Utc};
 
 use crate::user_common_derives;
@@ -19,11 +21,35 @@ user_common_derives! {
         pub record_start: Option<DateTime<Utc>>,
         pub record_end: Option<DateTime<Utc>>,
         pub pre_meeting_memo_html: Option<String>,
+        // In-progress words, periodically flushed while listening. Not yet part of `words`.
+        #[serde(default)]
+        pub partial_words: Vec<owhisper_interface::Word2>,
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: Utc::now(),
+            visited_at: Utc::now(),
+            user_id: String::new(),
+            calendar_event_id: None,
+            title: String::new(),
+            raw_memo_html: String::new(),
+            enhanced_memo_html: None,
+            conversations: vec![],
+            words: vec![],
+            record_start: None,
+            record_end: None,
+            pre_meeting_memo_html: None,
+            partial_words: vec![],
+        }
     }
 }
 
 impl Session {
-    pub fn from_row(row: &libsql::Row) -> Result<Self, serde::de::value::Error> {
+    pub fn from_row(row: &libsql::Row) -> Result<Self, crate::Error> {
         Ok(Self {
             id: row.get(0).expect("id"),
             created_at: {
@@ -44,10 +70,7 @@ impl Session {
             raw_memo_html: row.get(6).expect("raw_memo_html"),
             enhanced_memo_html: row.get(7).expect("enhanced_memo_html"),
             conversations: vec![],
-            words: row
-                .get_str(9)
-                .map(|s| serde_json::from_str(s).unwrap())
-                .unwrap(),
+            words: decode_words(row.get_value(9)?, row.get_value(14)?)?,
             record_start: row.get_str(10).ok().and_then(|str| {
                 DateTime::parse_from_rfc3339(str)
                     .map(|dt| dt.with_timezone(&Utc))
@@ -59,6 +82,8 @@ impl Session {
                     .ok()
             }),
             pre_meeting_memo_html: row.get(12).expect("pre_meeting_memo_html"),
+            // Stored like `words`, but never compressed.
+            partial_words: decode_words(row.get_value(13)?, libsql::Value::Null)?,
         })
     }
 
@@ -71,6 +96,45 @@ impl Session {
     }
 }
 
+// Returns the `words` and `words_gz` columns. Words are only compressed once their JSON exceeds
+// `compress_above` bytes; `words` is then left as an empty list.
+pub(crate) fn encode_words(
+    words: &[owhisper_interface::Word2],
+    compress_above: Option<usize>,
+) -> Result<(String, libsql::Value), crate::Error> {
+    let json = serde_json::to_string(words)?;
+
+    match compress_above {
+        Some(threshold) if json.len() > threshold => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(json.as_bytes())?;
+            let gz = encoder.finish()?;
+            Ok(("[]".to_string(), libsql::Value::Blob(gz)))
+        }
+        _ => Ok((json, libsql::Value::Null)),
+    }
+}
+
+// `words_gz` takes precedence when set. A row that can't be decoded is an error, not an empty list.
+pub(crate) fn decode_words(
+    words: libsql::Value,
+    words_gz: libsql::Value,
+) -> Result<Vec<owhisper_interface::Word2>, crate::Error> {
+    match (words_gz, words) {
+        (libsql::Value::Blob(gz), _) => {
+            let mut json = String::new();
+            flate2::read::GzDecoder::new(gz.as_slice()).read_to_string(&mut json)?;
+            Ok(serde_json::from_str(&json)?)
+        }
+        (_, libsql::Value::Text(json)) => Ok(serde_json::from_str(&json)?),
+        (_, libsql::Value::Null) => Ok(vec![]),
+        (_, other) => Err(crate::Error::InvalidInput(format!(
+            "unexpected words column: {other:?}"
+        ))),
+    }
+}
+
 user_common_derives! {
     pub enum GetSessionFilter {
         #[serde(rename = "id")]
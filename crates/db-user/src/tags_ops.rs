@@ -121,19 +121,9 @@ mod tests {
 
         let _ = db
             .upsert_session(Session {
-                id: uuid::Uuid::new_v4().to_string(),
                 user_id: user.id.clone(),
-                created_at: chrono::Utc::now(),
-                visited_at: chrono::Utc::now(),
-                calendar_event_id: None,
                 title: "Test Session".to_string(),
-                raw_memo_html: "".to_string(),
-                enhanced_memo_html: None,
-                conversations: vec![],
-                words: vec![],
-                record_start: None,
-                record_end: None,
-                pre_meeting_memo_html: None,
+                ..Session::default()
             })
             .await
             .unwrap();
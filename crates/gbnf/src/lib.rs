@@ -6,7 +6,12 @@ pub enum Grammar {
     #[serde(rename = "enhance")]
     Enhance { sections: Option<Vec<String>> },
     #[serde(rename = "title")]
-    Title,
+    Title {
+        #[serde(default)]
+        max_words: Option<u8>,
+        #[serde(default)]
+        sentence_case: bool,
+    },
     #[serde(rename = "tags")]
     Tags,
     #[serde(rename = "email-to-name")]
@@ -17,7 +22,10 @@ impl Grammar {
     pub fn build(&self) -> String {
         match self {
             Grammar::Enhance { sections } => build_enhance_other_grammar(sections),
-            Grammar::Title => build_title_grammar(),
+            Grammar::Title {
+                max_words,
+                sentence_case,
+            } => build_title_grammar(*max_words, *sentence_case),
             Grammar::Tags => build_tags_grammar(),
             Grammar::EmailToName => build_email_to_name_grammar(),
         }
@@ -70,23 +78,36 @@ fn build_enhance_other_grammar(s: &Option<Vec<String>>) -> String {
     }
 }
 
-fn build_title_grammar() -> String {
+fn build_title_grammar(max_words: Option<u8>, sentence_case: bool) -> String {
+    let rest = match max_words {
+        Some(n) => format!("{{0,{}}}", n.max(1) - 1),
+        None => "*".to_string(),
+    };
+
     vec![
-        r##"lowercase ::= [a-z]"##,
-        r##"uppercase ::= [A-Z]"##,
-        r##"number ::= [0-9]"##,
-        r##"acronym ::= uppercase{2,}"##,
-        r##"word ::= acronym | (uppercase | number) (lowercase | number)*"##,
-        r##"root ::= word (" " word)*"##,
+        r##"lowercase ::= [a-z]"##.to_string(),
+        r##"uppercase ::= [A-Z]"##.to_string(),
+        r##"number ::= [0-9]"##.to_string(),
+        r##"acronym ::= uppercase{2,}"##.to_string(),
+        r##"word ::= acronym | (uppercase | number) (lowercase | number)*"##.to_string(),
+        // Sentence case still allows capitalized proper nouns after the first word.
+        if sentence_case {
+            r##"next ::= word | lowercase (lowercase | number)*"##.to_string()
+        } else {
+            r##"next ::= word"##.to_string()
+        },
+        format!(r##"root ::= word (" " next){}"##, rest),
     ]
     .join("\n")
 }
 
 fn build_tags_grammar() -> String {
     vec![
-        r##"root ::= "[" string ("," string ("," string ("," string)?)?)? "]""##,
-        r##"string ::= "\"" tag "\"""##,
-        r##"tag ::= [a-zA-Z] ([a-zA-Z0-9_-])*"##,
+        r##"root ::= "[" tag ("," tag ("," tag ("," tag)?)?)? "]""##,
+        r##"tag ::= "{\"name\":" string ",\"confidence\":" confidence "}""##,
+        r##"string ::= "\"" name "\"""##,
+        r##"name ::= [a-zA-Z] ([a-zA-Z0-9_-])*"##,
+        r##"confidence ::= "0." [0-9] [0-9]? | "1.0""##,
     ]
     .join("\n")
 }
@@ -116,7 +137,35 @@ mod tests {
             ("", false),
             ("   ", false),
         ] {
-            let result = gbnf.validate(&build_title_grammar(), input).unwrap();
+            let result = gbnf
+                .validate(&build_title_grammar(None, false), input)
+                .unwrap();
+            assert_eq!(result, expected, "failed: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_title_grammar_style() {
+        let gbnf = gbnf_validator::Validator::new().unwrap();
+
+        for (input, max_words, sentence_case, expected) in vec![
+            ("Quarterly Budget Review", Some(3), false, true),
+            ("Quarterly Budget Review Meeting", Some(3), false, false),
+            ("Budget", Some(1), false, true),
+            ("Budget Review", Some(1), false, false),
+            ("Quarterly budget review", None, false, false),
+            ("Quarterly budget review", None, true, true),
+            ("Quarterly budget review with API team", Some(6), true, true),
+            (
+                "Quarterly budget review with the API team",
+                Some(6),
+                true,
+                false,
+            ),
+            ("quarterly budget review", None, true, false),
+        ] {
+            let grammar = build_title_grammar(max_words, sentence_case);
+            let result = gbnf.validate(&grammar, input).unwrap();
             assert_eq!(result, expected, "failed: {}", input);
         }
     }
@@ -125,26 +174,61 @@ mod tests {
     fn test_tags_grammar() {
         let gbnf = gbnf_validator::Validator::new().unwrap();
 
+        let tags = |tags: &[(&str, &str)]| {
+            let tags = tags
+                .iter()
+                .map(|(name, confidence)| {
+                    format!(r#"{{"name":"{}","confidence":{}}}"#, name, confidence)
+                })
+                .collect::<Vec<_>>();
+            format!("[{}]", tags.join(","))
+        };
+
         for (input, expected) in vec![
+            (tags(&[("meeting", "0.9"), ("summary", "0.35")]), true),
+            (tags(&[("meeting", "1.0"), ("summary", "0.0")]), true),
             (
-                serde_json::to_string(&vec!["meeting", "summary"]).unwrap(),
-                true,
+                tags(&[
+                    ("meeting", "0.9"),
+                    ("summary", "0.9"),
+                    ("meeting", "0.9"),
+                    ("summary", "0.9"),
+                    ("meeting", "0.9"),
+                    ("summary", "0.9"),
+                ]),
+                false,
             ),
             (
-                serde_json::to_string(&vec![
-                    "meeting", "summary", "meeting", "summary", "meeting", "summary",
-                ])
-                .unwrap(),
+                tags(&[("meeting", "0.9"), ("summary", "0.9"), ("", "0.9")]),
                 false,
             ),
+            (tags(&[("@meeting", "0.9"), ("#summary", "0.9")]), false),
+            // Up to four tags; `max_tags` only narrows them down further.
             (
-                serde_json::to_string(&vec!["meeting", "summary", ""]).unwrap(),
+                tags(&[
+                    ("meeting", "0.9"),
+                    ("summary", "0.9"),
+                    ("roadmap", "0.9"),
+                    ("hiring", "0.9"),
+                ]),
+                true,
+            ),
+            (
+                tags(&[
+                    ("meeting", "0.9"),
+                    ("summary", "0.9"),
+                    ("roadmap", "0.9"),
+                    ("hiring", "0.9"),
+                    ("standup", "0.9"),
+                ]),
                 false,
             ),
+            // Confidence is required, and must be within 0.0 to 1.0.
             (
-                serde_json::to_string(&vec!["@meeting", "#summary"]).unwrap(),
+                serde_json::to_string(&vec!["meeting", "summary"]).unwrap(),
                 false,
             ),
+            (tags(&[("meeting", "1.5")]), false),
         ] {
             let result = gbnf.validate(&build_tags_grammar(), &input).unwrap();
             assert_eq!(result, expected, "failed: {}", input);
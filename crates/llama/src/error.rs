@@ -20,6 +20,23 @@ pub enum Error {
     DecodeError(#[from] llama_cpp_2::DecodeError),
     #[error(transparent)]
     TaskSendError(#[from] tokio::sync::mpsc::error::SendError<crate::Task>),
+    #[error("cancelled while prefilling the prompt")]
+    Cancelled,
+}
+
+impl Error {
+    // Allocation failures surface as null results from llama.cpp, or as a full KV cache.
+    pub fn is_oom(&self) -> bool {
+        match self {
+            Error::LlamaModelLoadError(llama_cpp_2::LlamaModelLoadError::NullResult) => true,
+            Error::LlamaContextLoadError(llama_cpp_2::LlamaContextLoadError::NullReturn) => true,
+            Error::DecodeError(llama_cpp_2::DecodeError::NoKvCacheSlot) => true,
+            e => {
+                let message = e.to_string().to_lowercase();
+                message.contains("out of memory") || message.contains("failed to allocate")
+            }
+        }
+    }
 }
 
 impl Serialize for Error {
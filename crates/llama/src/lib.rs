@@ -12,6 +12,7 @@ use llama_cpp_2::{
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_util::sync::CancellationToken;
 
+use hypr_compute_backend::load_with_fallback;
 use hypr_gguf::GgufExt;
 
 mod error;
@@ -24,6 +25,8 @@ pub use types::*;
 
 const DEFAULT_MAX_INPUT_TOKENS: u32 = 1024 * 16;
 const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 1024 * 2;
+// The prompt is decoded this many tokens at a time, so a cancel lands between batches.
+const PREFILL_BATCH_TOKENS: usize = 512;
 
 static LLAMA_BACKEND: OnceLock<Arc<LlamaBackend>> = OnceLock::new();
 
@@ -35,6 +38,10 @@ pub enum ModelName {
 
 pub struct Llama {
     pub name: ModelName,
+    // Where the model ended up loaded, which is the CPU if the GPU failed.
+    pub backend: ComputeBackend,
+    model: Arc<LlamaModel>,
+    template: String,
     task_sender: tokio::sync::mpsc::UnboundedSender<Task>,
 }
 
@@ -44,6 +51,8 @@ pub enum Task {
         response_sender: tokio::sync::mpsc::UnboundedSender<Response>,
         callback: Box<dyn FnMut(f64) + Send + 'static>,
         cancellation_token: CancellationToken,
+        // Told whether the prompt could be prefilled, which is where running out of memory shows.
+        prefilled: Option<tokio::sync::oneshot::Sender<Result<(), crate::Error>>>,
     },
 }
 
@@ -66,21 +75,21 @@ impl Llama {
             .clone()
     }
 
-    fn load_model(model_path: impl AsRef<std::path::Path>) -> Result<LlamaModel, crate::Error> {
+    fn load_model(
+        model_path: impl AsRef<std::path::Path>,
+        preferred: ComputeBackend,
+    ) -> Result<(LlamaModel, ComputeBackend), crate::Error> {
         let backend = Self::get_backend();
 
-        let full_gpu_layers: u32 = std::num::NonZeroU32::MAX.into();
-        let cpu_only_layers: u32 = 0;
+        load_with_fallback(preferred, |compute| {
+            let n_gpu_layers: u32 = match compute {
+                ComputeBackend::Gpu => std::num::NonZeroU32::MAX.into(),
+                ComputeBackend::Cpu => 0,
+            };
+            let params = LlamaModelParams::default().with_n_gpu_layers(n_gpu_layers);
 
-        let gpu_params = LlamaModelParams::default().with_n_gpu_layers(full_gpu_layers);
-
-        match LlamaModel::load_from_file(&backend, &model_path, &gpu_params) {
-            Ok(model) => Ok(model),
-            Err(_) => {
-                let params = LlamaModelParams::default().with_n_gpu_layers(cpu_only_layers);
-                LlamaModel::load_from_file(&backend, model_path, &params).map_err(Into::into)
-            }
-        }
+            LlamaModel::load_from_file(&backend, &model_path, &params).map_err(Into::into)
+        })
     }
 
     fn get_sampler(model: &LlamaModel, grammar: Option<&str>) -> LlamaSampler {
@@ -110,6 +119,23 @@ impl Llama {
         LlamaSampler::chain_simple(samplers)
     }
 
+    fn render_prompt(template: &str, request: &LlamaRequest) -> String {
+        let mut env = minijinja::Environment::new();
+        env.set_unknown_method_callback(minijinja_contrib::pycompat::unknown_method_callback);
+
+        env.add_template("chat", template).unwrap();
+        env.get_template("chat")
+            .unwrap()
+            // https://huggingface.co/unsloth/Qwen3-1.7B/blob/main/chat_template.jinja
+            .render(serde_json::json!({
+                "messages": request.messages,
+                "tools": request.tools,
+                "add_generation_prompt": true,
+                "enable_thinking": true
+            }))
+            .unwrap()
+    }
+
     fn process_prefill<'a>(
         model: &'a LlamaModel,
         backend: &LlamaBackend,
@@ -127,24 +153,9 @@ impl Llama {
         ),
         crate::Error,
     > {
-        let prompt = {
-            let mut env = minijinja::Environment::new();
-            env.set_unknown_method_callback(minijinja_contrib::pycompat::unknown_method_callback);
-
-            env.add_template("chat", template).unwrap();
-            env.get_template("chat")
-                .unwrap()
-                // https://huggingface.co/unsloth/Qwen3-1.7B/blob/main/chat_template.jinja
-                .render(serde_json::json!({
-                    "messages": request.messages,
-                    "tools": request.tools,
-                    "add_generation_prompt": true,
-                    "enable_thinking": true
-                }))
-                .unwrap()
-        };
+        let prompt = Self::render_prompt(template, request);
 
-        let mut tokens_list = model.str_to_token(&prompt, AddBos::Always).unwrap();
+        let mut tokens_list = model.str_to_token(&prompt, AddBos::Always)?;
         tokens_list.truncate(DEFAULT_MAX_INPUT_TOKENS as usize);
         let input_tokens_len = tokens_list.len() as u32;
         let max_output_tokens = request.max_tokens.unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS);
@@ -155,7 +166,7 @@ impl Llama {
             enabled: AtomicBool::new(true),
             callback: Mutex::new(callback),
             last_reported: Mutex::new(-1),
-            cancellation_token,
+            cancellation_token: cancellation_token.clone(),
         });
         let progress_data_ptr = Box::into_raw(progress_data) as *mut std::ffi::c_void;
 
@@ -203,33 +214,52 @@ impl Llama {
             false
         }
 
-        let mut ctx = model
-            .new_context(
+        let prefill = || -> Result<_, crate::Error> {
+            let n_batch = tokens_list.len().min(PREFILL_BATCH_TOKENS);
+
+            let mut ctx = model.new_context(
                 backend,
                 LlamaContextParams::default()
                     .with_n_ctx(std::num::NonZeroU32::new(
                         input_tokens_len + max_output_tokens,
                     ))
-                    .with_n_batch(input_tokens_len)
+                    .with_n_batch(n_batch as u32)
                     .with_embeddings(false)
                     .with_swa_full(false)
                     // https://github.com/ggml-org/llama.cpp/blob/f505bd8/include/llama.h#L182
                     .with_flash_attention_policy(0)
                     .with_cb_eval_user_data(progress_data_ptr)
                     .with_cb_eval(Some(cb_eval_fn)),
-            )
-            .unwrap();
+            )?;
 
-        let batch_size = tokens_list.len().max(512);
-        let mut batch = LlamaBatch::new(batch_size, 1);
+            let mut batch = LlamaBatch::new(n_batch, 1);
 
-        let last_index = (tokens_list.len() - 1) as i32;
-        for (i, token) in (0_i32..).zip(tokens_list.into_iter()) {
-            let is_last = i == last_index;
-            batch.add(token, i, &[0], is_last).unwrap();
-        }
+            let last_index = (tokens_list.len() - 1) as i32;
+            for (chunk_index, chunk) in tokens_list.chunks(n_batch).enumerate() {
+                if cancellation_token.is_cancelled() {
+                    return Err(crate::Error::Cancelled);
+                }
+
+                batch.clear();
+                for (offset, token) in chunk.iter().enumerate() {
+                    let i = (chunk_index * n_batch + offset) as i32;
+                    batch.add(*token, i, &[0], i == last_index)?;
+                }
+                ctx.decode(&mut batch)?;
+            }
 
-        ctx.decode(&mut batch).unwrap();
+            Ok((ctx, batch, last_index))
+        };
+
+        let (ctx, batch, last_index) = match prefill() {
+            Ok(prefilled) => prefilled,
+            Err(e) => {
+                unsafe {
+                    let _ = Box::from_raw(progress_data_ptr as *mut ProgressData);
+                }
+                return Err(e);
+            }
+        };
 
         unsafe {
             let progress_data = &*(progress_data_ptr as *mut ProgressData);
@@ -256,7 +286,7 @@ impl Llama {
         cancellation_token: CancellationToken,
         max_output_tokens: u32,
     ) {
-        let mut n_cur = batch.n_tokens();
+        let mut n_cur = last_index + 1;
         let mut decoder = encoding_rs::UTF_8.new_decoder();
         let mut sampler = Self::get_sampler(model, request.grammar.as_deref());
         let mut parser = StreamingParser::new();
@@ -311,12 +341,21 @@ impl Llama {
     }
 
     pub fn new(model_path: impl AsRef<std::path::Path>) -> Result<Self, crate::Error> {
+        Self::with_backend(model_path, ComputeBackend::Gpu)
+    }
+
+    // With `ComputeBackend::Gpu`, falls back to the CPU if the model can not be loaded on the GPU.
+    pub fn with_backend(
+        model_path: impl AsRef<std::path::Path>,
+        preferred: ComputeBackend,
+    ) -> Result<Self, crate::Error> {
         Self::setup_log();
 
-        let template = model_path.chat_format()?.unwrap();
+        let template = model_path.chat_format()?.unwrap().as_ref().to_string();
 
         let backend = Self::get_backend();
-        let model = Self::load_model(model_path)?;
+        let (model, compute_backend) = Self::load_model(model_path, preferred)?;
+        let model = Arc::new(model);
         let name = match model.meta_val_str("general.name") {
             Ok(name) if name == "hypr-llm" => ModelName::HyprLLM,
             Ok(name) => ModelName::Other(Some(name.to_string())),
@@ -326,6 +365,9 @@ impl Llama {
         let (task_sender, mut task_receiver) = tokio::sync::mpsc::unbounded_channel::<Task>();
 
         std::thread::spawn({
+            let model = model.clone();
+            let template = template.clone();
+
             move || {
                 while let Some(task) = task_receiver.blocking_recv() {
                     match task {
@@ -334,11 +376,12 @@ impl Llama {
                             response_sender,
                             callback,
                             cancellation_token,
+                            prefilled,
                         } => {
                             match Self::process_prefill(
                                 &model,
                                 &backend,
-                                template.as_ref(),
+                                &template,
                                 &request,
                                 callback,
                                 cancellation_token.clone(),
@@ -350,6 +393,9 @@ impl Llama {
                                     progress_data_ptr,
                                     max_output_tokens,
                                 )) => {
+                                    if let Some(prefilled) = prefilled {
+                                        let _ = prefilled.send(Ok(()));
+                                    }
                                     Self::process_generation(
                                         &model,
                                         ctx,
@@ -362,9 +408,19 @@ impl Llama {
                                         max_output_tokens,
                                     );
                                 }
+                                // Ends the stream the same way a cancel during generation does.
+                                Err(crate::Error::Cancelled) => {
+                                    drop(response_sender);
+                                    if let Some(prefilled) = prefilled {
+                                        let _ = prefilled.send(Ok(()));
+                                    }
+                                }
                                 Err(e) => {
                                     tracing::error!("Prefill failed: {:?}", e);
                                     drop(response_sender);
+                                    if let Some(prefilled) = prefilled {
+                                        let _ = prefilled.send(Err(e));
+                                    }
                                 }
                             }
                         }
@@ -373,7 +429,21 @@ impl Llama {
             }
         });
 
-        Ok(Self { name, task_sender })
+        Ok(Self {
+            name,
+            backend: compute_backend,
+            model,
+            template,
+            task_sender,
+        })
+    }
+
+    // Number of tokens the request's prompt is prefilled with.
+    pub fn count_prompt_tokens(&self, request: &LlamaRequest) -> Result<usize, crate::Error> {
+        let prompt = Self::render_prompt(&self.template, request);
+        let tokens = self.model.str_to_token(&prompt, AddBos::Always)?;
+
+        Ok(tokens.len().min(DEFAULT_MAX_INPUT_TOKENS as usize))
     }
 
     pub fn generate_stream(
@@ -406,6 +476,7 @@ impl Llama {
             response_sender,
             callback,
             cancellation_token: cancellation_token.clone(),
+            prefilled: None,
         };
 
         self.task_sender.send(task)?;
@@ -413,6 +484,35 @@ impl Llama {
 
         Ok((stream, cancellation_token))
     }
+
+    // Like `generate_stream`, but resolves once the prompt is prefilled, so a failure there,
+    // e.g. running out of memory for the context, is returned rather than ending the stream.
+    // Cancelling `cancellation_token`, also while prefilling, ends the stream.
+    pub async fn generate_stream_prefilled(
+        &self,
+        request: LlamaRequest,
+        cancellation_token: CancellationToken,
+    ) -> Result<impl futures_util::Stream<Item = Response>, crate::Error> {
+        let (response_sender, response_receiver) =
+            tokio::sync::mpsc::unbounded_channel::<Response>();
+        let (prefilled_tx, prefilled_rx) = tokio::sync::oneshot::channel();
+
+        let task = Task::Generate {
+            request,
+            response_sender,
+            callback: Box::new(|_| {}),
+            cancellation_token,
+            prefilled: Some(prefilled_tx),
+        };
+
+        self.task_sender.send(task)?;
+        // The worker only drops the sender without a result if it is gone, which ends the stream.
+        if let Ok(result) = prefilled_rx.await {
+            result?;
+        }
+
+        Ok(UnboundedReceiverStream::new(response_receiver))
+    }
 }
 
 #[cfg(test)]
@@ -609,4 +709,21 @@ mod tests {
         handle.await.unwrap();
         assert!(*last_progress.lock().unwrap() < 0.5);
     }
+
+    // cargo test test_prefilled_cancelled -p llama -- --nocapture --ignored
+    #[ignore]
+    #[tokio::test]
+    async fn test_prefilled_cancelled() {
+        let llama = get_model();
+        let request = get_request();
+
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let stream = llama
+            .generate_stream_prefilled(request, cancellation_token)
+            .await
+            .unwrap();
+        assert_eq!(stream.count().await, 0);
+    }
 }
@@ -6,7 +6,9 @@ use async_openai::types::{
 
 pub use llama_cpp_2::model::LlamaChatMessage;
 
-#[derive(Default)]
+pub use hypr_compute_backend::ComputeBackend;
+
+#[derive(Default, Clone)]
 pub struct LlamaRequest {
     pub grammar: Option<String>,
     pub messages: Vec<LlamaMessage>,
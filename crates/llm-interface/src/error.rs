@@ -10,6 +10,18 @@ pub enum Error {
     HyprLlamaError(#[from] hypr_llama::Error),
     #[error("Model not downloaded")]
     ModelNotDownloaded,
+    #[error("Out of memory: {0}")]
+    OutOfMemory(String),
+}
+
+impl Error {
+    pub fn is_oom(&self) -> bool {
+        match self {
+            Error::OutOfMemory(_) => true,
+            Error::HyprLlamaError(e) => e.is_oom(),
+            _ => false,
+        }
+    }
 }
 
 impl Serialize for Error {
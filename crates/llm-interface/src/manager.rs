@@ -1,12 +1,22 @@
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::sync::{watch, Mutex};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelState {
+    Unloaded,
+    Loading,
+    Loaded,
+}
+
 #[derive(Default)]
 pub struct ModelManagerBuilder {
     model_path: Option<PathBuf>,
+    fallback_model_path: Option<PathBuf>,
+    compute_backend: Option<hypr_llama::ComputeBackend>,
     activity_check_interval: Option<Duration>,
     inactivity_threshold: Option<Duration>,
 }
@@ -17,6 +27,18 @@ impl ModelManagerBuilder {
         self
     }
 
+    // Smaller model to retry with when the primary one runs out of memory.
+    pub fn fallback_model_path(mut self, v: impl Into<PathBuf>) -> Self {
+        self.fallback_model_path = Some(v.into());
+        self
+    }
+
+    // Defaults to the GPU, which falls back to the CPU if loading on it fails.
+    pub fn compute_backend(mut self, v: hypr_llama::ComputeBackend) -> Self {
+        self.compute_backend = Some(v);
+        self
+    }
+
     pub fn activity_check_interval(mut self, v: Duration) -> Self {
         self.activity_check_interval = Some(v);
         self
@@ -29,11 +51,30 @@ impl ModelManagerBuilder {
 
     pub fn build(self) -> ModelManager {
         let (shutdown_tx, shutdown_rx) = watch::channel(());
+        let (state_tx, _) = watch::channel(ModelState::Unloaded);
+
+        let fallback = self.fallback_model_path.map(|path| {
+            Arc::new(
+                ModelManagerBuilder {
+                    model_path: Some(path),
+                    fallback_model_path: None,
+                    compute_backend: self.compute_backend,
+                    activity_check_interval: self.activity_check_interval,
+                    inactivity_threshold: self.inactivity_threshold,
+                }
+                .build(),
+            )
+        });
 
         let manager = ModelManager {
             model_path: self.model_path.unwrap(),
+            fallback,
+            compute_backend: self
+                .compute_backend
+                .unwrap_or(hypr_llama::ComputeBackend::Gpu),
             model: Arc::new(tokio::sync::Mutex::new(None)),
             last_activity: Arc::new(tokio::sync::Mutex::new(None)),
+            state_tx: Arc::new(state_tx),
             activity_check_interval: self
                 .activity_check_interval
                 .unwrap_or(Duration::from_secs(3)),
@@ -51,8 +92,11 @@ impl ModelManagerBuilder {
 #[derive(Clone)]
 pub struct ModelManager {
     model_path: PathBuf,
+    fallback: Option<Arc<ModelManager>>,
+    compute_backend: hypr_llama::ComputeBackend,
     model: Arc<Mutex<Option<Arc<hypr_llama::Llama>>>>,
     last_activity: Arc<Mutex<Option<tokio::time::Instant>>>,
+    state_tx: Arc<watch::Sender<ModelState>>,
     activity_check_interval: Duration,
     inactivity_threshold: Duration,
     _drop_guard: Arc<DropGuard>,
@@ -76,58 +120,329 @@ impl ModelManager {
     pub async fn get_model(&self) -> Result<Arc<hypr_llama::Llama>, crate::Error> {
         self.update_activity().await;
 
-        let mut guard = self.model.lock().await;
+        get_or_load(&self.model, &self.state_tx, || {
+            if !self.model_path.exists() {
+                return Err(crate::Error::ModelNotDownloaded);
+            }
 
-        match guard.as_ref() {
-            Some(model) => Ok(model.clone()),
-            None => {
-                if !self.model_path.exists() {
-                    return Err(crate::Error::ModelNotDownloaded);
+            hypr_llama::Llama::with_backend(&self.model_path, self.compute_backend).map_err(|e| {
+                if e.is_oom() {
+                    crate::Error::OutOfMemory(e.to_string())
+                } else {
+                    e.into()
                 }
+            })
+        })
+        .await
+    }
 
-                let model = Arc::new(hypr_llama::Llama::new(&self.model_path)?);
-                *guard = Some(model.clone());
-                Ok(model)
-            }
+    // Follows the model being loaded, and unloaded after being idle.
+    pub fn subscribe(&self) -> watch::Receiver<ModelState> {
+        self.state_tx.subscribe()
+    }
+
+    // Where the loaded model runs, or `None` if it is not loaded.
+    pub async fn backend(&self) -> Option<hypr_llama::ComputeBackend> {
+        self.model.lock().await.as_ref().map(|model| model.backend)
+    }
+
+    // Frees the model now rather than once it has been idle for a while.
+    pub async fn unload(&self) {
+        if self.model.lock().await.take().is_some() {
+            self.state_tx.send_replace(ModelState::Unloaded);
         }
     }
 
+    // File stem of the model, e.g. `hypr-llm`.
+    pub fn name(&self) -> String {
+        self.model_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    // Runs `f` with the model, retrying once with the fallback model (if any) on out-of-memory.
+    pub async fn run<T, F, Fut>(&self, f: F) -> Result<Generation<T>, crate::Error>
+    where
+        F: Fn(Arc<hypr_llama::Llama>) -> Fut,
+        Fut: Future<Output = Result<T, crate::Error>>,
+    {
+        run_with_fallback(self, self.fallback.as_deref(), f).await
+    }
+
     pub async fn update_activity(&self) {
         *self.last_activity.lock().await = Some(tokio::time::Instant::now());
     }
 
     fn monitor(&self, shutdown_rx: watch::Receiver<()>) {
-        let activity_check_interval = self.activity_check_interval;
-        let inactivity_threshold = self.inactivity_threshold;
-
-        let model = self.model.clone();
-        let last_activity = self.last_activity.clone();
-
-        let _handle = tokio::spawn(async move {
-            let mut shutdown_rx = shutdown_rx;
-            let mut interval = tokio::time::interval(activity_check_interval);
-
-            interval.tick().await;
-
-            loop {
-                tokio::select! {
-                    _ = shutdown_rx.changed() => {
-                        break;
-                    },
-                    _ = interval.tick() => {
-                        let should_unload = match *last_activity.lock().await {
-                            Some(last_time) if last_time.elapsed() > inactivity_threshold => {
-                                model.lock().await.is_some()
-                            },
-                            _ => false
-                        };
-
-                        if should_unload {
-                            *model.lock().await = None;
-                        }
+        spawn_monitor(
+            self.model.clone(),
+            self.last_activity.clone(),
+            self.state_tx.clone(),
+            self.activity_check_interval,
+            self.inactivity_threshold,
+            shutdown_rx,
+        );
+    }
+}
+
+async fn get_or_load<M>(
+    model: &Mutex<Option<Arc<M>>>,
+    state_tx: &watch::Sender<ModelState>,
+    load: impl FnOnce() -> Result<M, crate::Error>,
+) -> Result<Arc<M>, crate::Error> {
+    let mut guard = model.lock().await;
+
+    if let Some(model) = guard.as_ref() {
+        return Ok(model.clone());
+    }
+
+    state_tx.send_replace(ModelState::Loading);
+
+    match load() {
+        Ok(model) => {
+            let model = Arc::new(model);
+            *guard = Some(model.clone());
+            state_tx.send_replace(ModelState::Loaded);
+            Ok(model)
+        }
+        Err(e) => {
+            state_tx.send_replace(ModelState::Unloaded);
+            Err(e)
+        }
+    }
+}
+
+fn spawn_monitor<M: Send + Sync + 'static>(
+    model: Arc<Mutex<Option<Arc<M>>>>,
+    last_activity: Arc<Mutex<Option<tokio::time::Instant>>>,
+    state_tx: Arc<watch::Sender<ModelState>>,
+    activity_check_interval: Duration,
+    inactivity_threshold: Duration,
+    mut shutdown_rx: watch::Receiver<()>,
+) {
+    let _handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(activity_check_interval);
+
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    break;
+                },
+                _ = interval.tick() => {
+                    let should_unload = match *last_activity.lock().await {
+                        Some(last_time) if last_time.elapsed() > inactivity_threshold => {
+                            model.lock().await.is_some()
+                        },
+                        _ => false
+                    };
+
+                    if should_unload {
+                        *model.lock().await = None;
+                        state_tx.send_replace(ModelState::Unloaded);
                     }
                 }
             }
-        });
+        }
+    });
+}
+
+pub struct Generation<T> {
+    pub output: T,
+    // Name of the model that produced `output`.
+    pub model: String,
+}
+
+trait ModelSource {
+    type Model;
+
+    fn name(&self) -> String;
+    fn load(&self) -> impl Future<Output = Result<Arc<Self::Model>, crate::Error>>;
+    fn unload(&self) -> impl Future<Output = ()>;
+}
+
+impl ModelSource for ModelManager {
+    type Model = hypr_llama::Llama;
+
+    fn name(&self) -> String {
+        ModelManager::name(self)
+    }
+
+    async fn load(&self) -> Result<Arc<Self::Model>, crate::Error> {
+        self.get_model().await
+    }
+
+    async fn unload(&self) {
+        ModelManager::unload(self).await
+    }
+}
+
+async fn run_with_fallback<S, T, F, Fut>(
+    primary: &S,
+    fallback: Option<&S>,
+    f: F,
+) -> Result<Generation<T>, crate::Error>
+where
+    S: ModelSource,
+    F: Fn(Arc<S::Model>) -> Fut,
+    Fut: Future<Output = Result<T, crate::Error>>,
+{
+    let result = match primary.load().await {
+        Ok(model) => f(model).await,
+        Err(e) => Err(e),
+    };
+
+    match (result, fallback) {
+        (Ok(output), _) => Ok(Generation {
+            output,
+            model: primary.name(),
+        }),
+        (Err(e), Some(fallback)) if e.is_oom() => {
+            tracing::warn!(error = ?e, model = fallback.name(), "retrying_with_fallback_model");
+
+            // So the two models are never in memory together.
+            primary.unload().await;
+            let output = f(fallback.load().await?).await?;
+            Ok(Generation {
+                output,
+                model: fallback.name(),
+            })
+        }
+        (Err(e), _) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeSource {
+        name: &'static str,
+        oom: bool,
+        unloaded: std::sync::atomic::AtomicBool,
+    }
+
+    impl FakeSource {
+        fn new(name: &'static str, oom: bool) -> Self {
+            Self {
+                name,
+                oom,
+                unloaded: Default::default(),
+            }
+        }
+
+        fn unloaded(&self) -> bool {
+            self.unloaded.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    impl ModelSource for FakeSource {
+        type Model = &'static str;
+
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        async fn load(&self) -> Result<Arc<Self::Model>, crate::Error> {
+            if self.oom {
+                Err(crate::Error::OutOfMemory(
+                    "failed to allocate buffer".into(),
+                ))
+            } else {
+                Ok(Arc::new(self.name))
+            }
+        }
+
+        async fn unload(&self) {
+            self.unloaded.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    async fn generate(model: Arc<&'static str>) -> Result<String, crate::Error> {
+        Ok(format!("generated by {}", model))
+    }
+
+    #[tokio::test]
+    async fn test_oom_falls_back() {
+        let primary = FakeSource::new("large", true);
+        let fallback = FakeSource::new("small", false);
+
+        let generation = run_with_fallback(&primary, Some(&fallback), generate)
+            .await
+            .unwrap();
+        assert_eq!(generation.output, "generated by small");
+        assert_eq!(generation.model, "small");
+        assert!(primary.unloaded());
+
+        // Without a fallback configured, the error is returned as before.
+        let result = run_with_fallback(&primary, None, generate).await;
+        assert!(result.is_err_and(|e| e.is_oom()));
+    }
+
+    #[tokio::test]
+    async fn test_oom_while_generating_falls_back() {
+        let primary = FakeSource::new("large", false);
+        let fallback = FakeSource::new("small", false);
+
+        // Loads fine, but runs out of memory for the context once the prompt is known.
+        let generation = run_with_fallback(&primary, Some(&fallback), |model| async move {
+            match *model {
+                "large" => Err(crate::Error::OutOfMemory("failed to create context".into())),
+                _ => generate(model).await,
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(generation.model, "small");
+        // Loaded, so it has to make room for the fallback.
+        assert!(primary.unloaded());
+
+        // Succeeding keeps the model loaded for the next task.
+        let primary = FakeSource::new("large", false);
+        let generation = run_with_fallback(&primary, Some(&fallback), generate)
+            .await
+            .unwrap();
+        assert_eq!(generation.model, "large");
+        assert!(!primary.unloaded());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_state_follows_load_and_idle_unload() {
+        let model = Arc::new(Mutex::new(None));
+        let last_activity = Arc::new(Mutex::new(None));
+        let (state_tx, mut state_rx) = watch::channel(ModelState::Unloaded);
+        let state_tx = Arc::new(state_tx);
+        let (_shutdown_tx, shutdown_rx) = watch::channel(());
+
+        spawn_monitor(
+            model.clone(),
+            last_activity.clone(),
+            state_tx.clone(),
+            Duration::from_secs(1),
+            Duration::from_secs(5),
+            shutdown_rx,
+        );
+
+        *last_activity.lock().await = Some(tokio::time::Instant::now());
+        let loaded = get_or_load(&model, &state_tx, || {
+            assert_eq!(*state_rx.borrow(), ModelState::Loading);
+            Ok("model")
+        })
+        .await
+        .unwrap();
+        assert_eq!(*loaded, "model");
+        assert_eq!(*state_rx.borrow_and_update(), ModelState::Loaded);
+
+        // Already loaded, so nothing changes.
+        get_or_load(&model, &state_tx, || unreachable!())
+            .await
+            .unwrap();
+        assert!(!state_rx.has_changed().unwrap());
+
+        tokio::time::sleep(Duration::from_secs(7)).await;
+        assert_eq!(*state_rx.borrow_and_update(), ModelState::Unloaded);
+        assert!(model.lock().await.is_none());
     }
 }
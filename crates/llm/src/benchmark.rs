@@ -0,0 +1,143 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{Stream, StreamExt};
+use tokio::time::Instant;
+
+use hypr_llama::{Llama, LlamaMessage, LlamaRequest, Response};
+use hypr_llm_interface::ModelManager;
+
+const BENCHMARK_PROMPT: &str =
+    "Summarize the benefits of taking meeting notes in three short paragraphs.";
+const BENCHMARK_MAX_TOKENS: u32 = 128;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct BenchmarkResult {
+    pub load_ms: u64,
+    pub prompt_tokens_per_sec: f64,
+    pub gen_tokens_per_sec: f64,
+}
+
+trait BenchmarkModel {
+    fn count_prompt_tokens(&self, request: &LlamaRequest) -> Result<usize, crate::Error>;
+
+    fn generate_stream(
+        &self,
+        request: LlamaRequest,
+    ) -> Result<impl Stream<Item = Response>, crate::Error>;
+}
+
+impl BenchmarkModel for Llama {
+    fn count_prompt_tokens(&self, request: &LlamaRequest) -> Result<usize, crate::Error> {
+        Ok(Llama::count_prompt_tokens(self, request)?)
+    }
+
+    fn generate_stream(
+        &self,
+        request: LlamaRequest,
+    ) -> Result<impl Stream<Item = Response>, crate::Error> {
+        Ok(Llama::generate_stream(self, request)?)
+    }
+}
+
+// Runs a fixed prompt through the current model. Nothing is persisted.
+pub async fn benchmark_model(provider: &ModelManager) -> Result<BenchmarkResult, crate::Error> {
+    run_benchmark(|| provider.get_model()).await
+}
+
+async fn run_benchmark<M, F, Fut>(load: F) -> Result<BenchmarkResult, crate::Error>
+where
+    M: BenchmarkModel,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Arc<M>, hypr_llm_interface::Error>>,
+{
+    let load_started = Instant::now();
+    let model = load().await?;
+    let load_ms = load_started.elapsed().as_millis() as u64;
+
+    let request = LlamaRequest {
+        messages: vec![LlamaMessage {
+            role: "user".into(),
+            content: BENCHMARK_PROMPT.into(),
+        }],
+        max_tokens: Some(BENCHMARK_MAX_TOKENS),
+        ..Default::default()
+    };
+    let prompt_tokens = model.count_prompt_tokens(&request)?;
+
+    let started = Instant::now();
+    let stream = model.generate_stream(request)?;
+    futures_util::pin_mut!(stream);
+
+    // Each response item carries roughly one sampled token.
+    let mut first_token_at = None;
+    let mut gen_tokens = 0;
+    while stream.next().await.is_some() {
+        first_token_at.get_or_insert_with(Instant::now);
+        gen_tokens += 1;
+    }
+    let finished = Instant::now();
+    let first_token_at = first_token_at.unwrap_or(finished);
+
+    Ok(BenchmarkResult {
+        load_ms,
+        // Prefill ends when the first token comes out.
+        prompt_tokens_per_sec: per_sec(prompt_tokens, first_token_at - started),
+        gen_tokens_per_sec: per_sec(gen_tokens.saturating_sub(1), finished - first_token_at),
+    })
+}
+
+fn per_sec(tokens: usize, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+
+    tokens as f64 / elapsed.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockModel {
+        prompt_tokens: usize,
+        gen_tokens: usize,
+    }
+
+    impl BenchmarkModel for MockModel {
+        fn count_prompt_tokens(&self, _request: &LlamaRequest) -> Result<usize, crate::Error> {
+            Ok(self.prompt_tokens)
+        }
+
+        fn generate_stream(
+            &self,
+            _request: LlamaRequest,
+        ) -> Result<impl Stream<Item = Response>, crate::Error> {
+            Ok(
+                futures_util::stream::iter(0..self.gen_tokens).then(|i| async move {
+                    let delay = if i == 0 { 100 } else { 10 };
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    Response::TextDelta("a".into())
+                }),
+            )
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_benchmark_reports_timings() {
+        let result = run_benchmark(|| async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(Arc::new(MockModel {
+                prompt_tokens: 50,
+                gen_tokens: 11,
+            }))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result.load_ms, 200);
+        assert_eq!(result.prompt_tokens_per_sec, 500.0);
+        assert_eq!(result.gen_tokens_per_sec, 100.0);
+    }
+}
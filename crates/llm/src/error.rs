@@ -10,6 +10,10 @@ pub enum Error {
     HyprLlamaError(#[from] hypr_llama::Error),
     #[error(transparent)]
     HyprLlmInterfaceError(#[from] hypr_llm_interface::Error),
+    #[error(transparent)]
+    HyprTemplateError(#[from] hypr_template::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
     #[error("Model not downloaded")]
     ModelNotDownloaded,
 }
@@ -1,7 +1,17 @@
 mod error;
 pub use error::*;
 
+mod benchmark;
+pub use benchmark::*;
+
+mod sections;
+pub use sections::*;
+
 mod task;
 pub use task::*;
 
-pub use hypr_llm_interface::ModelManager;
+mod trivial;
+pub use trivial::*;
+
+pub use hypr_llm_interface::{Generation, ModelManager};
+pub use hypr_template::Template;
@@ -0,0 +1,241 @@
+// What a streamed enhanced note is made of, as it is generated. Every `SectionStart` is closed
+// by a `SectionEnd`, and text in between belongs to that section.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum EnhanceEvent {
+    #[serde(rename = "sectionStart")]
+    SectionStart { name: String },
+    #[serde(rename = "textDelta")]
+    TextDelta { text: String },
+    #[serde(rename = "sectionEnd")]
+    SectionEnd,
+}
+
+const THINKING_START: &str = "<thinking>";
+const THINKING_END: &str = "</thinking>";
+
+// Splits generated markdown into sections at its `# ` headers, as the enhance grammar emits them.
+// The `<thinking>` block ahead of the note is dropped.
+//
+// Lines that can't be a header or the thinking block are passed on as they arrive; the others are
+// held back until their line is complete.
+#[derive(Debug, Default)]
+pub struct SectionParser {
+    line: String,
+    streaming_line: bool,
+    in_thinking: bool,
+    in_section: bool,
+}
+
+impl SectionParser {
+    pub fn push(&mut self, delta: &str) -> Vec<EnhanceEvent> {
+        let mut events = Vec::new();
+        self.push_into(delta, &mut events);
+        events
+    }
+
+    pub fn finish(&mut self) -> Vec<EnhanceEvent> {
+        let mut events = Vec::new();
+
+        let line = std::mem::take(&mut self.line);
+        if !line.is_empty() {
+            self.on_line(&line, &mut events);
+        }
+        if std::mem::take(&mut self.in_section) {
+            events.push(EnhanceEvent::SectionEnd);
+        }
+
+        events
+    }
+
+    fn push_into(&mut self, delta: &str, events: &mut Vec<EnhanceEvent>) {
+        for c in delta.chars() {
+            if self.streaming_line {
+                push_text(events, c.to_string());
+                self.streaming_line = c != '\n';
+                continue;
+            }
+
+            self.line.push(c);
+            if c == '\n' {
+                let line = std::mem::take(&mut self.line);
+                self.on_line(&line, events);
+            } else if !self.in_thinking && !may_be_held(&self.line) {
+                push_text(events, std::mem::take(&mut self.line));
+                self.streaming_line = true;
+            }
+        }
+    }
+
+    fn on_line(&mut self, line: &str, events: &mut Vec<EnhanceEvent>) {
+        if self.in_thinking {
+            if let Some(i) = line.find(THINKING_END) {
+                self.in_thinking = false;
+                // The first header follows right after, on the same line.
+                self.push_into(&line[i + THINKING_END.len()..], events);
+            }
+            return;
+        }
+
+        if let Some(rest) = line.trim_start().strip_prefix(THINKING_START) {
+            self.in_thinking = true;
+            self.on_line(rest, events);
+            return;
+        }
+
+        match line.trim_end().strip_prefix("# ") {
+            Some(name) => {
+                if self.in_section {
+                    events.push(EnhanceEvent::SectionEnd);
+                }
+                events.push(EnhanceEvent::SectionStart {
+                    name: name.trim().to_string(),
+                });
+                self.in_section = true;
+            }
+            None => push_text(events, line.to_string()),
+        }
+    }
+}
+
+// Whether the line so far could still turn out to be a header or the start of the thinking block.
+fn may_be_held(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with(['#', '<'])
+}
+
+// The note without the `<thinking>` block the enhance grammar puts ahead of it. Nothing is left if
+// generation stopped while still thinking.
+pub fn strip_thinking(output: &str) -> &str {
+    let Some(thinking) = output.trim_start().strip_prefix(THINKING_START) else {
+        return output;
+    };
+
+    match thinking.find(THINKING_END) {
+        Some(i) => &thinking[i + THINKING_END.len()..],
+        None => "",
+    }
+}
+
+// Adjacent text is merged, so a delta yields as few events as possible.
+fn push_text(events: &mut Vec<EnhanceEvent>, text: String) {
+    if text.is_empty() {
+        return;
+    }
+
+    match events.last_mut() {
+        Some(EnhanceEvent::TextDelta { text: last }) => last.push_str(&text),
+        _ => events.push(EnhanceEvent::TextDelta { text }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The whole output of each run, text merged, regardless of how it was chunked.
+    fn parse(deltas: &[&str]) -> Vec<EnhanceEvent> {
+        let mut parser = SectionParser::default();
+        let mut events = Vec::new();
+        for delta in deltas {
+            for event in parser.push(delta) {
+                match event {
+                    EnhanceEvent::TextDelta { text } => push_text(&mut events, text),
+                    event => events.push(event),
+                }
+            }
+        }
+        events.extend(parser.finish());
+        events
+    }
+
+    #[test]
+    fn test_section_events() {
+        let output = "<thinking>\n- Summary\n- Action Items\n</thinking># Summary\n\n- **Launch**: Beta ships Friday.\n\n# Action Items\n\n- **Docs**: Update the changelog.\n";
+
+        let expected = vec![
+            EnhanceEvent::SectionStart {
+                name: "Summary".to_string(),
+            },
+            EnhanceEvent::TextDelta {
+                text: "\n- **Launch**: Beta ships Friday.\n\n".to_string(),
+            },
+            EnhanceEvent::SectionEnd,
+            EnhanceEvent::SectionStart {
+                name: "Action Items".to_string(),
+            },
+            EnhanceEvent::TextDelta {
+                text: "\n- **Docs**: Update the changelog.\n".to_string(),
+            },
+            EnhanceEvent::SectionEnd,
+        ];
+
+        // As one delta, token-sized ones, and one character at a time.
+        assert_eq!(parse(&[output]), expected);
+
+        let tokens = output
+            .as_bytes()
+            .chunks(3)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parse(&tokens), expected);
+
+        let chars = output
+            .char_indices()
+            .map(|(i, c)| &output[i..i + c.len_utf8()])
+            .collect::<Vec<_>>();
+        assert_eq!(parse(&chars), expected);
+    }
+
+    #[test]
+    fn test_strip_thinking() {
+        assert_eq!(
+            strip_thinking("<thinking>\n- Summary\n</thinking># Summary\n- Done\n"),
+            "# Summary\n- Done\n"
+        );
+        assert_eq!(strip_thinking("<thinking>\n- Summ"), "");
+        assert_eq!(strip_thinking("# Summary\n- Done\n"), "# Summary\n- Done\n");
+
+        // Nor does it reach the events, even when indented.
+        assert_eq!(parse(&[" <thinking>\n- Summ", "ary\n</thinking>"]), vec![]);
+    }
+
+    #[test]
+    fn test_text_streams_before_line_ends() {
+        let mut parser = SectionParser::default();
+
+        assert_eq!(
+            parser.push("# Decisions\n- Ship"),
+            vec![
+                EnhanceEvent::SectionStart {
+                    name: "Decisions".to_string()
+                },
+                EnhanceEvent::TextDelta {
+                    text: "- Ship".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            parser.push(" on Friday"),
+            vec![EnhanceEvent::TextDelta {
+                text: " on Friday".to_string()
+            }]
+        );
+        // Could still become a header, so it waits for the rest of the line.
+        assert_eq!(
+            parser.push("\n#"),
+            vec![EnhanceEvent::TextDelta {
+                text: "\n".to_string()
+            }]
+        );
+        assert_eq!(
+            parser.finish(),
+            vec![
+                EnhanceEvent::TextDelta {
+                    text: "#".to_string()
+                },
+                EnhanceEvent::SectionEnd
+            ]
+        );
+    }
+}
@@ -1,111 +1,497 @@
-use futures_util::StreamExt;
+use futures_util::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
 
 use hypr_gbnf::Grammar;
-use hypr_llm_interface::ModelManager;
+use hypr_llm_interface::{Generation, ModelManager};
 use hypr_template::{render, Template};
 
+use crate::{is_trivial_context, strip_thinking, EnhanceEvent, SectionParser, UNTITLED_MEETING};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct PromptPreview {
+    pub system: String,
+    pub user: String,
+}
+
+// Renders the messages a task sends for `template`, which can be either half of the pair,
+// without loading the model.
+pub fn preview_prompt(
+    template: Template,
+    ctx: &serde_json::Map<String, serde_json::Value>,
+) -> Result<PromptPreview, crate::Error> {
+    let (system, user) = template_pair(template);
+
+    Ok(PromptPreview {
+        system: render(system, ctx)?,
+        user: render(user, ctx)?,
+    })
+}
+
+fn template_pair(template: Template) -> (Template, Template) {
+    match template {
+        Template::EnhanceSystem | Template::EnhanceUser => {
+            (Template::EnhanceSystem, Template::EnhanceUser)
+        }
+        Template::CreateTitleSystem | Template::CreateTitleUser => {
+            (Template::CreateTitleSystem, Template::CreateTitleUser)
+        }
+        Template::SuggestTagsSystem | Template::SuggestTagsUser => {
+            (Template::SuggestTagsSystem, Template::SuggestTagsUser)
+        }
+        Template::ChatSystem | Template::ChatUser => (Template::ChatSystem, Template::ChatUser),
+        Template::AutoGenerateTagsSystem | Template::AutoGenerateTagsUser => (
+            Template::AutoGenerateTagsSystem,
+            Template::AutoGenerateTagsUser,
+        ),
+        Template::PostprocessTranscriptSystem | Template::PostprocessTranscriptUser => (
+            Template::PostprocessTranscriptSystem,
+            Template::PostprocessTranscriptUser,
+        ),
+    }
+}
+
+fn prompt_messages(
+    system: Template,
+    user: Template,
+    ctx: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Vec<hypr_llama::LlamaMessage>, crate::Error> {
+    Ok(vec![
+        hypr_llama::LlamaMessage {
+            role: "system".into(),
+            content: render(system, ctx)?,
+        },
+        hypr_llama::LlamaMessage {
+            role: "user".into(),
+            content: render(user, ctx)?,
+        },
+    ])
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct TitleStyle {
+    pub max_words: Option<u8>,
+    // Only the first word capitalized, rather than every word.
+    pub sentence_case: bool,
+}
+
+// The style goes both into the prompt and into the grammar, which enforces the length.
+fn title_request(
+    ctx: &serde_json::Map<String, serde_json::Value>,
+    style: &TitleStyle,
+) -> Result<hypr_llama::LlamaRequest, crate::Error> {
+    let mut ctx = ctx.clone();
+    ctx.insert("max_words".into(), style.max_words.into());
+    ctx.insert("sentence_case".into(), style.sentence_case.into());
+
+    let grammar = Grammar::Title {
+        max_words: style.max_words,
+        sentence_case: style.sentence_case,
+    };
+
+    Ok(hypr_llama::LlamaRequest {
+        messages: prompt_messages(Template::CreateTitleSystem, Template::CreateTitleUser, &ctx)?,
+        max_tokens: Some(30),
+        grammar: Some(grammar.build()),
+        ..Default::default()
+    })
+}
+
 pub async fn generate_title(
     provider: &ModelManager,
     ctx: serde_json::Map<String, serde_json::Value>,
-) -> Result<String, crate::Error> {
-    let model = provider.get_model().await?;
-
-    let stream = model.generate_stream(hypr_llama::LlamaRequest {
-        messages: vec![
-            hypr_llama::LlamaMessage {
-                role: "system".into(),
-                content: render(Template::CreateTitleSystem, &ctx).unwrap(),
-            },
-            hypr_llama::LlamaMessage {
-                role: "user".into(),
-                content: render(Template::CreateTitleUser, &ctx).unwrap(),
-            },
-        ],
-        max_tokens: Some(30),
-        grammar: Some(Grammar::Title.build()),
+    style: &TitleStyle,
+    cancel: &CancellationToken,
+) -> Result<Generation<String>, crate::Error> {
+    if is_trivial_context(&ctx) {
+        return Ok(skipped(UNTITLED_MEETING.to_string()));
+    }
+
+    generate_text(provider, title_request(&ctx, style)?, cancel).await
+}
+
+// Section titles, when given, are enforced by the grammar. Free-form markdown otherwise.
+fn enhance_request(
+    ctx: &serde_json::Map<String, serde_json::Value>,
+    sections: Option<Vec<String>>,
+) -> Result<hypr_llama::LlamaRequest, crate::Error> {
+    Ok(hypr_llama::LlamaRequest {
+        messages: prompt_messages(Template::EnhanceSystem, Template::EnhanceUser, ctx)?,
+        grammar: sections.map(|sections| {
+            Grammar::Enhance {
+                sections: Some(sections),
+            }
+            .build()
+        }),
         ..Default::default()
-    })?;
+    })
+}
 
-    let items = stream
-        .collect::<Vec<_>>()
-        .await
-        .into_iter()
-        .filter_map(|r| match r {
-            hypr_llama::Response::TextDelta(content) => Some(content.clone()),
-            _ => None,
+// Streams the note to `on_event` as it is generated, split into sections, and returns the full
+// text, without the thinking block. A retry with the fallback model starts its events over from
+// the first section. Cancelling returns what was generated so far.
+pub async fn enhance(
+    provider: &ModelManager,
+    ctx: serde_json::Map<String, serde_json::Value>,
+    sections: Option<Vec<String>>,
+    on_event: impl Fn(EnhanceEvent),
+    cancel: &CancellationToken,
+) -> Result<Generation<String>, crate::Error> {
+    if is_trivial_context(&ctx) {
+        return Ok(skipped(String::new()));
+    }
+
+    let request = enhance_request(&ctx, sections)?;
+
+    let generation = provider
+        .run(|model| {
+            let request = request.clone();
+            let on_event = &on_event;
+
+            async move {
+                let stream = model
+                    .generate_stream_prefilled(request, cancel.clone())
+                    .await?
+                    .take_until(cancel.cancelled());
+                futures_util::pin_mut!(stream);
+                let mut parser = SectionParser::default();
+                let mut output = String::new();
+
+                while let Some(response) = stream.next().await {
+                    if let hypr_llama::Response::TextDelta(delta) = response {
+                        parser.push(&delta).into_iter().for_each(on_event);
+                        output.push_str(&delta);
+                    }
+                }
+                parser.finish().into_iter().for_each(on_event);
+
+                Ok(strip_thinking(&output).to_string())
+            }
         })
-        .collect::<Vec<_>>();
-    let text = items.join("");
+        .await?;
+
+    Ok(generation)
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TagSuggestion {
+    pub name: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    // Tags below this confidence are dropped.
+    pub min_confidence: Option<f32>,
+    // Keeps only the most confident tags, up to this many.
+    pub max_tags: Option<usize>,
+}
 
-    Ok(text)
+impl TagFilter {
+    pub fn apply(&self, mut tags: Vec<TagSuggestion>) -> Vec<TagSuggestion> {
+        if let Some(min_confidence) = self.min_confidence {
+            tags.retain(|t| t.confidence >= min_confidence);
+        }
+
+        tags.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+        if let Some(max_tags) = self.max_tags {
+            tags.truncate(max_tags);
+        }
+
+        tags
+    }
 }
 
 pub async fn generate_tags(
     provider: &ModelManager,
     ctx: serde_json::Map<String, serde_json::Value>,
-) -> Result<Vec<String>, crate::Error> {
-    let model = provider.get_model().await?;
-
-    let stream = model.generate_stream(hypr_llama::LlamaRequest {
-        messages: vec![
-            hypr_llama::LlamaMessage {
-                role: "system".into(),
-                content: render(Template::SuggestTagsSystem, &ctx).unwrap(),
-            },
-            hypr_llama::LlamaMessage {
-                role: "user".into(),
-                content: render(Template::SuggestTagsUser, &ctx).unwrap(),
-            },
-        ],
-        max_tokens: Some(30),
+    filter: &TagFilter,
+    cancel: &CancellationToken,
+) -> Result<Generation<Vec<TagSuggestion>>, crate::Error> {
+    if is_trivial_context(&ctx) {
+        return Ok(skipped(vec![]));
+    }
+
+    let request = hypr_llama::LlamaRequest {
+        messages: prompt_messages(Template::SuggestTagsSystem, Template::SuggestTagsUser, &ctx)?,
+        max_tokens: Some(100),
         grammar: Some(Grammar::Tags.build()),
         ..Default::default()
-    })?;
+    };
 
-    let items = stream
-        .collect::<Vec<_>>()
-        .await
-        .into_iter()
-        .filter_map(|r| match r {
-            hypr_llama::Response::TextDelta(content) => Some(content.clone()),
-            _ => None,
-        })
-        .collect::<Vec<_>>();
-    let text = items.join("");
-    let tags = serde_json::from_str::<Vec<String>>(&text).unwrap_or_default();
-    Ok(tags)
+    let generation = generate_text(provider, request, cancel).await?;
+    Ok(Generation {
+        output: parse_tags(&generation.output, filter),
+        model: generation.model,
+    })
+}
+
+fn parse_tags(output: &str, filter: &TagFilter) -> Vec<TagSuggestion> {
+    filter.apply(serde_json::from_str::<Vec<TagSuggestion>>(output).unwrap_or_default())
+}
+
+// Retrieved by the caller, e.g. from past notes and transcripts, for the answer to build on.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct ContextChunk {
+    pub text: String,
+    // Shown next to the text, so the answer can cite where it came from.
+    pub source_id: String,
+}
+
+fn chat_request(
+    ctx: &serde_json::Map<String, serde_json::Value>,
+    context_chunks: &[ContextChunk],
+) -> Result<hypr_llama::LlamaRequest, crate::Error> {
+    let mut ctx = ctx.clone();
+    ctx.insert(
+        "contextChunks".into(),
+        serde_json::to_value(context_chunks)?,
+    );
+
+    Ok(hypr_llama::LlamaRequest {
+        messages: prompt_messages(Template::ChatSystem, Template::ChatUser, &ctx)?,
+        ..Default::default()
+    })
+}
+
+// Answers the user's `message` in `ctx`, grounded in `context_chunks`.
+pub async fn chat(
+    provider: &ModelManager,
+    ctx: serde_json::Map<String, serde_json::Value>,
+    context_chunks: Vec<ContextChunk>,
+    cancel: &CancellationToken,
+) -> Result<Generation<String>, crate::Error> {
+    generate_text(provider, chat_request(&ctx, &context_chunks)?, cancel).await
 }
 
 pub async fn postprocess_transcript(
     provider: &ModelManager,
     ctx: serde_json::Map<String, serde_json::Value>,
-) -> Result<String, crate::Error> {
-    let model = provider.get_model().await?;
-
-    let stream = model.generate_stream(hypr_llama::LlamaRequest {
-        messages: vec![
-            hypr_llama::LlamaMessage {
-                role: "system".into(),
-                content: render(Template::PostprocessTranscriptSystem, &ctx).unwrap(),
-            },
-            hypr_llama::LlamaMessage {
-                role: "user".into(),
-                content: render(Template::PostprocessTranscriptUser, &ctx).unwrap(),
-            },
-        ],
+    cancel: &CancellationToken,
+) -> Result<Generation<String>, crate::Error> {
+    let request = hypr_llama::LlamaRequest {
+        messages: prompt_messages(
+            Template::PostprocessTranscriptSystem,
+            Template::PostprocessTranscriptUser,
+            &ctx,
+        )?,
         max_tokens: Some(100),
         ..Default::default()
-    })?;
+    };
+
+    generate_text(provider, request, cancel).await
+}
+
+// For tasks answered without the model, which is why it has no name.
+fn skipped<T>(output: T) -> Generation<T> {
+    Generation {
+        output,
+        model: String::new(),
+    }
+}
+
+// Retried as a whole with the fallback model, if the primary one runs out of memory.
+async fn generate_text(
+    provider: &ModelManager,
+    request: hypr_llama::LlamaRequest,
+    cancel: &CancellationToken,
+) -> Result<Generation<String>, crate::Error> {
+    let generation = provider
+        .run(|model| {
+            let request = request.clone();
+
+            async move {
+                let stream = model
+                    .generate_stream_prefilled(request, cancel.clone())
+                    .await?;
+                Ok(collect_text(stream, cancel).await)
+            }
+        })
+        .await?;
 
+    Ok(generation)
+}
+
+// The text generated until the stream ends or `cancel` fires. Dropping the stream stops the model.
+pub async fn collect_text(
+    stream: impl Stream<Item = hypr_llama::Response>,
+    cancel: &CancellationToken,
+) -> String {
     let items = stream
-        .collect::<Vec<_>>()
-        .await
-        .into_iter()
-        .filter_map(|r| match r {
-            hypr_llama::Response::TextDelta(content) => Some(content.clone()),
-            _ => None,
+        .take_until(cancel.cancelled())
+        .filter_map(|r| async move {
+            match r {
+                hypr_llama::Response::TextDelta(content) => Some(content),
+                _ => None,
+            }
         })
-        .collect::<Vec<_>>();
-    let text = items.join("");
-    Ok(text)
+        .collect::<Vec<_>>()
+        .await;
+
+    items.join("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags_filters_by_confidence() {
+        let output = r#"[{"name":"standup","confidence":0.4},{"name":"roadmap","confidence":0.95},{"name":"hiring","confidence":0.7},{"name":"lunch","confidence":0.1},{"name":"q3-planning","confidence":0.85}]"#;
+
+        let names = |tags: Vec<TagSuggestion>| tags.into_iter().map(|t| t.name).collect::<Vec<_>>();
+
+        assert_eq!(
+            names(parse_tags(output, &TagFilter::default())),
+            vec!["roadmap", "q3-planning", "hiring", "standup", "lunch"]
+        );
+
+        let filter = TagFilter {
+            min_confidence: Some(0.5),
+            max_tags: None,
+        };
+        assert_eq!(
+            names(parse_tags(output, &filter)),
+            vec!["roadmap", "q3-planning", "hiring"]
+        );
+
+        let filter = TagFilter {
+            min_confidence: Some(0.5),
+            max_tags: Some(2),
+        };
+        assert_eq!(
+            names(parse_tags(output, &filter)),
+            vec!["roadmap", "q3-planning"]
+        );
+
+        assert!(parse_tags("not json", &filter).is_empty());
+    }
+
+    #[test]
+    fn test_preview_matches_generation_request() {
+        let ctx = serde_json::json!({ "enhanced_note": "# Roadmap\n- Ship Q3 planning" })
+            .as_object()
+            .unwrap()
+            .clone();
+
+        let messages = title_request(&ctx, &TitleStyle::default())
+            .unwrap()
+            .messages;
+
+        for template in [Template::CreateTitleSystem, Template::CreateTitleUser] {
+            let preview = preview_prompt(template, &ctx).unwrap();
+            assert_eq!(preview.system, messages[0].content);
+            assert_eq!(preview.user, messages[1].content);
+        }
+        assert!(messages[1].content.contains("Ship Q3 planning"));
+    }
+
+    #[test]
+    fn test_title_style_reaches_prompt_and_grammar() {
+        let ctx = serde_json::json!({ "enhanced_note": "# Roadmap\n- Ship Q3 planning" })
+            .as_object()
+            .unwrap()
+            .clone();
+
+        let style = TitleStyle {
+            max_words: Some(4),
+            sentence_case: true,
+        };
+        let request = title_request(&ctx, &style).unwrap();
+
+        let system = &request.messages[0].content;
+        assert!(system.contains("at most 4 words"), "{}", system);
+        assert!(system.contains("sentence case"), "{}", system);
+        assert!(request
+            .grammar
+            .unwrap()
+            .contains(r#"root ::= word (" " next){0,3}"#));
+
+        let request = title_request(&ctx, &TitleStyle::default()).unwrap();
+        let system = &request.messages[0].content;
+        assert!(!system.contains("at most"), "{}", system);
+        assert!(system.contains("title case"), "{}", system);
+        assert!(request
+            .grammar
+            .unwrap()
+            .contains(r#"root ::= word (" " next)*"#));
+    }
+
+    #[test]
+    fn test_chat_includes_context_chunks() {
+        let ctx = serde_json::json!({ "message": "When does the beta ship?" })
+            .as_object()
+            .unwrap()
+            .clone();
+        let chunks = vec![
+            ContextChunk {
+                text: "We ship the beta on Friday.".to_string(),
+                source_id: "session-1".to_string(),
+            },
+            ContextChunk {
+                text: "Beta feedback goes to #beta.".to_string(),
+                source_id: "note-2".to_string(),
+            },
+        ];
+
+        let user = &chat_request(&ctx, &chunks).unwrap().messages[1].content;
+        assert!(user.starts_with("When does the beta ship?"), "{}", user);
+        assert!(
+            user.contains("[source: session-1]\nWe ship the beta on Friday."),
+            "{}",
+            user
+        );
+        assert!(
+            user.contains("[source: note-2]\nBeta feedback goes to #beta."),
+            "{}",
+            user
+        );
+
+        let user = &chat_request(&ctx, &[]).unwrap().messages[1].content;
+        assert_eq!(user, "When does the beta ship?");
+    }
+
+    #[tokio::test]
+    async fn test_trivial_context_skips_the_model() {
+        // Never downloaded, so any task that reaches the model fails.
+        let provider = ModelManager::builder()
+            .model_path("/nonexistent/model.gguf")
+            .build();
+        let cancel = CancellationToken::new();
+
+        let empty = serde_json::json!({ "editor": "<p></p>", "words": "[]" });
+        let near_empty = serde_json::json!({
+            "editor": "",
+            "words": [{ "text": "hello", "start_ms": 0, "end_ms": 400 }]
+        });
+
+        for ctx in [empty, near_empty] {
+            let ctx = ctx.as_object().unwrap().clone();
+
+            let title = generate_title(&provider, ctx.clone(), &TitleStyle::default(), &cancel)
+                .await
+                .unwrap();
+            assert_eq!(title.output, UNTITLED_MEETING);
+
+            let tags = generate_tags(&provider, ctx.clone(), &TagFilter::default(), &cancel)
+                .await
+                .unwrap();
+            assert!(tags.output.is_empty());
+
+            let note = enhance(&provider, ctx, None, |_| panic!("no events"), &cancel)
+                .await
+                .unwrap();
+            assert_eq!(note.output, "");
+        }
+
+        let ctx = serde_json::json!({ "enhanced_note": "# Roadmap\n- Ship Q3 planning" })
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(
+            generate_title(&provider, ctx, &TitleStyle::default(), &cancel)
+                .await
+                .is_err()
+        );
+    }
 }
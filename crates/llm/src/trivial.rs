@@ -0,0 +1,132 @@
+// A transcript with fewer words, or less speech, than this has nothing to summarize.
+pub const MIN_TRANSCRIPT_WORDS: usize = 5;
+pub const MIN_TRANSCRIPT_SPEECH_MS: u64 = 3_000;
+
+// Title used when there is nothing to generate one from.
+pub const UNTITLED_MEETING: &str = "Untitled meeting";
+
+// Context keys that hold text written by the user or generated earlier.
+const NOTE_KEYS: &[&str] = &["editor", "enhanced_note", "content", "transcript"];
+
+/// Whether `ctx` has nothing for a task to work with: no note text, and a trivial transcript if
+/// there is one at all. The model would only make something up from it.
+pub fn is_trivial_context(ctx: &serde_json::Map<String, serde_json::Value>) -> bool {
+    let has_notes = NOTE_KEYS
+        .iter()
+        .filter_map(|key| ctx.get(*key).and_then(|v| v.as_str()))
+        .any(|text| !strip_tags(text).trim().is_empty());
+
+    !has_notes && is_trivial_transcript(ctx.get("words"))
+}
+
+fn is_trivial_transcript(words: Option<&serde_json::Value>) -> bool {
+    // Passed as a JSON string for the `timeline` filter.
+    let words = match words {
+        Some(serde_json::Value::String(s)) => {
+            serde_json::from_str::<Vec<serde_json::Value>>(s).unwrap_or_default()
+        }
+        Some(serde_json::Value::Array(words)) => words.clone(),
+        _ => vec![],
+    };
+
+    let spoken = words
+        .iter()
+        .filter(|w| {
+            w.get("kind")
+                .and_then(|k| k.as_str())
+                .is_none_or(|k| k == "speech")
+        })
+        .filter(|w| {
+            w.get("text")
+                .and_then(|t| t.as_str())
+                .is_some_and(|t| !t.trim().is_empty())
+        })
+        .collect::<Vec<_>>();
+
+    let durations = spoken
+        .iter()
+        .filter_map(|w| {
+            let start = w.get("start_ms")?.as_u64()?;
+            let end = w.get("end_ms")?.as_u64()?;
+            Some(end.saturating_sub(start))
+        })
+        .collect::<Vec<_>>();
+
+    // Untimed words are judged by their count alone.
+    spoken.len() < MIN_TRANSCRIPT_WORDS
+        || (!durations.is_empty() && durations.iter().sum::<u64>() < MIN_TRANSCRIPT_SPEECH_MS)
+}
+
+// The editor's notes are HTML, and an empty one is still `<p></p>`.
+fn strip_tags(html: &str) -> String {
+    let mut in_tag = false;
+
+    html.chars()
+        .filter(|&c| match c {
+            '<' => {
+                in_tag = true;
+                false
+            }
+            '>' => {
+                in_tag = false;
+                false
+            }
+            _ => !in_tag,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn words(texts: &[&str], ms_each: u64) -> serde_json::Value {
+        texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let start_ms = i as u64 * ms_each;
+                serde_json::json!({
+                    "text": text,
+                    "start_ms": start_ms,
+                    "end_ms": start_ms + ms_each,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trivial_context() {
+        let sentence = ["we", "ship", "the", "beta", "on", "friday"];
+
+        assert!(is_trivial_context(&ctx(serde_json::json!({}))));
+        assert!(is_trivial_context(&ctx(
+            serde_json::json!({ "editor": "<p></p>", "words": "[]" })
+        )));
+        assert!(is_trivial_context(&ctx(
+            serde_json::json!({ "words": words(&["um", "okay"], 400) })
+        )));
+        // Enough words, but mumbled in under a second.
+        assert!(is_trivial_context(&ctx(
+            serde_json::json!({ "words": words(&sentence, 100) })
+        )));
+
+        assert!(!is_trivial_context(&ctx(
+            serde_json::json!({ "words": words(&sentence, 600) })
+        )));
+        assert!(!is_trivial_context(&ctx(serde_json::json!({
+            "words": serde_json::to_string(&words(&sentence, 600)).unwrap()
+        }))));
+        // Notes alone are enough to work with.
+        assert!(!is_trivial_context(&ctx(
+            serde_json::json!({ "editor": "<p>beta launch</p>", "words": "[]" })
+        )));
+        assert!(!is_trivial_context(&ctx(
+            serde_json::json!({ "enhanced_note": "# Roadmap\n- Ship Q3 planning" })
+        )));
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Mutex;
+
+use notify_rust::Timeout;
+
+pub use hypr_notification_interface::*;
+
+// Sent by the notification server when the body itself is clicked.
+const DEFAULT_ACTION: &str = "default";
+// Reported by `notify-rust` once the notification is closed without an action.
+const CLOSED_ACTION: &str = "__closed";
+
+static CONFIRM_CB: Mutex<Option<Box<dyn Fn(String) + Send + Sync>>> = Mutex::new(None);
+static DISMISS_CB: Mutex<Option<Box<dyn Fn(String) + Send + Sync>>> = Mutex::new(None);
+
+pub fn setup_notification_dismiss_handler<F>(f: F)
+where
+    F: Fn(String) + Send + Sync + 'static,
+{
+    *DISMISS_CB.lock().unwrap() = Some(Box::new(f));
+}
+
+pub fn setup_notification_confirm_handler<F>(f: F)
+where
+    F: Fn(String) + Send + Sync + 'static,
+{
+    *CONFIRM_CB.lock().unwrap() = Some(Box::new(f));
+}
+
+fn rust_on_notification_confirm(id: String) {
+    if let Some(cb) = CONFIRM_CB.lock().unwrap().as_ref() {
+        cb(id);
+    }
+}
+
+fn rust_on_notification_dismiss(id: String) {
+    if let Some(cb) = DISMISS_CB.lock().unwrap().as_ref() {
+        cb(id);
+    }
+}
+
+fn to_desktop_notification(
+    notification: &hypr_notification_interface::Notification,
+) -> notify_rust::Notification {
+    let timeout = notification
+        .timeout
+        .unwrap_or(std::time::Duration::from_secs(5));
+
+    let mut desktop = notify_rust::Notification::new();
+    desktop
+        .appname("Hyprnote")
+        .summary(&notification.title)
+        .body(&notification.message)
+        .timeout(Timeout::Milliseconds(timeout.as_millis() as u32));
+
+    if notification.url.is_some() {
+        desktop.action(DEFAULT_ACTION, "Open");
+    }
+
+    desktop
+}
+
+// Sent over the freedesktop `org.freedesktop.Notifications` DBus interface.
+pub fn show(notification: &hypr_notification_interface::Notification) {
+    let handle = match to_desktop_notification(notification).show() {
+        Ok(handle) => handle,
+        Err(e) => {
+            tracing::error!(error = ?e, "failed_to_show_notification");
+            return;
+        }
+    };
+
+    let url = notification.url.clone();
+
+    // Waiting for the action blocks until the notification is gone.
+    std::thread::spawn(move || {
+        let id = handle.id().to_string();
+
+        handle.wait_for_action(|action| match action {
+            CLOSED_ACTION => rust_on_notification_dismiss(id),
+            _ => {
+                rust_on_notification_confirm(id);
+
+                if let Some(url) = url {
+                    let _ = std::process::Command::new("xdg-open").arg(url).spawn();
+                }
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_desktop_notification() {
+        let notification = hypr_notification_interface::Notification::builder()
+            .title("Test Title")
+            .message("Test message content")
+            .url("https://example.com")
+            .timeout(std::time::Duration::from_secs(3))
+            .build();
+
+        let desktop = to_desktop_notification(&notification);
+        assert_eq!(desktop.summary, "Test Title");
+        assert_eq!(desktop.body, "Test message content");
+        assert_eq!(desktop.actions, vec![DEFAULT_ACTION, "Open"]);
+        assert_eq!(desktop.timeout, Timeout::Milliseconds(3000));
+    }
+
+    #[cfg(feature = "dbus-test")]
+    #[test]
+    fn test_notification() {
+        if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+            return;
+        }
+
+        let notification = hypr_notification_interface::Notification::builder()
+            .title("Test Title")
+            .message("Test message content")
+            .url("https://example.com")
+            .timeout(std::time::Duration::from_secs(3))
+            .build();
+
+        to_desktop_notification(&notification).show().unwrap();
+    }
+}
@@ -5,6 +5,11 @@ use std::time::{Duration, Instant};
 
 pub use hypr_notification_interface::*;
 
+#[cfg(target_os = "linux")]
+use hypr_notification_linux as backend;
+#[cfg(target_os = "macos")]
+use hypr_notification_macos as backend;
+
 static RECENT_NOTIFICATIONS: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
 
 const DEDUPE_WINDOW: Duration = Duration::from_secs(60 * 5);
@@ -14,10 +19,10 @@ pub enum NotificationMutation {
     Dismiss,
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub fn show(notification: &hypr_notification_interface::Notification) {
     let Some(key) = &notification.key else {
-        hypr_notification_macos::show(notification);
+        backend::show(notification);
         return;
     };
 
@@ -42,10 +47,10 @@ pub fn show(notification: &hypr_notification_interface::Notification) {
         recent_notifications.insert(key.clone(), now);
     }
 
-    hypr_notification_macos::show(notification);
+    backend::show(notification);
 }
 
-#[cfg(not(target_os = "macos"))]
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
 pub fn show(notification: &hypr_notification_interface::Notification) {}
 
 pub fn clear() {
@@ -57,16 +62,16 @@ pub fn setup_notification_dismiss_handler<F>(f: F)
 where
     F: Fn(String) + Send + Sync + 'static,
 {
-    #[cfg(target_os = "macos")]
-    hypr_notification_macos::setup_notification_dismiss_handler(f);
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    backend::setup_notification_dismiss_handler(f);
 }
 
 pub fn setup_notification_confirm_handler<F>(f: F)
 where
     F: Fn(String) + Send + Sync + 'static,
 {
-    #[cfg(target_os = "macos")]
-    hypr_notification_macos::setup_notification_confirm_handler(f);
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    backend::setup_notification_confirm_handler(f);
 }
 
 #[cfg(target_os = "macos")]
@@ -0,0 +1,246 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use vorbis_rs::VorbisDecoder;
+
+pub const FILENAME_BASE: &str = "audio";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Wav(#[from] hound::Error),
+    #[error(transparent)]
+    Vorbis(#[from] vorbis_rs::VorbisError),
+}
+
+/// Where `session_id`'s recording lives under `base`, or `None` if the id would point elsewhere,
+/// e.g. `..` or an absolute path.
+pub fn session_dir(base: impl AsRef<Path>, session_id: &str) -> Option<PathBuf> {
+    let base = base.as_ref();
+
+    if session_id.is_empty()
+        || session_id.contains(['/', '\\'])
+        || session_id.contains("..")
+        || Path::new(session_id).is_absolute()
+    {
+        return None;
+    }
+
+    let dir = base.join(session_id);
+    (dir.parent() == Some(base)).then_some(dir)
+}
+
+/// The first of `bases` with a recording of `session_id`. Which base a session was recorded
+/// under depends on the config at the time, so callers look in every one of them.
+pub fn find_session_dir(
+    bases: impl IntoIterator<Item = impl AsRef<Path>>,
+    session_id: &str,
+) -> Option<PathBuf> {
+    bases
+        .into_iter()
+        .filter_map(|base| session_dir(base, session_id))
+        .find(|dir| !recording_segments(dir).is_empty())
+}
+
+/// Returns the recorded audio files of a session in playback order.
+///
+/// Unsegmented recordings are a single `audio.ogg`, while rotated recordings are
+/// split into `audio.000.ogg`, `audio.001.ogg`, ... Recordings kept as WAV use `.wav`
+/// instead; where a segment has both, the Ogg is used.
+pub fn recording_segments(session_dir: impl AsRef<Path>) -> Vec<PathBuf> {
+    let session_dir = session_dir.as_ref();
+    let mut segments = Vec::new();
+
+    let single = ["ogg", "wav"]
+        .iter()
+        .map(|ext| session_dir.join(format!("{}.{}", FILENAME_BASE, ext)))
+        .find(|path| path.exists());
+    segments.extend(single);
+
+    let mut numbered = std::collections::BTreeMap::new();
+    for path in audio_files(session_dir) {
+        if let Some(idx) = segment_index(&path) {
+            let is_ogg = path.extension().is_some_and(|ext| ext == "ogg");
+            if is_ogg || !numbered.contains_key(&idx) {
+                numbered.insert(idx, path);
+            }
+        }
+    }
+
+    segments.extend(numbered.into_values());
+    segments
+}
+
+/// Every audio file of the recording, including WAVs kept next to their Ogg.
+pub fn recording_files(session_dir: impl AsRef<Path>) -> Vec<PathBuf> {
+    let session_dir = session_dir.as_ref();
+
+    let mut files = ["ogg", "wav"]
+        .iter()
+        .map(|ext| session_dir.join(format!("{}.{}", FILENAME_BASE, ext)))
+        .filter(|path| path.exists())
+        .collect::<Vec<_>>();
+    files.extend(audio_files(session_dir).filter(|path| segment_index(path).is_some()));
+    files
+}
+
+fn audio_files(session_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(session_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+}
+
+pub fn is_wav(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "wav")
+}
+
+/// Position of a rotated segment, e.g. 1 for `audio.001.ogg`.
+pub fn segment_index(path: &Path) -> Option<usize> {
+    if path.extension()? != "ogg" && !is_wav(path) {
+        return None;
+    }
+
+    let stem = path.file_stem()?.to_str()?;
+    let (base, idx) = stem.split_once('.')?;
+    if base != FILENAME_BASE {
+        return None;
+    }
+
+    idx.parse().ok()
+}
+
+/// Sample rate of a segment, read from its header.
+pub fn segment_sample_rate(path: &Path) -> Result<u32, Error> {
+    if is_wav(path) {
+        return Ok(hound::WavReader::open(path)?.spec().sample_rate);
+    }
+
+    let decoder = VorbisDecoder::new(BufReader::new(File::open(path)?))?;
+    Ok(decoder.sampling_frequency().get())
+}
+
+/// Decodes a segment's first channel, handing it to `on_samples` a block at a time.
+pub fn decode_segment(path: &Path, mut on_samples: impl FnMut(&[f32])) -> Result<(), Error> {
+    if is_wav(path) {
+        let mut reader = hound::WavReader::open(path)?;
+        let channels = reader.spec().channels.max(1) as usize;
+        let samples = f32_samples(&mut reader)
+            .step_by(channels)
+            .collect::<Result<Vec<_>, _>>()?;
+        on_samples(&samples);
+        return Ok(());
+    }
+
+    let mut decoder = VorbisDecoder::new(BufReader::new(File::open(path)?))?;
+    while let Some(block) = decoder.decode_audio_block()? {
+        if let Some(channel) = block.samples().first() {
+            on_samples(channel);
+        }
+    }
+    Ok(())
+}
+
+/// A WAV's samples as floats from -1 to 1, whatever it was written with.
+pub fn f32_samples<'a, R: std::io::Read>(
+    reader: &'a mut hound::WavReader<R>,
+) -> Box<dyn Iterator<Item = Result<f32, hound::Error>> + 'a> {
+    let spec = reader.spec();
+
+    match spec.sample_format {
+        hound::SampleFormat::Float => Box::new(reader.samples::<f32>()),
+        hound::SampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            Box::new(
+                reader
+                    .samples::<i32>()
+                    .map(move |s| s.map(|s| s as f32 / scale)),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_wav(path: &Path, samples: &[f32]) {
+        let mut writer = hound::WavWriter::create(
+            path,
+            hound::WavSpec {
+                channels: 1,
+                sample_rate: 16000,
+                bits_per_sample: 32,
+                sample_format: hound::SampleFormat::Float,
+            },
+        )
+        .unwrap();
+        for sample in samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_session_dir() {
+        let base = Path::new("/recordings");
+        let id = uuid::Uuid::new_v4().to_string();
+
+        assert_eq!(session_dir(base, &id), Some(base.join(&id)));
+        for id in ["", ".", "..", "../other", "a/b", "a\\b", "/etc", "a..b"] {
+            assert_eq!(session_dir(base, id), None, "{id}");
+        }
+    }
+
+    #[test]
+    fn test_find_session_dir() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (configured, fallback) = (root.join("configured"), root.join("fallback"));
+        let id = uuid::Uuid::new_v4().to_string();
+
+        // Only an empty directory where the config points now.
+        std::fs::create_dir_all(configured.join(&id)).unwrap();
+        std::fs::create_dir_all(fallback.join(&id)).unwrap();
+        write_wav(&fallback.join(&id).join("audio.wav"), &[0.5; 10]);
+
+        let bases = [&configured, &fallback];
+        assert_eq!(find_session_dir(bases, &id), Some(fallback.join(&id)));
+        assert_eq!(find_session_dir(bases, "other"), None);
+        assert_eq!(find_session_dir(bases, "../fallback"), None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_wav_segments() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        write_wav(&dir.join("audio.001.wav"), &[0.5; 100]);
+        write_wav(&dir.join("audio.000.wav"), &[0.25; 50]);
+        std::fs::write(dir.join("audio.meta.json"), b"{}").unwrap();
+
+        assert_eq!(
+            recording_segments(&dir),
+            vec![dir.join("audio.000.wav"), dir.join("audio.001.wav")]
+        );
+        assert_eq!(recording_files(&dir).len(), 2);
+        assert_eq!(
+            segment_sample_rate(&dir.join("audio.000.wav")).unwrap(),
+            16000
+        );
+
+        let mut samples = vec![];
+        for path in recording_segments(&dir) {
+            decode_segment(&path, |block| samples.extend_from_slice(block)).unwrap();
+        }
+        assert_eq!(samples.len(), 150);
+        assert_eq!((samples[0], samples[149]), (0.25, 0.5));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,8 +2,78 @@ use serde::{ser::Serializer, Serialize};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("template not found: {name}")]
+    TemplateNotFound { name: String },
+    // Lines and columns are 1-based. The column is only known when the source is.
+    #[error("syntax error{}: {message}", location(.line, .column))]
+    SyntaxError {
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    // Only reading from an undefined value fails, e.g. `{{ missing.field }}`. Plain `{{ missing }}`
+    // renders as empty text.
+    #[error("undefined variable: {name}")]
+    UndefinedVariable { name: String },
     #[error(transparent)]
-    JinjaError(#[from] minijinja::Error),
+    RenderError(minijinja::Error),
+    // With `redactPii` set, a transcript that can't be read is never rendered as is.
+    #[error("transcript could not be redacted: {0}")]
+    RedactionFailed(String),
+}
+
+impl Error {
+    // `source` is the failing template's text, which the undefined variable's name is read from.
+    pub(crate) fn from_jinja(e: minijinja::Error, source: Option<&str>) -> Self {
+        use minijinja::ErrorKind;
+
+        match e.kind() {
+            ErrorKind::TemplateNotFound => Error::TemplateNotFound {
+                name: e.name().unwrap_or_default().to_string(),
+            },
+            ErrorKind::SyntaxError => Error::SyntaxError {
+                line: e.line(),
+                column: source
+                    .zip(e.range())
+                    .map(|(source, range)| column(source, range.start)),
+                message: e.detail().unwrap_or_default().to_string(),
+            },
+            ErrorKind::UndefinedError => Error::UndefinedVariable {
+                name: source
+                    .zip(e.range())
+                    .and_then(|(source, range)| source.get(range))
+                    .map(|expr| {
+                        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+                        expr.trim_start_matches(|c| !is_ident(c))
+                            .chars()
+                            .take_while(|c| is_ident(*c))
+                            .collect::<String>()
+                    })
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| "unknown".to_string()),
+            },
+            _ => Error::RenderError(e),
+        }
+    }
+}
+
+fn location(line: &Option<usize>, column: &Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(" at line {}, column {}", line, column),
+        (Some(line), None) => format!(" at line {}", line),
+        _ => String::new(),
+    }
+}
+
+fn column(source: &str, offset: usize) -> usize {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..offset].chars().count() + 1
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(e: minijinja::Error) -> Self {
+        Self::from_jinja(e, None)
+    }
 }
 
 impl Serialize for Error {
@@ -0,0 +1,221 @@
+use codes_iso_639::part_1::LanguageCode;
+use owhisper_interface::{Word2, WordKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, specta::Type, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillerMode {
+    // Fillers are removed from the transcript.
+    Strip,
+    // Fillers stay, bracketed as events like `[um]`, so the model can tell them apart.
+    Mark,
+}
+
+// Multi-word fillers first, so they are matched as a whole.
+fn fillers(language: LanguageCode) -> &'static [&'static [&'static str]] {
+    match language {
+        LanguageCode::En => &[
+            &["you", "know"],
+            &["um"],
+            &["umm"],
+            &["uh"],
+            &["uhh"],
+            &["uh-huh"],
+            &["er"],
+            &["erm"],
+            &["hmm"],
+            &["mhm"],
+        ],
+        LanguageCode::Ko => &[
+            &["음"],
+            &["으음"],
+            &["어"],
+            &["어어"],
+            &["아"],
+            &["저기"],
+            &["뭐랄까"],
+        ],
+        _ => &[],
+    }
+}
+
+fn ends_clause(text: &str) -> bool {
+    text.ends_with(|c: char| matches!(c, ',' | '.' | ';' | ':' | '!' | '?' | '…'))
+}
+
+// A multi-word filler like "you know" is also an ordinary phrase ("Do you know the deadline?"), so
+// it only counts when punctuation sets it off on both sides, the start of the transcript counting
+// as one.
+fn set_off(clause_ends: &[bool], start: usize, len: usize) -> bool {
+    (start == 0 || clause_ends[start - 1]) && clause_ends[start + len - 1]
+}
+
+// Compared without case or surrounding punctuation, so "Um," is a filler too.
+fn normalize(text: &str) -> String {
+    text.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Strips or marks filler words like "um" and "you know" in `language`.
+///
+/// Multi-word fillers are only matched when punctuation sets them off, as in "Friday, you know."
+/// Words are only ever removed or relabeled, so the ones left keep their timing.
+pub fn remove_fillers(words: Vec<Word2>, language: LanguageCode, mode: FillerMode) -> Vec<Word2> {
+    let fillers = fillers(language);
+    let normalized = words.iter().map(|w| normalize(&w.text)).collect::<Vec<_>>();
+    let clause_ends = words
+        .iter()
+        .map(|w| ends_clause(&w.text))
+        .collect::<Vec<_>>();
+
+    let mut out = Vec::with_capacity(words.len());
+    let mut skip = 0;
+
+    for (i, mut word) in words.into_iter().enumerate() {
+        if skip == 0 && word.kind == WordKind::Speech {
+            skip = fillers
+                .iter()
+                .find(|filler| {
+                    normalized[i..].len() >= filler.len()
+                        && filler.iter().zip(&normalized[i..]).all(|(f, w)| f == w)
+                        && (filler.len() == 1 || set_off(&clause_ends, i, filler.len()))
+                })
+                .map_or(0, |filler| filler.len());
+        }
+
+        if skip == 0 {
+            out.push(word);
+            continue;
+        }
+        skip -= 1;
+
+        if mode == FillerMode::Mark {
+            word.text = format!("[{}]", word.text);
+            word.kind = WordKind::Event;
+            out.push(word);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_ms: u64) -> Word2 {
+        Word2 {
+            text: text.to_string(),
+            start_ms: Some(start_ms),
+            end_ms: Some(start_ms + 100),
+            ..Default::default()
+        }
+    }
+
+    fn timed_texts(words: &[Word2]) -> Vec<(&str, Option<u64>)> {
+        words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start_ms))
+            .collect()
+    }
+
+    #[test]
+    fn test_remove_fillers() {
+        let words = vec![
+            word("Um,", 0),
+            word("we", 100),
+            word("uh", 200),
+            word("ship", 300),
+            word("on", 400),
+            word("Friday,", 500),
+            word("you", 600),
+            word("know.", 700),
+            word("Do", 800),
+            word("you", 900),
+            word("knowledge", 1000),
+        ];
+
+        let stripped = remove_fillers(words.clone(), LanguageCode::En, FillerMode::Strip);
+        assert_eq!(
+            timed_texts(&stripped),
+            vec![
+                ("we", Some(100)),
+                ("ship", Some(300)),
+                ("on", Some(400)),
+                ("Friday,", Some(500)),
+                ("Do", Some(800)),
+                ("you", Some(900)),
+                ("knowledge", Some(1000)),
+            ]
+        );
+
+        let marked = remove_fillers(words.clone(), LanguageCode::En, FillerMode::Mark);
+        assert_eq!(marked.len(), words.len());
+        assert_eq!(marked[0].text, "[Um,]");
+        assert_eq!(marked[0].kind, WordKind::Event);
+        assert_eq!(marked[7].text, "[know.]");
+        assert_eq!(marked[8], words[8]);
+
+        let korean = vec![
+            word("음", 0),
+            word("내일", 100),
+            word("어", 200),
+            word("출시", 300),
+        ];
+        assert_eq!(
+            timed_texts(&remove_fillers(korean, LanguageCode::Ko, FillerMode::Strip)),
+            vec![("내일", Some(100)), ("출시", Some(300))]
+        );
+
+        // Mid-sentence, "you know" is part of the question and stays.
+        let question = vec![
+            word("Do", 0),
+            word("you", 100),
+            word("know", 200),
+            word("the", 300),
+            word("deadline?", 400),
+        ];
+        assert_eq!(
+            remove_fillers(question.clone(), LanguageCode::En, FillerMode::Strip),
+            question
+        );
+        assert_eq!(
+            remove_fillers(question.clone(), LanguageCode::En, FillerMode::Mark),
+            question
+        );
+
+        let conditional = vec![
+            word("Tell", 0),
+            word("me", 100),
+            word("if", 200),
+            word("you", 300),
+            word("know", 400),
+            word("what", 500),
+            word("happened.", 600),
+        ];
+        assert_eq!(
+            remove_fillers(conditional.clone(), LanguageCode::En, FillerMode::Strip),
+            conditional
+        );
+
+        let leading = vec![
+            word("You", 0),
+            word("know,", 100),
+            word("it", 200),
+            word("works.", 300),
+        ];
+        assert_eq!(
+            timed_texts(&remove_fillers(
+                leading,
+                LanguageCode::En,
+                FillerMode::Strip
+            )),
+            vec![("it", Some(200)), ("works.", Some(300))]
+        );
+
+        // Other languages have no dictionary yet, so nothing changes.
+        assert_eq!(
+            remove_fillers(words.clone(), LanguageCode::De, FillerMode::Strip),
+            words
+        );
+    }
+}
@@ -2,19 +2,32 @@
 
 use codes_iso_639::part_1::LanguageCode;
 use itertools::Itertools;
+use minijinja::{value::ViaDeserialize, Error, ErrorKind};
 use owhisper_interface::{SpeakerIdentity, Word2};
+use std::collections::HashMap;
 use std::str::FromStr;
 
-pub fn language(value: String) -> String {
+pub fn language(value: String) -> Result<String, Error> {
     let lang_str = value.to_lowercase();
-    let lang_code = LanguageCode::from_str(&lang_str).unwrap();
-    lang_code.language_name().to_string()
+    let lang_code = LanguageCode::from_str(&lang_str).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("unknown language code: {}", value),
+        )
+    })?;
+    Ok(lang_code.language_name().to_string())
 }
 
-pub fn timeline(words: String) -> String {
-    let words: Vec<Word2> = serde_json::from_str(&words).unwrap();
+fn parse_words(words: &str) -> Result<Vec<Word2>, Error> {
+    serde_json::from_str(words).map_err(|e| {
+        Error::new(ErrorKind::InvalidOperation, "words are not valid JSON").with_source(e)
+    })
+}
+
+pub fn timeline(words: String) -> Result<String, Error> {
+    let words = parse_words(&words)?;
 
-    words
+    Ok(words
         .iter()
         .chunk_by(|word| word.speaker.clone())
         .into_iter()
@@ -31,7 +44,38 @@ pub fn timeline(words: String) -> String {
                 group.map(|word| word.text.as_str()).join(" ")
             )
         })
-        .join("\n\n")
+        .join("\n\n"))
+}
+
+// `speaker_names` maps unassigned speaker indices (as strings, e.g. "0") to display names.
+pub fn transcript(
+    words: String,
+    speaker_names: Option<ViaDeserialize<HashMap<String, String>>>,
+) -> Result<String, Error> {
+    let words = parse_words(&words)?;
+    let speaker_names = speaker_names.map(|names| names.0).unwrap_or_default();
+
+    Ok(words
+        .iter()
+        .chunk_by(|word| word.speaker.clone())
+        .into_iter()
+        .map(|(speaker, group)| {
+            let speaker_label = match speaker {
+                Some(SpeakerIdentity::Unassigned { index }) => speaker_names
+                    .get(&index.to_string())
+                    .cloned()
+                    .unwrap_or_else(|| format!("Speaker {}", index)),
+                Some(SpeakerIdentity::Assigned { label, .. }) => label,
+                None => "Unknown".to_string(),
+            };
+
+            format!(
+                "{}: {}",
+                speaker_label,
+                group.map(|word| word.text.as_str()).join(" ")
+            )
+        })
+        .join("\n"))
 }
 
 #[cfg(test)]
@@ -40,13 +84,21 @@ mod tests {
 
     #[test]
     fn test_language() {
-        assert_eq!(language("en".to_string()), "English");
-        assert_eq!(language("ko".to_string()), "Korean");
+        assert_eq!(language("en".to_string()).unwrap(), "English");
+        assert_eq!(language("ko".to_string()).unwrap(), "Korean");
+        assert!(language("zz".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_malformed_words_are_an_error() {
+        let err = transcript("not json".to_string(), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+        assert!(timeline("{}".to_string()).is_err());
     }
 
     #[test]
     fn test_timeline() {
-        insta::assert_snapshot!(timeline(hypr_data::english_3::WORDS_JSON.to_string()), @r###"
+        insta::assert_snapshot!(timeline(hypr_data::english_3::WORDS_JSON.to_string()).unwrap(), @r###"
         [SPEAKER 0]
         -okay michael why don't you start us off
 
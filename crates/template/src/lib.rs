@@ -1,9 +1,22 @@
 use codes_iso_639::part_1::LanguageCode;
+use std::str::FromStr;
 use std::sync::OnceLock;
 
 mod filters;
 mod testers;
 
+mod fillers;
+pub use fillers::*;
+
+mod redact;
+pub use redact::*;
+
+mod trim;
+pub use trim::*;
+
+mod validate;
+pub use validate::*;
+
 mod error;
 pub use error::*;
 
@@ -121,6 +134,7 @@ fn init_environment() -> minijinja::Environment<'static> {
 
     {
         env.add_filter("timeline", filters::timeline);
+        env.add_filter("transcript", filters::transcript);
         env.add_filter("language", filters::language);
         [LanguageCode::En, LanguageCode::Ko]
             .iter()
@@ -143,12 +157,289 @@ pub fn render(
     template: Template,
     ctx: &serde_json::Map<String, serde_json::Value>,
 ) -> Result<String, crate::Error> {
-    let env = get_environment();
-    let tpl = env.get_template(template.as_ref())?;
-
-    tpl.render(ctx).map_err(Into::into).map(|s| {
-        #[cfg(debug_assertions)]
-        println!("--\n{}\n--", s);
-        s
-    })
+    render_named(get_environment(), template.as_ref(), ctx)
+}
+
+fn render_named(
+    env: &minijinja::Environment<'_>,
+    name: &str,
+    ctx: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String, crate::Error> {
+    let tpl = env.get_template(name).map_err(|e| match e.kind() {
+        minijinja::ErrorKind::TemplateNotFound => crate::Error::TemplateNotFound {
+            name: name.to_string(),
+        },
+        _ => crate::Error::from_jinja(e, None),
+    })?;
+
+    let prepared;
+    let ctx = match prepare_context(ctx)? {
+        Some(c) => {
+            prepared = c;
+            &prepared
+        }
+        None => ctx,
+    };
+
+    tpl.render(ctx)
+        .map_err(|e| crate::Error::from_jinja(e, Some(tpl.source())))
+        .map(|s| {
+            #[cfg(debug_assertions)]
+            println!("--\n{}\n--", s);
+            s
+        })
+}
+
+#[derive(serde::Deserialize)]
+struct FillerOptions {
+    mode: FillerMode,
+    // ISO 639-1 code of the transcript, English if unset.
+    language: Option<String>,
+}
+
+// Prepares `words` in one pass, as set in the context:
+// - `fillerWords`, e.g. `{ "mode": "strip", "language": "ko" }`, strips or marks filler words.
+// - `redactPii` redacts PII. Words that can't be parsed then fail the render, rather than
+//   reaching the prompt unredacted.
+// - `transcriptTokenBudget` trims them to about that many tokens.
+fn prepare_context(
+    ctx: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Option<serde_json::Map<String, serde_json::Value>>, crate::Error> {
+    let fillers = ctx
+        .get("fillerWords")
+        .and_then(|v| serde_json::from_value::<FillerOptions>(v.clone()).ok())
+        .and_then(|options| {
+            let language = match options.language {
+                Some(code) => LanguageCode::from_str(&code.to_lowercase()).ok()?,
+                None => LanguageCode::En,
+            };
+            Some((language, options.mode))
+        });
+    let redact = ctx.get("redactPii").and_then(|v| v.as_bool()) == Some(true);
+    let budget = ctx
+        .get("transcriptTokenBudget")
+        .and_then(|v| v.as_u64())
+        .map(|budget| budget as usize);
+
+    if fillers.is_none() && !redact && budget.is_none() {
+        return Ok(None);
+    }
+    let Some(words) = ctx.get("words") else {
+        return Ok(None);
+    };
+
+    let parsed = words
+        .as_str()
+        .ok_or_else(|| "words is not a string".to_string())
+        .and_then(|words| {
+            serde_json::from_str::<Vec<owhisper_interface::Word2>>(words).map_err(|e| e.to_string())
+        });
+    let mut words = match parsed {
+        Ok(words) => words,
+        Err(e) if redact => return Err(crate::Error::RedactionFailed(e)),
+        Err(_) => return Ok(None),
+    };
+
+    if let Some((language, mode)) = fillers {
+        words = remove_fillers(words, language, mode);
+    }
+    if redact {
+        redact_words(&mut words, &RedactionConfig::default());
+    }
+    if let Some(budget) = budget {
+        words = trim_words(&words, budget);
+    }
+
+    let words = match serde_json::to_string(&words) {
+        Ok(words) => words,
+        Err(e) if redact => return Err(crate::Error::RedactionFailed(e.to_string())),
+        Err(_) => return Ok(None),
+    };
+
+    let mut ctx = ctx.clone();
+    ctx.insert("words".to_string(), words.into());
+    Ok(Some(ctx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postprocess_transcript_speaker_names() {
+        let words = serde_json::json!([
+            { "text": "hello", "speaker": { "type": "unassigned", "value": { "index": 0 } }, "confidence": null, "start_ms": 0, "end_ms": 100 },
+            { "text": "there", "speaker": { "type": "unassigned", "value": { "index": 0 } }, "confidence": null, "start_ms": 100, "end_ms": 200 },
+            { "text": "hi", "speaker": { "type": "unassigned", "value": { "index": 1 } }, "confidence": null, "start_ms": 200, "end_ms": 300 },
+            { "text": "bye", "speaker": { "type": "unassigned", "value": { "index": 2 } }, "confidence": null, "start_ms": 300, "end_ms": 400 },
+        ]);
+
+        let ctx = serde_json::json!({
+            "words": words.to_string(),
+            "speaker_names": { "0": "Alice", "1": "Bob" },
+        });
+
+        let rendered = render(
+            Template::PostprocessTranscriptUser,
+            ctx.as_object().unwrap(),
+        )
+        .unwrap();
+
+        assert!(rendered.contains("Alice: hello there"), "{}", rendered);
+        assert!(rendered.contains("Bob: hi"), "{}", rendered);
+        assert!(rendered.contains("Speaker 2: bye"), "{}", rendered);
+        assert!(!rendered.contains("Speaker 0"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_postprocess_transcript_strips_fillers() {
+        let words = serde_json::json!([
+            { "text": "um", "speaker": null, "confidence": null, "start_ms": 0, "end_ms": 100 },
+            { "text": "ship", "speaker": null, "confidence": null, "start_ms": 100, "end_ms": 200 },
+            { "text": "it", "speaker": null, "confidence": null, "start_ms": 200, "end_ms": 300 },
+        ]);
+
+        let ctx = serde_json::json!({
+            "words": words.to_string(),
+            "fillerWords": { "mode": "strip", "language": "en" },
+        });
+
+        let rendered = render(
+            Template::PostprocessTranscriptUser,
+            ctx.as_object().unwrap(),
+        )
+        .unwrap();
+
+        assert!(rendered.contains("ship it"), "{}", rendered);
+        assert!(!rendered.contains("um"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_render_error_kinds() {
+        let mut env = minijinja::Environment::new();
+        env.set_loader(|name| {
+            Ok(match name {
+                "syntax" => Some("Notes:\n{% if %}done{% endif %}".to_string()),
+                "undefined" => Some("Title: {{ session.title }}".to_string()),
+                "lenient" => Some("Title: {{ title }}".to_string()),
+                "failing" => Some("{% for word in 42 %}{{ word }}{% endfor %}".to_string()),
+                _ => None,
+            })
+        });
+        let ctx = serde_json::Map::new();
+
+        assert!(matches!(
+            render_named(&env, "missing", &ctx),
+            Err(Error::TemplateNotFound { name }) if name == "missing"
+        ));
+        assert!(matches!(
+            render_named(&env, "syntax", &ctx),
+            Err(Error::SyntaxError { line: Some(2), .. })
+        ));
+        assert!(matches!(
+            render_named(&env, "undefined", &ctx),
+            Err(Error::UndefinedVariable { name }) if name == "session"
+        ));
+        assert!(matches!(
+            render_named(&env, "failing", &ctx),
+            Err(Error::RenderError(_))
+        ));
+
+        // Undefined values that are only printed don't fail.
+        assert_eq!(render_named(&env, "lenient", &ctx).unwrap(), "Title: ");
+    }
+
+    #[test]
+    fn test_chat_system_prompt_override() {
+        let ctx = serde_json::json!({ "systemPrompt": "Answer tersely." });
+        let rendered = render(Template::ChatSystem, ctx.as_object().unwrap()).unwrap();
+        assert!(rendered.starts_with("Answer tersely.\n"), "{}", rendered);
+
+        let rendered = render(Template::ChatSystem, &serde_json::Map::new()).unwrap();
+        assert!(rendered.starts_with("You are a helpful AI meeting assistant"));
+    }
+
+    #[test]
+    fn test_enhance_redacts_pii() {
+        let words = serde_json::json!([
+            { "text": "email", "speaker": null, "confidence": null, "start_ms": 0, "end_ms": 100 },
+            { "text": "me", "speaker": null, "confidence": null, "start_ms": 100, "end_ms": 200 },
+            { "text": "at", "speaker": null, "confidence": null, "start_ms": 200, "end_ms": 300 },
+            { "text": "john@example.com", "speaker": null, "confidence": null, "start_ms": 300, "end_ms": 400 },
+        ]);
+
+        let ctx = |redact: bool| {
+            serde_json::json!({
+                "words": words.to_string(),
+                "participants": [],
+                "editor": "",
+                "redactPii": redact,
+            })
+        };
+
+        let rendered = render(Template::EnhanceUser, ctx(true).as_object().unwrap()).unwrap();
+        assert!(rendered.contains("email me at [EMAIL]"), "{}", rendered);
+        assert!(!rendered.contains("john@example.com"), "{}", rendered);
+
+        let rendered = render(Template::EnhanceUser, ctx(false).as_object().unwrap()).unwrap();
+        assert!(rendered.contains("john@example.com"), "{}", rendered);
+
+        // Fillers come out in the same pass.
+        let mut with_fillers = ctx(true);
+        let mut um = words.as_array().unwrap().clone();
+        um.insert(
+            0,
+            serde_json::json!({ "text": "um", "speaker": null, "confidence": null, "start_ms": 0, "end_ms": 0 }),
+        );
+        with_fillers["words"] = serde_json::Value::Array(um).to_string().into();
+        with_fillers["fillerWords"] = serde_json::json!({ "mode": "strip" });
+        let rendered = render(Template::EnhanceUser, with_fillers.as_object().unwrap()).unwrap();
+        assert!(rendered.contains("email me at [EMAIL]"), "{}", rendered);
+        assert!(!rendered.contains("um email"), "{}", rendered);
+
+        // Malformed words can't be checked for PII, so nothing is rendered.
+        let mut malformed = ctx(true);
+        malformed["words"] = "[{ \"text\": \"john@example.com\" ".into();
+        assert!(matches!(
+            render(Template::EnhanceUser, malformed.as_object().unwrap()),
+            Err(Error::RedactionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_enhance_trims_long_transcript() {
+        let words = (0..20_000)
+            .map(|i| {
+                serde_json::json!({
+                    "text": format!("word{}", i),
+                    "speaker": { "type": "unassigned", "value": { "index": (i / 50) % 3 } },
+                    "confidence": null,
+                    "start_ms": i * 100,
+                    "end_ms": i * 100 + 100,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let ctx = |words: &[serde_json::Value], budget: Option<u64>| {
+            let mut ctx = serde_json::json!({
+                "words": serde_json::to_string(words).unwrap(),
+                "participants": [],
+                "editor": "",
+            });
+            if let Some(budget) = budget {
+                ctx["transcriptTokenBudget"] = budget.into();
+            }
+            ctx.as_object().unwrap().clone()
+        };
+
+        let overhead = estimate_tokens(&render(Template::EnhanceUser, &ctx(&[], None)).unwrap());
+        let untrimmed = render(Template::EnhanceUser, &ctx(&words, None)).unwrap();
+        assert!(estimate_tokens(&untrimmed) > overhead + 4000);
+
+        let rendered = render(Template::EnhanceUser, &ctx(&words, Some(4000))).unwrap();
+        assert!(estimate_tokens(&rendered) <= overhead + 4000);
+        assert!(rendered.contains("word0 word1"));
+        assert!(rendered.contains(TRIMMED_MARKER));
+        assert!(rendered.contains("word19998 word19999"));
+    }
 }
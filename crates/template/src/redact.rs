@@ -0,0 +1,207 @@
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use owhisper_interface::Word2;
+use regex::Regex;
+
+#[derive(Debug, Clone, specta::Type, serde::Serialize, serde::Deserialize)]
+pub struct RedactionConfig {
+    pub emails: bool,
+    pub phone_numbers: bool,
+    pub card_numbers: bool,
+    pub ssns: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            emails: true,
+            phone_numbers: true,
+            card_numbers: true,
+            ssns: true,
+        }
+    }
+}
+
+struct Pattern {
+    label: &'static str,
+    regex: Regex,
+    enabled: fn(&RedactionConfig) -> bool,
+}
+
+// Earlier patterns win, so the more specific digit patterns come before phone numbers.
+fn patterns() -> &'static [Pattern] {
+    static PATTERNS: OnceLock<Vec<Pattern>> = OnceLock::new();
+
+    PATTERNS.get_or_init(|| {
+        vec![
+            Pattern {
+                label: "[SSN]",
+                regex: Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").unwrap(),
+                enabled: |c| c.ssns,
+            },
+            Pattern {
+                label: "[CARD]",
+                regex: Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap(),
+                enabled: |c| c.card_numbers,
+            },
+            Pattern {
+                label: "[EMAIL]",
+                regex: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+                enabled: |c| c.emails,
+            },
+            Pattern {
+                label: "[PHONE]",
+                regex: Regex::new(
+                    r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\) ?|\b\d{3}[ .-]?)\d{3}[ .-]?\d{4}\b",
+                )
+                .unwrap(),
+                enabled: |c| c.phone_numbers,
+            },
+        ]
+    })
+}
+
+// Non-overlapping PII matches in `text`, sorted by position.
+fn find_pii(text: &str, config: &RedactionConfig) -> Vec<(Range<usize>, &'static str)> {
+    let mut found: Vec<(Range<usize>, &'static str)> = Vec::new();
+
+    for pattern in patterns().iter().filter(|p| (p.enabled)(config)) {
+        for m in pattern.regex.find_iter(text) {
+            let range = m.range();
+            if !found
+                .iter()
+                .any(|(r, _)| r.start < range.end && range.start < r.end)
+            {
+                found.push((range, pattern.label));
+            }
+        }
+    }
+
+    found.sort_by_key(|(r, _)| r.start);
+    found
+}
+
+/// Replaces emails, phone numbers, card numbers and SSNs in `text` with placeholders like `[EMAIL]`.
+pub fn redact_pii(text: &str, config: &RedactionConfig) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (range, label) in find_pii(text, config) {
+        redacted.push_str(&text[last..range.start]);
+        redacted.push_str(label);
+        last = range.end;
+    }
+
+    redacted.push_str(&text[last..]);
+    redacted
+}
+
+/// Redacts words in place, keeping their count and timing.
+///
+/// PII spanning several words (e.g. `555 123 4567`) is replaced in the first word,
+/// and the rest of the match is removed from the words that follow.
+pub fn redact_words(words: &mut [Word2], config: &RedactionConfig) {
+    let mut text = String::new();
+    let mut spans = Vec::with_capacity(words.len());
+    for word in words.iter() {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        spans.push(text.len()..text.len() + word.text.len());
+        text.push_str(&word.text);
+    }
+
+    let found = find_pii(&text, config);
+    if found.is_empty() {
+        return;
+    }
+
+    for (word, span) in words.iter_mut().zip(spans) {
+        let overlapping = found
+            .iter()
+            .filter(|(r, _)| r.start < span.end && span.start < r.end)
+            .collect::<Vec<_>>();
+        if overlapping.is_empty() {
+            continue;
+        }
+
+        let mut redacted = String::new();
+        let mut last = span.start;
+        for (range, label) in overlapping {
+            let start = range.start.max(span.start);
+            redacted.push_str(&text[last..start]);
+            if range.start >= span.start {
+                redacted.push_str(label);
+            }
+            last = range.end.min(span.end);
+        }
+        redacted.push_str(&text[last..span.end]);
+
+        word.text = redacted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_pii() {
+        let config = RedactionConfig::default();
+
+        let text = "Mail john.doe@example.com or call (555) 123-4567, my SSN is 123-45-6789 and card 4111 1111 1111 1111.";
+        let redacted = redact_pii(text, &config);
+        assert_eq!(
+            redacted,
+            "Mail [EMAIL] or call [PHONE], my SSN is [SSN] and card [CARD]."
+        );
+
+        for original in [
+            "john.doe@example.com",
+            "123-4567",
+            "123-45-6789",
+            "4111 1111",
+        ] {
+            assert!(!redacted.contains(original), "{}", redacted);
+        }
+
+        let redacted = redact_pii(
+            text,
+            &RedactionConfig {
+                emails: false,
+                ..Default::default()
+            },
+        );
+        assert!(redacted.contains("john.doe@example.com"));
+    }
+
+    #[test]
+    fn test_redact_words_keeps_timing() {
+        let words = "call me at 555 123 4567 or jane@example.com thanks"
+            .split(' ')
+            .enumerate()
+            .map(|(i, text)| Word2 {
+                text: text.to_string(),
+                start_ms: Some(i as u64 * 100),
+                end_ms: Some(i as u64 * 100 + 80),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut redacted = words.clone();
+        redact_words(&mut redacted, &RedactionConfig::default());
+
+        assert_eq!(redacted.len(), words.len());
+        for (before, after) in words.iter().zip(&redacted) {
+            assert_eq!(before.start_ms, after.start_ms);
+            assert_eq!(before.end_ms, after.end_ms);
+        }
+
+        let texts = redacted.iter().map(|w| w.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec!["call", "me", "at", "[PHONE]", "", "", "or", "[EMAIL]", "thanks"]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use owhisper_interface::{Word2, WordKind};
+
+// Share of the budget spent on the start of the meeting; the rest goes to its end.
+const HEAD_SHARE: f32 = 0.25;
+// Roughly what a `[SPEAKER 0]` label costs whenever the speaker changes.
+const SPEAKER_LABEL_TOKENS: usize = 4;
+
+pub const TRIMMED_MARKER: &str = "[transcript trimmed]";
+
+// About 4 characters per token, which holds for English text with most tokenizers.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+// Keeps the start and the end of a transcript that is over `max_tokens`, dropping the middle.
+//
+// This is a sliding window rather than a summary of the dropped part: it needs no model call,
+// and the opening (agenda, introductions) and the closing (decisions, action items) are what
+// notes draw on the most. A marker word is left where the middle was.
+pub fn trim_words(words: &[Word2], max_tokens: usize) -> Vec<Word2> {
+    if transcript_tokens(words.iter()) <= max_tokens {
+        return words.to_vec();
+    }
+
+    let marker = Word2 {
+        text: TRIMMED_MARKER.to_string(),
+        kind: WordKind::Event,
+        ..Default::default()
+    };
+    let budget = max_tokens.saturating_sub(word_tokens(&marker) + SPEAKER_LABEL_TOKENS * 2);
+    let head_budget = (budget as f32 * HEAD_SHARE) as usize;
+
+    let head = take_within(words.iter(), head_budget);
+    let tail = take_within(words[head..].iter().rev(), budget - head_budget);
+
+    let mut trimmed = Vec::with_capacity(head + tail + 1);
+    trimmed.extend_from_slice(&words[..head]);
+    trimmed.push(marker);
+    trimmed.extend_from_slice(&words[words.len() - tail..]);
+    trimmed
+}
+
+fn word_tokens(word: &Word2) -> usize {
+    // Plus the separating space.
+    estimate_tokens(&word.text) + 1
+}
+
+fn transcript_tokens<'a>(words: impl Iterator<Item = &'a Word2>) -> usize {
+    let mut total = 0;
+    let mut speaker = None;
+
+    for word in words {
+        if speaker != Some(&word.speaker) {
+            total += SPEAKER_LABEL_TOKENS;
+            speaker = Some(&word.speaker);
+        }
+        total += word_tokens(word);
+    }
+
+    total
+}
+
+// How many words from the front of `words` fit in `budget`.
+fn take_within<'a>(words: impl Iterator<Item = &'a Word2>, budget: usize) -> usize {
+    let mut total = 0;
+    let mut speaker = None;
+    let mut count = 0;
+
+    for word in words {
+        let mut cost = word_tokens(word);
+        if speaker != Some(&word.speaker) {
+            cost += SPEAKER_LABEL_TOKENS;
+        }
+        if total + cost > budget {
+            break;
+        }
+
+        total += cost;
+        speaker = Some(&word.speaker);
+        count += 1;
+    }
+
+    count
+}
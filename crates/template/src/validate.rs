@@ -0,0 +1,99 @@
+use std::collections::BTreeSet;
+
+use crate::Error;
+
+// A template that compiles and renders against the sample context. Variables the template reads
+// that the context doesn't have render as empty text, so they're warnings rather than errors.
+#[derive(Debug, Clone, PartialEq, specta::Type, serde::Serialize, serde::Deserialize)]
+pub struct Validation {
+    pub rendered: String,
+    pub missing_variables: Vec<String>,
+}
+
+/// Compiles `source` and renders it against `sample_ctx`, with the same filters and tests as the
+/// built-in templates.
+pub fn validate(
+    source: &str,
+    sample_ctx: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Validation, Error> {
+    let env = crate::get_environment();
+
+    let tpl = env
+        .template_from_str(source)
+        .map_err(|e| Error::from_jinja(e, Some(source)))?;
+
+    // Globals like `range` are declared by the environment, not the context.
+    let missing_variables = tpl
+        .undeclared_variables(false)
+        .into_iter()
+        .filter(|name| !sample_ctx.contains_key(name))
+        .filter(|name| {
+            env.compile_expression(name)
+                .and_then(|expr| expr.eval(()))
+                .ok()
+                .is_none_or(|v| v.is_undefined())
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let rendered = tpl
+        .render(sample_ctx)
+        .map_err(|e| Error::from_jinja(e, Some(source)))?;
+
+    Ok(Validation {
+        rendered,
+        missing_variables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let ctx = serde_json::json!({ "title": "Standup", "participants": ["Alice", "Bob"] });
+        let ctx = ctx.as_object().unwrap();
+
+        assert_eq!(
+            validate(
+                "# {{ title }}\n{% for p in participants %}- {{ p }}\n{% endfor %}{% for i in range(2) %}{% endfor %}",
+                ctx
+            )
+            .unwrap(),
+            Validation {
+                rendered: "# Standup\n- Alice\n- Bob\n".to_string(),
+                missing_variables: vec![],
+            }
+        );
+
+        match validate("# {{ title }}\n{% for p in participants %}\n  {{ p }", ctx) {
+            Err(Error::SyntaxError { line, column, .. }) => {
+                assert_eq!(line, Some(3));
+                assert!(column.is_some());
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // Missing variables don't stop the render.
+        assert_eq!(
+            validate(
+                "{{ title }} with {{ attendees }}{% if place %} at {{ place }}{% endif %}",
+                ctx
+            )
+            .unwrap(),
+            Validation {
+                rendered: "Standup with ".to_string(),
+                missing_variables: vec!["attendees".to_string(), "place".to_string()],
+            }
+        );
+
+        assert!(matches!(
+            validate("{{ title }} at {{ place.name }}", ctx),
+            Err(Error::UndefinedVariable { name }) if name == "place"
+        ));
+
+        assert!(validate("{{ title | no_such_filter }}", ctx).is_err());
+    }
+}
@@ -29,7 +29,7 @@ use aws_sdk_transcribestreaming::types::{
 };
 use aws_sdk_transcribestreaming::{config::Region, Client};
 
-use owhisper_interface::{ListenInputChunk, ListenOutputChunk, ListenParams, Word2};
+use owhisper_interface::{ListenInputChunk, ListenOutputChunk, ListenParams, Word2, WordKind};
 
 mod error;
 pub use error::*;
@@ -164,6 +164,9 @@ impl TranscribeService {
                                                 confidence: None,
                                                 start_ms: Some((result.start_time * 1000.0) as u64),
                                                 end_ms: Some((result.end_time * 1000.0) as u64),
+                                                recording_start_s: None,
+                                                recording_end_s: None,
+                                                kind: WordKind::classify(word_text),
                                             });
                                         }
 
@@ -20,7 +20,7 @@ use deepgram::{
     Deepgram,
 };
 
-use owhisper_interface::{ListenInputChunk, ListenOutputChunk, ListenParams, Word2};
+use owhisper_interface::{ListenInputChunk, ListenOutputChunk, ListenParams, Word2, WordKind};
 
 #[derive(Clone)]
 pub struct TranscribeService {
@@ -127,6 +127,9 @@ impl TranscribeService {
                                                 confidence: Some(word.confidence as f32),
                                                 start_ms: Some((word.start * 1000.0) as u64),
                                                 end_ms: Some((word.end * 1000.0) as u64),
+                                                recording_start_s: None,
+                                                recording_end_s: None,
+                                                kind: WordKind::classify(&word.word),
                                             });
                                         }
                                     } else if !first_alt.transcript.is_empty() {
@@ -137,6 +140,9 @@ impl TranscribeService {
                                                 confidence: Some(first_alt.confidence as f32),
                                                 start_ms: None,
                                                 end_ms: None,
+                                                recording_start_s: None,
+                                                recording_end_s: None,
+                                                kind: WordKind::classify(text),
                                             });
                                         }
                                     }
@@ -286,6 +286,7 @@ where
                                 alternatives: vec![Alternatives {
                                     transcript: text.clone(),
                                     languages: vec![],
+                                    language_confidence: None,
                                     words,
                                     confidence,
                                 }],
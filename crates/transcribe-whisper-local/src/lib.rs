@@ -12,8 +12,7 @@ mod tests {
     use futures_util::StreamExt;
     use hypr_audio_utils::AudioFormatExt;
 
-    #[tokio::test]
-    async fn test_service() -> Result<(), Box<dyn std::error::Error>> {
+    async fn start_server() -> (std::net::SocketAddr, tokio::task::JoinHandle<()>) {
         let model_path = dirs::data_dir()
             .unwrap()
             .join("com.hyprnote.dev")
@@ -26,8 +25,8 @@ mod tests {
 
         let app = axum::Router::new().route_service("/v1/listen", service);
 
-        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
-        let addr = listener.local_addr()?;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
 
         let server = axum::serve(listener, app);
         let server_handle = tokio::spawn(async move {
@@ -36,6 +35,13 @@ mod tests {
             }
         });
 
+        (addr, server_handle)
+    }
+
+    #[tokio::test]
+    async fn test_service() -> Result<(), Box<dyn std::error::Error>> {
+        let (addr, server_handle) = start_server().await;
+
         let client = owhisper_client::ListenClient::builder()
             .api_base(format!("http://{}", addr))
             .build_single();
@@ -52,4 +58,40 @@ mod tests {
         server_handle.abort();
         Ok(())
     }
+
+    // Start of the first transcript the server sends back for the test audio.
+    async fn first_start(addr: std::net::SocketAddr) -> f64 {
+        let client = owhisper_client::ListenClient::builder()
+            .api_base(format!("http://{}", addr))
+            .build_single();
+
+        let audio = rodio::Decoder::new(std::io::BufReader::new(
+            std::fs::File::open(hypr_data::english_1::AUDIO_PATH).unwrap(),
+        ))
+        .unwrap()
+        .to_i16_le_chunks(16000, 512);
+        let input = audio.map(|chunk| owhisper_interface::MixedMessage::Audio(chunk));
+
+        let (stream, _handle) = client.from_realtime_audio(input).await.unwrap();
+        let mut stream = std::pin::pin!(stream);
+        while let Some(response) = stream.next().await {
+            if let owhisper_interface::StreamResponse::TranscriptResponse { start, .. } =
+                response.unwrap()
+            {
+                return start;
+            }
+        }
+        panic!("no transcript");
+    }
+
+    #[tokio::test]
+    async fn test_each_connection_starts_from_zero() {
+        let (addr, server_handle) = start_server().await;
+
+        // The second connection doesn't continue from where the first one's audio ended.
+        assert_eq!(first_start(addr).await, 0.0);
+        assert_eq!(first_start(addr).await, 0.0);
+
+        server_handle.abort();
+    }
 }
@@ -1,4 +1,4 @@
-use owhisper_interface::Word2;
+use owhisper_interface::{Word2, WordKind};
 
 pub fn process_recorded(
     model_path: impl AsRef<std::path::Path>,
@@ -47,6 +47,9 @@ pub fn process_recorded(
                 confidence: Some(whisper_segment.confidence()),
                 start_ms: Some(start_ms),
                 end_ms: Some(end_ms),
+                recording_start_s: None,
+                recording_end_s: None,
+                kind: WordKind::classify(whisper_segment.text()),
             };
 
             // TODO
@@ -26,6 +26,7 @@ use crate::GlobalTimer;
 #[derive(Clone)]
 pub struct TranscribeService {
     model_path: PathBuf,
+    compute_backend: hypr_whisper_local::ComputeBackend,
     connection_manager: ConnectionManager,
 }
 
@@ -38,6 +39,7 @@ impl TranscribeService {
 #[derive(Default)]
 pub struct TranscribeServiceBuilder {
     model_path: Option<PathBuf>,
+    compute_backend: Option<hypr_whisper_local::ComputeBackend>,
     connection_manager: Option<ConnectionManager>,
 }
 
@@ -47,9 +49,16 @@ impl TranscribeServiceBuilder {
         self
     }
 
+    // Preferred backend; a GPU that fails to initialize falls back to the CPU.
+    pub fn compute_backend(mut self, compute_backend: hypr_whisper_local::ComputeBackend) -> Self {
+        self.compute_backend = Some(compute_backend);
+        self
+    }
+
     pub fn build(self) -> TranscribeService {
         TranscribeService {
             model_path: self.model_path.unwrap(),
+            compute_backend: self.compute_backend.unwrap_or_default(),
             connection_manager: self
                 .connection_manager
                 .unwrap_or_else(ConnectionManager::default),
@@ -71,6 +80,7 @@ where
 
     fn call(&mut self, req: Request<B>) -> Self::Future {
         let model_path = self.model_path.clone();
+        let compute_backend = self.compute_backend;
         let connection_manager = self.connection_manager.clone();
 
         Box::pin(async move {
@@ -101,9 +111,13 @@ where
                         .filter_map(|lang| lang.clone().try_into().ok())
                         .collect::<Vec<hypr_whisper::Language>>(),
                 )
+                .compute_backend(compute_backend)
                 .build()
             {
-                Ok(model) => model,
+                Ok(model) => {
+                    tracing::info!(backend = ?model.backend(), "whisper_model_loaded");
+                    model
+                }
                 Err(e) => {
                     let res = (
                         StatusCode::INTERNAL_SERVER_ERROR,
@@ -138,6 +152,7 @@ async fn handle_websocket_connection(
         .map(|ms| Duration::from_millis(ms))
         .unwrap_or(Duration::from_millis(400));
 
+    // Per connection, so a new recording doesn't continue from the previous one's timestamps.
     let global_timer = GlobalTimer::new();
 
     match params.channels {
@@ -234,6 +249,7 @@ async fn process_transcription_stream(
                 let meta = chunk.meta();
                 let text = chunk.text().to_string();
                 let language = chunk.language().map(|s| s.to_string()).map(|s| vec![s]).unwrap_or_default();
+                let language_confidence = chunk.language_confidence().map(|c| c as f64);
                 let duration_f64 = chunk.duration() as f64;
                 let confidence = chunk.confidence() as f64;
 
@@ -280,6 +296,7 @@ async fn process_transcription_stream(
                         alternatives: vec![Alternatives{
                             transcript: text.clone(),
                             languages: language.clone(),
+                            language_confidence,
                             words,
                             confidence,
                         }],
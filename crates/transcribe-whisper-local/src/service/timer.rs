@@ -1,5 +1,7 @@
 use std::sync::{Arc, Mutex};
 
+// Position of the transcribed audio within a stream, in seconds. Clones share it, so each
+// connection makes its own rather than reusing one across streams.
 #[derive(Debug, Clone)]
 pub struct GlobalTimer {
     inner: Arc<Mutex<GlobalTimerInner>>,
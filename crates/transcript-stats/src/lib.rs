@@ -0,0 +1,175 @@
+use owhisper_interface::{SpeakerIdentity, Word2, WordKind};
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct TranscriptStats {
+    // In order of first appearance.
+    pub speakers: Vec<SpeakerStats>,
+    pub word_count: usize,
+    // From the first word's start to the last word's end.
+    pub duration_seconds: f64,
+    // Share of `duration_seconds` in which nobody speaks.
+    pub silence_ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct SpeakerStats {
+    pub speaker: Option<SpeakerIdentity>,
+    pub talk_seconds: f64,
+    pub word_count: usize,
+    pub turns: usize,
+}
+
+// Talk time comes from word durations. Overlapping speech counts for each speaker involved,
+// so talk times can add up to more than the duration.
+pub fn analyze(words: &[Word2]) -> TranscriptStats {
+    let mut words = words
+        .iter()
+        .filter(|w| w.kind == WordKind::Speech)
+        .collect::<Vec<_>>();
+    words.sort_by_key(|w| w.start_ms);
+
+    let mut speakers: Vec<SpeakerStats> = Vec::new();
+    let mut intervals: Vec<Vec<(u64, u64)>> = Vec::new();
+    let mut previous: Option<usize> = None;
+
+    for word in &words {
+        let idx = match speakers.iter().position(|s| s.speaker == word.speaker) {
+            Some(idx) => idx,
+            None => {
+                speakers.push(SpeakerStats {
+                    speaker: word.speaker.clone(),
+                    talk_seconds: 0.0,
+                    word_count: 0,
+                    turns: 0,
+                });
+                intervals.push(Vec::new());
+                speakers.len() - 1
+            }
+        };
+
+        let stats = &mut speakers[idx];
+        stats.word_count += 1;
+        if previous != Some(idx) {
+            stats.turns += 1;
+        }
+        previous = Some(idx);
+
+        if let Some(interval) = interval(word) {
+            intervals[idx].push(interval);
+        }
+    }
+
+    for (stats, intervals) in speakers.iter_mut().zip(&intervals) {
+        stats.talk_seconds = covered_ms(intervals.clone()) as f64 / 1000.0;
+    }
+
+    let all = intervals.into_iter().flatten().collect::<Vec<_>>();
+    let duration_ms = match (all.iter().map(|i| i.0).min(), all.iter().map(|i| i.1).max()) {
+        (Some(start), Some(end)) => end - start,
+        _ => 0,
+    };
+    let silence_ratio = if duration_ms == 0 {
+        0.0
+    } else {
+        1.0 - covered_ms(all) as f64 / duration_ms as f64
+    };
+
+    TranscriptStats {
+        speakers,
+        word_count: words.len(),
+        duration_seconds: duration_ms as f64 / 1000.0,
+        silence_ratio,
+    }
+}
+
+fn interval(word: &Word2) -> Option<(u64, u64)> {
+    match (word.start_ms, word.end_ms) {
+        (Some(start), Some(end)) if end > start => Some((start, end)),
+        _ => None,
+    }
+}
+
+// Total length of the union of `intervals`, so overlaps are only counted once.
+fn covered_ms(mut intervals: Vec<(u64, u64)>) -> u64 {
+    intervals.sort_unstable();
+
+    let mut total = 0;
+    let mut current: Option<(u64, u64)> = None;
+
+    for (start, end) in intervals {
+        current = match current {
+            Some((s, e)) if start <= e => Some((s, e.max(end))),
+            Some((s, e)) => {
+                total += e - s;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+
+    total + current.map_or(0, |(s, e)| e - s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(speaker: u8, start_ms: u64, end_ms: u64) -> Word2 {
+        Word2 {
+            text: "word".to_string(),
+            speaker: Some(SpeakerIdentity::Unassigned { index: speaker }),
+            start_ms: Some(start_ms),
+            end_ms: Some(end_ms),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_two_speakers() {
+        let words = vec![
+            // Speaker 0 talks for 2s.
+            word(0, 0, 1000),
+            word(0, 1000, 2000),
+            // 1s of silence, then speaker 1 for 2s, overlapped by speaker 0 for its last 0.5s.
+            word(1, 3000, 4000),
+            word(1, 4000, 5000),
+            word(0, 4500, 5000),
+            // Speaker 1 again, after 1s of silence.
+            word(1, 6000, 7000),
+            Word2 {
+                text: "[laughter]".to_string(),
+                kind: WordKind::Event,
+                start_ms: Some(7000),
+                end_ms: Some(9000),
+                ..Default::default()
+            },
+        ];
+
+        let stats = analyze(&words);
+
+        assert_eq!(stats.word_count, 6);
+        assert_eq!(stats.duration_seconds, 7.0);
+        assert!((stats.silence_ratio - 2.0 / 7.0).abs() < 1e-9);
+
+        assert_eq!(stats.speakers.len(), 2);
+
+        let speaker_0 = &stats.speakers[0];
+        assert_eq!(
+            speaker_0.speaker,
+            Some(SpeakerIdentity::Unassigned { index: 0 })
+        );
+        assert_eq!(speaker_0.talk_seconds, 2.5);
+        assert_eq!(speaker_0.word_count, 3);
+        assert_eq!(speaker_0.turns, 2);
+
+        let speaker_1 = &stats.speakers[1];
+        assert_eq!(speaker_1.talk_seconds, 3.0);
+        assert_eq!(speaker_1.word_count, 3);
+        assert_eq!(speaker_1.turns, 2);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(analyze(&[]), TranscriptStats::default());
+    }
+}
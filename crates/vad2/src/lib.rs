@@ -1,4 +1,6 @@
 use std::ops::{Deref, DerefMut};
+
+use futures_util::{Stream, StreamExt};
 use ten_vad_rs::{TenVad, TenVadError};
 
 pub struct Vad {
@@ -7,11 +9,51 @@ pub struct Vad {
 
 const MODEL_BYTES: &[u8] = include_bytes!("../assets/model.onnx");
 
+// Samples per frame: 16ms at 16kHz, the hop size the model runs at.
+pub const FRAME_SIZE: usize = 256;
+
 impl Vad {
     pub fn new() -> Result<Self, TenVadError> {
         let inner = TenVad::new_from_bytes(MODEL_BYTES, 16000)?;
         Ok(Self { inner })
     }
+
+    /// Probability, from 0 to 1, that `frame` is speech. `frame` is `FRAME_SIZE` samples of
+    /// 16kHz audio. Frames are scored in context, so feed them in order.
+    pub fn probability(&mut self, frame: &[i16]) -> Result<f32, TenVadError> {
+        self.inner.process_frame(frame)
+    }
+
+    /// Same as `probability`, for samples from -1 to 1.
+    pub fn probability_f32(&mut self, frame: &[f32]) -> Result<f32, TenVadError> {
+        let frame = frame
+            .iter()
+            .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect::<Vec<_>>();
+        self.probability(&frame)
+    }
+
+    /// Speech probability of every `FRAME_SIZE` samples of `chunks`, in order. Chunks can be any
+    /// length; a trailing partial frame is not scored.
+    pub fn probabilities<S>(self, chunks: S) -> impl Stream<Item = Result<f32, TenVadError>>
+    where
+        S: Stream<Item = Vec<f32>>,
+    {
+        chunks
+            .scan((self, Vec::new()), |(vad, buf), chunk| {
+                buf.extend(chunk);
+
+                let frames = buf.len() / FRAME_SIZE;
+                let probabilities = buf
+                    .drain(..frames * FRAME_SIZE)
+                    .collect::<Vec<_>>()
+                    .chunks(FRAME_SIZE)
+                    .map(|frame| vad.probability_f32(frame))
+                    .collect::<Vec<_>>();
+                futures_util::future::ready(Some(probabilities))
+            })
+            .flat_map(futures_util::stream::iter)
+    }
 }
 
 impl Deref for Vad {
@@ -27,3 +69,55 @@ impl DerefMut for Vad {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speech() -> Vec<i16> {
+        hound::WavReader::open(hypr_data::english_1::AUDIO_PART2_16000HZ_PATH)
+            .unwrap()
+            .into_samples::<i16>()
+            .map(|s| s.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_probability() {
+        let mut vad = Vad::new().unwrap();
+        let silence = (0..100)
+            .map(|_| vad.probability(&[0; FRAME_SIZE]).unwrap())
+            .collect::<Vec<_>>();
+        assert!(silence.iter().all(|&p| p < 0.1), "{:?}", silence);
+
+        let mut vad = Vad::new().unwrap();
+        let speech = speech()
+            .chunks_exact(FRAME_SIZE)
+            .map(|frame| vad.probability(frame).unwrap())
+            .collect::<Vec<_>>();
+        // Pauses between words score low too, but plenty of a conversation is speech.
+        let voiced = speech.iter().filter(|&&p| p > 0.5).count();
+        assert!(voiced * 4 > speech.len(), "{} of {}", voiced, speech.len());
+    }
+
+    #[tokio::test]
+    async fn test_probabilities_stream() {
+        let samples = speech()
+            .into_iter()
+            .map(|s| s as f32 / i16::MAX as f32)
+            .collect::<Vec<_>>();
+
+        // Chunked unlike the frames, as audio sources deliver it.
+        let probabilities = Vad::new()
+            .unwrap()
+            .probabilities(futures_util::stream::iter(
+                samples.chunks(1000).map(|c| c.to_vec()),
+            ))
+            .map(|p| p.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(probabilities.len(), samples.len() / FRAME_SIZE);
+        assert!(probabilities.iter().any(|&p| p > 0.5));
+    }
+}
@@ -10,7 +10,7 @@ use whisper_rs::{
 
 use hypr_whisper::Language;
 
-use crate::Segment;
+use crate::{ComputeBackend, Segment};
 
 lazy_static! {
     static ref TRAILING_DOTS: Regex = Regex::new(r"\.{2,}$").unwrap();
@@ -20,6 +20,7 @@ lazy_static! {
 pub struct WhisperBuilder {
     model_path: Option<String>,
     languages: Option<Vec<Language>>,
+    compute_backend: Option<ComputeBackend>,
 }
 
 impl WhisperBuilder {
@@ -33,25 +34,38 @@ impl WhisperBuilder {
         self
     }
 
+    // Defaults to the GPU, falling back to the CPU if it fails to initialize.
+    pub fn compute_backend(mut self, backend: ComputeBackend) -> Self {
+        self.compute_backend = Some(backend);
+        self
+    }
+
     pub fn build(self) -> Result<Whisper, crate::Error> {
         unsafe { Self::suppress_log() };
 
-        let context_param = {
-            let mut p = WhisperContextParameters::default();
-            p.gpu_device = 0;
-            p.use_gpu = true;
-            p.flash_attn = false; // crash on macos
-            p.dtw_parameters.mode = whisper_rs::DtwMode::None;
-            p
-        };
-
         let model_path = self.model_path.unwrap();
         if !std::path::Path::new(&model_path).exists() {
             return Err(crate::Error::ModelNotFound);
         }
 
-        let ctx = WhisperContext::new_with_params(&model_path, context_param)?;
-        let state = ctx.create_state()?;
+        let ((ctx, state), backend) = hypr_compute_backend::load_with_fallback(
+            self.compute_backend.unwrap_or_default(),
+            |backend| {
+                let context_param = {
+                    let mut p = WhisperContextParameters::default();
+                    p.gpu_device = 0;
+                    p.use_gpu = backend == ComputeBackend::Gpu;
+                    p.flash_attn = false; // crash on macos
+                    p.dtw_parameters.mode = whisper_rs::DtwMode::None;
+                    p
+                };
+
+                // Some GPUs only fail once the state's buffers are allocated.
+                let ctx = WhisperContext::new_with_params(&model_path, context_param)?;
+                let state = ctx.create_state()?;
+                Ok((ctx, state))
+            },
+        )?;
         let token_beg = ctx.token_beg();
 
         Ok(Whisper {
@@ -61,6 +75,7 @@ impl WhisperBuilder {
             dynamic_prompt: "".to_string(),
             state,
             token_beg,
+            backend,
         })
     }
 
@@ -84,6 +99,7 @@ pub struct Whisper {
     dynamic_prompt: String,
     state: WhisperState,
     token_beg: WhisperTokenId,
+    backend: ComputeBackend,
 }
 
 impl Whisper {
@@ -91,6 +107,10 @@ impl Whisper {
         WhisperBuilder::default()
     }
 
+    pub fn backend(&self) -> ComputeBackend {
+        self.backend
+    }
+
     pub fn transcribe(&mut self, audio: &[f32]) -> Result<Vec<Segment>, crate::Error> {
         #[cfg(debug_assertions)]
         self.debug(audio);
@@ -102,7 +122,7 @@ impl Whisper {
         }
 
         let token_beg = self.token_beg;
-        let language = self.get_language(audio)?;
+        let (language, language_confidence) = self.get_language(audio)?;
 
         let params = {
             let mut p = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
@@ -164,6 +184,7 @@ impl Whisper {
             segments.push(Segment {
                 text,
                 language: language.clone(),
+                language_confidence,
                 start,
                 end,
                 // https://github.com/ggml-org/whisper.cpp/pull/971/files#diff-2d3599a9fad195f2c3c60bd06691bc1815325b3560b5feda41a91fa71194e805R310-R327
@@ -189,19 +210,23 @@ impl Whisper {
         Ok(segments)
     }
 
-    fn get_language(&mut self, audio: &[f32]) -> Result<Option<String>, crate::Error> {
+    // Confidence is only reported when the language was actually detected.
+    fn get_language(
+        &mut self,
+        audio: &[f32],
+    ) -> Result<(Option<String>, Option<f32>), crate::Error> {
         if self.languages.len() == 0 {
             tracing::info!("no_language_specified");
-            return Ok(None);
+            return Ok((None, None));
         }
 
         if self.languages.len() == 1 {
             let lang = &self.languages[0];
             tracing::info!("single_language_specified: {}", lang);
-            return Ok(Some(lang.to_string()));
+            return Ok((Some(lang.to_string()), None));
         }
 
-        let lang_str = {
+        let detected = {
             self.state.pcm_to_mel(audio, 1)?;
             let (_lang_id, lang_probs) = self.state.lang_detect(0, 1)?;
 
@@ -219,11 +244,17 @@ impl Whisper {
                 }
             }
 
-            tracing::info!("predicted: {:#?}, from: {:#?}", best_lang, self.languages);
-            best_lang
+            tracing::info!(
+                "predicted: {:#?} ({}), from: {:#?}",
+                best_lang,
+                best_prob,
+                self.languages
+            );
+            let confidence = best_lang.is_some().then_some(best_prob);
+            (best_lang, confidence)
         };
 
-        Ok(lang_str)
+        Ok(detected)
     }
 
     fn filter_segments(segments: Vec<Segment>) -> Vec<Segment> {
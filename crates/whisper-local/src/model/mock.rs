@@ -1,4 +1,4 @@
-use crate::Segment;
+use crate::{ComputeBackend, Segment};
 use hypr_whisper::Language;
 
 #[derive(Default)]
@@ -16,6 +16,10 @@ impl WhisperBuilder {
         self
     }
 
+    pub fn compute_backend(self, _backend: ComputeBackend) -> Self {
+        self
+    }
+
     pub fn build(self) -> Result<Whisper, crate::Error> {
         Ok(Whisper {})
     }
@@ -26,10 +30,15 @@ impl Whisper {
         WhisperBuilder::default()
     }
 
+    pub fn backend(&self) -> ComputeBackend {
+        ComputeBackend::Cpu
+    }
+
     pub fn transcribe(&mut self, _samples: &[f32]) -> Result<Vec<Segment>, crate::Error> {
         Ok(vec![Segment {
             text: "mock".to_string(),
             language: None,
+            language_confidence: None,
             start: 0.0,
             end: 1.0,
             confidence: 1.0,
@@ -8,10 +8,14 @@ mod mock;
 #[cfg(not(feature = "actual"))]
 pub use mock::*;
 
+pub use hypr_compute_backend::ComputeBackend;
+
 #[derive(Debug, Default)]
 pub struct Segment {
     pub text: String,
     pub language: Option<String>,
+    // Detection probability of `language`, when it was detected rather than given.
+    pub language_confidence: Option<f32>,
     pub start: f64,
     pub end: f64,
     pub confidence: f32,
@@ -27,6 +31,10 @@ impl Segment {
         self.language.as_deref()
     }
 
+    pub fn language_confidence(&self) -> Option<f32> {
+        self.language_confidence
+    }
+
     pub fn start(&self) -> f64 {
         self.start
     }
@@ -0,0 +1,12 @@
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Ws(#[from] hypr_ws::Error),
+    #[error(transparent)]
+    Encode(#[from] hypr_audio_utils::Error),
+    #[error(
+        "opus is only encoded at {} Hz, not {0} Hz",
+        hypr_audio_utils::OPUS_SAMPLE_RATE
+    )]
+    UnsupportedOpusSampleRate(u32),
+}
@@ -1,7 +1,11 @@
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
 
+use hypr_audio_utils::OpusEncoder;
 use hypr_ws::client::{ClientRequestBuilder, Message, WebSocketClient, WebSocketIO};
-use owhisper_interface::{ControlMessage, MixedMessage, StreamResponse};
+use owhisper_interface::{AudioEncoding, ControlMessage, MixedMessage, StreamResponse};
+
+mod error;
+pub use error::*;
 
 fn interleave_audio(mic: &[u8], speaker: &[u8]) -> Vec<u8> {
     let mic_samples: Vec<i16> = mic
@@ -26,6 +30,96 @@ fn interleave_audio(mic: &[u8], speaker: &[u8]) -> Vec<u8> {
     interleaved
 }
 
+// Turns 16-bit PCM into what the backend was told to expect.
+struct AudioEncoder {
+    opus: Option<OpusEncoder>,
+}
+
+impl AudioEncoder {
+    // Opus is encoded at a fixed rate, so audio at any other rate would be sent mislabeled.
+    fn new(encoding: AudioEncoding, channels: u8, sample_rate: u32) -> Result<Self, Error> {
+        let opus = match encoding {
+            AudioEncoding::Linear16 => None,
+            AudioEncoding::Opus if sample_rate != hypr_audio_utils::OPUS_SAMPLE_RATE => {
+                return Err(Error::UnsupportedOpusSampleRate(sample_rate));
+            }
+            AudioEncoding::Opus => Some(OpusEncoder::new(channels)?),
+        };
+
+        Ok(Self { opus })
+    }
+
+    fn encode(&mut self, pcm: bytes::Bytes) -> Result<Vec<ListenClientInput>, Error> {
+        let Some(opus) = self.opus.as_mut() else {
+            return Ok(vec![ListenClientInput::Audio(pcm)]);
+        };
+
+        let samples: Vec<i16> = pcm
+            .chunks_exact(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+
+        let packets = opus.encode(&samples)?;
+        Ok(packets.into_iter().map(ListenClientInput::Audio).collect())
+    }
+
+    // Sends out the audio still held back for an incomplete frame.
+    fn finish(&mut self) -> Result<Vec<ListenClientInput>, Error> {
+        let Some(opus) = self.opus.as_mut() else {
+            return Ok(vec![]);
+        };
+
+        Ok(opus.finish()?.map(ListenClientInput::Audio).into_iter().collect())
+    }
+}
+
+// Encodes the outgoing audio. The first chunk that fails to encode ends it, and the error comes
+// out of the returned stream instead, so it reaches whoever reads the responses. Held-back audio
+// is flushed before `Finalize`/`CloseStream` and when the input ends.
+fn encode_stream(
+    stream: impl Stream<Item = ListenClientInput> + Send + Unpin + 'static,
+    mut encoder: AudioEncoder,
+) -> (
+    impl Stream<Item = ListenClientInput> + Send + Unpin + 'static,
+    impl Stream<Item = Error>,
+) {
+    let (error_tx, error_rx) = tokio::sync::oneshot::channel();
+    let mut error_tx = Some(error_tx);
+
+    let audio = stream
+        .map(Some)
+        .chain(futures_util::stream::once(futures_util::future::ready(None)))
+        .scan((), move |_, input| {
+            let encoded = match input {
+                Some(ListenClientInput::Audio(data)) => encoder.encode(data),
+                Some(ListenClientInput::Control(
+                    control @ (ControlMessage::Finalize | ControlMessage::CloseStream),
+                )) => encoder.finish().map(|mut flushed| {
+                    flushed.push(ListenClientInput::Control(control));
+                    flushed
+                }),
+                Some(control) => Ok(vec![control]),
+                None => encoder.finish(),
+            };
+            let encoded = match encoded {
+                Ok(encoded) => Some(encoded),
+                Err(e) => {
+                    if let Some(error_tx) = error_tx.take() {
+                        let _ = error_tx.send(e);
+                    }
+                    None
+                }
+            };
+            futures_util::future::ready(encoded)
+        })
+        .flat_map(futures_util::stream::iter);
+
+    let errors = futures_util::stream::once(error_rx)
+        .filter_map(|received| futures_util::future::ready(received.ok()));
+
+    (audio, errors)
+}
+
 #[derive(Default)]
 pub struct ListenClientBuilder {
     api_base: Option<String>,
@@ -103,8 +197,8 @@ impl ListenClientBuilder {
                 .append_pair("filler_words", "false")
                 .append_pair("interim_results", "true")
                 .append_pair("mip_opt_out", "true")
-                .append_pair("sample_rate", "16000")
-                .append_pair("encoding", "linear16")
+                .append_pair("sample_rate", &params.sample_rate.to_string())
+                .append_pair("encoding", params.encoding.as_ref())
                 .append_pair("diarize", "true")
                 .append_pair("multichannel", "true")
                 .append_pair("punctuate", "true")
@@ -143,20 +237,37 @@ impl ListenClientBuilder {
         request
     }
 
+    fn encoding(&self) -> (AudioEncoding, u32) {
+        let params = self.params.clone().unwrap_or_default();
+        (params.encoding, params.sample_rate)
+    }
+
     pub fn build_single(self) -> ListenClient {
+        let (encoding, sample_rate) = self.encoding();
         let request = self.build_request(1);
-        ListenClient { request }
+        ListenClient {
+            request,
+            encoding,
+            sample_rate,
+        }
     }
 
     pub fn build_dual(self) -> ListenClientDual {
+        let (encoding, sample_rate) = self.encoding();
         let request = self.build_request(2);
-        ListenClientDual { request }
+        ListenClientDual {
+            request,
+            encoding,
+            sample_rate,
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct ListenClient {
     request: ClientRequestBuilder,
+    encoding: AudioEncoding,
+    sample_rate: u32,
 }
 
 type ListenClientInput = MixedMessage<bytes::Bytes, ControlMessage>;
@@ -188,41 +299,13 @@ impl WebSocketIO for ListenClient {
     }
 }
 
+// Audio is interleaved and encoded before it reaches the socket, so it is sent the same way
+// `ListenClient` sends it.
 #[derive(Clone)]
 pub struct ListenClientDual {
     request: ClientRequestBuilder,
-}
-
-impl WebSocketIO for ListenClientDual {
-    type Data = ListenClientDualInput;
-    type Input = ListenClientInput;
-    type Output = StreamResponse;
-
-    fn to_input(data: Self::Data) -> Self::Input {
-        match data {
-            ListenClientDualInput::Audio((mic, speaker)) => {
-                let interleaved = interleave_audio(&mic, &speaker);
-                ListenClientInput::Audio(interleaved.into())
-            }
-            ListenClientDualInput::Control(control) => ListenClientInput::Control(control),
-        }
-    }
-
-    fn to_message(input: Self::Input) -> Message {
-        match input {
-            ListenClientInput::Audio(data) => Message::Binary(data),
-            ListenClientInput::Control(control) => {
-                Message::Text(serde_json::to_string(&control).unwrap().into())
-            }
-        }
-    }
-
-    fn from_message(msg: Message) -> Option<Self::Output> {
-        match msg {
-            Message::Text(text) => serde_json::from_str::<Self::Output>(&text).ok(),
-            _ => None,
-        }
-    }
+    encoding: AudioEncoding,
+    sample_rate: u32,
 }
 
 impl ListenClient {
@@ -235,13 +318,22 @@ impl ListenClient {
         audio_stream: impl Stream<Item = ListenClientInput> + Send + Unpin + 'static,
     ) -> Result<
         (
-            impl Stream<Item = Result<StreamResponse, hypr_ws::Error>>,
+            impl Stream<Item = Result<StreamResponse, Error>>,
             hypr_ws::client::WebSocketHandle,
         ),
-        hypr_ws::Error,
+        Error,
     > {
+        let encoder = AudioEncoder::new(self.encoding, 1, self.sample_rate)?;
+        let (audio_stream, encode_errors) = encode_stream(audio_stream, encoder);
+
         let ws = WebSocketClient::new(self.request.clone());
-        ws.from_audio::<Self>(audio_stream).await
+        let (stream, handle) = ws.from_audio::<ListenClient>(audio_stream).await?;
+
+        let stream = futures_util::stream::select(
+            stream.map(|response| response.map_err(Error::from)),
+            encode_errors.map(Err),
+        );
+        Ok((stream, handle))
     }
 }
 
@@ -251,13 +343,28 @@ impl ListenClientDual {
         stream: impl Stream<Item = ListenClientDualInput> + Send + Unpin + 'static,
     ) -> Result<
         (
-            impl Stream<Item = Result<StreamResponse, hypr_ws::Error>>,
+            impl Stream<Item = Result<StreamResponse, Error>>,
             hypr_ws::client::WebSocketHandle,
         ),
-        hypr_ws::Error,
+        Error,
     > {
+        let encoder = AudioEncoder::new(self.encoding, 2, self.sample_rate)?;
+        let stream = stream.map(|input| match input {
+            ListenClientDualInput::Audio((mic, speaker)) => {
+                ListenClientInput::Audio(interleave_audio(&mic, &speaker).into())
+            }
+            ListenClientDualInput::Control(control) => ListenClientInput::Control(control),
+        });
+        let (stream, encode_errors) = encode_stream(stream, encoder);
+
         let ws = WebSocketClient::new(self.request.clone());
-        ws.from_audio::<Self>(stream).await
+        let (stream, handle) = ws.from_audio::<ListenClient>(stream).await?;
+
+        let stream = futures_util::stream::select(
+            stream.map(|response| response.map_err(Error::from)),
+            encode_errors.map(Err),
+        );
+        Ok((stream, handle))
     }
 }
 
@@ -265,9 +372,18 @@ impl ListenClientDual {
 mod tests {
     use super::*;
 
-    use futures_util::StreamExt;
     use hypr_audio_utils::AudioFormatExt;
 
+    #[test]
+    fn test_opus_only_at_its_sample_rate() {
+        assert!(AudioEncoder::new(AudioEncoding::Opus, 1, 16000).is_ok());
+        assert!(matches!(
+            AudioEncoder::new(AudioEncoding::Opus, 1, 48000),
+            Err(Error::UnsupportedOpusSampleRate(48000))
+        ));
+        assert!(AudioEncoder::new(AudioEncoding::Linear16, 1, 48000).is_ok());
+    }
+
     #[tokio::test]
     // cargo test -p owhisper-client test_client_deepgram -- --nocapture
     async fn test_client_deepgram() {
@@ -27,19 +27,65 @@ common_derives! {
         pub confidence: Option<f32>,
         pub start_ms: Option<u64>,
         pub end_ms: Option<u64>,
+        // Seconds since the start of the session's recording, for syncing with playback.
+        // `start_ms`/`end_ms` are wall-clock based instead.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub recording_start_s: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub recording_end_s: Option<f64>,
+        #[serde(default, skip_serializing_if = "WordKind::is_speech")]
+        pub kind: WordKind,
+    }
+}
+
+common_derives! {
+    #[derive(Default, Copy, Eq)]
+    pub enum WordKind {
+        #[default]
+        #[serde(rename = "speech")]
+        Speech,
+        // Non-lexical sounds some backends transcribe, like `[laughter]` or `(applause)`.
+        #[serde(rename = "event")]
+        Event,
+    }
+}
+
+impl WordKind {
+    pub fn is_speech(&self) -> bool {
+        matches!(self, WordKind::Speech)
+    }
+
+    pub fn classify(text: &str) -> Self {
+        let text = text.trim().trim_end_matches(['.', ',', '!', '?']);
+
+        let bracketed = [('[', ']'), ('(', ')')].iter().any(|(open, close)| {
+            text.len() > 2 && text.starts_with(*open) && text.ends_with(*close)
+        });
+
+        if bracketed {
+            WordKind::Event
+        } else {
+            WordKind::Speech
+        }
     }
 }
 
 impl From<Word> for Word2 {
     fn from(word: Word) -> Self {
+        let kind = word.kind();
+        let text = word.punctuated_word.unwrap_or(word.word);
+
         Word2 {
-            text: word.punctuated_word.unwrap_or(word.word),
+            text,
             speaker: word
                 .speaker
                 .map(|s| SpeakerIdentity::Unassigned { index: s as u8 }),
             confidence: Some(word.confidence as f32),
             start_ms: Some((word.start * 1000.0) as u64),
             end_ms: Some((word.end * 1000.0) as u64),
+            recording_start_s: None,
+            recording_end_s: None,
+            kind,
         }
     }
 }
@@ -117,6 +163,21 @@ impl Default for AudioMode {
     }
 }
 
+common_derives! {
+    #[derive(Default, Copy, Eq, strum::AsRefStr)]
+    pub enum AudioEncoding {
+        // 16-bit little-endian PCM.
+        #[default]
+        #[serde(rename = "linear16")]
+        #[strum(serialize = "linear16")]
+        Linear16,
+        // Raw Opus packets, one per message. Saves bandwidth with remote backends.
+        #[serde(rename = "opus")]
+        #[strum(serialize = "opus")]
+        Opus,
+    }
+}
+
 common_derives! {
     pub struct ListenParams {
         #[serde(default)]
@@ -126,9 +187,18 @@ common_derives! {
         #[serde(default)]
         pub languages: Vec<hypr_language::Language>,
         pub redemption_time_ms: Option<u64>,
+        #[serde(default)]
+        pub encoding: AudioEncoding,
+        // Rate of the audio sent to the server, in Hz.
+        #[serde(default = "default_sample_rate")]
+        pub sample_rate: u32,
     }
 }
 
+fn default_sample_rate() -> u32 {
+    16000
+}
+
 impl Default for ListenParams {
     fn default() -> Self {
         ListenParams {
@@ -136,6 +206,8 @@ impl Default for ListenParams {
             channels: 1,
             languages: vec![],
             redemption_time_ms: None,
+            encoding: AudioEncoding::Linear16,
+            sample_rate: default_sample_rate(),
         }
     }
 }
@@ -22,6 +22,9 @@ common_derives! {
         pub confidence: f64,
         #[serde(default)]
         pub languages: Vec<String>,
+        // How sure the STT was about `languages`, when it detected it itself.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub language_confidence: Option<f64>,
     }
 }
 
@@ -98,6 +101,13 @@ common_derives! {
     }
 }
 
+impl Word {
+    // Classified on the punctuated text when there is one, as that's what ends up in `Word2`.
+    pub fn kind(&self) -> crate::WordKind {
+        crate::WordKind::classify(self.punctuated_word.as_deref().unwrap_or(&self.word))
+    }
+}
+
 impl StreamResponse {
     pub fn is_transcript_response_final(&self) -> bool {
         match self {
@@ -9,7 +9,7 @@ type State = {
   sessionEventUnlisten?: () => void;
   loading: boolean;
   status: "inactive" | "running_active";
-  amplitude: { mic: number; speaker: number };
+  amplitude: { mic: number; speaker: number; micDbfs: number; speakerDbfs: number };
   enhanceController: AbortController | null;
   micMuted: boolean;
   speakerMuted: boolean;
@@ -29,7 +29,7 @@ const initialState: State = {
   sessionId: null,
   status: "inactive",
   loading: false,
-  amplitude: { mic: 0, speaker: 0 },
+  amplitude: { mic: 0, speaker: 0, micDbfs: -60, speakerDbfs: -60 },
   enhanceController: null,
   micMuted: false,
   speakerMuted: false,
@@ -92,6 +92,8 @@ export const createOngoingSessionStore = (
               draft.amplitude = {
                 mic: payload.mic,
                 speaker: payload.speaker,
+                micDbfs: payload.mic_dbfs,
+                speakerDbfs: payload.speaker_dbfs,
               };
             })
           );
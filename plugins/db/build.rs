@@ -59,6 +59,12 @@ const COMMANDS: &[&str] = &[
     "list_session_tags",
     "assign_tag_to_session",
     "unassign_tag_from_session",
+    "update_conversation_system_prompt",
+    "export_conversation",
+    "get_session_stats",
+    "create_enhanced_note",
+    "list_enhanced_notes",
+    "delete_enhanced_note",
 ];
 
 fn main() {
@@ -76,6 +76,9 @@ async sessionGetEvent(sessionId: string) : Promise<Event | null> {
 async getWords(sessionId: string) : Promise<Word2[]> {
     return await TAURI_INVOKE("plugin:db|get_words", { sessionId });
 },
+async getSessionStats(sessionId: string) : Promise<TranscriptStats> {
+    return await TAURI_INVOKE("plugin:db|get_session_stats", { sessionId });
+},
 async getWordsOnboarding() : Promise<Word2[]> {
     return await TAURI_INVOKE("plugin:db|get_words_onboarding");
 },
@@ -162,6 +165,21 @@ async listMessagesV2(conversationId: string) : Promise<ChatMessageV2[]> {
 },
 async updateMessageV2Parts(id: string, parts: string) : Promise<null> {
     return await TAURI_INVOKE("plugin:db|update_message_v2_parts", { id, parts });
+},
+async updateConversationSystemPrompt(id: string, systemPrompt: string | null) : Promise<null> {
+    return await TAURI_INVOKE("plugin:db|update_conversation_system_prompt", { id, systemPrompt });
+},
+async exportConversation(conversationId: string) : Promise<string> {
+    return await TAURI_INVOKE("plugin:db|export_conversation", { conversationId });
+},
+async createEnhancedNote(note: EnhancedNote) : Promise<EnhancedNote> {
+    return await TAURI_INVOKE("plugin:db|create_enhanced_note", { note });
+},
+async listEnhancedNotes(sessionId: string) : Promise<EnhancedNote[]> {
+    return await TAURI_INVOKE("plugin:db|list_enhanced_notes", { sessionId });
+},
+async deleteEnhancedNote(id: string) : Promise<null> {
+    return await TAURI_INVOKE("plugin:db|delete_enhanced_note", { id });
 }
 }
 
@@ -176,7 +194,7 @@ async updateMessageV2Parts(id: string, parts: string) : Promise<null> {
 /** user-defined types **/
 
 export type Calendar = { id: string; tracking_id: string; user_id: string; platform: Platform; name: string; selected: boolean; source: string | null }
-export type ChatConversation = { id: string; session_id: string; user_id: string; name: string | null; created_at: string; updated_at: string }
+export type ChatConversation = { id: string; session_id: string; user_id: string; name: string | null; created_at: string; updated_at: string; system_prompt: string | null }
 export type ChatGroup = { id: string; user_id: string; name: string | null; created_at: string; session_id: string }
 export type ChatMessage = { id: string; group_id: string; created_at: string; role: ChatMessageRole; content: string; type: ChatMessageType; tool_details: string | null }
 export type ChatMessageRole = "User" | "Assistant"
@@ -184,9 +202,11 @@ export type ChatMessageType = "text-delta" | "tool-start" | "tool-result" | "too
 export type ChatMessageV2 = { id: string; conversation_id: string; role: ChatMessageV2Role; parts: string; metadata: string | null; created_at: string; updated_at: string }
 export type ChatMessageV2Role = "system" | "user" | "assistant"
 export type Config = { id: string; user_id: string; general: ConfigGeneral; notification: ConfigNotification; ai: ConfigAI }
-export type ConfigAI = { api_base: string | null; api_key: string | null; ai_specificity: number | null; redemption_time_ms: number | null }
-export type ConfigGeneral = { autostart: boolean; display_language: string; spoken_languages?: string[]; jargons?: string[]; telemetry_consent: boolean; save_recordings: boolean | null; selected_template_id: string | null; summary_language?: string }
+export type ConfigAI = { api_base: string | null; api_key: string | null; ai_specificity: number | null; redemption_time_ms: number | null; redact_pii?: boolean; strip_filler_words?: boolean; stt_fallbacks?: ConfigSttBackend[]; stt_sample_rate?: number | null; stt_block_on_backpressure?: boolean; stt_reconnect_grace_seconds?: number | null; stt_aggregation_window_ms?: number | null; stt_price_per_minute?: number | null; stt_budget_cap?: number | null; stt_opus?: boolean; stt_redemption_time_ms?: Partial<{ [key in string]: number }>; title_max_words?: number | null; title_sentence_case?: boolean }
+export type ConfigGeneral = { autostart: boolean; display_language: string; spoken_languages?: string[]; jargons?: string[]; telemetry_consent: boolean; save_recordings: boolean | null; selected_template_id: string | null; summary_language?: string; recordings_dir?: string | null; max_recording_seconds?: number | null; transcript_sink_path?: string | null; pre_roll_seconds?: number | null; incremental_word_events?: boolean; min_free_disk_mb?: number | null; restore_punctuation_languages?: string[]; mute_ramp_ms?: number | null; fallback_language?: string | null; transcribe_live?: boolean | null; compress_words?: boolean | null }
 export type ConfigNotification = { before: boolean; auto: boolean; ignoredPlatforms: string[] | null }
+export type ConfigSttBackend = { base_url: string; api_key: string | null; model: string | null; sample_rate?: number | null; price_per_minute?: number | null; opus?: boolean }
+export type EnhancedNote = { id: string; session_id: string; template_variant: string | null; model: string; content: string; created_at: string }
 export type Event = { id: string; user_id: string; tracking_id: string; calendar_id: string | null; name: string; note: string; start_date: string; end_date: string; google_event_url: string | null; participants: string | null; is_recurring: boolean }
 export type GetSessionFilter = { id: string } | { calendarEventId: string } | { tagId: string }
 export type Human = { id: string; organization_id: string | null; is_user: boolean; full_name: string | null; email: string | null; job_title: string | null; linkedin_username: string | null }
@@ -196,12 +216,15 @@ export type ListOrganizationFilter = { search: [number, string] }
 export type ListSessionFilter = ({ user_id: string; limit: number | null }) & ({ type: "search"; query: string } | { type: "recentlyVisited" } | { type: "dateRange"; start: string; end: string } | { type: "tagFilter"; tag_ids: string[] })
 export type Organization = { id: string; name: string; description: string | null }
 export type Platform = "Apple" | "Google" | "Outlook"
-export type Session = { id: string; created_at: string; visited_at: string; user_id: string; calendar_event_id: string | null; title: string; raw_memo_html: string; enhanced_memo_html: string | null; words: Word2[]; record_start: string | null; record_end: string | null; pre_meeting_memo_html: string | null }
+export type Session = { id: string; created_at: string; visited_at: string; user_id: string; calendar_event_id: string | null; title: string; raw_memo_html: string; enhanced_memo_html: string | null; words: Word2[]; record_start: string | null; record_end: string | null; pre_meeting_memo_html: string | null; partial_words?: Word2[] }
 export type SpeakerIdentity = { type: "unassigned"; value: { index: number } } | { type: "assigned"; value: { id: string; label: string } }
+export type SpeakerStats = { speaker: SpeakerIdentity | null; talk_seconds: number; word_count: number; turns: number }
 export type Tag = { id: string; name: string }
 export type Template = { id: string; user_id: string; title: string; description: string; sections: TemplateSection[]; tags: string[]; context_option: string | null }
 export type TemplateSection = { title: string; description: string }
-export type Word2 = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null }
+export type TranscriptStats = { speakers: SpeakerStats[]; word_count: number; duration_seconds: number; silence_ratio: number }
+export type Word2 = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null; recording_start_s?: number | null; recording_end_s?: number | null; kind?: WordKind }
+export type WordKind = "speech" | "event"
 
 /** tauri-specta globals **/
 
@@ -100,3 +100,46 @@ pub async fn update_message_v2_parts(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn update_conversation_system_prompt(
+    state: tauri::State<'_, crate::ManagedState>,
+    id: String,
+    system_prompt: Option<String>,
+) -> Result<(), String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.update_conversation_system_prompt(id, system_prompt)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn export_conversation(
+    state: tauri::State<'_, crate::ManagedState>,
+    conversation_id: String,
+) -> Result<String, String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.export_conversation(conversation_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or(crate::Error::NoneConversation)
+        .map_err(|e| e.to_string())
+}
@@ -37,18 +37,24 @@ pub async fn get_config(
 
 #[tauri::command]
 #[specta::specta]
-#[tracing::instrument(skip(state))]
+#[tracing::instrument(skip(state, config_watch))]
 pub async fn set_config(
     state: tauri::State<'_, crate::ManagedState>,
+    config_watch: tauri::State<'_, crate::ConfigWatch>,
     config: hypr_db_user::Config,
 ) -> Result<(), String> {
-    let guard = state.lock().await;
+    let mut guard = state.lock().await;
 
     let db = guard
         .db
-        .as_ref()
+        .as_mut()
         .ok_or(crate::Error::NoneDatabase)
         .map_err(|e| e.to_string())?;
 
-    db.set_config(config).await.map_err(|e| e.to_string())
+    db.set_config(config.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    db.set_words_compression(config.general.words_compression_threshold());
+    config_watch.send_replace(Some(config));
+    Ok(())
 }
@@ -0,0 +1,59 @@
+use hypr_db_user::EnhancedNote;
+
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn create_enhanced_note(
+    state: tauri::State<'_, crate::ManagedState>,
+    note: EnhancedNote,
+) -> Result<EnhancedNote, String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.create_enhanced_note(note)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn list_enhanced_notes(
+    state: tauri::State<'_, crate::ManagedState>,
+    session_id: String,
+) -> Result<Vec<EnhancedNote>, String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.list_enhanced_notes(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn delete_enhanced_note(
+    state: tauri::State<'_, crate::ManagedState>,
+    id: String,
+) -> Result<(), String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.delete_enhanced_note(id).await.map_err(|e| e.to_string())
+}
@@ -2,6 +2,7 @@ pub mod calendars;
 pub mod chats;
 pub mod chats_v2;
 pub mod configs;
+pub mod enhanced_notes;
 pub mod events;
 pub mod humans;
 pub mod organizations;
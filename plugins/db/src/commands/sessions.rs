@@ -71,6 +71,25 @@ pub async fn get_words(
     Ok(v)
 }
 
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn get_session_stats(
+    state: tauri::State<'_, crate::ManagedState>,
+    session_id: String,
+) -> Result<hypr_transcript_stats::TranscriptStats, String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    let words = db.get_words(session_id).await.map_err(|e| e.to_string())?;
+    Ok(hypr_transcript_stats::analyze(&words))
+}
+
 #[tauri::command]
 #[specta::specta]
 #[tracing::instrument(skip(state))]
@@ -14,6 +14,16 @@ pub enum Error {
     DatabaseCoreError(#[from] hypr_db_core::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VorbisError(#[from] vorbis_rs::VorbisError),
+    #[error(transparent)]
+    RecordingError(#[from] hypr_recording::Error),
+    #[error("conversation not found")]
+    NoneConversation,
+    #[error("incompatible recordings: {0}")]
+    IncompatibleRecordings(String),
+    #[error("invalid session id: {0}")]
+    InvalidSessionId(String),
 }
 
 impl Serialize for Error {
@@ -17,6 +17,8 @@ pub trait DatabasePluginExt<R: tauri::Runtime> {
         &self,
         user_id: impl Into<String>,
     ) -> impl Future<Output = Result<Option<hypr_db_user::Config>, crate::Error>>;
+    // Sees every config saved from now on; the current value is `None` until the first save.
+    fn db_subscribe_config(&self) -> tokio::sync::watch::Receiver<Option<hypr_db_user::Config>>;
     fn db_get_session(
         &self,
         session_id: impl Into<String>,
@@ -25,6 +27,32 @@ pub trait DatabasePluginExt<R: tauri::Runtime> {
         &self,
         session: hypr_db_user::Session,
     ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_recordings_dir(
+        &self,
+    ) -> impl Future<Output = Result<Option<std::path::PathBuf>, crate::Error>>;
+    // Every directory recordings may be in, the configured one first.
+    fn db_recording_bases(
+        &self,
+    ) -> impl Future<Output = Result<Vec<std::path::PathBuf>, crate::Error>>;
+    // The merged recording is encoded at `quality`. Callers make sure neither is being recorded.
+    fn db_merge_sessions(
+        &self,
+        primary_id: impl Into<String>,
+        secondary_id: impl Into<String>,
+        quality: f32,
+    ) -> impl Future<Output = Result<hypr_db_user::Session, crate::Error>>;
+    fn db_purge_session(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_get_template(
+        &self,
+        template_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Option<hypr_db_user::Template>, crate::Error>>;
+    fn db_session_participants(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<hypr_db_user::Human>, crate::Error>>;
 }
 
 impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
@@ -51,7 +79,8 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
         let state = self.state::<crate::ManagedState>();
         let mut s = state.lock().await;
 
-        let user_db = hypr_db_user::UserDatabase::from(db);
+        let user_db = hypr_db_user::UserDatabase::from(db)
+            .with_words_compression(hypr_db_user::DEFAULT_WORDS_COMPRESSION_THRESHOLD);
         hypr_db_user::migrate(&user_db).await?;
 
         s.db = Some(user_db);
@@ -75,7 +104,12 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
         let user_id_string = user_id.into();
         guard.user_id = Some(user_id_string.clone());
 
-        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let db = guard.db.as_mut().ok_or(crate::Error::NoneDatabase)?;
+
+        // Compression is on until the user's config says otherwise.
+        if let Some(config) = db.get_config(&user_id_string).await? {
+            db.set_words_compression(config.general.words_compression_threshold());
+        }
 
         match db.get_human(&user_id_string).await? {
             Some(_) => Ok(false),
@@ -131,4 +165,133 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
         let config = db.get_config(user_id.into()).await?;
         Ok(config)
     }
+
+    fn db_subscribe_config(&self) -> tokio::sync::watch::Receiver<Option<hypr_db_user::Config>> {
+        self.state::<crate::ConfigWatch>().subscribe()
+    }
+
+    async fn db_recordings_dir(&self) -> Result<Option<std::path::PathBuf>, crate::Error> {
+        let Some(user_id) = self.db_user_id().await? else {
+            return Ok(None);
+        };
+
+        let config = self.db_get_config(user_id).await?;
+        Ok(config
+            .and_then(|c| c.general.recordings_dir)
+            .filter(|dir| !dir.trim().is_empty())
+            .map(std::path::PathBuf::from))
+    }
+
+    async fn db_recording_bases(&self) -> Result<Vec<std::path::PathBuf>, crate::Error> {
+        Ok([
+            self.db_recordings_dir().await?,
+            Some(self.path().app_data_dir()?),
+        ]
+        .into_iter()
+        .flatten()
+        .collect())
+    }
+
+    async fn db_merge_sessions(
+        &self,
+        primary_id: impl Into<String>,
+        secondary_id: impl Into<String>,
+        quality: f32,
+    ) -> Result<hypr_db_user::Session, crate::Error> {
+        let (primary_id, secondary_id) = (primary_id.into(), secondary_id.into());
+
+        let bases = self.db_recording_bases().await?;
+        let session_dir = |id: &str| {
+            let fallback = hypr_recording::session_dir(&bases[0], id)
+                .ok_or_else(|| crate::Error::InvalidSessionId(id.to_string()))?;
+            Ok::<_, crate::Error>(hypr_recording::find_session_dir(&bases, id).unwrap_or(fallback))
+        };
+
+        // Audio is merged into a separate file first, so a failure up to and including the
+        // database merge leaves both sessions as they were. After that, a failed commit can leave
+        // stale files behind, but never leaves the primary without audio.
+        let (primary_dir, secondary_dir) = (session_dir(&primary_id)?, session_dir(&secondary_id)?);
+        let merge = {
+            let (primary_dir, secondary_dir) = (primary_dir.clone(), secondary_dir.clone());
+            tokio::task::spawn_blocking(move || {
+                crate::recording::prepare_merge(&primary_dir, &secondary_dir, quality)
+            })
+            .await
+            .map_err(std::io::Error::other)??
+        };
+
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let merged = match guard.db.as_ref() {
+            Some(db) => db
+                .merge_sessions(&primary_id, &secondary_id, merge.primary_recording_s())
+                .await
+                .map_err(crate::Error::from),
+            None => Err(crate::Error::NoneDatabase),
+        };
+        drop(guard);
+
+        let session = match merged {
+            Ok(session) => session,
+            Err(e) => {
+                tokio::task::spawn_blocking(move || merge.abort());
+                return Err(e);
+            }
+        };
+
+        tokio::task::spawn_blocking(move || {
+            // The secondary's directory only goes once its audio is in the primary's recording.
+            merge.commit()?;
+            if secondary_dir.exists() {
+                std::fs::remove_dir_all(secondary_dir)?;
+            }
+            Ok::<_, crate::Error>(())
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        Ok(session)
+    }
+
+    async fn db_purge_session(&self, session_id: impl Into<String>) -> Result<(), crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        db.purge_session(session_id).await?;
+
+        Ok(())
+    }
+
+    async fn db_get_template(
+        &self,
+        template_id: impl Into<String>,
+    ) -> Result<Option<hypr_db_user::Template>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let user_id = guard.user_id.clone().unwrap_or_default();
+
+        let template_id = template_id.into();
+        let template = db
+            .list_templates(user_id)
+            .await?
+            .into_iter()
+            .find(|t| t.id == template_id);
+        Ok(template)
+    }
+
+    async fn db_session_participants(
+        &self,
+        session_id: impl Into<String>,
+    ) -> Result<Vec<hypr_db_user::Human>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let participants = db.session_list_participants(session_id).await?;
+        Ok(participants)
+    }
 }
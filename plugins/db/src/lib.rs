@@ -4,6 +4,7 @@ use tokio::sync::Mutex;
 mod commands;
 mod error;
 mod ext;
+mod recording;
 
 pub use error::{Error, Result};
 pub use ext::DatabasePluginExt;
@@ -11,6 +12,9 @@ pub use hypr_db_user::UserDatabase;
 
 pub type ManagedState = Mutex<State>;
 
+// Holds the last config saved through `set_config`, for anything that follows settings live.
+pub type ConfigWatch = tokio::sync::watch::Sender<Option<hypr_db_user::Config>>;
+
 #[derive(Default)]
 pub struct State {
     pub user_id: Option<String>,
@@ -46,6 +50,7 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::sessions::session_list_participants,
             commands::sessions::session_get_event,
             commands::sessions::get_words,
+            commands::sessions::get_session_stats,
             commands::sessions::get_words_onboarding,
             commands::configs::get_config,
             commands::configs::set_config,
@@ -75,6 +80,11 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::chats_v2::create_message_v2,
             commands::chats_v2::list_messages_v2,
             commands::chats_v2::update_message_v2_parts,
+            commands::chats_v2::update_conversation_system_prompt,
+            commands::chats_v2::export_conversation,
+            commands::enhanced_notes::create_enhanced_note,
+            commands::enhanced_notes::list_enhanced_notes,
+            commands::enhanced_notes::delete_enhanced_note,
         ])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
 }
@@ -86,6 +96,7 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
         .invoke_handler(specta_builder.invoke_handler())
         .setup(|app, _api| {
             app.manage(ManagedState::default());
+            app.manage(ConfigWatch::new(None));
             Ok(())
         })
         .build()
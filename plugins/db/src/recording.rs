@@ -0,0 +1,297 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::num::{NonZeroU32, NonZeroU8};
+use std::path::{Path, PathBuf};
+
+use hypr_recording::{decode_segment, recording_files, recording_segments, FILENAME_BASE};
+use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisEncoderBuilder};
+
+/// The secondary's recording concatenated onto the primary's, written next to the primary's
+/// audio but not yet in place of it.
+pub struct PendingMerge {
+    primary_dir: PathBuf,
+    merging_path: Option<PathBuf>,
+    primary_s: f64,
+}
+
+impl PendingMerge {
+    /// Length of the primary's recording in seconds.
+    pub fn primary_recording_s(&self) -> f64 {
+        self.primary_s
+    }
+
+    /// Whether the secondary had any audio, which now lives in the merged recording.
+    pub fn merged_secondary(&self) -> bool {
+        self.merging_path.is_some()
+    }
+
+    /// Replaces the primary's audio with the merged recording. The merged file is renamed over
+    /// `audio.ogg` first, so the primary's other files are only removed once it is in place.
+    pub fn commit(self) -> Result<(), crate::Error> {
+        let Some(merging_path) = self.merging_path else {
+            return Ok(());
+        };
+
+        let merged_path = self.primary_dir.join(format!("{}.ogg", FILENAME_BASE));
+        let replaced = recording_files(&self.primary_dir)
+            .into_iter()
+            .filter(|path| *path != merged_path)
+            .collect::<Vec<_>>();
+
+        std::fs::rename(&merging_path, &merged_path)?;
+        for path in replaced {
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn abort(self) {
+        if let Some(merging_path) = self.merging_path {
+            let _ = std::fs::remove_file(merging_path);
+        }
+    }
+}
+
+// Concatenates the secondary's recording onto the primary's, into `audio.merging.ogg` in
+// `primary_dir`, at Vorbis `quality`. Neither session's audio is touched until the merge is
+// committed.
+pub fn prepare_merge(
+    primary_dir: &Path,
+    secondary_dir: &Path,
+    quality: f32,
+) -> Result<PendingMerge, crate::Error> {
+    let primary = recording_segments(primary_dir);
+    let secondary = recording_segments(secondary_dir);
+
+    if secondary.is_empty() {
+        return Ok(PendingMerge {
+            primary_dir: primary_dir.to_path_buf(),
+            merging_path: None,
+            primary_s: primary.iter().try_fold(0.0, |acc, path| {
+                Ok::<_, crate::Error>(acc + segment_duration_s(path)?)
+            })?,
+        });
+    }
+
+    let sample_rate = common_sample_rate(primary.iter().chain(secondary.iter()))?;
+
+    std::fs::create_dir_all(primary_dir)?;
+    let merging_path = primary_dir.join(format!("{}.merging.ogg", FILENAME_BASE));
+
+    let encode = || -> Result<usize, crate::Error> {
+        let mut encoder = VorbisEncoderBuilder::new(
+            sample_rate,
+            NonZeroU8::new(1).unwrap(),
+            BufWriter::new(File::create(&merging_path)?),
+        )?
+        .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+            target_quality: quality,
+        })
+        .build()?;
+
+        let mut primary_frames = 0;
+        for (path, is_primary) in primary
+            .iter()
+            .map(|p| (p, true))
+            .chain(secondary.iter().map(|p| (p, false)))
+        {
+            let mut result = Ok(());
+            decode_segment(path, |samples| {
+                if is_primary {
+                    primary_frames += samples.len();
+                }
+                if result.is_ok() {
+                    result = encoder.encode_audio_block([samples]);
+                }
+            })?;
+            result?;
+        }
+        encoder.finish()?;
+
+        Ok(primary_frames)
+    };
+
+    match encode() {
+        Ok(primary_frames) => Ok(PendingMerge {
+            primary_dir: primary_dir.to_path_buf(),
+            merging_path: Some(merging_path),
+            primary_s: primary_frames as f64 / sample_rate.get() as f64,
+        }),
+        Err(e) => {
+            let _ = std::fs::remove_file(&merging_path);
+            Err(e)
+        }
+    }
+}
+
+// Segments are re-encoded as a single stream, so they all need the same sample rate.
+fn common_sample_rate<'a>(
+    paths: impl Iterator<Item = &'a PathBuf>,
+) -> Result<NonZeroU32, crate::Error> {
+    let mut sample_rate = None;
+
+    for path in paths {
+        let current = hypr_recording::segment_sample_rate(path)?;
+
+        match sample_rate {
+            Some(expected) if expected != current => {
+                return Err(crate::Error::IncompatibleRecordings(
+                    path.to_string_lossy().to_string(),
+                ));
+            }
+            _ => sample_rate = Some(current),
+        }
+    }
+
+    sample_rate
+        .and_then(NonZeroU32::new)
+        .ok_or_else(|| crate::Error::IncompatibleRecordings("no recording".to_string()))
+}
+
+fn segment_duration_s(path: &Path) -> Result<f64, crate::Error> {
+    let sample_rate = hypr_recording::segment_sample_rate(path)? as f64;
+
+    let mut frames = 0;
+    decode_segment(path, |samples| frames += samples.len())?;
+
+    Ok(frames as f64 / sample_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn tone(seconds: u32, freq: f32) -> Vec<f32> {
+        (0..SAMPLE_RATE * seconds)
+            .map(|i| {
+                (i as f32 / SAMPLE_RATE as f32 * freq * 2.0 * std::f32::consts::PI).sin() * 0.5
+            })
+            .collect()
+    }
+
+    fn write_ogg(path: &Path, seconds: u32, freq: f32) {
+        let mut encoder = VorbisEncoderBuilder::new(
+            NonZeroU32::new(SAMPLE_RATE).unwrap(),
+            NonZeroU8::new(1).unwrap(),
+            BufWriter::new(File::create(path).unwrap()),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        encoder.encode_audio_block([tone(seconds, freq)]).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    fn write_wav(path: &Path, seconds: u32, freq: f32) {
+        let mut writer = hound::WavWriter::create(
+            path,
+            hound::WavSpec {
+                channels: 1,
+                sample_rate: SAMPLE_RATE,
+                bits_per_sample: 32,
+                sample_format: hound::SampleFormat::Float,
+            },
+        )
+        .unwrap();
+        for sample in tone(seconds, freq) {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn duration_s(session_dir: &Path) -> f64 {
+        recording_segments(session_dir)
+            .iter()
+            .map(|path| segment_duration_s(path).unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn test_merge_recordings() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (primary_dir, secondary_dir) = (root.join("primary"), root.join("secondary"));
+        std::fs::create_dir_all(&primary_dir).unwrap();
+        std::fs::create_dir_all(&secondary_dir).unwrap();
+
+        write_ogg(&primary_dir.join("audio.ogg"), 2, 440.0);
+        write_ogg(&secondary_dir.join("audio.000.ogg"), 1, 220.0);
+        write_ogg(&secondary_dir.join("audio.001.ogg"), 1, 220.0);
+
+        let merge = prepare_merge(&primary_dir, &secondary_dir, 0.7).unwrap();
+        assert!(
+            (merge.primary_recording_s() - 2.0).abs() < 0.05,
+            "{}",
+            merge.primary_recording_s()
+        );
+
+        // Nothing is replaced until the merge is committed.
+        assert!((duration_s(&primary_dir) - 2.0).abs() < 0.05);
+        assert_eq!(recording_segments(&secondary_dir).len(), 2);
+
+        merge.commit().unwrap();
+
+        // A single continuous recording remains, holding both sessions' audio.
+        assert_eq!(
+            recording_segments(&primary_dir),
+            vec![primary_dir.join("audio.ogg")]
+        );
+        let merged_s = duration_s(&primary_dir);
+        assert!((merged_s - 4.0).abs() < 0.05, "{}", merged_s);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_merge_wav_recordings() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (primary_dir, secondary_dir) = (root.join("primary"), root.join("secondary"));
+        std::fs::create_dir_all(&primary_dir).unwrap();
+        std::fs::create_dir_all(&secondary_dir).unwrap();
+
+        write_wav(&primary_dir.join("audio.wav"), 2, 440.0);
+        write_wav(&secondary_dir.join("audio.wav"), 1, 220.0);
+
+        let merge = prepare_merge(&primary_dir, &secondary_dir, 0.7).unwrap();
+        assert!(merge.merged_secondary());
+        assert!((merge.primary_recording_s() - 2.0).abs() < 0.05);
+        merge.commit().unwrap();
+
+        // The primary's WAV is replaced rather than left to shadow the merged recording.
+        assert_eq!(
+            recording_files(&primary_dir),
+            vec![primary_dir.join("audio.ogg")]
+        );
+        let merged_s = duration_s(&primary_dir);
+        assert!((merged_s - 3.0).abs() < 0.05, "{}", merged_s);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_aborted_merge_keeps_recordings() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (primary_dir, secondary_dir) = (root.join("primary"), root.join("secondary"));
+        std::fs::create_dir_all(&primary_dir).unwrap();
+        std::fs::create_dir_all(&secondary_dir).unwrap();
+
+        write_ogg(&primary_dir.join("audio.ogg"), 1, 440.0);
+        write_ogg(&secondary_dir.join("audio.ogg"), 1, 220.0);
+
+        prepare_merge(&primary_dir, &secondary_dir, 0.7)
+            .unwrap()
+            .abort();
+
+        assert_eq!(
+            recording_files(&primary_dir),
+            vec![primary_dir.join("audio.ogg")]
+        );
+        assert!((duration_s(&primary_dir) - 1.0).abs() < 0.05);
+        assert_eq!(recording_segments(&secondary_dir).len(), 1);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
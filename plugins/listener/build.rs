@@ -15,6 +15,31 @@ const COMMANDS: &[&str] = &[
     "start_session",
     "stop_session",
     "get_state",
+    "is_recording",
+    "get_transcript_snapshot",
+    "export_subtitles",
+    "get_active_stt_info",
+    "purge_session",
+    "merge_sessions",
+    "start_mic_test",
+    "stop_mic_test",
+    "set_pre_roll_seconds",
+    "pause_transcription",
+    "resume_transcription",
+    "set_channel_gain",
+    "get_recording_meta",
+    "find_duplicate_recordings",
+    "retranscribe_missing",
+    "set_restore_punctuation",
+    "estimate_stt_cost",
+    "transcribe_uploaded_file",
+    "get_recording_prefs",
+    "set_recording_prefs",
+    "list_recording_formats",
+    "get_plain_transcript",
+    "get_actor_status",
+    "enhance_recording_audio",
+    "set_transcribe_live",
 ];
 
 fn main() {
@@ -13,8 +13,8 @@ async listMicrophoneDevices() : Promise<string[]> {
 async getCurrentMicrophoneDevice() : Promise<string | null> {
     return await TAURI_INVOKE("plugin:listener|get_current_microphone_device");
 },
-async setMicrophoneDevice(deviceName: string) : Promise<null> {
-    return await TAURI_INVOKE("plugin:listener|set_microphone_device", { deviceName });
+async setMicrophoneDevice(deviceName: string, pinned: boolean) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_microphone_device", { deviceName, pinned });
 },
 async checkMicrophoneAccess() : Promise<boolean> {
     return await TAURI_INVOKE("plugin:listener|check_microphone_access");
@@ -46,14 +46,89 @@ async getSpeakerMuted() : Promise<boolean> {
 async setSpeakerMuted(muted: boolean) : Promise<null> {
     return await TAURI_INVOKE("plugin:listener|set_speaker_muted", { muted });
 },
+async pauseTranscription() : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|pause_transcription");
+},
+async resumeTranscription() : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|resume_transcription");
+},
+async setTranscribeLive(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_transcribe_live", { enabled });
+},
+async setChannelGain(channel: Channel, gain: number) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_channel_gain", { channel, gain });
+},
+async setRestorePunctuation(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_restore_punctuation", { enabled });
+},
 async startSession(sessionId: string) : Promise<null> {
     return await TAURI_INVOKE("plugin:listener|start_session", { sessionId });
 },
 async stopSession() : Promise<null> {
     return await TAURI_INVOKE("plugin:listener|stop_session");
 },
+async purgeSession(sessionId: string) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|purge_session", { sessionId });
+},
+async mergeSessions(primaryId: string, secondaryId: string) : Promise<Session> {
+    return await TAURI_INVOKE("plugin:listener|merge_sessions", { primaryId, secondaryId });
+},
+async startMicTest(deviceName: string | null, channel: TAURI_CHANNEL<MicLevel>) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|start_mic_test", { deviceName, channel });
+},
+async stopMicTest() : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|stop_mic_test");
+},
+async setPreRollSeconds(seconds: number | null) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_pre_roll_seconds", { seconds });
+},
 async getState() : Promise<string> {
     return await TAURI_INVOKE("plugin:listener|get_state");
+},
+async isRecording(sessionId: string) : Promise<boolean> {
+    return await TAURI_INVOKE("plugin:listener|is_recording", { sessionId });
+},
+async getTranscriptSnapshot(sessionId: string) : Promise<TranscriptSnapshot | null> {
+    return await TAURI_INVOKE("plugin:listener|get_transcript_snapshot", { sessionId });
+},
+async exportSubtitles(sessionId: string, format: SubtitleFormat) : Promise<string> {
+    return await TAURI_INVOKE("plugin:listener|export_subtitles", { sessionId, format });
+},
+async getActiveSttInfo() : Promise<SttInfo> {
+    return await TAURI_INVOKE("plugin:listener|get_active_stt_info");
+},
+async estimateSttCost(durationSeconds: number) : Promise<CostEstimate | null> {
+    return await TAURI_INVOKE("plugin:listener|estimate_stt_cost", { durationSeconds });
+},
+async getRecordingMeta(sessionId: string) : Promise<RecordingMeta | null> {
+    return await TAURI_INVOKE("plugin:listener|get_recording_meta", { sessionId });
+},
+async findDuplicateRecordings() : Promise<string[][]> {
+    return await TAURI_INVOKE("plugin:listener|find_duplicate_recordings");
+},
+async retranscribeMissing(sessionId: string) : Promise<Word2[]> {
+    return await TAURI_INVOKE("plugin:listener|retranscribe_missing", { sessionId });
+},
+async transcribeUploadedFile(path: string, languages: Language[]) : Promise<string> {
+    return await TAURI_INVOKE("plugin:listener|transcribe_uploaded_file", { path, languages });
+},
+async getRecordingPrefs() : Promise<RecordingPrefs> {
+    return await TAURI_INVOKE("plugin:listener|get_recording_prefs");
+},
+async setRecordingPrefs(prefs: RecordingPrefs) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_recording_prefs", { prefs });
+},
+async listRecordingFormats() : Promise<FormatInfo[]> {
+    return await TAURI_INVOKE("plugin:listener|list_recording_formats");
+},
+async getPlainTranscript(sessionId: string) : Promise<string | null> {
+    return await TAURI_INVOKE("plugin:listener|get_plain_transcript", { sessionId });
+},
+async getActorStatus(sessionId: string) : Promise<Partial<{ [key in string]: ChildStatus }> | null> {
+    return await TAURI_INVOKE("plugin:listener|get_actor_status", { sessionId });
+},
+async enhanceRecordingAudio(sessionId: string, config: AudioEnhanceConfig) : Promise<string> {
+    return await TAURI_INVOKE("plugin:listener|enhance_recording_audio", { sessionId, config });
 }
 }
 
@@ -72,9 +147,28 @@ sessionEvent: "plugin:listener:session-event"
 
 /** user-defined types **/
 
-export type SessionEvent = { type: "inactive" } | { type: "running_active" } | { type: "finalWords"; words: Partial<{ [key in number]: Word2[] }> } | { type: "partialWords"; words: Partial<{ [key in number]: Word2[] }> } | { type: "audioAmplitude"; mic: number; speaker: number } | { type: "micMuted"; value: boolean } | { type: "speakerMuted"; value: boolean }
+export type AudioEnhanceConfig = { denoise?: boolean; agc?: boolean; limiter?: boolean }
+export type AudioFingerprint = { frame_ms: number; envelope: string }
+export type Channel = "mic" | "speaker"
+export type ChildStatus = { alive: boolean; state: string | null }
+export type CostEstimate = { audio_seconds: number; cost: number; within_budget: boolean }
+export type FormatInfo = { format: RecordingFormat; extension: string; lossless: boolean }
+export type Language = { iso639: string }
+export type MicLevel = { rms: number; peak: number }
+export type RecordingFormat = "ogg" | "wav"
+export type RecordingMeta = { session_id: string; title: string; started_at: string | null; ended_at: string | null; sample_rate: number; duration_ms: number; speakers: string[]; word_count: number; fingerprint?: AudioFingerprint | null }
+export type RecordingPrefs = { format?: RecordingFormat; quality?: number; keep_wav?: boolean; rotate?: RotatePolicy | null; skip_silence?: boolean }
+export type RotatePolicy = { BySeconds: number } | { ByBytes: number }
+export type Session = { id: string; created_at: string; visited_at: string; user_id: string; calendar_event_id: string | null; title: string; raw_memo_html: string; enhanced_memo_html: string | null; words: Word2[]; record_start: string | null; record_end: string | null; pre_meeting_memo_html: string | null; partial_words?: Word2[] }
+export type SessionEvent = { type: "inactive" } | { type: "running_active" } | { type: "finalWords"; words: Partial<{ [key in number]: Word2[] }> } | { type: "partialWords"; words: Partial<{ [key in number]: Word2[] }> } | { type: "wordsPatch"; partial_ops: WordOp[]; final_ops: WordOp[] } | { type: "audioAmplitude"; mic: number; speaker: number; mic_dbfs: number; speaker_dbfs: number } | { type: "micMuted"; value: boolean } | { type: "speakerMuted"; value: boolean } | { type: "recordingsDirFallback"; requested: string; fallback: string } | { type: "recordingLimitApproaching"; remaining_seconds: number } | { type: "lowDiskSpace"; available_bytes: number } | { type: "recorderSlow" } | { type: "inputClipping"; channel: Channel } | { type: "sttBackendSelected"; backend: SttInfo } | { type: "costEstimate"; estimate: CostEstimate } | { type: "sttBackendSlow"; dropped_chunks: number } | { type: "micDeviceUnavailable"; device: string } | { type: "transcriptionCompleted" } | { type: "transcriptionFailed"; reason: string } | { type: "transcriptionTimedOut"; kind: StreamTimeoutKind }
 export type SpeakerIdentity = { type: "unassigned"; value: { index: number } } | { type: "assigned"; value: { id: string; label: string } }
-export type Word2 = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null }
+export type StreamTimeoutKind = "noAudioActivity" | "noTranscriptResponse"
+export type SttInfo = { backend_url: string; model: string | null; is_local: boolean; sample_rate: number; price_per_minute: number | null }
+export type SubtitleFormat = "srt" | "vtt"
+export type TranscriptSnapshot = { partial_words: Partial<{ [key in number]: Word2[] }>; final_words: Word2[] }
+export type Word2 = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null; recording_start_s?: number | null; recording_end_s?: number | null; kind?: WordKind }
+export type WordKind = "speech" | "event"
+export type WordOp = { op: "add"; channel: number; index: number; word: Word2 } | { op: "replace"; channel: number; index: number; word: Word2 } | { op: "remove"; channel: number; index: number }
 
 /** tauri-specta globals **/
 
@@ -1,20 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::time::Duration;
 
 use bytes::Bytes;
 use futures_util::StreamExt;
 
 use owhisper_interface::{ControlMessage, MixedMessage, Word2};
-use ractor::{Actor, ActorName, ActorProcessingErr, ActorRef, SupervisionEvent};
+use ractor::{Actor, ActorName, ActorProcessingErr, ActorRef, RpcReplyPort, SupervisionEvent};
 use tauri_specta::Event;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    manager::{TranscriptManager, WordsByChannel},
-    SessionEvent,
+    actors::until_cancelled,
+    aggregate::AudioAggregator,
+    cost::{pcm_duration, CostMeter},
+    language_pin::LanguagePin,
+    manager::{
+        flatten, group_by_channel, to_conversation, with_recording_time, TranscriptManager,
+        WordJoinRule, WordsByChannel, DEFAULT_PARTIAL_MERGE_TOLERANCE,
+    },
+    resample::DualResampler,
+    sink::TranscriptSink,
+    word_patch::WordPatcher,
+    SessionEvent, SttPricing,
 };
 
 // Not too short to support non-realtime pipelines like whisper.cpp
-const LISTEN_STREAM_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+pub const DEFAULT_LISTEN_STREAM_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+pub const DEFAULT_PARTIAL_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
 
 pub enum ListenerMsg {
     Audio(Bytes, Bytes),
@@ -23,6 +36,11 @@ pub enum ListenerMsg {
     StreamEnded,
     StreamTimeout,
     StreamStartFailed(String),
+    GetPartials(RpcReplyPort<HashMap<usize, Vec<Word2>>>),
+    // Saved finals and current partials as speaker-labeled text.
+    GetPlainTranscript(RpcReplyPort<String>),
+    SetRestorePunctuation(bool),
+    BackendSelected(crate::SttInfo),
 }
 
 #[derive(Clone)]
@@ -32,21 +50,87 @@ pub struct ListenerArgs {
     pub languages: Vec<hypr_language::Language>,
     pub onboarding: bool,
     pub partial_words_by_channel: WordsByChannel,
+    // Finalized words the previous listener of the session ended with.
+    pub final_tail_by_channel: WordsByChannel,
+    // Wall-clock time (in ms) of the recording's first sample, if the session is recorded.
+    pub recording_offset_ms: Option<u64>,
+    // The session's token; cancelling it aborts a start that is still in progress.
+    pub token: CancellationToken,
+    // Reset by both transcript responses and successfully sent audio.
+    pub stream_timeout: Duration,
+    // Periodically persists partials to `session.partial_words`. `None` disables it.
+    pub partial_flush_interval: Option<Duration>,
+    // Every `Diff` is also appended here as JSON lines. `None` disables it.
+    pub transcript_sink: Option<PathBuf>,
+    // When set, words go out as `SessionEvent::WordsPatch` instead of full lists.
+    pub word_patcher: Option<WordPatcher>,
+    // Tried in order after the primary connection, until one accepts the stream.
+    pub fallback_connections: Vec<tauri_plugin_local_stt::Connection>,
+    // Rate for the primary connection when it doesn't state one.
+    pub stt_sample_rate: Option<u32>,
+    // Wait for room in the send channel instead of dropping audio when the backend falls behind.
+    pub block_on_backpressure: bool,
+    // How long a dropped stream may take to reconnect, with audio held back meanwhile.
+    // `None` stops the listener right away.
+    pub reconnect_grace: Option<Duration>,
+    // Audio goes out in frames of about this length rather than chunk by chunk. `None` disables it.
+    pub aggregation_window: Option<Duration>,
+    // Adds capitalization and sentence-ending punctuation to final words before they are saved.
+    pub restore_punctuation: bool,
+    // Price for the primary connection when it doesn't state one.
+    pub stt_price_per_minute: Option<f64>,
+    // Whether the primary connection takes Opus when it doesn't say so itself.
+    pub stt_opus: bool,
+    // Budget the running `SessionEvent::CostEstimate` is checked against.
+    pub stt_budget_cap: Option<f64>,
+    // Per-language redemption times, by ISO 639-1 code, in place of the defaults.
+    pub redemption_overrides: HashMap<String, u32>,
+    // Language kept when detection is unsure, until one is detected with confidence.
+    pub fallback_language: Option<hypr_language::Language>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamTimeoutKind {
+    // Nothing is reaching the listener; the audio pipeline upstream has stalled.
+    NoAudioActivity,
+    // Audio keeps arriving but the backend neither accepts it nor responds.
+    NoTranscriptResponse,
 }
 
 pub struct ListenerState {
     pub args: ListenerArgs,
     pub manager: TranscriptManager,
     tx: tokio::sync::mpsc::Sender<MixedMessage<(Bytes, Bytes), ControlMessage>>,
+    audio_activity_tx: tokio::sync::watch::Sender<tokio::time::Instant>,
+    last_audio_received: tokio::time::Instant,
     rx_task: tokio::task::JoinHandle<()>,
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    backpressure: Backpressure,
+    partial_flush: Option<PartialFlushThrottle>,
+    transcript_sink: Option<TranscriptSink>,
+    pub word_patcher: Option<WordPatcher>,
+    aggregator: Option<AudioAggregator>,
+    cost_meter: CostMeter,
+    language_pin: LanguagePin,
+}
+
+impl ListenerState {
+    // Why the stream would be timing out now.
+    fn timeout_kind(&self) -> StreamTimeoutKind {
+        if self.last_audio_received.elapsed() >= self.args.stream_timeout {
+            StreamTimeoutKind::NoAudioActivity
+        } else {
+            StreamTimeoutKind::NoTranscriptResponse
+        }
+    }
 }
 
 pub struct ListenerActor;
 
 impl ListenerActor {
-    pub fn name() -> ActorName {
-        "listener_actor".into()
+    pub fn name(session_id: &str) -> ActorName {
+        format!("listener_actor:{}", session_id)
     }
 }
 
@@ -62,7 +146,7 @@ impl Actor for ListenerActor {
     ) -> Result<Self::State, ActorProcessingErr> {
         {
             use tauri_plugin_local_stt::LocalSttPluginExt;
-            let r = args.app.start_server(None).await;
+            let r = until_cancelled(&args.token, args.app.start_server(None)).await?;
             tracing::info!("{:?}", r);
         }
 
@@ -74,13 +158,53 @@ impl Actor for ListenerActor {
         let manager = TranscriptManager::builder()
             .with_manager_offset(current_timestamp_ms)
             .with_existing_partial_words(args.partial_words_by_channel.clone())
+            .with_existing_final_tail(args.final_tail_by_channel.clone())
+            .with_word_join_rule(
+                args.languages
+                    .first()
+                    .map(WordJoinRule::for_language)
+                    .unwrap_or_default(),
+            )
+            .with_partial_merge_tolerance(DEFAULT_PARTIAL_MERGE_TOLERANCE)
             .build();
 
-        let (tx, rx_task, shutdown_tx) = spawn_rx_task(args.clone(), myself).await?;
+        let (audio_activity_tx, audio_activity_rx) =
+            tokio::sync::watch::channel(tokio::time::Instant::now());
+
+        let (tx, rx_task, shutdown_tx) = until_cancelled(
+            &args.token,
+            spawn_rx_task(args.clone(), myself, audio_activity_rx),
+        )
+        .await??;
+
+        let partial_flush = args.partial_flush_interval.map(PartialFlushThrottle::new);
+
+        let transcript_sink = args.transcript_sink.as_ref().and_then(|path| {
+            TranscriptSink::open(path)
+                .inspect_err(
+                    |e| tracing::error!(path = ?path, error = ?e, "transcript_sink_open_failed"),
+                )
+                .ok()
+        });
+
+        let word_patcher = args.word_patcher.clone();
+        let aggregator = args
+            .aggregation_window
+            .map(|window| AudioAggregator::new(window, crate::pre_roll::SAMPLE_RATE));
+        let language_pin = LanguagePin::new(args.fallback_language.clone());
 
         let state = ListenerState {
             args,
+            word_patcher,
+            aggregator,
+            cost_meter: CostMeter::default(),
+            language_pin,
+            backpressure: Backpressure::default(),
+            partial_flush,
+            transcript_sink,
             tx,
+            audio_activity_tx,
+            last_audio_received: tokio::time::Instant::now(),
             rx_task,
             shutdown_tx: Some(shutdown_tx),
             manager,
@@ -94,10 +218,46 @@ impl Actor for ListenerActor {
         _myself: ActorRef<Self::Msg>,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
+        // The aggregator holds back audio until a window fills, so the last of it goes out now,
+        // before the stream is closed.
+        if let Some(frame) = state.aggregator.as_mut().and_then(AudioAggregator::flush) {
+            let sent = pcm_duration(frame.0.len(), crate::pre_roll::SAMPLE_RATE);
+            if let AudioSend::Sent = send_audio(
+                &state.tx,
+                MixedMessage::Audio(frame),
+                state.args.block_on_backpressure,
+                &mut state.backpressure,
+            )
+            .await
+            {
+                state.cost_meter.add(sent);
+            }
+        }
+
         if let Some(shutdown_tx) = state.shutdown_tx.take() {
             let _ = shutdown_tx.send(());
         }
         state.rx_task.abort();
+
+        // The last tally, since the periodic ones only go out every so often.
+        if let Some(estimate) = state.cost_meter.estimate() {
+            let _ = SessionEvent::CostEstimate { estimate }.emit(&state.args.app);
+        }
+
+        // Partials that changed after the last periodic flush would otherwise be lost.
+        if let Some(words) = state.partial_flush.as_mut().and_then(|throttle| {
+            throttle.flush_now(flatten(to_word2_by_channel(
+                &state.manager.partial_words_by_channel,
+                state.args.recording_offset_ms,
+            )))
+        }) {
+            if let Err(e) =
+                update_partial_words(&state.args.app, &state.args.session_id, words).await
+            {
+                tracing::error!("partial_flush_failed: {:?}", e);
+            }
+        }
+
         Ok(())
     }
 
@@ -107,64 +267,176 @@ impl Actor for ListenerActor {
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
+        if let Some(event) = stream_stop_event(&message, state.timeout_kind()) {
+            event.emit(&state.args.app)?;
+        }
+
         match message {
             ListenerMsg::Audio(mic, spk) => {
-                let _ = state.tx.try_send(MixedMessage::Audio((mic, spk)));
+                state.last_audio_received = tokio::time::Instant::now();
+
+                let frame = match state.aggregator.as_mut() {
+                    Some(aggregator) => aggregator.push(&mic, &spk),
+                    None => Some((mic, spk)),
+                };
+                let Some((mic, spk)) = frame else {
+                    return Ok(());
+                };
+                let mic_len = mic.len();
+
+                match send_audio(
+                    &state.tx,
+                    MixedMessage::Audio((mic, spk)),
+                    state.args.block_on_backpressure,
+                    &mut state.backpressure,
+                )
+                .await
+                {
+                    AudioSend::Sent => {
+                        state
+                            .audio_activity_tx
+                            .send_replace(state.last_audio_received);
+
+                        let sent = pcm_duration(mic_len, crate::pre_roll::SAMPLE_RATE);
+                        if let Some(estimate) = state.cost_meter.add(sent) {
+                            let _ = SessionEvent::CostEstimate { estimate }.emit(&state.args.app);
+                        }
+                    }
+                    AudioSend::Dropped { sustained } => {
+                        if sustained {
+                            let dropped_chunks = state.backpressure.dropped_chunks;
+                            tracing::warn!(dropped_chunks, "stt_backend_slow");
+                            let _ = SessionEvent::SttBackendSlow { dropped_chunks }
+                                .emit(&state.args.app);
+                        }
+                    }
+                    AudioSend::Closed => {}
+                }
             }
 
-            ListenerMsg::StreamResponse(response) => {
+            ListenerMsg::StreamResponse(mut response) => {
+                state.language_pin.apply(&mut response);
                 let diff = state.manager.append(response);
 
-                let partial_words_by_channel: HashMap<usize, Vec<Word2>> = diff
-                    .partial_words
-                    .iter()
-                    .map(|(channel_idx, words)| {
-                        (
-                            *channel_idx,
-                            words
-                                .iter()
-                                .map(|w| Word2::from(w.clone()))
-                                .collect::<Vec<_>>(),
-                        )
-                    })
-                    .collect();
+                if let Some(sink) = state.transcript_sink.as_mut() {
+                    if let Err(e) = sink.write(&diff) {
+                        tracing::error!("transcript_sink_write_failed: {:?}", e);
+                    }
+                }
+
+                let partial_words_by_channel =
+                    to_word2_by_channel(&diff.partial_words, state.args.recording_offset_ms);
 
-                SessionEvent::PartialWords {
-                    words: partial_words_by_channel,
+                if let Some(words) = state.partial_flush.as_mut().and_then(|throttle| {
+                    throttle.poll(
+                        tokio::time::Instant::now(),
+                        flatten(to_word2_by_channel(
+                            &state.manager.partial_words_by_channel,
+                            state.args.recording_offset_ms,
+                        )),
+                    )
+                }) {
+                    if let Err(e) =
+                        update_partial_words(&state.args.app, &state.args.session_id, words).await
+                    {
+                        tracing::error!("partial_flush_failed: {:?}", e);
+                    }
                 }
-                .emit(&state.args.app)?;
 
-                let final_words_by_channel: HashMap<usize, Vec<Word2>> = diff
-                    .final_words
-                    .iter()
-                    .map(|(channel_idx, words)| {
-                        (
-                            *channel_idx,
-                            words
-                                .iter()
-                                .map(|w| Word2::from(w.clone()))
-                                .collect::<Vec<_>>(),
-                        )
-                    })
-                    .collect();
+                // Finalized words leave the saved partials in the same write, so none are in both.
+                let has_final_words = diff.final_words.values().any(|w| !w.is_empty());
+                let partial_words = state
+                    .partial_flush
+                    .as_mut()
+                    .filter(|_| has_final_words)
+                    .and_then(|throttle| {
+                        throttle.flush_now(flatten(to_word2_by_channel(
+                            &state.manager.partial_words_by_channel,
+                            state.args.recording_offset_ms,
+                        )))
+                    });
 
-                update_session(
+                // Saved first, so the events carry the words as punctuated on save.
+                let final_words_by_channel = update_session(
                     &state.args.app,
                     &state.args.session_id,
-                    final_words_by_channel
-                        .clone()
-                        .values()
-                        .flatten()
-                        .cloned()
-                        .collect(),
+                    to_word2_by_channel(&diff.final_words, state.args.recording_offset_ms),
+                    partial_words,
+                    state
+                        .language_pin
+                        .current()
+                        .or(state.args.languages.first())
+                        .filter(|_| state.args.restore_punctuation),
                 )
                 .await
                 .unwrap();
 
-                SessionEvent::FinalWords {
-                    words: final_words_by_channel,
+                match state.word_patcher.as_mut() {
+                    Some(patcher) => {
+                        let (partial_ops, final_ops) =
+                            patcher.patch(&partial_words_by_channel, &final_words_by_channel);
+
+                        SessionEvent::WordsPatch {
+                            partial_ops,
+                            final_ops,
+                        }
+                        .emit(&state.args.app)?;
+                    }
+                    None => {
+                        SessionEvent::PartialWords {
+                            words: partial_words_by_channel,
+                        }
+                        .emit(&state.args.app)?;
+                        SessionEvent::FinalWords {
+                            words: final_words_by_channel,
+                        }
+                        .emit(&state.args.app)?;
+                    }
+                }
+            }
+
+            ListenerMsg::GetPartials(reply) => {
+                if !reply.is_closed() {
+                    let _ = reply.send(to_word2_by_channel(
+                        &state.manager.partial_words_by_channel,
+                        state.args.recording_offset_ms,
+                    ));
                 }
-                .emit(&state.args.app)?;
+            }
+
+            ListenerMsg::GetPlainTranscript(reply) => {
+                use tauri_plugin_db::DatabasePluginExt;
+
+                let final_words = state
+                    .args
+                    .app
+                    .db_get_session(&state.args.session_id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|s| s.words)
+                    .unwrap_or_default();
+                let partial_words = to_word2_by_channel(
+                    &state.manager.partial_words_by_channel,
+                    state.args.recording_offset_ms,
+                );
+
+                if !reply.is_closed() {
+                    let _ = reply.send(plain_transcript(&final_words, partial_words));
+                }
+            }
+
+            ListenerMsg::SetRestorePunctuation(enabled) => {
+                state.args.restore_punctuation = enabled;
+            }
+
+            ListenerMsg::BackendSelected(backend) => {
+                state
+                    .cost_meter
+                    .set_pricing(backend.price_per_minute.map(|price_per_minute| SttPricing {
+                        price_per_minute,
+                        budget_cap: state.args.stt_budget_cap,
+                    }));
             }
 
             ListenerMsg::StreamStartFailed(error) => {
@@ -183,7 +455,7 @@ impl Actor for ListenerActor {
             }
 
             ListenerMsg::StreamTimeout => {
-                tracing::info!("listen_stream_timeout");
+                tracing::info!(kind = ?state.timeout_kind(), "listen_stream_timeout");
                 myself.stop(None);
             }
         }
@@ -209,9 +481,27 @@ impl Actor for ListenerActor {
     }
 }
 
+// Tells the UI why the listener is about to stop on its own.
+fn stream_stop_event(
+    message: &ListenerMsg,
+    timeout_kind: StreamTimeoutKind,
+) -> Option<SessionEvent> {
+    match message {
+        ListenerMsg::StreamEnded => Some(SessionEvent::TranscriptionCompleted {}),
+        ListenerMsg::StreamError(reason) => Some(SessionEvent::TranscriptionFailed {
+            reason: reason.clone(),
+        }),
+        ListenerMsg::StreamTimeout => {
+            Some(SessionEvent::TranscriptionTimedOut { kind: timeout_kind })
+        }
+        _ => None,
+    }
+}
+
 async fn spawn_rx_task(
     args: ListenerArgs,
     myself: ActorRef<ListenerMsg>,
+    audio_activity_rx: tokio::sync::watch::Receiver<tokio::time::Instant>,
 ) -> Result<
     (
         tokio::sync::mpsc::Sender<MixedMessage<(Bytes, Bytes), ControlMessage>>,
@@ -224,62 +514,130 @@ async fn spawn_rx_task(
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
 
     let app = args.app.clone();
+    let stream_timeout = args.stream_timeout;
+    let reconnect_grace = args.reconnect_grace;
 
-    let conn = {
+    let primary = {
         use tauri_plugin_local_stt::LocalSttPluginExt;
-        app.get_connection().await?
+        match app.get_connection().await {
+            Ok(conn) => Some(tauri_plugin_local_stt::Connection {
+                sample_rate: conn.sample_rate.or(args.stt_sample_rate),
+                price_per_minute: conn.price_per_minute.or(args.stt_price_per_minute),
+                opus: conn.opus || args.stt_opus,
+                ..conn
+            }),
+            // With fallbacks configured, an unavailable primary is not fatal.
+            Err(e) if !args.fallback_connections.is_empty() => {
+                tracing::warn!(error = ?e, "primary_stt_connection_unavailable");
+                None
+            }
+            Err(e) => return Err(e.into()),
+        }
     };
 
-    let client = owhisper_client::ListenClient::builder()
-        .api_base(conn.base_url)
-        .api_key(conn.api_key.unwrap_or_default())
-        .params(owhisper_interface::ListenParams {
-            model: conn.model,
-            languages: args.languages,
-            redemption_time_ms: Some(if args.onboarding { 60 } else { 400 }),
-            ..Default::default()
+    let clients = primary
+        .into_iter()
+        .chain(args.fallback_connections.iter().cloned())
+        .map(|conn| {
+            let redemption_time_ms = crate::redemption::redemption_time_ms(
+                &args.languages,
+                args.onboarding,
+                &args.redemption_overrides,
+            );
+            let client = build_client(&conn, &args.languages, redemption_time_ms);
+            (conn, client)
         })
-        .build_dual();
+        .collect::<Vec<_>>();
 
     let rx_task = tokio::spawn(async move {
-        let outbound = tokio_stream::wrappers::ReceiverStream::new(rx);
-        let (listen_stream, handle) = match client.from_realtime_audio(outbound).await {
+        // Shared so a backend that fails to connect leaves the audio for the next one.
+        let rx = std::sync::Arc::new(tokio::sync::Mutex::new(rx));
+
+        let connected = connect_with_failover(&clients, |(conn, client)| {
+            client.from_realtime_audio(resampled_stream(
+                shared_receiver_stream(rx.clone()),
+                crate::SttInfo::from(conn).sample_rate,
+            ))
+        })
+        .await;
+        let ((conn, client), (listen_stream, mut handle)) = match connected {
             Ok(res) => res,
             Err(e) => {
-                let _ = myself.send_message(ListenerMsg::StreamStartFailed(format!("{:?}", e)));
+                let _ = myself.send_message(ListenerMsg::StreamStartFailed(e));
                 return;
             }
         };
-        futures_util::pin_mut!(listen_stream);
 
-        loop {
-            tokio::select! {
-                _ = &mut shutdown_rx => {
-                    handle.finalize_with_text(serde_json::json!({"type": "Finalize"}).to_string().into()).await;
-                    break;
+        let backend = crate::SttInfo::from(conn);
+        let sample_rate = backend.sample_rate;
+        tracing::info!(backend = ?backend, "stt_backend_selected");
+        let _ = myself.send_message(ListenerMsg::BackendSelected(backend.clone()));
+        let _ = SessionEvent::SttBackendSelected { backend }.emit(&app);
+
+        let mut exit = drive_stream(
+            listen_stream,
+            &mut shutdown_rx,
+            audio_activity_rx.clone(),
+            stream_timeout,
+            |response| {
+                let _ = myself.send_message(ListenerMsg::StreamResponse(response));
+            },
+        )
+        .await;
+
+        while let Some(grace) = reconnect_grace {
+            let StreamExit::Error(e) = &exit else {
+                break;
+            };
+            let e = e.clone();
+            tracing::warn!(error = %e, "listen_stream_dropped_reconnecting");
+
+            let reconnected =
+                reconnect_with_buffer(&rx, grace, MAX_OUTAGE_SAMPLES, outgoing_samples, |audio| {
+                    client.from_realtime_audio(resampled_stream(audio, sample_rate))
+                })
+                .await;
+            let listen_stream = match reconnected {
+                Ok((listen_stream, new_handle)) => {
+                    handle = new_handle;
+                    listen_stream
                 }
-                result = tokio::time::timeout(LISTEN_STREAM_TIMEOUT, listen_stream.next()) => {
-                    match result {
-                        Ok(Some(Ok(response))) => {
-                            let _ = myself.send_message(ListenerMsg::StreamResponse(response));
-                        }
-                        // Something went wrong while sending or receiving a websocket message. Should restart.
-                        Ok(Some(Err(e))) => {
-                            let _ = myself.send_message(ListenerMsg::StreamError(format!("{:?}", e)));
-                            break;
-                        }
-                         // Stream ended gracefully. Safe to stop the whole session.
-                        Ok(None) => {
-                            let _ = myself.send_message(ListenerMsg::StreamEnded);
-                            break;
-                        }
-                        // We're not hearing back any transcript. Better to stop the whole session.
-                        Err(_) => {
-                            let _ = myself.send_message(ListenerMsg::StreamTimeout);
-                            break;
-                        }
-                    }
+                Err(reason) => {
+                    exit = StreamExit::Error(format!("{}, then {}", e, reason));
+                    break;
                 }
+            };
+
+            tracing::info!("listen_stream_reconnected");
+            exit = drive_stream(
+                listen_stream,
+                &mut shutdown_rx,
+                audio_activity_rx.clone(),
+                stream_timeout,
+                |response| {
+                    let _ = myself.send_message(ListenerMsg::StreamResponse(response));
+                },
+            )
+            .await;
+        }
+
+        match exit {
+            StreamExit::Shutdown => {
+                handle
+                    .finalize_with_text(serde_json::json!({"type": "Finalize"}).to_string().into())
+                    .await;
+            }
+            // Something went wrong while sending or receiving a websocket message. Should restart.
+            StreamExit::Error(e) => {
+                let _ = myself.send_message(ListenerMsg::StreamError(e));
+            }
+            // Stream ended gracefully. Safe to stop the whole session.
+            StreamExit::Ended => {
+                let _ = myself.send_message(ListenerMsg::StreamEnded);
+            }
+            // Neither audio nor transcript went through for too long. Better to stop the whole session.
+            StreamExit::Timeout => {
+                let _ = myself.send_message(ListenerMsg::StreamTimeout);
             }
         }
     });
@@ -287,11 +645,397 @@ async fn spawn_rx_task(
     Ok((tx, rx_task, shutdown_tx))
 }
 
-async fn update_session<R: tauri::Runtime>(
+pub(crate) fn build_client(
+    conn: &tauri_plugin_local_stt::Connection,
+    languages: &[hypr_language::Language],
+    redemption_time_ms: u64,
+) -> owhisper_client::ListenClientDual {
+    let info = crate::SttInfo::from(conn);
+
+    // Opus saves bandwidth, but only backends configured to take it get it, and never local ones.
+    let encoding =
+        if conn.opus && !info.is_local && info.sample_rate == hypr_audio_utils::OPUS_SAMPLE_RATE {
+            owhisper_interface::AudioEncoding::Opus
+        } else {
+            owhisper_interface::AudioEncoding::Linear16
+        };
+
+    owhisper_client::ListenClient::builder()
+        .api_base(conn.base_url.clone())
+        .api_key(conn.api_key.clone().unwrap_or_default())
+        .params(owhisper_interface::ListenParams {
+            model: conn.model.clone(),
+            languages: languages.to_vec(),
+            redemption_time_ms: Some(redemption_time_ms),
+            encoding,
+            sample_rate: info.sample_rate,
+            ..Default::default()
+        })
+        .build_dual()
+}
+
+// Audio is captured at 16kHz; backends wanting another rate get it converted on the way out.
+pub(crate) fn resampled_stream<S>(
+    stream: S,
+    sample_rate: u32,
+) -> std::pin::Pin<Box<dyn futures_util::Stream<Item = S::Item> + Send>>
+where
+    S: futures_util::Stream<Item = MixedMessage<(Bytes, Bytes), ControlMessage>> + Send + 'static,
+{
+    if sample_rate == crate::pre_roll::SAMPLE_RATE {
+        return Box::pin(stream);
+    }
+
+    let mut resampler = DualResampler::new(crate::pre_roll::SAMPLE_RATE, sample_rate);
+    Box::pin(stream.map(move |msg| resampler.process(msg)))
+}
+
+// Tries each backend in order and keeps the first that connects. Fails once all have been tried.
+async fn connect_with_failover<'a, C, T, E, F, Fut>(
+    backends: &'a [C],
+    mut connect: F,
+) -> Result<(&'a C, T), String>
+where
+    F: FnMut(&'a C) -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: std::fmt::Debug,
+{
+    let mut errors = Vec::with_capacity(backends.len());
+
+    for (i, backend) in backends.iter().enumerate() {
+        match connect(backend).await {
+            Ok(connected) => return Ok((backend, connected)),
+            Err(e) => {
+                tracing::warn!(backend = i, error = ?e, "stt_backend_connect_failed");
+                errors.push(format!("{:?}", e));
+            }
+        }
+    }
+
+    Err(format!("all stt backends failed: [{}]", errors.join(", ")))
+}
+
+// About 30 seconds of audio at 16kHz, whatever size the chunks are aggregated into.
+const MAX_OUTAGE_SAMPLES: usize = 30 * crate::pre_roll::SAMPLE_RATE as usize;
+const RECONNECT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// Samples per channel in an outgoing message, which holds 16-bit PCM.
+fn outgoing_samples(message: &MixedMessage<(Bytes, Bytes), ControlMessage>) -> usize {
+    match message {
+        MixedMessage::Audio((mic, _)) => mic.len() / 2,
+        MixedMessage::Control(_) => 0,
+    }
+}
+
+// Holds on to outgoing audio while reconnecting a dropped stream, and sends it first on the new
+// one, so a short outage loses no speech. Gives up once `grace` is over, or when more than
+// `max_samples` are waiting, as counted by `samples`.
+async fn reconnect_with_buffer<T, S, E, F, Fut>(
+    rx: &std::sync::Arc<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<T>>>,
+    grace: Duration,
+    max_samples: usize,
+    samples: fn(&T) -> usize,
+    mut connect: F,
+) -> Result<S, String>
+where
+    T: Send + 'static,
+    F: FnMut(std::pin::Pin<Box<dyn futures_util::Stream<Item = T> + Send>>) -> Fut,
+    Fut: std::future::Future<Output = Result<S, E>>,
+    E: std::fmt::Debug,
+{
+    let buffer = std::sync::Arc::new(std::sync::Mutex::new(VecDeque::new()));
+    let deadline = tokio::time::Instant::now() + grace;
+
+    loop {
+        let attempt = tokio::time::timeout_at(
+            deadline,
+            connect(Box::pin(buffered_receiver_stream(
+                buffer.clone(),
+                rx.clone(),
+            ))),
+        );
+
+        match buffer_while(rx, &buffer, max_samples, samples, attempt).await? {
+            Ok(Ok(connected)) => return Ok(connected),
+            Ok(Err(e)) => tracing::warn!(error = ?e, "listen_stream_reconnect_failed"),
+            Err(_) => break,
+        }
+
+        let retry_at = (tokio::time::Instant::now() + RECONNECT_RETRY_INTERVAL).min(deadline);
+        buffer_while(
+            rx,
+            &buffer,
+            max_samples,
+            samples,
+            tokio::time::sleep_until(retry_at),
+        )
+        .await?;
+
+        if tokio::time::Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    Err(format!("could not reconnect within {:?}", grace))
+}
+
+// Moves audio from `rx` into `buffer` until `fut` is done.
+async fn buffer_while<T, O>(
+    rx: &tokio::sync::Mutex<tokio::sync::mpsc::Receiver<T>>,
+    buffer: &std::sync::Mutex<VecDeque<T>>,
+    max_samples: usize,
+    samples: fn(&T) -> usize,
+    fut: impl std::future::Future<Output = O>,
+) -> Result<O, String> {
+    futures_util::pin_mut!(fut);
+
+    loop {
+        tokio::select! {
+            output = &mut fut => return Ok(output),
+            item = async { rx.lock().await.recv().await } => {
+                let Some(item) = item else {
+                    return Err("audio channel closed".to_string());
+                };
+
+                let mut buffer = buffer.lock().unwrap();
+                buffer.push_back(item);
+                if buffer.iter().map(samples).sum::<usize>() > max_samples {
+                    return Err(format!("more than {} samples buffered", max_samples));
+                }
+            }
+        }
+    }
+}
+
+// Drains `buffer` before reading from `rx`.
+fn buffered_receiver_stream<T: Send + 'static>(
+    buffer: std::sync::Arc<std::sync::Mutex<VecDeque<T>>>,
+    rx: std::sync::Arc<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<T>>>,
+) -> impl futures_util::Stream<Item = T> + Send + 'static {
+    futures_util::stream::unfold((buffer, rx), |(buffer, rx)| async move {
+        let buffered = buffer.lock().unwrap().pop_front();
+        let item = match buffered {
+            Some(item) => Some(item),
+            None => rx.lock().await.recv().await,
+        };
+        item.map(|item| (item, (buffer, rx)))
+    })
+}
+
+fn shared_receiver_stream<T: Send + 'static>(
+    rx: std::sync::Arc<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<T>>>,
+) -> impl futures_util::Stream<Item = T> + Send + Unpin + 'static {
+    Box::pin(futures_util::stream::unfold(rx, |rx| async move {
+        let item = rx.lock().await.recv().await;
+        item.map(|item| (item, rx))
+    }))
+}
+
+#[derive(Debug, PartialEq)]
+enum StreamExit {
+    Shutdown,
+    Error(String),
+    Ended,
+    Timeout,
+}
+
+async fn drive_stream<S, E>(
+    listen_stream: S,
+    shutdown_rx: &mut tokio::sync::oneshot::Receiver<()>,
+    audio_activity_rx: tokio::sync::watch::Receiver<tokio::time::Instant>,
+    stream_timeout: Duration,
+    mut on_response: impl FnMut(owhisper_interface::StreamResponse),
+) -> StreamExit
+where
+    S: futures_util::Stream<Item = Result<owhisper_interface::StreamResponse, E>>,
+    E: std::fmt::Debug,
+{
+    futures_util::pin_mut!(listen_stream);
+
+    let mut last_response = tokio::time::Instant::now();
+
+    loop {
+        let last_activity = last_response.max(*audio_activity_rx.borrow());
+
+        tokio::select! {
+            _ = &mut *shutdown_rx => {
+                return StreamExit::Shutdown;
+            }
+            next = listen_stream.next() => {
+                last_response = tokio::time::Instant::now();
+
+                match next {
+                    Some(Ok(response)) => on_response(response),
+                    Some(Err(e)) => return StreamExit::Error(format!("{:?}", e)),
+                    None => return StreamExit::Ended,
+                }
+            }
+            _ = tokio::time::sleep_until(last_activity + stream_timeout) => {
+                // Audio may have been sent while we were sleeping.
+                if last_response.max(*audio_activity_rx.borrow()) + stream_timeout
+                    <= tokio::time::Instant::now()
+                {
+                    return StreamExit::Timeout;
+                }
+            }
+        }
+    }
+}
+
+fn to_word2_by_channel(
+    words: &WordsByChannel,
+    recording_offset_ms: Option<u64>,
+) -> HashMap<usize, Vec<Word2>> {
+    words
+        .iter()
+        .map(|(channel_idx, words)| {
+            (
+                *channel_idx,
+                words
+                    .iter()
+                    .map(|w| {
+                        let word = Word2::from(w.clone());
+                        match recording_offset_ms {
+                            Some(offset) => with_recording_time(word, offset),
+                            None => word,
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+// The partials of each channel go after its finals, and the channels are then merged by time.
+pub(crate) fn plain_transcript(
+    final_words: &[Word2],
+    partial_words: HashMap<usize, Vec<Word2>>,
+) -> String {
+    let mut words = group_by_channel(final_words);
+    for (channel_idx, partials) in partial_words {
+        words.entry(channel_idx).or_default().extend(partials);
+    }
+
+    crate::to_plain_text(&to_conversation(words))
+}
+
+// Decides when the in-progress partials should be written to the session.
+// Writes happen at most once per `interval`, and only if the partials changed since the last write.
+struct PartialFlushThrottle {
+    interval: Duration,
+    last_flush_at: Option<tokio::time::Instant>,
+    last_flushed: Option<Vec<Word2>>,
+}
+
+impl PartialFlushThrottle {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_flush_at: None,
+            last_flushed: None,
+        }
+    }
+
+    fn poll(&mut self, now: tokio::time::Instant, words: Vec<Word2>) -> Option<Vec<Word2>> {
+        if self
+            .last_flush_at
+            .is_some_and(|last| now < last + self.interval)
+        {
+            return None;
+        }
+
+        let words = self.flush_now(words)?;
+        self.last_flush_at = Some(now);
+        Some(words)
+    }
+
+    fn flush_now(&mut self, words: Vec<Word2>) -> Option<Vec<Word2>> {
+        if self.last_flushed.as_ref() == Some(&words) {
+            return None;
+        }
+
+        self.last_flushed = Some(words.clone());
+        Some(words)
+    }
+}
+
+// About 1.5 seconds of audio, in 512-sample chunks at 16kHz.
+const SUSTAINED_DROPS: u32 = 48;
+
+// Audio dropped because the send channel was full, i.e. gaps in what the backend hears.
+#[derive(Debug, Default)]
+struct Backpressure {
+    dropped_chunks: u64,
+    consecutive_drops: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AudioSend {
+    Sent,
+    // `sustained` is set once per run of drops, when it gets long enough to warn about.
+    Dropped { sustained: bool },
+    Closed,
+}
+
+async fn send_audio<T>(
+    tx: &tokio::sync::mpsc::Sender<T>,
+    msg: T,
+    block: bool,
+    backpressure: &mut Backpressure,
+) -> AudioSend {
+    use tokio::sync::mpsc::error::TrySendError;
+
+    let result = if block {
+        tx.send(msg).await.map_err(|_| TrySendError::Closed(()))
+    } else {
+        tx.try_send(msg).map_err(|e| match e {
+            TrySendError::Full(_) => TrySendError::Full(()),
+            TrySendError::Closed(_) => TrySendError::Closed(()),
+        })
+    };
+
+    match result {
+        Ok(()) => {
+            backpressure.consecutive_drops = 0;
+            AudioSend::Sent
+        }
+        Err(TrySendError::Full(())) => {
+            backpressure.dropped_chunks += 1;
+            backpressure.consecutive_drops += 1;
+            AudioSend::Dropped {
+                sustained: backpressure.consecutive_drops == SUSTAINED_DROPS,
+            }
+        }
+        Err(TrySendError::Closed(())) => AudioSend::Closed,
+    }
+}
+
+async fn update_partial_words<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
     session_id: impl Into<String>,
     words: Vec<Word2>,
-) -> Result<Vec<Word2>, crate::Error> {
+) -> Result<(), crate::Error> {
+    use tauri_plugin_db::DatabasePluginExt;
+
+    let mut session = app
+        .db_get_session(session_id)
+        .await?
+        .ok_or(crate::Error::NoneSession)?;
+
+    session.partial_words = words;
+    app.db_upsert_session(session).await?;
+
+    Ok(())
+}
+
+// Appends the new final words to the session, and returns them as saved.
+async fn update_session<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    session_id: impl Into<String>,
+    words_by_channel: HashMap<usize, Vec<Word2>>,
+    partial_words: Option<Vec<Word2>>,
+    punctuation_language: Option<&hypr_language::Language>,
+) -> Result<HashMap<usize, Vec<Word2>>, crate::Error> {
     use tauri_plugin_db::DatabasePluginExt;
 
     let mut session = app
@@ -299,8 +1043,515 @@ async fn update_session<R: tauri::Runtime>(
         .await?
         .ok_or(crate::Error::NoneSession)?;
 
-    session.words.extend(words);
-    app.db_upsert_session(session.clone()).await.unwrap();
+    let mut merged = group_by_channel(&session.words);
+    let mut added = HashMap::new();
+    for (channel_idx, words) in words_by_channel {
+        let channel = merged.entry(channel_idx).or_default();
+        let from = channel.len();
+        channel.extend(words);
+
+        if let Some(language) = punctuation_language {
+            crate::punctuation::restore_punctuation(channel, from, language);
+        }
+        added.insert(channel_idx, channel[from..].to_vec());
+    }
+    session.words = flatten(merged);
+    if let Some(partial_words) = partial_words {
+        session.partial_words = partial_words;
+    }
+    app.db_upsert_session(session).await.unwrap();
+
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnect_replays_buffered_audio() {
+        let (tx, rx) = tokio::sync::mpsc::channel::<u32>(32);
+        let rx = std::sync::Arc::new(tokio::sync::Mutex::new(rx));
+
+        // Audio keeps coming while the stream is down.
+        for chunk in 1..=3 {
+            tx.send(chunk).await.unwrap();
+        }
+        let late_tx = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            late_tx.send(4).await.unwrap();
+            late_tx.send(5).await.unwrap();
+        });
+
+        // The network is back for the second attempt.
+        let mut attempts = 0;
+        // Each chunk counts as 512 samples.
+        let samples = |_: &u32| 512;
+
+        let audio =
+            reconnect_with_buffer(&rx, Duration::from_secs(5), 10 * 512, samples, |audio| {
+                attempts += 1;
+                let online = attempts > 1;
+                async move {
+                    if online {
+                        Ok(audio)
+                    } else {
+                        Err("offline")
+                    }
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(attempts, 2);
+
+        tx.send(6).await.unwrap();
+        assert_eq!(
+            audio.take(6).collect::<Vec<_>>().await,
+            vec![1, 2, 3, 4, 5, 6]
+        );
+
+        // Longer outages give up, once the buffer is full or the grace window is over.
+        let (tx, rx) = tokio::sync::mpsc::channel::<u32>(32);
+        let rx = std::sync::Arc::new(tokio::sync::Mutex::new(rx));
+        for chunk in 1..=3 {
+            tx.send(chunk).await.unwrap();
+        }
+        let result =
+            reconnect_with_buffer(&rx, Duration::from_secs(5), 2 * 512, samples, |_| async {
+                Err::<(), _>("offline")
+            })
+            .await;
+        assert!(result.is_err());
+
+        let started = tokio::time::Instant::now();
+        let result =
+            reconnect_with_buffer(&rx, Duration::from_secs(5), 10 * 512, samples, |_| async {
+                Err::<(), _>("offline")
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_stream_stop_events() {
+        let event = |message: ListenerMsg| {
+            stream_stop_event(&message, StreamTimeoutKind::NoAudioActivity)
+                .map(|e| serde_json::to_value(e).unwrap())
+        };
+
+        assert_eq!(
+            event(ListenerMsg::StreamEnded),
+            Some(serde_json::json!({ "type": "transcriptionCompleted" }))
+        );
+        assert_eq!(
+            event(ListenerMsg::StreamError("connection reset".to_string())),
+            Some(serde_json::json!({
+                "type": "transcriptionFailed",
+                "reason": "connection reset"
+            }))
+        );
+        assert_eq!(
+            event(ListenerMsg::StreamTimeout),
+            Some(serde_json::json!({
+                "type": "transcriptionTimedOut",
+                "kind": "noAudioActivity"
+            }))
+        );
+        assert_eq!(
+            stream_stop_event(
+                &ListenerMsg::StreamTimeout,
+                StreamTimeoutKind::NoTranscriptResponse
+            )
+            .map(|e| serde_json::to_value(e).unwrap()),
+            Some(serde_json::json!({
+                "type": "transcriptionTimedOut",
+                "kind": "noTranscriptResponse"
+            }))
+        );
+        assert_eq!(event(ListenerMsg::Audio(Bytes::new(), Bytes::new())), None);
+    }
+
+    #[tokio::test]
+    async fn test_stream_timeout_reset_by_audio() {
+        let stream_timeout = Duration::from_millis(200);
+        let audio_duration = Duration::from_millis(600);
+
+        let (audio_activity_tx, audio_activity_rx) =
+            tokio::sync::watch::channel(tokio::time::Instant::now());
+        let (_shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        // Slow backend: accepts audio but never responds.
+        let listen_stream =
+            futures_util::stream::pending::<Result<owhisper_interface::StreamResponse, ()>>();
+
+        let started = tokio::time::Instant::now();
+        tokio::spawn(async move {
+            while started.elapsed() < audio_duration {
+                audio_activity_tx.send_replace(tokio::time::Instant::now());
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            // Keep the sender alive so the receiver keeps seeing the last value.
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let exit = drive_stream(
+            listen_stream,
+            &mut shutdown_rx,
+            audio_activity_rx,
+            stream_timeout,
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(exit, StreamExit::Timeout);
+        assert!(started.elapsed() >= audio_duration);
+        assert!(started.elapsed() < audio_duration + stream_timeout * 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_timeout_without_audio() {
+        let stream_timeout = Duration::from_millis(100);
+
+        let (_audio_activity_tx, audio_activity_rx) =
+            tokio::sync::watch::channel(tokio::time::Instant::now());
+        let (_shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let listen_stream =
+            futures_util::stream::pending::<Result<owhisper_interface::StreamResponse, ()>>();
 
-    Ok(session.words)
+        let started = tokio::time::Instant::now();
+        let exit = drive_stream(
+            listen_stream,
+            &mut shutdown_rx,
+            audio_activity_rx,
+            stream_timeout,
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(exit, StreamExit::Timeout);
+        assert!(started.elapsed() < stream_timeout * 3);
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_second_backend() {
+        let (tx, rx) = tokio::sync::mpsc::channel::<u32>(8);
+        let rx = std::sync::Arc::new(tokio::sync::Mutex::new(rx));
+
+        // Audio already queued while connecting must reach whichever backend comes up.
+        for i in 1..=3 {
+            tx.send(i).await.unwrap();
+        }
+
+        let connect = |backend: &&str| {
+            let audio = shared_receiver_stream(rx.clone());
+            let refuses = backend.contains("local");
+            async move {
+                if refuses {
+                    Err("connection refused")
+                } else {
+                    Ok(audio)
+                }
+            }
+        };
+
+        let backends = ["ws://localhost:50060", "wss://api.example.com"];
+        let (backend, audio) = connect_with_failover(&backends, connect).await.unwrap();
+        assert_eq!(*backend, "wss://api.example.com");
+
+        drop(tx);
+        assert_eq!(audio.collect::<Vec<_>>().await, vec![1, 2, 3]);
+
+        let result = connect_with_failover(&backends[..1], connect).await;
+        assert!(result.unwrap_err().contains("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_audio_sent_at_backend_sample_rate() {
+        let conn = tauri_plugin_local_stt::Connection {
+            model: None,
+            base_url: "wss://api.example.com".to_string(),
+            api_key: None,
+            sample_rate: Some(8000),
+            price_per_minute: None,
+            opus: false,
+        };
+        let sample_rate = crate::SttInfo::from(&conn).sample_rate;
+
+        // One second of 16kHz capture, in 20ms chunks.
+        let chunk = Bytes::from(vec![0u8; 320 * 2]);
+        let captured = (0..50)
+            .map(|_| MixedMessage::Audio((chunk.clone(), chunk.clone())))
+            .chain([MixedMessage::Control(ControlMessage::KeepAlive)])
+            .collect::<Vec<_>>();
+
+        let sent = resampled_stream(futures_util::stream::iter(captured), sample_rate)
+            .collect::<Vec<_>>()
+            .await;
+
+        let (mut mic_samples, mut spk_samples) = (0, 0);
+        for msg in &sent {
+            if let MixedMessage::Audio((mic, spk)) = msg {
+                mic_samples += mic.len() / 2;
+                spk_samples += spk.len() / 2;
+            }
+        }
+        assert_eq!(mic_samples, 8000);
+        assert_eq!(spk_samples, 8000);
+        assert!(matches!(
+            sent.last(),
+            Some(MixedMessage::Control(ControlMessage::KeepAlive))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_drops_and_warns() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(32);
+        let mut backpressure = Backpressure::default();
+
+        for i in 0..32 {
+            assert_eq!(
+                send_audio(&tx, i, false, &mut backpressure).await,
+                AudioSend::Sent
+            );
+        }
+
+        // The backend stops reading: every chunk from here on is dropped.
+        for i in 1..=SUSTAINED_DROPS + 2 {
+            let sent = send_audio(&tx, 0, false, &mut backpressure).await;
+            assert_eq!(
+                sent,
+                AudioSend::Dropped {
+                    sustained: i == SUSTAINED_DROPS
+                }
+            );
+            assert_eq!(backpressure.dropped_chunks, i as u64);
+        }
+
+        // Room again ends the run of drops, but keeps the count.
+        rx.recv().await.unwrap();
+        assert_eq!(
+            send_audio(&tx, 0, false, &mut backpressure).await,
+            AudioSend::Sent
+        );
+        assert_eq!(backpressure.consecutive_drops, 0);
+        assert_eq!(backpressure.dropped_chunks, SUSTAINED_DROPS as u64 + 2);
+
+        // Blocking waits for the backend instead of dropping.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            rx.recv().await.unwrap();
+            // Keep the receiver alive until the send went through.
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+        assert_eq!(
+            send_audio(&tx, 0, true, &mut backpressure).await,
+            AudioSend::Sent
+        );
+        assert_eq!(backpressure.dropped_chunks, SUSTAINED_DROPS as u64 + 2);
+    }
+
+    #[test]
+    fn test_partial_flush_cadence() {
+        let interval = Duration::from_secs(5);
+        let mut throttle = PartialFlushThrottle::new(interval);
+
+        let start = tokio::time::Instant::now();
+        let mut field: Vec<Word2> = vec![];
+        let mut written_at = vec![];
+
+        // A new partial every second for 20 seconds.
+        for sec in 0..=20u64 {
+            let words = vec![Word2 {
+                text: format!("word{}", sec),
+                ..Default::default()
+            }];
+
+            if let Some(words) = throttle.poll(start + Duration::from_secs(sec), words) {
+                field = words;
+                written_at.push(sec);
+            }
+        }
+
+        assert_eq!(written_at, vec![0, 5, 10, 15, 20]);
+        assert_eq!(field[0].text, "word20");
+    }
+
+    #[test]
+    fn test_partial_flush_idempotent() {
+        let mut throttle = PartialFlushThrottle::new(Duration::from_secs(5));
+        let start = tokio::time::Instant::now();
+        let words = vec![Word2 {
+            text: "hello".to_string(),
+            ..Default::default()
+        }];
+
+        assert!(throttle.poll(start, words.clone()).is_some());
+        assert!(throttle
+            .poll(start + Duration::from_secs(10), words.clone())
+            .is_none());
+        assert!(throttle.flush_now(words).is_none());
+        assert!(throttle.flush_now(vec![]).is_some());
+    }
+
+    fn response(
+        words: &[(&str, f64, f64)],
+        channel_idx: i32,
+        is_final: bool,
+    ) -> owhisper_interface::StreamResponse {
+        serde_json::from_value(serde_json::json!({
+            "type": "Results",
+            "start": 0.0,
+            "duration": 1.0,
+            "is_final": is_final,
+            "speech_final": false,
+            "from_finalize": false,
+            "channel": {
+                "alternatives": [{
+                    "transcript": words.iter().map(|(w, _, _)| *w).collect::<Vec<_>>().join(" "),
+                    "words": words.iter().map(|(w, start, end)| serde_json::json!({
+                        "word": w,
+                        "start": start,
+                        "end": end,
+                        "confidence": 0.9,
+                        "speaker": null,
+                        "punctuated_word": w,
+                        "language": null,
+                    })).collect::<Vec<_>>(),
+                    "confidence": 0.9,
+                }]
+            },
+            "metadata": owhisper_interface::Metadata::default(),
+            "channel_index": [channel_idx, 2],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_partials_snapshot() {
+        let mut manager = TranscriptManager::builder().build();
+
+        for (channel_idx, words) in [(0, vec!["hello", "there"]), (1, vec!["hi"])] {
+            let words = words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (*w, i as f64 * 0.5, i as f64 * 0.5 + 0.4))
+                .collect::<Vec<_>>();
+
+            manager.append(response(&words, channel_idx, false));
+        }
+
+        let snapshot = to_word2_by_channel(&manager.partial_words_by_channel, None);
+        let texts = |idx: usize| {
+            snapshot[&idx]
+                .iter()
+                .map(|w| w.text.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(texts(0), vec!["hello", "there"]);
+        assert_eq!(texts(1), vec!["hi"]);
+        assert_eq!(snapshot[&0][1].start_ms, Some(500));
+    }
+
+    #[test]
+    fn test_plain_transcript() {
+        let mut manager = TranscriptManager::builder().build();
+        let mut final_words = vec![];
+
+        for response in [
+            response(&[("hello", 0.0, 0.4), ("there", 0.5, 0.9)], 0, true),
+            response(&[("hi", 1.0, 1.3)], 1, true),
+            response(&[("how", 2.0, 2.2), ("are", 2.3, 2.6)], 0, false),
+        ] {
+            let diff = manager.append(response);
+            // As saved to the session.
+            final_words.extend(flatten(to_word2_by_channel(&diff.final_words, None)));
+        }
+
+        let partial_words = to_word2_by_channel(&manager.partial_words_by_channel, None);
+        assert_eq!(
+            plain_transcript(&final_words, partial_words),
+            "Me: hello there\nThem: hi\nMe: how are"
+        );
+        assert_eq!(plain_transcript(&[], HashMap::new()), "");
+    }
+
+    #[test]
+    fn test_transcript_sink() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let path = dir.join("transcript.jsonl");
+
+        let mut manager = TranscriptManager::builder().build();
+        let mut sink = TranscriptSink::open(&path).unwrap();
+
+        let responses = [
+            response(&[("hello", 0.0, 0.4)], 0, false),
+            response(&[("hello", 0.0, 0.4), ("there", 0.5, 0.9)], 0, false),
+            response(&[("hello", 0.0, 0.4), ("there", 0.5, 0.9)], 0, true),
+            response(&[("hi", 1.0, 1.3)], 1, true),
+        ];
+        for (i, response) in responses.into_iter().enumerate() {
+            sink.write(&manager.append(response)).unwrap();
+
+            // Each line is visible as soon as it is written.
+            let lines = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(lines.lines().count(), i + 1);
+        }
+
+        let diffs = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<crate::manager::Diff>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(diffs[1].partial_content()[&0], "hello there");
+        assert_eq!(diffs[2].final_content()[&0], "hello there");
+        assert_eq!(diffs[3].final_content()[&1], "hi");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recording_relative_time() {
+        const SAMPLE_RATE: usize = 16000;
+
+        let recording_offset_ms = 1_700_000_000_000;
+        // The listener started 500ms after the recorder, so stream time 0 is 0.5s into the recording.
+        let manager_offset = recording_offset_ms + 500;
+
+        // 3s of silence, with a word spoken from 2.0s to 2.4s.
+        let recording = (0..SAMPLE_RATE * 3)
+            .map(|i| {
+                if (SAMPLE_RATE * 2..SAMPLE_RATE * 24 / 10).contains(&i) {
+                    0.5
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<f32>>();
+
+        let mut manager = TranscriptManager::builder()
+            .with_manager_offset(manager_offset)
+            .build();
+        let diff = manager.append(response(&[("hello", 1.5, 1.9)], 0, true));
+
+        let words = to_word2_by_channel(&diff.final_words, Some(recording_offset_ms));
+        let word = &words[&0][0];
+
+        // Absolute time stays wall-clock based.
+        assert_eq!(word.start_ms, Some(recording_offset_ms + 2000));
+
+        let start_s = word.recording_start_s.unwrap();
+        let end_s = word.recording_end_s.unwrap();
+        assert!((start_s - 2.0).abs() < 0.01, "{}", start_s);
+        assert!((end_s - 2.4).abs() < 0.01, "{}", end_s);
+
+        let start_sample = (start_s * SAMPLE_RATE as f64).round() as usize;
+        assert_eq!(recording[start_sample], 0.5);
+        assert_eq!(recording[start_sample - 1], 0.0);
+    }
 }
@@ -1,10 +1,14 @@
 mod listener;
+#[cfg(test)]
+mod noop;
 mod processor;
 mod recorder;
 mod session;
 mod source;
 
 pub use listener::*;
+#[cfg(test)]
+pub use noop::*;
 pub use processor::*;
 pub use recorder::*;
 pub use session::*;
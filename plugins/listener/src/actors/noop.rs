@@ -0,0 +1,26 @@
+use std::marker::PhantomData;
+
+use ractor::{Actor, ActorProcessingErr, ActorRef};
+
+// Does nothing. Tests spawn it under a real actor's name to stand in for that actor.
+pub struct NoopActor<M = ()>(PhantomData<fn() -> M>);
+
+impl<M> Default for NoopActor<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: ractor::Message> Actor for NoopActor<M> {
+    type Msg = M;
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+}
@@ -8,37 +8,66 @@ use ractor::{registry, Actor, ActorName, ActorProcessingErr, ActorRef};
 use tauri_specta::Event;
 
 use crate::{
-    actors::{AudioChunk, ListenerActor, ListenerMsg, RecMsg, RecorderActor},
-    SessionEvent,
+    actors::{
+        AudioChunk, ListenerActor, ListenerMsg, RecMsg, RecorderActor, RecorderQueue, RecorderSend,
+    },
+    Channel, SessionEvent,
 };
 
 const AUDIO_AMPLITUDE_THROTTLE: Duration = Duration::from_millis(100);
+const SAMPLE_RATE: u32 = 16000;
+pub const DEFAULT_DC_BLOCKER_CUTOFF_HZ: f32 = 20.0;
+
+// Samples this close to full scale count as clipped.
+const CLIPPING_LEVEL: f32 = 0.99;
+// Clipping is judged per second of audio, and only a sustained share of it is reported.
+const CLIPPING_WINDOW_SAMPLES: usize = SAMPLE_RATE as usize;
+const CLIPPING_MAX_RATIO: f32 = 0.005;
+// Seconds of audio before the same channel is reported again.
+const CLIPPING_COOLDOWN_WINDOWS: u32 = 10;
 
 pub enum ProcMsg {
     Mic(AudioChunk),
     Speaker(AudioChunk),
     Mixed(AudioChunk),
+    // The source switched to capturing from this mic.
+    MicDevice(String),
 }
 
 pub struct ProcArgs {
     pub app: tauri::AppHandle,
+    pub session_id: String,
+    // High-pass cutoff applied to the mic before AGC. `None` disables it.
+    pub dc_blocker_cutoff_hz: Option<f32>,
+    // Shared with the recorder, which may fall behind on a slow disk.
+    pub recorder_queue: RecorderQueue,
+    // Tells the recorder which audio is speech, for its VAD gate.
+    pub detect_speech: bool,
 }
 
 pub struct ProcState {
     app: tauri::AppHandle,
+    session_id: String,
+    dc_blocker: Option<DcBlocker>,
+    recorder_queue: RecorderQueue,
+    speech_detector: Option<SpeechDetector>,
     agc_m: hypr_agc::Agc,
     agc_s: hypr_agc::Agc,
+    // Whose gain `agc_m` is tracking, so it is saved under the right device.
+    mic_device: Option<String>,
     joiner: Joiner,
     last_sent_mic: Option<Arc<[f32]>>,
     last_sent_spk: Option<Arc<[f32]>>,
     last_amp_emit: Instant,
+    clipping_m: ClippingDetector,
+    clipping_s: ClippingDetector,
 }
 
 pub struct ProcessorActor {}
 
 impl ProcessorActor {
-    pub fn name() -> ActorName {
-        "processor_actor".into()
+    pub fn name(session_id: &str) -> ActorName {
+        format!("processor_actor:{}", session_id)
     }
 }
 
@@ -54,12 +83,21 @@ impl Actor for ProcessorActor {
     ) -> Result<Self::State, ActorProcessingErr> {
         Ok(ProcState {
             app: args.app.clone(),
+            session_id: args.session_id,
+            dc_blocker: args
+                .dc_blocker_cutoff_hz
+                .map(|cutoff_hz| DcBlocker::new(cutoff_hz, SAMPLE_RATE)),
+            recorder_queue: args.recorder_queue,
+            speech_detector: args.detect_speech.then(SpeechDetector::new).flatten(),
             joiner: Joiner::new(),
             agc_m: hypr_agc::Agc::default(),
             agc_s: hypr_agc::Agc::default(),
+            mic_device: None,
             last_sent_mic: None,
             last_sent_spk: None,
             last_amp_emit: Instant::now(),
+            clipping_m: ClippingDetector::new(),
+            clipping_s: ClippingDetector::new(),
         })
     }
 
@@ -71,18 +109,34 @@ impl Actor for ProcessorActor {
     ) -> Result<(), ActorProcessingErr> {
         match msg {
             ProcMsg::Mic(mut c) => {
+                if st.clipping_m.push(&c.data) {
+                    emit_clipping(st, Channel::Mic);
+                }
+                if let Some(dc_blocker) = st.dc_blocker.as_mut() {
+                    dc_blocker.process(&mut c.data);
+                }
                 st.agc_m.process(&mut c.data);
                 let arc = Arc::<[f32]>::from(c.data);
                 st.joiner.push_mic(arc);
                 process_ready(st).await;
             }
             ProcMsg::Speaker(mut c) => {
+                if st.clipping_s.push(&c.data) {
+                    emit_clipping(st, Channel::Speaker);
+                }
                 st.agc_s.process(&mut c.data);
                 let arc = Arc::<[f32]>::from(c.data);
                 st.joiner.push_spk(arc);
                 process_ready(st).await;
             }
             ProcMsg::Mixed(mut c) => {
+                // A single device, so the gain to lower is the mic's.
+                if st.clipping_m.push(&c.data) {
+                    emit_clipping(st, Channel::Mic);
+                }
+                if let Some(dc_blocker) = st.dc_blocker.as_mut() {
+                    dc_blocker.process(&mut c.data);
+                }
                 st.agc_m.process(&mut c.data);
 
                 let empty_arc = Arc::<[f32]>::from(vec![0.0; c.data.len()]);
@@ -92,16 +146,56 @@ impl Actor for ProcessorActor {
                 st.joiner.push_spk(arc);
                 process_ready(st).await;
             }
+            ProcMsg::MicDevice(device) => {
+                if st.mic_device.as_ref() != Some(&device) {
+                    save_agc_gain(st);
+                    st.agc_m = seeded_agc(&st.app, &device);
+                    st.mic_device = Some(device);
+                }
+            }
         }
         Ok(())
     }
+
+    async fn post_stop(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        st: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        save_agc_gain(st);
+        Ok(())
+    }
+}
+
+// Starts from where the mic's AGC settled last time, rather than from unity.
+fn seeded_agc(app: &tauri::AppHandle, device: &str) -> hypr_agc::Agc {
+    use crate::ListenerPluginExt;
+
+    match app.get_agc_gain(device) {
+        Ok(Some(gain)) => hypr_agc::Agc::default().with_seed(gain),
+        Ok(None) => hypr_agc::Agc::default(),
+        Err(e) => {
+            tracing::warn!(error = ?e, "agc_gain_unavailable");
+            hypr_agc::Agc::default()
+        }
+    }
+}
+
+fn save_agc_gain(st: &ProcState) {
+    use crate::ListenerPluginExt;
+
+    if let Some(device) = st.mic_device.as_deref() {
+        if let Err(e) = st.app.set_agc_gain(device, st.agc_m.gain()) {
+            tracing::warn!(error = ?e, "agc_gain_save_failed");
+        }
+    }
 }
 
 async fn process_ready(st: &mut ProcState) {
     while let Some((mic, spk)) = st.joiner.pop_pair() {
         let mut audio_sent_successfully = false;
 
-        if let Some(cell) = registry::where_is(RecorderActor::name()) {
+        if let Some(cell) = registry::where_is(RecorderActor::name(&st.session_id)) {
             let mixed: Vec<f32> = mic
                 .iter()
                 .zip(spk.iter())
@@ -109,10 +203,26 @@ async fn process_ready(st: &mut ProcState) {
                 .collect();
 
             let actor: ActorRef<RecMsg> = cell.into();
-            actor.cast(RecMsg::Audio(mixed)).ok();
+            // The decision goes ahead of the audio it was made on.
+            if let Some(speech) = st
+                .speech_detector
+                .as_mut()
+                .and_then(|detector| detector.push(&mixed))
+            {
+                let _ = actor.cast(RecMsg::Vad(speech));
+            }
+            if matches!(
+                st.recorder_queue.send(&actor, mixed),
+                RecorderSend::Dropped { first: true }
+            ) {
+                tracing::warn!(actor = RecorderActor::name(&st.session_id), "recorder_slow");
+                if let Err(e) = (SessionEvent::RecorderSlow {}).emit(&st.app) {
+                    tracing::error!("{:?}", e);
+                }
+            }
         }
 
-        if let Some(cell) = registry::where_is(ListenerActor::name()) {
+        if let Some(cell) = registry::where_is(ListenerActor::name(&st.session_id)) {
             let mic_bytes = hypr_audio_utils::f32_to_i16_bytes(mic.iter().copied());
             let spk_bytes = hypr_audio_utils::f32_to_i16_bytes(spk.iter().copied());
 
@@ -125,10 +235,10 @@ async fn process_ready(st: &mut ProcState) {
                 st.last_sent_mic = Some(mic.clone());
                 st.last_sent_spk = Some(spk.clone());
             } else {
-                tracing::warn!(actor = ListenerActor::name(), "cast_failed");
+                tracing::warn!(actor = ListenerActor::name(&st.session_id), "cast_failed");
             }
         } else {
-            tracing::debug!(actor = ListenerActor::name(), "unavailable");
+            tracing::debug!(actor = ListenerActor::name(&st.session_id), "unavailable");
         }
 
         if audio_sent_successfully && st.last_amp_emit.elapsed() >= AUDIO_AMPLITUDE_THROTTLE {
@@ -144,6 +254,142 @@ async fn process_ready(st: &mut ProcState) {
     }
 }
 
+// Frames at or above this probability count as speech.
+const SPEECH_THRESHOLD: f32 = 0.5;
+
+struct SpeechDetector {
+    vad: hypr_vad2::Vad,
+    pending: Vec<f32>,
+    speech: Option<bool>,
+}
+
+impl SpeechDetector {
+    fn new() -> Option<Self> {
+        match hypr_vad2::Vad::new() {
+            Ok(vad) => Some(Self {
+                vad,
+                pending: Vec::new(),
+                speech: None,
+            }),
+            Err(e) => {
+                tracing::error!(error = ?e, "vad_unavailable");
+                None
+            }
+        }
+    }
+
+    // Returns the decision for `samples` when it differs from the last one. Any speech in them
+    // counts, and a failed frame is taken for speech so nothing is skipped by mistake.
+    fn push(&mut self, samples: &[f32]) -> Option<bool> {
+        self.pending.extend_from_slice(samples);
+
+        let frames = self.pending.len() / hypr_vad2::FRAME_SIZE;
+        if frames == 0 {
+            return None;
+        }
+
+        let scored = self
+            .pending
+            .drain(..frames * hypr_vad2::FRAME_SIZE)
+            .collect::<Vec<_>>();
+        // Every frame is scored, since the model keeps context between them.
+        let speech = scored
+            .chunks(hypr_vad2::FRAME_SIZE)
+            .map(|frame| {
+                self.vad
+                    .probability_f32(frame)
+                    .ok()
+                    .is_none_or(|p| p >= SPEECH_THRESHOLD)
+            })
+            .fold(false, |any, speech| any || speech);
+
+        (self.speech != Some(speech)).then(|| {
+            self.speech = Some(speech);
+            speech
+        })
+    }
+}
+
+fn emit_clipping(st: &ProcState, channel: Channel) {
+    tracing::warn!(channel = ?channel, "input_clipping");
+
+    if let Err(e) = (SessionEvent::InputClipping { channel }).emit(&st.app) {
+        tracing::error!("{:?}", e);
+    }
+}
+
+// Counts samples at full scale in the raw input, before AGC could hide them.
+struct ClippingDetector {
+    clipped: usize,
+    seen: usize,
+    cooldown: u32,
+}
+
+impl ClippingDetector {
+    fn new() -> Self {
+        Self {
+            clipped: 0,
+            seen: 0,
+            cooldown: 0,
+        }
+    }
+
+    // Returns true once a window clipped too much, unless it was reported only recently.
+    fn push(&mut self, data: &[f32]) -> bool {
+        self.clipped += data.iter().filter(|s| s.abs() >= CLIPPING_LEVEL).count();
+        self.seen += data.len();
+
+        if self.seen < CLIPPING_WINDOW_SAMPLES {
+            return false;
+        }
+
+        let ratio = self.clipped as f32 / self.seen as f32;
+        self.clipped = 0;
+        self.seen = 0;
+
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return false;
+        }
+        if ratio <= CLIPPING_MAX_RATIO {
+            return false;
+        }
+
+        self.cooldown = CLIPPING_COOLDOWN_WINDOWS;
+        true
+    }
+}
+
+// One-pole high-pass: y[n] = x[n] - x[n-1] + r * y[n-1]
+pub(crate) struct DcBlocker {
+    r: f32,
+    prev_x: f32,
+    prev_y: f32,
+}
+
+impl DcBlocker {
+    pub(crate) fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let r = (-2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32).exp();
+
+        Self {
+            r,
+            prev_x: 0.0,
+            prev_y: 0.0,
+        }
+    }
+
+    pub(crate) fn process(&mut self, data: &mut [f32]) {
+        for sample in data.iter_mut() {
+            let x = *sample;
+            let y = x - self.prev_x + self.r * self.prev_y;
+
+            self.prev_x = x;
+            self.prev_y = y;
+            *sample = y;
+        }
+    }
+}
+
 struct Joiner {
     mic: VecDeque<Arc<[f32]>>,
     spk: VecDeque<Arc<[f32]>>,
@@ -183,3 +429,61 @@ impl Joiner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dc_blocker_removes_offset() {
+        let mut dc_blocker = DcBlocker::new(DEFAULT_DC_BLOCKER_CUTOFF_HZ, SAMPLE_RATE);
+
+        let input = (0..SAMPLE_RATE as usize * 2)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                0.3 + 0.2 * (2.0 * std::f32::consts::PI * 440.0 * t).sin()
+            })
+            .collect::<Vec<_>>();
+
+        let mut output = Vec::with_capacity(input.len());
+        for chunk in input.chunks(512) {
+            let mut processed = chunk.to_vec();
+            dc_blocker.process(&mut processed);
+            assert_eq!(processed.len(), chunk.len());
+            output.extend(processed);
+        }
+
+        assert_eq!(output.len(), input.len());
+
+        let mean = |xs: &[f32]| xs.iter().sum::<f32>() / xs.len() as f32;
+        assert!((mean(&input) - 0.3).abs() < 0.01);
+
+        let tail = &output[SAMPLE_RATE as usize..];
+        assert!(mean(tail).abs() < 0.01, "mean: {}", mean(tail));
+    }
+
+    #[test]
+    fn test_clipping_detector() {
+        let tone = |gain: f32| {
+            (0..SAMPLE_RATE as usize * 30)
+                .map(|i| {
+                    let t = i as f32 / SAMPLE_RATE as f32;
+                    (gain * (2.0 * std::f32::consts::PI * 440.0 * t).sin()).clamp(-1.0, 1.0)
+                })
+                .collect::<Vec<_>>()
+        };
+        let count_reports = |input: &[f32]| {
+            let mut detector = ClippingDetector::new();
+            input
+                .chunks(512)
+                .filter(|chunk| detector.push(chunk))
+                .count()
+        };
+
+        // Loud, but well short of full scale.
+        assert_eq!(count_reports(&tone(0.8)), 0);
+
+        // Over-driven 4x: most samples hit the rails. Reported once, then again after the cooldown.
+        assert_eq!(count_reports(&tone(4.0)), 3);
+    }
+}
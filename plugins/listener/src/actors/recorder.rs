@@ -1,51 +1,583 @@
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Write};
 use std::num::{NonZeroU32, NonZeroU8};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 
 use ractor::{Actor, ActorName, ActorProcessingErr, ActorRef};
 use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisDecoder, VorbisEncoderBuilder};
 
+pub use hypr_recording::recording_segments;
+use hypr_recording::{decode_segment, f32_samples, segment_index, FILENAME_BASE};
+
+use crate::actors::{SessionActor, SessionMsg};
+use crate::disk::DiskGuard;
+use crate::{RecordingFormat, RecordingPrefs};
+
 pub enum RecMsg {
     Audio(Vec<f32>),
+    // Speech decision for the audio that follows. Only used with `vad_gate`.
+    Vad(bool),
+}
+
+const SAMPLE_RATE: u32 = 16000;
+const BITS_PER_SAMPLE: u16 = 32;
+
+// Short pauses are kept as is; only silence beyond this is skipped.
+const VAD_GATE_HANGOVER_SAMPLES: u64 = SAMPLE_RATE as u64 / 2;
+// Written right before speech resumes, so VAD latency doesn't clip the first word.
+const VAD_GATE_PRE_ROLL_SAMPLES: usize = SAMPLE_RATE as usize * 3 / 10;
+
+// Finalizing several sessions at once shouldn't take every core.
+const DEFAULT_MAX_CONCURRENT_ENCODES: usize = 2;
+
+const RECORDING_LIMIT_WARNING_SECS: u64 = 60;
+pub const MAX_RECORDING_DURATION_REASON: &str = "max_recording_duration";
+
+// How far the recorder may fall behind before new audio is dropped. About 2MB of samples.
+pub const RECORDER_QUEUE_SECONDS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum RotatePolicy {
+    BySeconds(u64),
+    ByBytes(u64),
+}
+
+impl RotatePolicy {
+    // `output_bytes` is only asked for by `ByBytes`, as it may have to look at the file.
+    fn should_rotate(&self, samples_written: u64, output_bytes: impl FnOnce() -> u64) -> bool {
+        match self {
+            RotatePolicy::BySeconds(secs) => samples_written >= secs * SAMPLE_RATE as u64,
+            RotatePolicy::ByBytes(bytes) => output_bytes() >= *bytes,
+        }
+    }
+}
+
+// Encodes a segment's audio the way `finalize_segment` will, as it comes in, to know how large
+// its Ogg is before it exists. The encoder can't move between threads, so it gets its own.
+struct OggSizeProbe {
+    tx: std::sync::mpsc::Sender<Vec<f32>>,
+    bytes: Arc<AtomicU64>,
+    // Only waited on in tests. Otherwise the thread ends by itself once the channel closes.
+    #[allow(dead_code)]
+    thread: std::thread::JoinHandle<()>,
+}
+
+struct CountingSink(Arc<AtomicU64>);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.fetch_add(buf.len() as u64, Ordering::Relaxed);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OggSizeProbe {
+    fn spawn(quality: f32) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel::<Vec<f32>>();
+        let bytes = Arc::new(AtomicU64::new(0));
+
+        let sink = CountingSink(bytes.clone());
+        let thread = std::thread::spawn(move || {
+            let encode = || -> Result<(), vorbis_rs::VorbisError> {
+                let mut encoder = VorbisEncoderBuilder::new(
+                    NonZeroU32::new(SAMPLE_RATE).unwrap(),
+                    NonZeroU8::new(1).unwrap(),
+                    sink,
+                )?
+                .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+                    target_quality: quality.clamp(0.0, 1.0),
+                })
+                .build()?;
+
+                for samples in rx {
+                    encoder.encode_audio_block([samples])?;
+                }
+                encoder.finish()?;
+                Ok(())
+            };
+            if let Err(e) = encode() {
+                tracing::warn!("ogg_size_probe_failed: {:?}", e);
+            }
+        });
+
+        Self { tx, bytes, thread }
+    }
+
+    fn write(&self, samples: &[f32]) {
+        if !samples.is_empty() {
+            let _ = self.tx.send(samples.to_vec());
+        }
+    }
+
+    // Encoded bytes so far. Trails the audio by what the encoder hasn't got to yet.
+    fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    // Waits for everything written to be encoded, and returns the Ogg's full size.
+    #[cfg(test)]
+    fn finish(self) -> u64 {
+        drop(self.tx);
+        let _ = self.thread.join();
+        self.bytes.load(Ordering::Relaxed)
+    }
 }
 
 pub struct RecArgs {
     pub app_dir: PathBuf,
     pub session_id: String,
+    // Skip long silences, recording each skipped stretch in `audio.gaps.jsonl`.
+    pub vad_gate: bool,
+    // Stops the recorder, and with it the session, once this much audio was received.
+    pub max_recording_seconds: Option<u64>,
+    // Audio received towards the limit. Owned by the session, so it carries across pauses.
+    pub samples_received: Arc<AtomicU64>,
+    // Audio captured right before the session started, written ahead of everything else.
+    pub pre_roll: Vec<f32>,
+    // Warns the session once the recording volume runs low on space.
+    pub disk_guard: Option<DiskGuard>,
+    pub prefs: RecordingPrefs,
+    // Shared with whoever sends the audio. Unbounded when unset.
+    pub queue: Option<RecorderQueue>,
+}
+
+// A plain recording of `session_id`; tests override what they exercise.
+#[cfg(test)]
+impl RecArgs {
+    pub(crate) fn for_test(app_dir: &Path, session_id: &str) -> Self {
+        Self {
+            app_dir: app_dir.to_path_buf(),
+            session_id: session_id.to_string(),
+            vad_gate: false,
+            max_recording_seconds: None,
+            samples_received: Default::default(),
+            pre_roll: vec![],
+            disk_guard: None,
+            prefs: Default::default(),
+            queue: None,
+        }
+    }
 }
 
 pub struct RecState {
+    session_id: String,
     writer: Option<hound::WavWriter<BufWriter<File>>>,
     wav_path: PathBuf,
     ogg_path: PathBuf,
+    dir: PathBuf,
+    segment: usize,
+    samples_written: u64,
+    // Only kept while rotating by size into Ogg files, which don't exist until finalized.
+    ogg_size: Option<OggSizeProbe>,
+    vad_gate: Option<VadGate>,
+    max_samples: Option<u64>,
+    // Counted from the audio itself rather than the clock, so paused time is not included.
+    samples_received: Arc<AtomicU64>,
+    limit_warned: bool,
+    disk_guard: Option<DiskGuard>,
+    prefs: RecordingPrefs,
+    queue: Option<RecorderQueue>,
+}
+
+struct VadGate {
+    speech: bool,
+    // Consecutive non-speech samples seen so far.
+    silence_run: u64,
+    pre_roll: VecDeque<f32>,
+    // Samples dropped since speech was last written.
+    skipped: u64,
+}
+
+impl VadGate {
+    fn new() -> Self {
+        Self {
+            // Until the first decision arrives, record everything.
+            speech: true,
+            silence_run: 0,
+            pre_roll: VecDeque::with_capacity(VAD_GATE_PRE_ROLL_SAMPLES),
+            skipped: 0,
+        }
+    }
+
+    // Returns the samples to write, and how many samples were skipped right before them.
+    fn process(&mut self, mut samples: Vec<f32>) -> (Vec<f32>, u64) {
+        if self.speech {
+            self.silence_run = 0;
+
+            let mut out = self.pre_roll.drain(..).collect::<Vec<_>>();
+            out.append(&mut samples);
+            return (out, std::mem::take(&mut self.skipped));
+        }
+
+        let hangover = VAD_GATE_HANGOVER_SAMPLES.saturating_sub(self.silence_run) as usize;
+        self.silence_run += samples.len() as u64;
+
+        let held = samples.split_off(hangover.min(samples.len()));
+        self.pre_roll.extend(held);
+
+        let overflow = self
+            .pre_roll
+            .len()
+            .saturating_sub(VAD_GATE_PRE_ROLL_SAMPLES);
+        self.pre_roll.drain(..overflow);
+        self.skipped += overflow as u64;
+
+        (samples, 0)
+    }
+
+    // Silence still held back when the recording ends.
+    fn finish(&mut self) -> u64 {
+        let pending = self.skipped + self.pre_roll.len() as u64;
+        self.skipped = 0;
+        self.pre_roll.clear();
+        pending
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecorderSend {
+    Sent,
+    // `first` is set for the first drop after audio last went through, to warn once per stall.
+    Dropped { first: bool },
+    Closed,
+}
+
+/// Bounds the audio waiting in the recorder's mailbox. Senders reserve room before casting, and
+/// the recorder gives it back once the audio is handled, so a stalled disk drops audio instead of
+/// piling it up in memory. Dropped audio is recorded as a gap.
+#[derive(Clone)]
+pub struct RecorderQueue {
+    inner: Arc<RecorderQueueInner>,
+}
+
+struct RecorderQueueInner {
+    capacity: u64,
+    queued: AtomicU64,
+    // Not yet recorded as a gap.
+    dropped: AtomicU64,
+    dropping: AtomicBool,
+}
+
+impl RecorderQueue {
+    pub fn new(seconds: u64) -> Self {
+        Self {
+            inner: Arc::new(RecorderQueueInner {
+                capacity: seconds * SAMPLE_RATE as u64,
+                queued: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                dropping: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    pub fn send(&self, actor: &ActorRef<RecMsg>, samples: Vec<f32>) -> RecorderSend {
+        let len = samples.len() as u64;
+        let inner = &self.inner;
+
+        let reserved = inner
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued + len <= inner.capacity).then_some(queued + len)
+            })
+            .is_ok();
+
+        if !reserved {
+            inner.dropped.fetch_add(len, Ordering::AcqRel);
+            let first = !inner.dropping.swap(true, Ordering::AcqRel);
+            return RecorderSend::Dropped { first };
+        }
+
+        if actor.cast(RecMsg::Audio(samples)).is_err() {
+            self.done(len as usize);
+            return RecorderSend::Closed;
+        }
+
+        inner.dropping.store(false, Ordering::Release);
+        RecorderSend::Sent
+    }
+
+    pub fn queued_samples(&self) -> u64 {
+        self.inner.queued.load(Ordering::Acquire)
+    }
+
+    fn done(&self, samples: usize) {
+        let _ = self
+            .inner
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                Some(queued.saturating_sub(samples as u64))
+            });
+    }
+
+    fn take_dropped(&self) -> u64 {
+        self.inner.dropped.swap(0, Ordering::AcqRel)
+    }
+
+    // Whatever a previous recorder left in its mailbox went away with it.
+    fn reset(&self) {
+        self.inner.queued.store(0, Ordering::Release);
+    }
+}
+
+/// Total duration, in milliseconds, of the audio already recorded in `session_dir`.
+pub fn recorded_duration_ms(session_dir: impl AsRef<Path>) -> u64 {
+    recording_segments(session_dir)
+        .iter()
+        .filter_map(|path| segment_duration_ms(path).ok())
+        .sum()
+}
+
+/// Deletes everything recorded for a session. Missing directories are not an error.
+pub fn remove_recordings(session_dir: impl AsRef<Path>) -> std::io::Result<()> {
+    match std::fs::remove_dir_all(session_dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+pub fn write_recording_meta(
+    session_dir: impl AsRef<Path>,
+    meta: &crate::RecordingMeta,
+) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(meta)?;
+    std::fs::write(meta_path(session_dir.as_ref()), json)
+}
+
+pub fn read_recording_meta(session_dir: impl AsRef<Path>) -> Option<crate::RecordingMeta> {
+    let json = std::fs::read(meta_path(session_dir.as_ref())).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+fn meta_path(session_dir: &Path) -> PathBuf {
+    session_dir.join(format!("{}.meta.json", FILENAME_BASE))
+}
+
+/// Fingerprint of everything recorded in `session_dir`, or `None` if nothing could be decoded.
+pub fn recording_fingerprint(session_dir: impl AsRef<Path>) -> Option<crate::AudioFingerprint> {
+    let mut builder = crate::fingerprint::FingerprintBuilder::new(SAMPLE_RATE);
+    let mut decoded = false;
+
+    for path in recording_segments(session_dir) {
+        match decode_segment(&path, |samples| builder.push(samples)) {
+            Ok(()) => decoded = true,
+            Err(e) => tracing::error!(path = ?path, error = ?e, "fingerprint_decode_failed"),
+        }
+    }
+
+    decoded.then(|| builder.finish())
+}
+
+/// Every segment recorded in `session_dir`, decoded and joined, at the recording's 16kHz.
+pub fn read_recording(session_dir: impl AsRef<Path>) -> Result<Vec<f32>, ActorProcessingErr> {
+    let mut samples = Vec::new();
+    for path in recording_segments(session_dir) {
+        decode_segment(&path, |block| samples.extend_from_slice(block))?;
+    }
+    Ok(samples)
+}
+
+/// Writes 16kHz mono `samples` to `session_dir` as a single-segment recording, encoded at
+/// `quality`.
+pub fn write_recording(
+    session_dir: impl AsRef<Path>,
+    samples: &[f32],
+    quality: f32,
+) -> Result<(), ActorProcessingErr> {
+    let session_dir = session_dir.as_ref();
+    std::fs::create_dir_all(session_dir)?;
+    let file = File::create(session_dir.join(format!("{}.ogg", FILENAME_BASE)))?;
+
+    let mut encoder = VorbisEncoderBuilder::new(
+        NonZeroU32::new(SAMPLE_RATE).unwrap(),
+        NonZeroU8::new(1).unwrap(),
+        BufWriter::new(file),
+    )?
+    .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+        target_quality: quality,
+    })
+    .build()?;
+
+    for block in samples.chunks(TRANSCODE_BLOCK_SIZE) {
+        encoder.encode_audio_block([block])?;
+    }
+    encoder.finish()?;
+    Ok(())
+}
+
+fn segment_duration_ms(path: &Path) -> Result<u64, ActorProcessingErr> {
+    let info = hypr_audio::probe_file(path)?;
+    Ok(info.duration.as_millis() as u64)
+}
+
+/// Picks the base directory for recordings, preferring `requested` when it is writable.
+///
+/// Returns the directory to use, along with the error that caused the fallback to `default`.
+pub fn resolve_recordings_dir(
+    requested: Option<&Path>,
+    default: &Path,
+) -> (PathBuf, Option<std::io::Error>) {
+    let Some(requested) = requested else {
+        return (default.to_path_buf(), None);
+    };
+
+    match ensure_writable(requested) {
+        Ok(()) => (requested.to_path_buf(), None),
+        Err(e) => (default.to_path_buf(), Some(e)),
+    }
+}
+
+fn ensure_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let probe = dir.join(format!(".write_probe_{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+fn segment_paths(dir: &Path, segment: Option<usize>) -> (PathBuf, PathBuf) {
+    let name = match segment {
+        Some(idx) => format!("{}.{:03}", FILENAME_BASE, idx),
+        None => FILENAME_BASE.to_string(),
+    };
+
+    (
+        dir.join(format!("{}.wav", name)),
+        dir.join(format!("{}.ogg", name)),
+    )
+}
+
+const TRANSCODE_BLOCK_SIZE: usize = 4096;
+
+// Recovered or externally produced files are not necessarily 32-bit float, so normalize to f32.
+#[cfg(test)]
+fn read_f32_samples<R: std::io::Read>(
+    mut reader: hound::WavReader<R>,
+) -> Result<Vec<f32>, hound::Error> {
+    f32_samples(&mut reader).collect()
+}
+
+// Reads and encodes `TRANSCODE_BLOCK_SIZE` frames at a time, so memory use doesn't grow with the recording.
+fn transcode_wav_to_ogg<R: std::io::Read, W: std::io::Write>(
+    mut wav_reader: hound::WavReader<R>,
+    ogg_writer: W,
+    quality: f32,
+) -> Result<(), ActorProcessingErr> {
+    let spec = wav_reader.spec();
+    let channels = spec.channels as usize;
+
+    let mut encoder = VorbisEncoderBuilder::new(
+        NonZeroU32::new(spec.sample_rate).unwrap(),
+        NonZeroU8::new(spec.channels as u8).unwrap(),
+        ogg_writer,
+    )?
+    .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+        target_quality: quality.clamp(0.0, 1.0),
+    })
+    .build()?;
+
+    let mut block = vec![Vec::with_capacity(TRANSCODE_BLOCK_SIZE); channels];
+
+    for (i, sample) in f32_samples(&mut wav_reader).enumerate() {
+        block[i % channels].push(sample?);
+
+        if i % channels == channels - 1 && block[channels - 1].len() == TRANSCODE_BLOCK_SIZE {
+            encoder.encode_audio_block(&block)?;
+            block.iter_mut().for_each(|c| c.clear());
+        }
+    }
+
+    // Drop a trailing partial frame, if any, so every channel has the same length.
+    let frames = block[channels - 1].len();
+    block.iter_mut().for_each(|c| c.truncate(frames));
+    if frames > 0 {
+        encoder.encode_audio_block(&block)?;
+    }
+
+    encoder.finish()?;
+    Ok(())
+}
+
+// Runs the synchronous vorbis encoder on blocking threads, a bounded number at a time.
+pub struct EncoderPool {
+    slots: Arc<tokio::sync::Semaphore>,
+}
+
+impl EncoderPool {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            slots: Arc::new(tokio::sync::Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    // Waits for a free slot, then for `f` to complete.
+    pub async fn run<T, F>(&self, f: F) -> Result<T, ActorProcessingErr>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, ActorProcessingErr> + Send + 'static,
+    {
+        let _permit = self.slots.acquire().await?;
+        tokio::task::spawn_blocking(f).await?
+    }
+}
+
+fn encoder_pool() -> &'static EncoderPool {
+    static POOL: OnceLock<EncoderPool> = OnceLock::new();
+    POOL.get_or_init(|| EncoderPool::new(DEFAULT_MAX_CONCURRENT_ENCODES))
+}
+
+fn write_f32_sample<W: std::io::Write + std::io::Seek>(
+    writer: &mut hound::WavWriter<W>,
+    spec: hound::WavSpec,
+    sample: f32,
+) -> Result<(), hound::Error> {
+    match spec.sample_format {
+        hound::SampleFormat::Float => writer.write_sample(sample),
+        hound::SampleFormat::Int => {
+            let max = ((1_i64 << (spec.bits_per_sample - 1)) - 1) as f32;
+            writer.write_sample((sample.clamp(-1.0, 1.0) * max).round() as i32)
+        }
+    }
 }
 
 pub struct RecorderActor;
 
 impl RecorderActor {
-    pub fn name() -> ActorName {
-        "recorder_actor".into()
+    pub fn name(session_id: &str) -> ActorName {
+        format!("recorder_actor:{}", session_id)
     }
 
-    async fn ogg_to_wav(ogg_path: &PathBuf, wav_path: &PathBuf) -> Result<(), ActorProcessingErr> {
+    async fn ogg_to_wav(
+        ogg_path: &PathBuf,
+        wav_path: &PathBuf,
+        sample_format: hound::SampleFormat,
+        bits_per_sample: u16,
+    ) -> Result<(), ActorProcessingErr> {
         let ogg_file = BufReader::new(File::open(ogg_path)?);
         let mut decoder = VorbisDecoder::new(ogg_file)?;
 
         let spec = hound::WavSpec {
             channels: decoder.channels().get() as u16,
             sample_rate: decoder.sampling_frequency().get(),
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
+            bits_per_sample,
+            sample_format,
         };
 
         let mut wav_writer = hound::WavWriter::create(wav_path, spec)?;
 
         while let Some(block) = decoder.decode_audio_block()? {
-            let samples = block.samples();
-            if samples.len() > 0 {
-                for sample in samples[0] {
-                    wav_writer.write_sample(*sample)?;
+            let channels = block.samples();
+            let frames = channels.first().map(|c| c.len()).unwrap_or(0);
+
+            for i in 0..frames {
+                for channel in channels.iter() {
+                    write_f32_sample(&mut wav_writer, spec, channel[i])?;
                 }
             }
         }
@@ -54,39 +586,134 @@ impl RecorderActor {
         Ok(())
     }
 
-    async fn wav_to_ogg(wav_path: &PathBuf, ogg_path: &PathBuf) -> Result<(), ActorProcessingErr> {
-        let wav_reader = hound::WavReader::open(wav_path)?;
-        let spec = wav_reader.spec();
+    async fn wav_to_ogg(
+        wav_path: &Path,
+        ogg_path: &Path,
+        quality: f32,
+    ) -> Result<(), ActorProcessingErr> {
+        let wav_path = wav_path.to_path_buf();
+        let ogg_path = ogg_path.to_path_buf();
 
-        let samples = wav_reader
-            .into_samples::<f32>()
-            .collect::<Result<Vec<_>, _>>()?;
+        encoder_pool()
+            .run(move || {
+                let wav_reader = hound::WavReader::open(&wav_path)?;
+                let ogg_writer = BufWriter::new(File::create(&ogg_path)?);
 
-        let mut ogg_buffer = Vec::new();
-        let mut encoder = VorbisEncoderBuilder::new(
-            NonZeroU32::new(spec.sample_rate).unwrap(),
-            NonZeroU8::new(spec.channels as u8).unwrap(),
-            &mut ogg_buffer,
-        )
-        .unwrap()
-        .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
-            target_quality: 0.7,
-        })
-        .build()?;
+                transcode_wav_to_ogg(wav_reader, ogg_writer, quality)
+            })
+            .await
+    }
+
+    fn open_writer(
+        wav_path: &PathBuf,
+    ) -> Result<hound::WavWriter<BufWriter<File>>, ActorProcessingErr> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: BITS_PER_SAMPLE,
+            sample_format: hound::SampleFormat::Float,
+        };
+
+        let writer = if wav_path.exists() {
+            hound::WavWriter::append(wav_path)?
+        } else {
+            hound::WavWriter::create(wav_path, spec)?
+        };
 
-        const BLOCK_SIZE: usize = 4096;
-        let channel_data = vec![samples];
+        Ok(writer)
+    }
 
-        for chunk in channel_data[0].chunks(BLOCK_SIZE) {
-            encoder.encode_audio_block(&[chunk])?;
+    async fn finalize_segment(st: &mut RecState) -> Result<(), ActorProcessingErr> {
+        if let Some(writer) = st.writer.take() {
+            writer.finalize()?;
+        }
+
+        // WAV recordings are done once the writer is finalized.
+        if st.wav_path.exists() && st.prefs.format == RecordingFormat::Ogg {
+            Self::wav_to_ogg(&st.wav_path, &st.ogg_path, st.prefs.quality).await?;
+            if !st.prefs.keep_wav {
+                std::fs::remove_file(&st.wav_path)?;
+            }
         }
 
-        encoder.finish()?;
+        Ok(())
+    }
 
-        std::fs::write(ogg_path, ogg_buffer)?;
+    // Lets playback positions be mapped back to the original timeline.
+    fn record_gap(st: &RecState, skipped_samples: u64) -> Result<(), ActorProcessingErr> {
+        let offset_samples = st.writer.as_ref().map(|w| w.len() as u64).unwrap_or(0);
+        let entry = serde_json::json!({
+            "segment": st.prefs.rotate.map(|_| st.segment),
+            "offset_samples": offset_samples,
+            "skipped_samples": skipped_samples,
+        });
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(st.dir.join(format!("{}.gaps.jsonl", FILENAME_BASE)))?;
+        writeln!(file, "{}", entry)?;
 
         Ok(())
     }
+
+    // Truncates `samples` to what fits within the limit, and returns whether the limit is reached.
+    fn apply_limit(st: &mut RecState, samples: &mut Vec<f32>) -> bool {
+        let Some(max_samples) = st.max_samples else {
+            return false;
+        };
+
+        let received = st.samples_received.load(Ordering::Relaxed);
+        let remaining = max_samples.saturating_sub(received);
+        samples.truncate(remaining.min(samples.len() as u64) as usize);
+
+        let received = received + samples.len() as u64;
+        st.samples_received.store(received, Ordering::Relaxed);
+
+        let remaining = max_samples.saturating_sub(received);
+        if !st.limit_warned && remaining <= RECORDING_LIMIT_WARNING_SECS * SAMPLE_RATE as u64 {
+            st.limit_warned = true;
+
+            if let Some(cell) = ractor::registry::where_is(SessionActor::name(&st.session_id)) {
+                let session: ActorRef<SessionMsg> = cell.into();
+                let _ = session.cast(SessionMsg::RecordingLimitApproaching {
+                    remaining_seconds: remaining / SAMPLE_RATE as u64,
+                });
+            }
+        }
+
+        remaining == 0
+    }
+
+    async fn rotate(st: &mut RecState) -> Result<(), ActorProcessingErr> {
+        Self::finalize_segment(st).await?;
+
+        st.segment += 1;
+        st.samples_written = 0;
+        st.ogg_size = Self::ogg_size_probe(&st.prefs);
+
+        let (wav_path, ogg_path) = segment_paths(&st.dir, Some(st.segment));
+        st.writer = Some(Self::open_writer(&wav_path)?);
+        st.wav_path = wav_path;
+        st.ogg_path = ogg_path;
+
+        Ok(())
+    }
+
+    fn ogg_size_probe(prefs: &RecordingPrefs) -> Option<OggSizeProbe> {
+        let by_bytes = matches!(prefs.rotate, Some(RotatePolicy::ByBytes(_)));
+        (by_bytes && prefs.format == RecordingFormat::Ogg)
+            .then(|| OggSizeProbe::spawn(prefs.quality))
+    }
+
+    // Size of the current segment's file as it ends up on disk: the Ogg being encoded, or the
+    // WAV itself when that is what's kept.
+    fn output_bytes(st: &RecState) -> u64 {
+        match st.ogg_size.as_ref() {
+            Some(probe) => probe.bytes(),
+            None => std::fs::metadata(&st.wav_path).map_or(0, |m| m.len()),
+        }
+    }
 }
 
 impl Actor for RecorderActor {
@@ -99,51 +726,135 @@ impl Actor for RecorderActor {
         _myself: ActorRef<Self::Msg>,
         args: Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let dir = args.app_dir.join(&args.session_id);
+        let dir = hypr_recording::session_dir(&args.app_dir, &args.session_id)
+            .ok_or_else(|| crate::Error::InvalidSessionId(args.session_id.clone()))?;
         std::fs::create_dir_all(&dir)?;
 
-        let filename_base = "audio".to_string();
-        let wav_path = dir.join(format!("{}.wav", filename_base));
-        let ogg_path = dir.join(format!("{}.ogg", filename_base));
+        // Rotated recordings never reopen a finalized segment; resuming starts a new one.
+        let segment = args.prefs.rotate.map(|_| {
+            recording_segments(&dir)
+                .iter()
+                .filter_map(|p| segment_index(p))
+                .max()
+                .map(|idx| idx + 1)
+                .unwrap_or(0)
+        });
 
-        if ogg_path.exists() {
-            Self::ogg_to_wav(&ogg_path, &wav_path).await?;
+        let (wav_path, ogg_path) = segment_paths(&dir, segment);
+
+        // A WAV kept next to the Ogg is the lossless copy of it, so that one is appended to.
+        if segment.is_none() && ogg_path.exists() && !wav_path.exists() {
+            Self::ogg_to_wav(
+                &ogg_path,
+                &wav_path,
+                hound::SampleFormat::Float,
+                BITS_PER_SAMPLE,
+            )
+            .await?;
+        }
+        if segment.is_none() && ogg_path.exists() {
             std::fs::remove_file(&ogg_path)?;
         }
 
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: 16000,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
-
-        let writer = if wav_path.exists() {
-            hound::WavWriter::append(&wav_path)?
-        } else {
-            hound::WavWriter::create(&wav_path, spec)?
-        };
+        let mut writer = Self::open_writer(&wav_path)?;
+        for s in &args.pre_roll {
+            writer.write_sample(*s)?;
+        }
+        let ogg_size = Self::ogg_size_probe(&args.prefs);
+        if let Some(probe) = ogg_size.as_ref() {
+            probe.write(&args.pre_roll);
+        }
 
         Ok(RecState {
+            session_id: args.session_id,
             writer: Some(writer),
             wav_path,
             ogg_path,
+            dir,
+            segment: segment.unwrap_or(0),
+            samples_written: args.pre_roll.len() as u64,
+            ogg_size,
+            vad_gate: args.vad_gate.then(VadGate::new),
+            max_samples: args.max_recording_seconds.map(|s| s * SAMPLE_RATE as u64),
+            samples_received: args.samples_received,
+            limit_warned: false,
+            disk_guard: args.disk_guard,
+            prefs: args.prefs,
+            queue: args.queue.inspect(RecorderQueue::reset),
         })
     }
 
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         msg: Self::Msg,
         st: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match msg {
-            RecMsg::Audio(v) => {
+            RecMsg::Audio(mut v) => {
+                if let Some(queue) = st.queue.as_ref() {
+                    queue.done(v.len());
+
+                    let dropped = queue.take_dropped();
+                    if dropped > 0 {
+                        tracing::warn!(session_id = %st.session_id, dropped, "recorder_dropped_audio");
+                        Self::record_gap(st, dropped)?;
+                    }
+                }
+
+                let limit_reached = Self::apply_limit(st, &mut v);
+
+                let v = match st.vad_gate.as_mut() {
+                    Some(gate) => {
+                        let (v, skipped) = gate.process(v);
+                        if skipped > 0 {
+                            Self::record_gap(st, skipped)?;
+                        }
+                        v
+                    }
+                    None => v,
+                };
+
+                if let Some(available_bytes) = st
+                    .disk_guard
+                    .as_mut()
+                    .and_then(|guard| guard.check(&st.dir, v.len() as u64))
+                {
+                    tracing::warn!(session_id = %st.session_id, available_bytes, "low_disk_space");
+
+                    if let Some(cell) =
+                        ractor::registry::where_is(SessionActor::name(&st.session_id))
+                    {
+                        let session: ActorRef<SessionMsg> = cell.into();
+                        let _ = session.cast(SessionMsg::LowDiskSpace { available_bytes });
+                    }
+                }
+
                 if let Some(ref mut writer) = st.writer {
+                    st.samples_written += v.len() as u64;
+                    if let Some(probe) = st.ogg_size.as_ref() {
+                        probe.write(&v);
+                    }
                     for s in v {
                         writer.write_sample(s)?;
                     }
                 }
+
+                if let Some(policy) = st.prefs.rotate {
+                    if policy.should_rotate(st.samples_written, || Self::output_bytes(st)) {
+                        Self::rotate(st).await?;
+                    }
+                }
+
+                if limit_reached {
+                    tracing::info!(session_id = %st.session_id, "max_recording_duration_reached");
+                    myself.stop(Some(MAX_RECORDING_DURATION_REASON.to_string()));
+                }
+            }
+            RecMsg::Vad(speech) => {
+                if let Some(gate) = st.vad_gate.as_mut() {
+                    gate.speech = speech;
+                }
             }
         }
 
@@ -155,15 +866,878 @@ impl Actor for RecorderActor {
         _myself: ActorRef<Self::Msg>,
         st: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
-        if let Some(writer) = st.writer.take() {
-            writer.finalize()?;
+        if let Some(skipped) = st.vad_gate.as_mut().map(|gate| gate.finish()) {
+            if skipped > 0 {
+                Self::record_gap(st, skipped)?;
+            }
         }
 
-        if st.wav_path.exists() {
-            Self::wav_to_ogg(&st.wav_path, &st.ogg_path).await?;
-            std::fs::remove_file(&st.wav_path)?;
+        Self::finalize_segment(st).await?;
+
+        if st.prefs.rotate.is_some() && st.samples_written == 0 {
+            let _ = std::fs::remove_file(&st.ogg_path);
+            let _ = std::fs::remove_file(&st.wav_path);
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::NoopActor;
+
+    #[tokio::test]
+    async fn test_encoder_pool_runs_off_async_worker() {
+        let pool = EncoderPool::new(1);
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+
+        // Only completes if the concurrent task below gets to run while this one blocks.
+        let encode = pool.run(move || {
+            rx.recv_timeout(std::time::Duration::from_secs(5))?;
+            Ok(())
+        });
+        let unblock = async move {
+            tokio::task::yield_now().await;
+            tx.send(()).unwrap();
+        };
+
+        let (result, _) = tokio::join!(encode, unblock);
+        result.unwrap();
+
+        let result = pool
+            .run(|| -> Result<(), ActorProcessingErr> { Err("encode failed".into()) })
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wav_to_ogg_int16() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let wav_path = dir.join("input.wav");
+        let ogg_path = dir.join("output.ogg");
+        let roundtrip_path = dir.join("roundtrip.wav");
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&wav_path, spec).unwrap();
+        for i in 0..SAMPLE_RATE {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let s = (t * 440.0 * 2.0 * std::f32::consts::PI).sin() * 0.5;
+            writer.write_sample((s * i16::MAX as f32) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        RecorderActor::wav_to_ogg(&wav_path, &ogg_path, RecordingPrefs::default().quality)
+            .await
+            .unwrap();
+        assert!(std::fs::metadata(&ogg_path).unwrap().len() > 0);
+
+        RecorderActor::ogg_to_wav(&ogg_path, &roundtrip_path, hound::SampleFormat::Int, 16)
+            .await
+            .unwrap();
+
+        let reader = hound::WavReader::open(&roundtrip_path).unwrap();
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        assert_eq!(reader.spec().sample_format, hound::SampleFormat::Int);
+
+        let samples = read_f32_samples(reader).unwrap();
+        assert!(samples.len() >= SAMPLE_RATE as usize / 2);
+
+        let peak = samples.iter().fold(0.0_f32, |acc, s| acc.max(s.abs()));
+        assert!(peak > 0.3 && peak < 0.7, "peak: {}", peak);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recorder_named_per_session() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        let mut actors = vec![];
+        for session_id in ["a", "b"] {
+            let session_id = format!("{}-{}", session_id, uuid::Uuid::new_v4());
+            let (actor, handle) = Actor::spawn(
+                Some(RecorderActor::name(&session_id)),
+                RecorderActor,
+                RecArgs::for_test(&app_dir, &session_id),
+            )
+            .await
+            .unwrap();
+
+            assert!(ractor::registry::where_is(RecorderActor::name(&session_id)).is_some());
+            // The recorder alone doesn't count; its session must also be alive.
+            assert!(!SessionActor::is_recording(&session_id));
+
+            // Stands in for the session, registered under its real name.
+            let (session, session_handle) = Actor::spawn(
+                Some(SessionActor::name(&session_id)),
+                NoopActor::<SessionMsg>::default(),
+                (),
+            )
+            .await
+            .unwrap();
+            assert!(SessionActor::is_recording(&session_id));
+
+            session.stop(None);
+            session_handle.await.unwrap();
+            assert!(!SessionActor::is_recording(&session_id));
+
+            actors.push((actor, handle));
+        }
+
+        for (actor, handle) in actors {
+            actor.drain_and_wait(None).await.unwrap();
+            handle.await.unwrap();
+        }
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_sessions_do_not_interfere() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let sessions = [
+            (format!("a-{}", uuid::Uuid::new_v4()), 1),
+            (format!("b-{}", uuid::Uuid::new_v4()), 3),
+        ];
+
+        let mut handles = vec![];
+        for (session_id, _) in sessions.iter() {
+            let (_, handle) = Actor::spawn(
+                Some(RecorderActor::name(session_id)),
+                RecorderActor,
+                RecArgs::for_test(&app_dir, session_id),
+            )
+            .await
+            .unwrap();
+            handles.push(handle);
+        }
+
+        // Resolve each recorder by name, the same way the processor does.
+        for (session_id, seconds) in sessions.iter() {
+            let actor: ActorRef<RecMsg> =
+                ractor::registry::where_is(RecorderActor::name(session_id))
+                    .unwrap()
+                    .into();
+            for _ in 0..*seconds {
+                actor
+                    .cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]))
+                    .unwrap();
+            }
+        }
+
+        for (session_id, _) in sessions.iter() {
+            let actor: ActorRef<RecMsg> =
+                ractor::registry::where_is(RecorderActor::name(session_id))
+                    .unwrap()
+                    .into();
+            actor.drain_and_wait(None).await.unwrap();
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        for (session_id, seconds) in sessions.iter() {
+            let dir = app_dir.join(session_id);
+            let wav_path = dir.join("decoded.wav");
+            RecorderActor::ogg_to_wav(
+                &dir.join("audio.ogg"),
+                &wav_path,
+                hound::SampleFormat::Float,
+                BITS_PER_SAMPLE,
+            )
+            .await
+            .unwrap();
+
+            let duration = hound::WavReader::open(&wav_path).unwrap().duration();
+            let expected = *seconds as u32 * SAMPLE_RATE;
+            assert!(
+                duration.abs_diff(expected) < SAMPLE_RATE / 10,
+                "{}: {} != {}",
+                session_id,
+                duration,
+                expected
+            );
+        }
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_custom_recordings_dir() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let default_dir = root.join("app_data");
+        let external_dir = root.join("external");
+
+        let (recordings_dir, err) = resolve_recordings_dir(Some(&external_dir), &default_dir);
+        assert!(err.is_none());
+        assert_eq!(recordings_dir, external_dir);
+
+        let (actor, handle) = Actor::spawn(
+            None,
+            RecorderActor,
+            RecArgs::for_test(&recordings_dir, "session"),
+        )
+        .await
+        .unwrap();
+
+        actor
+            .cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]))
+            .unwrap();
+        actor.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+
+        assert!(external_dir.join("session").join("audio.ogg").exists());
+        assert!(!default_dir.join("session").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recording_prefs_format() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        for (session_id, format, keep_wav) in [
+            ("wav", RecordingFormat::Wav, false),
+            ("both", RecordingFormat::Ogg, true),
+        ] {
+            let (actor, handle) = Actor::spawn(
+                None,
+                RecorderActor,
+                RecArgs {
+                    prefs: RecordingPrefs {
+                        format,
+                        keep_wav,
+                        ..Default::default()
+                    },
+                    ..RecArgs::for_test(&app_dir, session_id)
+                },
+            )
+            .await
+            .unwrap();
+
+            actor
+                .cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]))
+                .unwrap();
+            actor.drain_and_wait(None).await.unwrap();
+            handle.await.unwrap();
+        }
+
+        let wav_dir = app_dir.join("wav");
+        assert!(wav_dir.join("audio.wav").exists());
+        assert!(!wav_dir.join("audio.ogg").exists());
+        assert_eq!(
+            recording_segments(&wav_dir),
+            vec![wav_dir.join("audio.wav")]
+        );
+        assert_eq!(
+            read_recording(&wav_dir).unwrap().len(),
+            SAMPLE_RATE as usize
+        );
+        assert_eq!(recorded_duration_ms(&wav_dir), 1000);
+
+        // With both kept, the Ogg is what gets played back.
+        let both_dir = app_dir.join("both");
+        assert!(both_dir.join("audio.wav").exists());
+        assert_eq!(
+            recording_segments(&both_dir),
+            vec![both_dir.join("audio.ogg")]
+        );
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queue_bounds_stalled_recorder() {
+        // Stands in for a recorder stuck on disk IO, writing a chunk per permit.
+        struct StalledRecorder;
+
+        impl Actor for StalledRecorder {
+            type Msg = RecMsg;
+            type State = (RecorderQueue, Arc<tokio::sync::Semaphore>);
+            type Arguments = (RecorderQueue, Arc<tokio::sync::Semaphore>);
+
+            async fn pre_start(
+                &self,
+                _myself: ActorRef<Self::Msg>,
+                args: Self::Arguments,
+            ) -> Result<Self::State, ActorProcessingErr> {
+                Ok(args)
+            }
+
+            async fn handle(
+                &self,
+                _myself: ActorRef<Self::Msg>,
+                msg: Self::Msg,
+                (queue, resume): &mut Self::State,
+            ) -> Result<(), ActorProcessingErr> {
+                if let RecMsg::Audio(v) = msg {
+                    resume.acquire().await?.forget();
+                    queue.done(v.len());
+                }
+                Ok(())
+            }
+        }
+
+        let queue = RecorderQueue::new(1);
+        let resume = Arc::new(tokio::sync::Semaphore::new(0));
+        let (actor, handle) = Actor::spawn(None, StalledRecorder, (queue.clone(), resume.clone()))
+            .await
+            .unwrap();
+
+        // 100ms chunks: ten fit in the queue, the rest of the two seconds is dropped.
+        let chunk = SAMPLE_RATE as usize / 10;
+        let sends = (0..20)
+            .map(|_| queue.send(&actor, vec![0.1; chunk]))
+            .collect::<Vec<_>>();
+
+        assert!(sends[..10].iter().all(|s| *s == RecorderSend::Sent));
+        assert_eq!(sends[10], RecorderSend::Dropped { first: true });
+        assert!(sends[11..]
+            .iter()
+            .all(|s| *s == RecorderSend::Dropped { first: false }));
+        assert_eq!(queue.queued_samples(), SAMPLE_RATE as u64);
+        assert_eq!(queue.take_dropped(), 10 * chunk as u64);
+
+        // Once the writer catches up, audio goes through again.
+        resume.add_permits(11);
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while queue.queued_samples() > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(queue.send(&actor, vec![0.1; chunk]), RecorderSend::Sent);
+
+        actor.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+        assert_eq!(queue.queued_samples(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pre_roll_is_prepended() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        let sine = |amplitude: f32| {
+            (0..SAMPLE_RATE)
+                .map(|i| {
+                    let t = i as f32 / SAMPLE_RATE as f32;
+                    (t * 440.0 * 2.0 * std::f32::consts::PI).sin() * amplitude
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let (actor, handle) = Actor::spawn(
+            None,
+            RecorderActor,
+            RecArgs {
+                pre_roll: sine(0.5),
+                ..RecArgs::for_test(&app_dir, "session")
+            },
+        )
+        .await
+        .unwrap();
+
+        actor.cast(RecMsg::Audio(sine(0.1))).unwrap();
+        actor.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+
+        let dir = app_dir.join("session");
+        let ogg_path = dir.join("audio.ogg");
+        let duration_ms = segment_duration_ms(&ogg_path).unwrap();
+        assert!(duration_ms.abs_diff(2000) < 100, "{}", duration_ms);
+
+        let wav_path = dir.join("roundtrip.wav");
+        RecorderActor::ogg_to_wav(&ogg_path, &wav_path, hound::SampleFormat::Float, 32)
+            .await
+            .unwrap();
+        let samples = read_f32_samples(hound::WavReader::open(&wav_path).unwrap()).unwrap();
+
+        let peak = |s: &[f32]| s.iter().fold(0.0_f32, |acc, s| acc.max(s.abs()));
+        let window = SAMPLE_RATE as usize / 4;
+        // The louder pre-roll comes first, followed by the session's own audio.
+        let pre_roll_peak = peak(&samples[window..2 * window]);
+        let session_peak = peak(&samples[5 * window..6 * window]);
+        assert!(pre_roll_peak > 0.3, "{}", pre_roll_peak);
+        assert!(session_peak < 0.2, "{}", session_peak);
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recording_meta_sidecar() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        let (actor, handle) =
+            Actor::spawn(None, RecorderActor, RecArgs::for_test(&app_dir, "session"))
+                .await
+                .unwrap();
+
+        for _ in 0..2 {
+            actor
+                .cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]))
+                .unwrap();
+        }
+        actor.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+
+        let word = |text: &str, speaker| owhisper_interface::Word2 {
+            text: text.to_string(),
+            speaker: Some(speaker),
+            kind: owhisper_interface::WordKind::classify(text),
+            ..Default::default()
+        };
+        let me = owhisper_interface::SpeakerIdentity::Assigned {
+            id: "human".to_string(),
+            label: "Alice".to_string(),
+        };
+        let other = owhisper_interface::SpeakerIdentity::Unassigned { index: 1 };
+
+        let started_at = chrono::Utc::now() - chrono::Duration::seconds(2);
+        let session = hypr_db_user::Session {
+            id: "session".to_string(),
+            created_at: started_at,
+            visited_at: started_at,
+            user_id: "user".to_string(),
+            title: "Weekly sync".to_string(),
+            words: vec![
+                word("hello", me.clone()),
+                word("hi", other.clone()),
+                word("[laughter]", other),
+                word("bye", me),
+            ],
+            record_start: Some(started_at),
+            record_end: Some(chrono::Utc::now()),
+            ..hypr_db_user::Session::default()
+        };
+
+        let dir = app_dir.join("session");
+        let meta = crate::RecordingMeta::from_session(&session, recorded_duration_ms(&dir));
+        write_recording_meta(&dir, &meta).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.join("audio.meta.json")).unwrap()).unwrap();
+        assert_eq!(json["session_id"], "session");
+        assert_eq!(json["title"], "Weekly sync");
+        assert_eq!(json["sample_rate"], SAMPLE_RATE);
+        assert_eq!(json["speakers"], serde_json::json!(["Alice", "Speaker 1"]));
+        assert_eq!(json["word_count"], 3);
+        assert!(json["started_at"].is_string());
+        assert!(json["ended_at"].is_string());
+        assert!(json["duration_ms"].as_u64().unwrap().abs_diff(2000) < 100);
+
+        assert_eq!(read_recording_meta(&dir), Some(meta));
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_recording_duration_finalizes() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        let (actor, handle) = Actor::spawn(
+            None,
+            RecorderActor,
+            RecArgs {
+                max_recording_seconds: Some(2),
+                ..RecArgs::for_test(&app_dir, "session")
+            },
+        )
+        .await
+        .unwrap();
+
+        for _ in 0..3 {
+            let _ = actor.cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]));
+        }
+
+        // Stops on its own, without being asked to.
+        tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let dir = app_dir.join("session");
+        assert!(dir.join("audio.ogg").exists());
+        assert!(!dir.join("audio.wav").exists());
+
+        let duration_ms = segment_duration_ms(&dir.join("audio.ogg")).unwrap();
+        assert!(duration_ms.abs_diff(2000) < 100, "{}", duration_ms);
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_recording_duration_spans_restarts() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let samples_received = Arc::new(AtomicU64::new(0));
+
+        let spawn = || {
+            Actor::spawn(
+                None,
+                RecorderActor,
+                RecArgs {
+                    max_recording_seconds: Some(2),
+                    samples_received: samples_received.clone(),
+                    ..RecArgs::for_test(&app_dir, "session")
+                },
+            )
+        };
+
+        // Paused after a second, as the session does by stopping the recorder.
+        let (actor, _) = spawn().await.unwrap();
+        let _ = actor.cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]));
+        actor.drain_and_wait(None).await.unwrap();
+
+        let (actor, handle) = spawn().await.unwrap();
+        for _ in 0..3 {
+            let _ = actor.cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize]));
+        }
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let duration_ms = segment_duration_ms(&app_dir.join("session").join("audio.ogg")).unwrap();
+        assert!(duration_ms.abs_diff(2000) < 100, "{}", duration_ms);
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[test]
+    fn test_unavailable_recordings_dir_falls_back() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&root).unwrap();
+
+        // A directory can't be created beneath a regular file.
+        let blocker = root.join("not_a_dir");
+        std::fs::write(&blocker, b"").unwrap();
+
+        let default_dir = root.join("app_data");
+        let (recordings_dir, err) =
+            resolve_recordings_dir(Some(&blocker.join("recordings")), &default_dir);
+
+        assert!(err.is_some());
+        assert_eq!(recordings_dir, default_dir);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[derive(Default)]
+    struct IoCounter {
+        bytes_read: std::cell::Cell<usize>,
+        bytes_written: std::cell::Cell<usize>,
+        half_input: usize,
+        // Bytes of OGG written by the time half of the WAV had been read.
+        written_at_half_read: std::cell::Cell<Option<usize>>,
+    }
+
+    struct CountingReader<'a, R> {
+        inner: R,
+        counter: &'a IoCounter,
+    }
+
+    impl<R: std::io::Read> std::io::Read for CountingReader<'_, R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            let c = self.counter;
+
+            c.bytes_read.set(c.bytes_read.get() + n);
+            if c.written_at_half_read.get().is_none() && c.bytes_read.get() >= c.half_input {
+                c.written_at_half_read.set(Some(c.bytes_written.get()));
+            }
+            Ok(n)
+        }
+    }
+
+    struct CountingWriter<'a> {
+        counter: &'a IoCounter,
+    }
+
+    impl std::io::Write for CountingWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let c = self.counter;
+            c.bytes_written.set(c.bytes_written.get() + buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_transcode_is_streaming() {
+        const SECONDS: u32 = 120;
+
+        let mut wav = std::io::Cursor::new(Vec::new());
+        {
+            let spec = hound::WavSpec {
+                channels: 1,
+                sample_rate: SAMPLE_RATE,
+                bits_per_sample: BITS_PER_SAMPLE,
+                sample_format: hound::SampleFormat::Float,
+            };
+            let mut writer = hound::WavWriter::new(&mut wav, spec).unwrap();
+            for i in 0..SAMPLE_RATE * SECONDS {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let s = (t * 220.0 * 2.0 * std::f32::consts::PI).sin() * 0.3;
+                writer.write_sample(s).unwrap();
+            }
+            writer.finalize().unwrap();
+        }
+        let wav = wav.into_inner();
+
+        let counter = IoCounter {
+            half_input: wav.len() / 2,
+            ..Default::default()
+        };
+        let reader = hound::WavReader::new(CountingReader {
+            inner: wav.as_slice(),
+            counter: &counter,
+        })
+        .unwrap();
+
+        transcode_wav_to_ogg(reader, CountingWriter { counter: &counter }, 0.7).unwrap();
+
+        let total_written = counter.bytes_written.get();
+        assert_eq!(counter.bytes_read.get(), wav.len());
+        assert!(total_written > 0);
+
+        // A buffering implementation would have written nothing but the headers
+        // by the time half of the input was consumed.
+        let written_at_half_read = counter.written_at_half_read.get().unwrap();
+        assert!(
+            written_at_half_read > total_written / 4,
+            "{} of {} bytes written at half input",
+            written_at_half_read,
+            total_written
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rotate_by_seconds() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let session_id = "session".to_string();
+
+        let (actor, handle) = Actor::spawn(
+            None,
+            RecorderActor,
+            RecArgs {
+                prefs: RecordingPrefs {
+                    rotate: Some(RotatePolicy::BySeconds(1)),
+                    ..Default::default()
+                },
+                ..RecArgs::for_test(&app_dir, &session_id)
+            },
+        )
+        .await
+        .unwrap();
+
+        for _ in 0..5 {
+            actor
+                .cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize / 2]))
+                .unwrap();
+        }
+
+        actor.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+
+        let segments = recording_segments(app_dir.join(&session_id));
+        let names = segments
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec!["audio.000.ogg", "audio.001.ogg", "audio.002.ogg"]
+        );
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotate_by_bytes_counts_output_file() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        // 2.5s of audio is 160KB as WAV, but compresses to a fraction of the limit as Ogg.
+        for (session_id, format) in [("wav", RecordingFormat::Wav), ("ogg", RecordingFormat::Ogg)] {
+            let (actor, handle) = Actor::spawn(
+                None,
+                RecorderActor,
+                RecArgs {
+                    prefs: RecordingPrefs {
+                        format,
+                        rotate: Some(RotatePolicy::ByBytes(48_000)),
+                        ..Default::default()
+                    },
+                    ..RecArgs::for_test(&app_dir, session_id)
+                },
+            )
+            .await
+            .unwrap();
+
+            for _ in 0..5 {
+                actor
+                    .cast(RecMsg::Audio(vec![0.1; SAMPLE_RATE as usize / 2]))
+                    .unwrap();
+            }
+            actor.drain_and_wait(None).await.unwrap();
+            handle.await.unwrap();
+        }
+
+        let names = |session_id: &str| {
+            recording_segments(app_dir.join(session_id))
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names("wav"),
+            vec!["audio.000.wav", "audio.001.wav", "audio.002.wav"]
+        );
+        assert_eq!(names("ogg"), vec!["audio.000.ogg"]);
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ogg_size_probe_matches_output() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let samples = (0..SAMPLE_RATE * 3)
+            .map(|i| (i as f32 / SAMPLE_RATE as f32 * 440.0 * std::f32::consts::TAU).sin() * 0.3)
+            .collect::<Vec<_>>();
+        let quality = RecordingPrefs::default().quality;
+
+        let probe = OggSizeProbe::spawn(quality);
+        for chunk in samples.chunks(SAMPLE_RATE as usize / 10) {
+            probe.write(chunk);
+        }
+        let probed = probe.finish();
+
+        let wav_path = dir.join("audio.wav");
+        let ogg_path = dir.join("audio.ogg");
+        let mut writer = RecorderActor::open_writer(&wav_path).unwrap();
+        for s in &samples {
+            writer.write_sample(*s).unwrap();
+        }
+        writer.finalize().unwrap();
+        RecorderActor::wav_to_ogg(&wav_path, &ogg_path, quality)
+            .await
+            .unwrap();
+
+        let written = std::fs::metadata(&ogg_path).unwrap().len();
+        assert!(
+            probed.abs_diff(written) <= written / 100,
+            "{} != {}",
+            probed,
+            written
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_vad_gate_skips_long_silence() {
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let session_id = "session".to_string();
+
+        let (actor, handle) = Actor::spawn(
+            None,
+            RecorderActor,
+            RecArgs {
+                vad_gate: true,
+                ..RecArgs::for_test(&app_dir, &session_id)
+            },
+        )
+        .await
+        .unwrap();
+
+        // 3 x (2s speech, 5s silence), in 100ms chunks.
+        let chunk = SAMPLE_RATE as usize / 10;
+        for _ in 0..3 {
+            actor.cast(RecMsg::Vad(true)).unwrap();
+            for _ in 0..20 {
+                actor.cast(RecMsg::Audio(vec![0.1; chunk])).unwrap();
+            }
+            actor.cast(RecMsg::Vad(false)).unwrap();
+            for _ in 0..50 {
+                actor.cast(RecMsg::Audio(vec![0.0; chunk])).unwrap();
+            }
+        }
+
+        actor.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+
+        let dir = app_dir.join(&session_id);
+        let wav_path = dir.join("decoded.wav");
+        RecorderActor::ogg_to_wav(
+            &dir.join("audio.ogg"),
+            &wav_path,
+            hound::SampleFormat::Float,
+            BITS_PER_SAMPLE,
+        )
+        .await
+        .unwrap();
+
+        let recorded = hound::WavReader::open(&wav_path).unwrap().duration() as u64;
+        let speech = 6 * SAMPLE_RATE as u64;
+        let total = 21 * SAMPLE_RATE as u64;
+        let overhead = 3 * (VAD_GATE_HANGOVER_SAMPLES + VAD_GATE_PRE_ROLL_SAMPLES as u64);
+
+        assert!(recorded >= speech, "{} < {}", recorded, speech);
+        assert!(
+            recorded <= speech + overhead + SAMPLE_RATE as u64 / 10,
+            "{} > {}",
+            recorded,
+            speech + overhead
+        );
+
+        // Recorded audio plus skipped gaps must add up to the original timeline.
+        let skipped: u64 = std::fs::read_to_string(dir.join("audio.gaps.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["skipped_samples"]
+                    .as_u64()
+                    .unwrap()
+            })
+            .sum();
+        assert!(
+            (recorded + skipped).abs_diff(total) < SAMPLE_RATE as u64 / 10,
+            "{} + {} != {}",
+            recorded,
+            skipped,
+            total
+        );
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_recordings() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("audio.000.ogg"), b"").unwrap();
+        std::fs::write(dir.join("audio.001.ogg"), b"").unwrap();
+        std::fs::write(dir.join("audio.gaps.jsonl"), b"").unwrap();
+
+        remove_recordings(&dir).unwrap();
+        assert!(!dir.exists());
+
+        // Already gone, so this is a no-op.
+        remove_recordings(&dir).unwrap();
+    }
+}
@@ -1,18 +1,27 @@
+use std::collections::HashMap;
+use std::future::Future;
+
 use tauri::Manager;
 use tauri_specta::Event;
 
 use ractor::{
     call_t, concurrency, registry, Actor, ActorCell, ActorName, ActorProcessingErr, ActorRef,
-    RpcReplyPort, SupervisionEvent,
+    ActorStatus, RpcReplyPort, SupervisionEvent,
 };
 use tokio_util::sync::CancellationToken;
 
 use crate::{
     actors::{
-        ListenerActor, ListenerArgs, ListenerMsg, ListenerState, ProcArgs, ProcMsg, ProcessorActor,
-        RecArgs, RecMsg, RecorderActor, SourceActor, SourceArgs, SourceMsg,
+        plain_transcript, recorded_duration_ms, recording_fingerprint, recording_segments,
+        resolve_recordings_dir, write_recording_meta, ListenerActor, ListenerArgs, ListenerMsg,
+        ListenerState, ProcArgs, ProcMsg, ProcessorActor, RecArgs, RecMsg, RecorderActor,
+        RecorderQueue, SourceActor, SourceArgs, SourceMsg, DEFAULT_DC_BLOCKER_CUTOFF_HZ,
+        DEFAULT_LISTEN_STREAM_TIMEOUT, DEFAULT_MUTE_RAMP_MS, DEFAULT_PARTIAL_FLUSH_INTERVAL,
+        MAX_RECORDING_DURATION_REASON, RECORDER_QUEUE_SECONDS,
     },
-    SessionEvent,
+    disk::{DiskGuard, DEFAULT_MIN_FREE_DISK_MB, LOW_DISK_SPACE_REASON},
+    word_patch::WordPatcher,
+    ActorStatusMap, Channel, ChildStatus, RecordingMeta, SessionEvent, TranscriptSnapshot,
 };
 
 #[derive(Debug)]
@@ -22,12 +31,34 @@ pub enum SessionMsg {
     GetMicMute(RpcReplyPort<bool>),
     GetSpeakerMute(RpcReplyPort<bool>),
     GetMicDeviceName(RpcReplyPort<Option<String>>),
-    ChangeMicDevice(Option<String>),
+    // The device and whether it is pinned.
+    ChangeMicDevice(Option<String>, bool),
+    // The pinned mic was disconnected, so the source captures the speaker with a silent mic.
+    MicDeviceLost(String),
+    GetTranscriptSnapshot(RpcReplyPort<TranscriptSnapshot>),
+    GetPlainTranscript(RpcReplyPort<String>),
+    RecordingLimitApproaching { remaining_seconds: u64 },
+    LowDiskSpace { available_bytes: u64 },
+    // Closes the STT stream, while the sources and the recorder keep running.
+    PauseTranscription,
+    ResumeTranscription,
+    // From 0.0 to 2.0, applied on top of mute.
+    SetChannelGain { channel: Channel, gain: f32 },
+    // Reconnects the listener, keeping its partial words.
+    SetLanguages(Vec<hypr_language::Language>),
+    // Only applies to the languages punctuation restoration is configured for.
+    SetRestorePunctuation(bool),
+    // Which child actors are alive, for diagnosing why a session stopped.
+    GetActorStatus(RpcReplyPort<ActorStatusMap>),
 }
 
 pub struct SessionArgs {
     pub app: tauri::AppHandle,
     pub session_id: String,
+    // Cancelling it while starting aborts the setup; afterwards, it stops the audio sources.
+    pub token: CancellationToken,
+    // Captured before the session started; prepended to the recording, if there is one.
+    pub pre_roll: Vec<f32>,
 }
 
 pub struct SessionState {
@@ -37,13 +68,215 @@ pub struct SessionState {
     onboarding: bool,
     token: CancellationToken,
     record_enabled: bool,
+    recordings_dir: std::path::PathBuf,
+    // This session's recordings, within `recordings_dir`.
+    session_dir: std::path::PathBuf,
+    recording_offset_ms: Option<u64>,
+    max_recording_seconds: Option<u64>,
+    // Audio recorded so far, kept across pauses so the limit doesn't start over on resume.
+    recorded_samples: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    transcript_sink: Option<std::path::PathBuf>,
+    incremental_word_events: bool,
+    stt_fallbacks: Vec<tauri_plugin_local_stt::Connection>,
+    stt_sample_rate: Option<u32>,
+    stt_block_on_backpressure: bool,
+    stt_reconnect_grace: Option<std::time::Duration>,
+    stt_aggregation_window: Option<std::time::Duration>,
+    stt_price_per_minute: Option<f64>,
+    stt_opus: bool,
+    stt_budget_cap: Option<f64>,
+    redemption_overrides: HashMap<String, u32>,
+    fallback_language: Option<hypr_language::Language>,
+    // Per-session switch on top of `punctuation_languages`.
+    restore_punctuation: bool,
+    punctuation_languages: Vec<hypr_language::Language>,
+    disk_guard: Option<DiskGuard>,
+    recording_prefs: crate::RecordingPrefs,
+    recorder_queue: RecorderQueue,
+    mute_ramp_ms: u64,
+    // Set while the listener is stopped on purpose, so it is not restarted.
+    transcription_paused: bool,
+    // How each child last stopped.
+    child_exits: HashMap<ChildKind, String>,
+}
+
+impl SessionState {
+    // The transcript is in the session's first language.
+    fn restores_punctuation(&self) -> bool {
+        self.restore_punctuation
+            && self
+                .languages
+                .first()
+                .is_some_and(|l| self.punctuation_languages.contains(l))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ChildKind {
+    Source,
+    Processor,
+    Listener,
+    Recorder,
+}
+
+impl ChildKind {
+    const ALL: [ChildKind; 4] = [
+        ChildKind::Source,
+        ChildKind::Processor,
+        ChildKind::Listener,
+        ChildKind::Recorder,
+    ];
+
+    // Its key in `ActorStatusMap`.
+    fn key(self) -> &'static str {
+        match self {
+            ChildKind::Source => "source",
+            ChildKind::Processor => "processor",
+            ChildKind::Listener => "listener",
+            ChildKind::Recorder => "recorder",
+        }
+    }
+
+    fn actor_name(self, session_id: &str) -> ActorName {
+        match self {
+            ChildKind::Source => SourceActor::name(session_id),
+            ChildKind::Processor => ProcessorActor::name(session_id),
+            ChildKind::Listener => ListenerActor::name(session_id),
+            ChildKind::Recorder => RecorderActor::name(session_id),
+        }
+    }
+}
+
+// The children a session starts with, besides the source and processor.
+fn optional_children(record_enabled: bool, transcribe_live: bool) -> Vec<ChildKind> {
+    let mut children = vec![];
+    if transcribe_live {
+        children.push(ChildKind::Listener);
+    }
+    if record_enabled {
+        children.push(ChildKind::Recorder);
+    }
+    children
+}
+
+trait SpawnChild {
+    async fn spawn_child(&mut self, kind: ChildKind) -> Result<(), ActorProcessingErr>;
+}
+
+struct SessionChildren<'a> {
+    supervisor: ActorCell,
+    state: &'a SessionState,
+    pre_roll: Vec<f32>,
+}
+
+impl SpawnChild for SessionChildren<'_> {
+    async fn spawn_child(&mut self, kind: ChildKind) -> Result<(), ActorProcessingErr> {
+        let supervisor = self.supervisor.clone();
+        match kind {
+            ChildKind::Processor => {
+                SessionActor::start_processor(supervisor, self.state).await?;
+            }
+            ChildKind::Source => {
+                SessionActor::start_source(supervisor, self.state).await?;
+            }
+            ChildKind::Listener => {
+                SessionActor::start_listener(supervisor, self.state, None).await?;
+            }
+            ChildKind::Recorder => {
+                let pre_roll = std::mem::take(&mut self.pre_roll);
+                SessionActor::start_recorder(supervisor, self.state, pre_roll).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn child_kind(session_id: &str, actor_name: &str) -> Option<ChildKind> {
+    ChildKind::ALL
+        .into_iter()
+        .find(|kind| kind.actor_name(session_id) == actor_name)
+}
+
+// Children are looked up in the registry, which they leave once stopped.
+fn actor_status(session_id: &str, child_exits: &HashMap<ChildKind, String>) -> ActorStatusMap {
+    ChildKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let status = match registry::where_is(kind.actor_name(session_id)) {
+                Some(cell) => ChildStatus {
+                    alive: true,
+                    state: Some(format!("{:?}", cell.get_status())),
+                },
+                None => ChildStatus {
+                    alive: false,
+                    state: child_exits.get(&kind).cloned(),
+                },
+            };
+            (kind.key().to_string(), status)
+        })
+        .collect()
+}
+
+// What to do once one of the session's actors has terminated.
+#[derive(Debug, PartialEq, Eq)]
+enum ChildExit {
+    RestartListener,
+    Ignore,
+    Stop,
+    StopAndWait,
+}
+
+fn on_child_terminated(
+    session_id: &str,
+    actor_name: &str,
+    reason: Option<&str>,
+    transcription_paused: bool,
+) -> ChildExit {
+    if actor_name == ListenerActor::name(session_id) {
+        if transcription_paused {
+            ChildExit::Ignore
+        } else {
+            ChildExit::RestartListener
+        }
+    } else if reason == Some(MAX_RECORDING_DURATION_REASON) {
+        // `post_stop` finalizes the session like a regular stop.
+        ChildExit::Stop
+    } else {
+        ChildExit::StopAndWait
+    }
 }
 
 pub struct SessionActor;
 
+const SESSION_ACTOR_PREFIX: &str = "session:";
+
+// Settings save on every toggle; reconnecting once they settle is enough.
+const CONFIG_RELOAD_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Session start waits at most 1.5s (100ms, doubling) for a DB that is still initializing.
+const DB_RETRY_ATTEMPTS: usize = 5;
+const DB_RETRY_INITIAL_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
 impl SessionActor {
-    pub fn name() -> ActorName {
-        "session".into()
+    pub fn name(session_id: &str) -> ActorName {
+        format!("{}{}", SESSION_ACTOR_PREFIX, session_id)
+    }
+
+    // The most recently started session that is still running, if any. Actor ids count up as
+    // actors are spawned, and a session still in `pre_start` or already stopping isn't `Running`.
+    pub fn active() -> Option<ActorRef<SessionMsg>> {
+        registry::registered()
+            .into_iter()
+            .filter(|name| name.starts_with(SESSION_ACTOR_PREFIX))
+            .filter_map(registry::where_is)
+            .filter(|cell| matches!(cell.get_status(), ActorStatus::Running))
+            .max_by_key(|cell| cell.get_id().pid())
+            .map(Into::into)
+    }
+
+    pub fn is_recording(session_id: &str) -> bool {
+        registry::where_is(Self::name(session_id)).is_some()
+            && registry::where_is(RecorderActor::name(session_id)).is_some()
     }
 }
 
@@ -62,19 +295,142 @@ impl Actor for SessionActor {
         let session_id = args.session_id.clone();
         let onboarding_session_id = UserDatabase::onboarding_session_id();
         let onboarding = session_id == onboarding_session_id;
-        let user_id = args.app.db_user_id().await?.unwrap();
+        let cancellation_token = args.token;
 
-        let config = args.app.db_get_config(&user_id).await?;
+        let config = until_cancelled(
+            &cancellation_token,
+            load_config(|| args.app.db_user_id(), |id| args.app.db_get_config(id)),
+        )
+        .await?
+        .unwrap_or_else(|e| {
+            tracing::error!(error = ?e, "config_unavailable_using_defaults");
+            None
+        });
         let record_enabled = config
             .as_ref()
             .is_none_or(|c| c.general.save_recordings.unwrap_or(true));
+        // Otherwise the session starts as if transcription were paused, and resuming starts it.
+        let transcribe_live = config
+            .as_ref()
+            .is_none_or(|c| c.general.transcribe_live.unwrap_or(true));
+        let max_recording_seconds = config
+            .as_ref()
+            .and_then(|c| c.general.max_recording_seconds);
+        let transcript_sink = config
+            .as_ref()
+            .and_then(|c| c.general.transcript_sink_path.as_ref())
+            .filter(|path| !path.trim().is_empty())
+            .map(std::path::PathBuf::from);
+        let incremental_word_events = config
+            .as_ref()
+            .is_some_and(|c| c.general.incremental_word_events);
+        let stt_fallbacks = config
+            .as_ref()
+            .map(|c| {
+                c.ai.stt_fallbacks
+                    .iter()
+                    .map(|b| tauri_plugin_local_stt::Connection {
+                        model: b.model.clone(),
+                        base_url: b.base_url.clone(),
+                        api_key: b.api_key.clone(),
+                        sample_rate: b.sample_rate,
+                        price_per_minute: b.price_per_minute,
+                        opus: b.opus,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let stt_sample_rate = config.as_ref().and_then(|c| c.ai.stt_sample_rate);
+        let stt_block_on_backpressure = config
+            .as_ref()
+            .is_some_and(|c| c.ai.stt_block_on_backpressure);
+        let stt_reconnect_grace = config
+            .as_ref()
+            .and_then(|c| c.ai.stt_reconnect_grace_seconds)
+            .map(std::time::Duration::from_secs);
+        let stt_aggregation_window = config
+            .as_ref()
+            .and_then(|c| c.ai.stt_aggregation_window_ms)
+            .map(std::time::Duration::from_millis);
+        let stt_price_per_minute = config.as_ref().and_then(|c| c.ai.stt_price_per_minute);
+        let stt_opus = config.as_ref().is_some_and(|c| c.ai.stt_opus);
+        let stt_budget_cap = config.as_ref().and_then(|c| c.ai.stt_budget_cap);
+        let redemption_overrides = config
+            .as_ref()
+            .map(|c| c.ai.stt_redemption_time_ms.clone())
+            .unwrap_or_default();
+        let fallback_language = config
+            .as_ref()
+            .and_then(|c| c.general.fallback_language.clone());
+        let mute_ramp_ms = config
+            .as_ref()
+            .and_then(|c| c.general.mute_ramp_ms)
+            .unwrap_or(DEFAULT_MUTE_RAMP_MS);
+        let punctuation_languages = config
+            .as_ref()
+            .map(|c| c.general.restore_punctuation_languages.clone())
+            .unwrap_or_default();
         let languages = config.as_ref().map_or_else(
             || vec![hypr_language::ISO639::En.into()],
             |c| c.general.spoken_languages.clone(),
         );
-        let cancellation_token = CancellationToken::new();
 
-        if let Ok(Some(mut session)) = args.app.db_get_session(&args.session_id).await {
+        let recordings_dir = {
+            let default_dir = args.app.path().app_data_dir()?;
+            let requested = config
+                .as_ref()
+                .and_then(|c| c.general.recordings_dir.as_ref())
+                .filter(|dir| !dir.trim().is_empty())
+                .map(std::path::PathBuf::from);
+
+            let (dir, err) = resolve_recordings_dir(requested.as_deref(), &default_dir);
+            if let (Some(requested), Some(err)) = (requested, err) {
+                tracing::warn!(requested = ?requested, error = ?err, "recordings_dir_unavailable");
+
+                SessionEvent::RecordingsDirFallback {
+                    requested: requested.to_string_lossy().to_string(),
+                    fallback: dir.to_string_lossy().to_string(),
+                }
+                .emit(&args.app)?;
+            }
+
+            dir
+        };
+        let session_dir = hypr_recording::session_dir(&recordings_dir, &session_id)
+            .ok_or_else(|| crate::Error::InvalidSessionId(session_id.clone()))?;
+
+        let min_free_disk_mb = config
+            .as_ref()
+            .and_then(|c| c.general.min_free_disk_mb)
+            .unwrap_or(DEFAULT_MIN_FREE_DISK_MB);
+        let disk_guard =
+            (record_enabled && min_free_disk_mb > 0).then(|| DiskGuard::new(min_free_disk_mb));
+        if let Some(available_bytes) = disk_guard
+            .as_ref()
+            .and_then(|guard| guard.low_space(&recordings_dir))
+        {
+            SessionEvent::LowDiskSpace { available_bytes }.emit(&args.app)?;
+            return Err(format!(
+                "{}: {} bytes available",
+                LOW_DISK_SPACE_REASON, available_bytes
+            )
+            .into());
+        }
+
+        let recording_prefs = {
+            use crate::ListenerPluginExt;
+            args.app.get_recording_prefs().unwrap_or_else(|e| {
+                tracing::warn!(error = ?e, "recording_prefs_unavailable");
+                Default::default()
+            })
+        };
+
+        if let Ok(Some(mut session)) = until_cancelled(
+            &cancellation_token,
+            args.app.db_get_session(&args.session_id),
+        )
+        .await?
+        {
             session.record_start = Some(chrono::Utc::now());
             let _ = args.app.db_upsert_session(session).await;
         }
@@ -84,6 +440,32 @@ impl Actor for SessionActor {
             let _ = args.app.set_start_disabled(true);
         }
 
+        // New audio is appended to what was already recorded for this session,
+        // starting with the pre-roll, which was captured before now.
+        let pre_roll = if record_enabled {
+            args.pre_roll
+        } else {
+            vec![]
+        };
+        let pre_roll_ms = pre_roll.len() as u64 * 1000 / crate::pre_roll::SAMPLE_RATE as u64;
+        // Reads every earlier segment's headers, which is file IO the actor shouldn't wait on.
+        let recorded_ms = if record_enabled {
+            let session_dir = session_dir.clone();
+            tokio::task::spawn_blocking(move || recorded_duration_ms(session_dir)).await?
+        } else {
+            0
+        };
+        let recording_offset_ms = record_enabled.then(|| {
+            let now_ms = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64;
+
+            now_ms
+                .saturating_sub(recorded_ms)
+                .saturating_sub(pre_roll_ms)
+        });
+
         let state = SessionState {
             app: args.app,
             session_id,
@@ -91,11 +473,77 @@ impl Actor for SessionActor {
             onboarding,
             token: cancellation_token,
             record_enabled,
+            recordings_dir,
+            session_dir,
+            recording_offset_ms,
+            max_recording_seconds,
+            recorded_samples: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(
+                recorded_ms * crate::pre_roll::SAMPLE_RATE as u64 / 1000,
+            )),
+            transcript_sink,
+            incremental_word_events,
+            stt_fallbacks,
+            stt_sample_rate,
+            stt_block_on_backpressure,
+            stt_reconnect_grace,
+            stt_aggregation_window,
+            stt_price_per_minute,
+            stt_opus,
+            stt_budget_cap,
+            redemption_overrides,
+            fallback_language,
+            restore_punctuation: true,
+            punctuation_languages,
+            disk_guard,
+            recording_prefs,
+            recorder_queue: RecorderQueue::new(RECORDER_QUEUE_SECONDS),
+            mute_ramp_ms,
+            transcription_paused: !transcribe_live,
+            child_exits: HashMap::new(),
         };
 
         {
-            let c = myself.get_cell();
-            Self::start_all_actors(c, &state).await?;
+            let mut children = SessionChildren {
+                supervisor: myself.get_cell(),
+                state: &state,
+                pre_roll,
+            };
+            let started = until_cancelled(
+                &state.token,
+                Self::start_all_actors(
+                    &mut children,
+                    state.record_enabled,
+                    !state.transcription_paused,
+                ),
+            )
+            .await;
+
+            // Nothing will stop the actors started so far if `pre_start` fails.
+            if let Err(e) = started.and_then(|r| r) {
+                tracing::info!(error = ?e, "session_start_aborted");
+                Self::stop_all_actors(&state.session_id).await;
+
+                use tauri_plugin_tray::TrayPluginExt;
+                let _ = state.app.set_start_disabled(false);
+
+                return Err(e);
+            }
+        }
+
+        {
+            let token = state.token.clone();
+            let configs = state.app.db_subscribe_config();
+            let languages = state.languages.clone();
+
+            tokio::spawn(async move {
+                let _ = until_cancelled(
+                    &token,
+                    watch_spoken_languages(configs, languages, CONFIG_RELOAD_DEBOUNCE, |l| {
+                        let _ = myself.cast(SessionMsg::SetLanguages(l));
+                    }),
+                )
+                .await;
+            });
         }
 
         SessionEvent::RunningActive {}.emit(&state.app).unwrap();
@@ -104,13 +552,13 @@ impl Actor for SessionActor {
 
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
+        myself: ActorRef<Self::Msg>,
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match message {
             SessionMsg::SetMicMute(muted) => {
-                if let Some(cell) = registry::where_is(SourceActor::name()) {
+                if let Some(cell) = registry::where_is(SourceActor::name(&state.session_id)) {
                     let actor: ActorRef<SourceMsg> = cell.into();
                     actor.cast(SourceMsg::SetMicMute(muted))?;
                 }
@@ -118,16 +566,25 @@ impl Actor for SessionActor {
             }
 
             SessionMsg::SetSpeakerMute(muted) => {
-                if let Some(cell) = registry::where_is(SourceActor::name()) {
+                if let Some(cell) = registry::where_is(SourceActor::name(&state.session_id)) {
                     let actor: ActorRef<SourceMsg> = cell.into();
                     actor.cast(SourceMsg::SetSpkMute(muted))?;
                 }
                 SessionEvent::SpeakerMuted { value: muted }.emit(&state.app)?;
             }
 
+            SessionMsg::SetChannelGain { channel, gain } => {
+                if let Some(cell) = registry::where_is(SourceActor::name(&state.session_id)) {
+                    let actor: ActorRef<SourceMsg> = cell.into();
+                    actor.cast(SourceMsg::SetGain(channel, gain))?;
+                }
+            }
+
             SessionMsg::GetMicDeviceName(reply) => {
                 if !reply.is_closed() {
-                    let device_name = if let Some(cell) = registry::where_is(SourceActor::name()) {
+                    let device_name = if let Some(cell) =
+                        registry::where_is(SourceActor::name(&state.session_id))
+                    {
                         let actor: ActorRef<SourceMsg> = cell.into();
                         call_t!(actor, SourceMsg::GetMicDevice, 100).unwrap_or(None)
                     } else {
@@ -138,36 +595,153 @@ impl Actor for SessionActor {
                 }
             }
 
-            SessionMsg::GetMicMute(reply) => {
-                let muted = if let Some(cell) = registry::where_is(SourceActor::name()) {
-                    let actor: ActorRef<SourceMsg> = cell.into();
-                    call_t!(actor, SourceMsg::GetMicMute, 100)?
+            SessionMsg::GetTranscriptSnapshot(reply) => {
+                use tauri_plugin_db::DatabasePluginExt;
+
+                let partial_words = if let Some(cell) =
+                    registry::where_is(ListenerActor::name(&state.session_id))
+                {
+                    let actor: ActorRef<ListenerMsg> = cell.into();
+                    call_t!(actor, ListenerMsg::GetPartials, 100).unwrap_or_default()
                 } else {
-                    false
+                    HashMap::new()
                 };
 
+                let final_words = state
+                    .app
+                    .db_get_session(&state.session_id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|s| s.words)
+                    .unwrap_or_default();
+
+                if !reply.is_closed() {
+                    let _ = reply.send(TranscriptSnapshot {
+                        partial_words,
+                        final_words,
+                    });
+                }
+            }
+
+            SessionMsg::GetActorStatus(reply) => {
+                if !reply.is_closed() {
+                    let _ = reply.send(actor_status(&state.session_id, &state.child_exits));
+                }
+            }
+
+            SessionMsg::GetPlainTranscript(reply) => {
+                use tauri_plugin_db::DatabasePluginExt;
+
+                let text = match registry::where_is(ListenerActor::name(&state.session_id)) {
+                    Some(cell) => {
+                        let actor: ActorRef<ListenerMsg> = cell.into();
+                        call_t!(actor, ListenerMsg::GetPlainTranscript, 500).ok()
+                    }
+                    None => None,
+                };
+
+                // Without a listener, e.g. while transcription is paused, only the finals are left.
+                let text = match text {
+                    Some(text) => text,
+                    None => {
+                        let final_words = state
+                            .app
+                            .db_get_session(&state.session_id)
+                            .await
+                            .ok()
+                            .flatten()
+                            .map(|s| s.words)
+                            .unwrap_or_default();
+                        plain_transcript(&final_words, HashMap::new())
+                    }
+                };
+
+                if !reply.is_closed() {
+                    let _ = reply.send(text);
+                }
+            }
+
+            SessionMsg::GetMicMute(reply) => {
+                let muted =
+                    if let Some(cell) = registry::where_is(SourceActor::name(&state.session_id)) {
+                        let actor: ActorRef<SourceMsg> = cell.into();
+                        call_t!(actor, SourceMsg::GetMicMute, 100)?
+                    } else {
+                        false
+                    };
+
                 if !reply.is_closed() {
                     let _ = reply.send(muted);
                 }
             }
 
             SessionMsg::GetSpeakerMute(reply) => {
-                let muted = if let Some(cell) = registry::where_is(SourceActor::name()) {
-                    let actor: ActorRef<SourceMsg> = cell.into();
-                    call_t!(actor, SourceMsg::GetSpkMute, 100)?
-                } else {
-                    false
-                };
+                let muted =
+                    if let Some(cell) = registry::where_is(SourceActor::name(&state.session_id)) {
+                        let actor: ActorRef<SourceMsg> = cell.into();
+                        call_t!(actor, SourceMsg::GetSpkMute, 100)?
+                    } else {
+                        false
+                    };
 
                 if !reply.is_closed() {
                     let _ = reply.send(muted);
                 }
             }
 
-            SessionMsg::ChangeMicDevice(device) => {
-                if let Some(cell) = registry::where_is(SourceActor::name()) {
+            SessionMsg::RecordingLimitApproaching { remaining_seconds } => {
+                SessionEvent::RecordingLimitApproaching { remaining_seconds }.emit(&state.app)?;
+            }
+            SessionMsg::LowDiskSpace { available_bytes } => {
+                SessionEvent::LowDiskSpace { available_bytes }.emit(&state.app)?;
+            }
+
+            SessionMsg::ChangeMicDevice(device, pinned) => {
+                if let Some(cell) = registry::where_is(SourceActor::name(&state.session_id)) {
                     let actor: ActorRef<SourceMsg> = cell.into();
-                    actor.cast(SourceMsg::SetMicDevice(device))?;
+                    actor.cast(SourceMsg::SetMicDevice { device, pinned })?;
+                }
+            }
+            SessionMsg::MicDeviceLost(device) => {
+                SessionEvent::MicDeviceUnavailable { device }.emit(&state.app)?;
+            }
+
+            // Without a listener, the processor only feeds the recorder.
+            SessionMsg::PauseTranscription => {
+                if !state.transcription_paused {
+                    state.transcription_paused = true;
+                    Self::stop_listener(&state.session_id).await;
+                }
+            }
+
+            // The supervisor restarts the stopped listener with the new languages.
+            SessionMsg::SetLanguages(languages) => {
+                if languages != state.languages {
+                    tracing::info!(languages = ?languages, "session_languages_changed");
+                    state.languages = languages;
+
+                    if !state.transcription_paused {
+                        Self::stop_listener(&state.session_id).await;
+                    }
+                }
+            }
+
+            SessionMsg::SetRestorePunctuation(enabled) => {
+                state.restore_punctuation = enabled;
+
+                if let Some(cell) = registry::where_is(ListenerActor::name(&state.session_id)) {
+                    let actor: ActorRef<ListenerMsg> = cell.into();
+                    actor.cast(ListenerMsg::SetRestorePunctuation(
+                        state.restores_punctuation(),
+                    ))?;
+                }
+            }
+
+            SessionMsg::ResumeTranscription => {
+                if state.transcription_paused {
+                    state.transcription_paused = false;
+                    Self::start_listener(myself.get_cell(), state, None).await?;
                 }
             }
         }
@@ -185,30 +759,54 @@ impl Actor for SessionActor {
             SupervisionEvent::ActorStarted(actor) => {
                 tracing::info!("{:?}_actor_started", actor.get_name());
             }
-            SupervisionEvent::ActorTerminated(actor, maybe_state, _) => {
+            SupervisionEvent::ActorTerminated(actor, maybe_state, reason) => {
                 let actor_name = actor
                     .get_name()
                     .map(|n| n.to_string())
                     .unwrap_or_else(|| "unknown".to_string());
 
-                if actor_name == ListenerActor::name() {
-                    let last_state: Option<ListenerState> =
-                        maybe_state.and_then(|mut s| s.take().ok());
-
-                    Self::start_listener(
-                        myself.get_cell(),
-                        state,
-                        last_state.map(|s| ListenerArgs {
-                            partial_words_by_channel: s.manager.partial_words_by_channel,
-                            ..s.args
-                        }),
-                    )
-                    .await?;
-                } else {
-                    let _ = myself.stop_and_wait(None, None).await;
+                if let Some(kind) = child_kind(&state.session_id, &actor_name) {
+                    let exit = reason.clone().unwrap_or_else(|| "stopped".to_string());
+                    state.child_exits.insert(kind, exit);
+                }
+
+                match on_child_terminated(
+                    &state.session_id,
+                    &actor_name,
+                    reason.as_deref(),
+                    state.transcription_paused,
+                ) {
+                    ChildExit::RestartListener => {
+                        let last_state: Option<ListenerState> =
+                            maybe_state.and_then(|mut s| s.take().ok());
+
+                        Self::start_listener(
+                            myself.get_cell(),
+                            state,
+                            last_state.map(|s| ListenerArgs {
+                                partial_words_by_channel: s.manager.partial_words_by_channel,
+                                final_tail_by_channel: s.manager.final_tail_by_channel,
+                                word_patcher: s.word_patcher,
+                                languages: state.languages.clone(),
+                                restore_punctuation: state.restores_punctuation(),
+                                ..s.args
+                            }),
+                        )
+                        .await?;
+                    }
+                    ChildExit::Ignore => {}
+                    ChildExit::Stop => myself.stop(reason),
+                    ChildExit::StopAndWait => {
+                        let _ = myself.stop_and_wait(None, None).await;
+                    }
+                }
+            }
+            SupervisionEvent::ActorFailed(actor, error) => {
+                let actor_name = actor.get_name().unwrap_or_default();
+                if let Some(kind) = child_kind(&state.session_id, &actor_name) {
+                    state.child_exits.insert(kind, format!("failed: {}", error));
                 }
             }
-            SupervisionEvent::ActorFailed(_, _) => {}
             _ => {}
         }
 
@@ -223,13 +821,44 @@ impl Actor for SessionActor {
         state.token.cancel();
 
         {
-            Self::stop_all_actors().await;
+            Self::stop_all_actors(&state.session_id).await;
         }
 
         use tauri_plugin_db::DatabasePluginExt;
 
         if let Ok(Some(mut session)) = state.app.db_get_session(&state.session_id).await {
             session.record_end = Some(chrono::Utc::now());
+
+            // The recorder has finalized by now, so the duration covers every segment.
+            let session_dir = state.session_dir.clone();
+            if state.record_enabled && !recording_segments(&session_dir).is_empty() {
+                // Decodes the whole recording and reads every segment's headers, so it is kept
+                // off the async runtime.
+                let read = tokio::task::spawn_blocking({
+                    let session_dir = session_dir.clone();
+                    move || {
+                        (
+                            recording_fingerprint(&session_dir),
+                            recorded_duration_ms(&session_dir),
+                        )
+                    }
+                })
+                .await;
+
+                match read {
+                    Ok((fingerprint, duration_ms)) => {
+                        let meta = RecordingMeta {
+                            fingerprint,
+                            ..RecordingMeta::from_session(&session, duration_ms)
+                        };
+                        if let Err(e) = write_recording_meta(&session_dir, &meta) {
+                            tracing::error!(error = ?e, "recording_meta_write_failed");
+                        }
+                    }
+                    Err(e) => tracing::error!(error = ?e, "recording_meta_read_failed"),
+                }
+            }
+
             let _ = state.app.db_upsert_session(session).await;
         }
 
@@ -251,25 +880,25 @@ impl Actor for SessionActor {
 
 impl SessionActor {
     async fn start_all_actors(
-        supervisor: ActorCell,
-        state: &SessionState,
+        children: &mut impl SpawnChild,
+        record_enabled: bool,
+        transcribe_live: bool,
     ) -> Result<(), ActorProcessingErr> {
-        Self::start_processor(supervisor.clone(), state).await?;
-        Self::start_source(supervisor.clone(), state).await?;
-        Self::start_listener(supervisor.clone(), state, None).await?;
-
-        if state.record_enabled {
-            Self::start_recorder(supervisor, state).await?;
+        for kind in [ChildKind::Processor, ChildKind::Source] {
+            children.spawn_child(kind).await?;
+        }
+        for kind in optional_children(record_enabled, transcribe_live) {
+            children.spawn_child(kind).await?;
         }
 
         Ok(())
     }
 
-    async fn stop_all_actors() {
-        Self::stop_processor().await;
-        Self::stop_source().await;
-        Self::stop_listener().await;
-        Self::stop_recorder().await;
+    async fn stop_all_actors(session_id: &str) {
+        Self::stop_processor(session_id).await;
+        Self::stop_source(session_id).await;
+        Self::stop_listener(session_id).await;
+        Self::stop_recorder(session_id).await;
     }
 
     async fn start_source(
@@ -277,12 +906,14 @@ impl SessionActor {
         state: &SessionState,
     ) -> Result<ActorRef<SourceMsg>, ActorProcessingErr> {
         let (ar, _) = Actor::spawn_linked(
-            Some(SourceActor::name()),
+            Some(SourceActor::name(&state.session_id)),
             SourceActor,
             SourceArgs {
+                session_id: state.session_id.clone(),
                 token: state.token.clone(),
                 mic_device: None,
                 onboarding: state.onboarding,
+                mute_ramp_ms: state.mute_ramp_ms,
             },
             supervisor,
         )
@@ -290,8 +921,8 @@ impl SessionActor {
         Ok(ar)
     }
 
-    async fn stop_source() {
-        if let Some(cell) = registry::where_is(SourceActor::name()) {
+    async fn stop_source(session_id: &str) {
+        if let Some(cell) = registry::where_is(SourceActor::name(session_id)) {
             let actor: ActorRef<SourceMsg> = cell.into();
             let _ = actor
                 .stop_and_wait(
@@ -307,10 +938,14 @@ impl SessionActor {
         state: &SessionState,
     ) -> Result<ActorRef<ProcMsg>, ActorProcessingErr> {
         let (ar, _) = Actor::spawn_linked(
-            Some(ProcessorActor::name()),
+            Some(ProcessorActor::name(&state.session_id)),
             ProcessorActor {},
             ProcArgs {
                 app: state.app.clone(),
+                session_id: state.session_id.clone(),
+                dc_blocker_cutoff_hz: Some(DEFAULT_DC_BLOCKER_CUTOFF_HZ),
+                recorder_queue: state.recorder_queue.clone(),
+                detect_speech: state.record_enabled && state.recording_prefs.skip_silence,
             },
             supervisor,
         )
@@ -318,8 +953,8 @@ impl SessionActor {
         Ok(ar)
     }
 
-    async fn stop_processor() {
-        if let Some(cell) = registry::where_is(ProcessorActor::name()) {
+    async fn stop_processor(session_id: &str) {
+        if let Some(cell) = registry::where_is(ProcessorActor::name(session_id)) {
             let actor: ActorRef<ProcMsg> = cell.into();
             let _ = actor
                 .stop_and_wait(
@@ -333,13 +968,21 @@ impl SessionActor {
     async fn start_recorder(
         supervisor: ActorCell,
         state: &SessionState,
+        pre_roll: Vec<f32>,
     ) -> Result<ActorRef<RecMsg>, ActorProcessingErr> {
         let (rec_ref, _) = Actor::spawn_linked(
-            Some(RecorderActor::name()),
+            Some(RecorderActor::name(&state.session_id)),
             RecorderActor,
             RecArgs {
-                app_dir: state.app.path().app_data_dir().unwrap(),
+                app_dir: state.recordings_dir.clone(),
                 session_id: state.session_id.clone(),
+                vad_gate: state.recording_prefs.skip_silence,
+                max_recording_seconds: state.max_recording_seconds,
+                samples_received: state.recorded_samples.clone(),
+                pre_roll,
+                disk_guard: state.disk_guard,
+                prefs: state.recording_prefs.clone(),
+                queue: Some(state.recorder_queue.clone()),
             },
             supervisor,
         )
@@ -347,8 +990,8 @@ impl SessionActor {
         Ok(rec_ref)
     }
 
-    async fn stop_recorder() {
-        if let Some(cell) = registry::where_is(RecorderActor::name()) {
+    async fn stop_recorder(session_id: &str) {
+        if let Some(cell) = registry::where_is(RecorderActor::name(session_id)) {
             let actor: ActorRef<RecMsg> = cell.into();
             let _ = actor
                 .stop_and_wait(
@@ -365,7 +1008,7 @@ impl SessionActor {
         listener_args: Option<ListenerArgs>,
     ) -> Result<ActorRef<ListenerMsg>, ActorProcessingErr> {
         let (listen_ref, _) = Actor::spawn_linked(
-            Some(ListenerActor::name()),
+            Some(ListenerActor::name(&session_state.session_id)),
             ListenerActor,
             listener_args.unwrap_or(ListenerArgs {
                 app: session_state.app.clone(),
@@ -373,6 +1016,26 @@ impl SessionActor {
                 languages: session_state.languages.clone(),
                 onboarding: session_state.onboarding,
                 partial_words_by_channel: Default::default(),
+                final_tail_by_channel: Default::default(),
+                recording_offset_ms: session_state.recording_offset_ms,
+                token: session_state.token.clone(),
+                stream_timeout: DEFAULT_LISTEN_STREAM_TIMEOUT,
+                partial_flush_interval: Some(DEFAULT_PARTIAL_FLUSH_INTERVAL),
+                transcript_sink: session_state.transcript_sink.clone(),
+                word_patcher: session_state
+                    .incremental_word_events
+                    .then(WordPatcher::default),
+                fallback_connections: session_state.stt_fallbacks.clone(),
+                stt_sample_rate: session_state.stt_sample_rate,
+                block_on_backpressure: session_state.stt_block_on_backpressure,
+                reconnect_grace: session_state.stt_reconnect_grace,
+                aggregation_window: session_state.stt_aggregation_window,
+                stt_price_per_minute: session_state.stt_price_per_minute,
+                stt_opus: session_state.stt_opus,
+                stt_budget_cap: session_state.stt_budget_cap,
+                redemption_overrides: session_state.redemption_overrides.clone(),
+                fallback_language: session_state.fallback_language.clone(),
+                restore_punctuation: session_state.restores_punctuation(),
             }),
             supervisor,
         )
@@ -380,8 +1043,8 @@ impl SessionActor {
         Ok(listen_ref)
     }
 
-    async fn stop_listener() {
-        if let Some(cell) = registry::where_is(ListenerActor::name()) {
+    async fn stop_listener(session_id: &str) {
+        if let Some(cell) = registry::where_is(ListenerActor::name(session_id)) {
             let actor: ActorRef<ListenerMsg> = cell.into();
             let _ = actor
                 .stop_and_wait(
@@ -392,3 +1055,422 @@ impl SessionActor {
         }
     }
 }
+
+// Retries a DB read that errors, e.g. while the DB is still initializing.
+async fn with_db_retry<T, E, F, Fut>(mut read: F) -> Result<T, E>
+where
+    E: std::fmt::Debug,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut delay = DB_RETRY_INITIAL_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        let result = read().await;
+        match &result {
+            Err(e) if attempt < DB_RETRY_ATTEMPTS => {
+                tracing::warn!(attempt, error = ?e, "db_read_retry");
+            }
+            _ => return result,
+        }
+
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+// The user id is only set once the DB is ready, so its absence is retried like an error. A
+// missing config is not; there just isn't one yet.
+async fn load_config<U, UFut, C, CFut>(
+    mut user_id: U,
+    mut get_config: C,
+) -> Result<Option<hypr_db_user::Config>, crate::Error>
+where
+    U: FnMut() -> UFut,
+    UFut: Future<Output = Result<Option<String>, tauri_plugin_db::Error>>,
+    C: FnMut(String) -> CFut,
+    CFut: Future<Output = Result<Option<hypr_db_user::Config>, tauri_plugin_db::Error>>,
+{
+    let user_id = with_db_retry(|| {
+        let read = user_id();
+        async move { read.await?.ok_or(crate::Error::NoneUser) }
+    })
+    .await?;
+    Ok(with_db_retry(|| get_config(user_id.clone())).await?)
+}
+
+// Reports `spoken_languages` once it differs from `current` and hasn't changed for `debounce`.
+async fn watch_spoken_languages(
+    mut configs: tokio::sync::watch::Receiver<Option<hypr_db_user::Config>>,
+    mut current: Vec<hypr_language::Language>,
+    debounce: std::time::Duration,
+    mut on_change: impl FnMut(Vec<hypr_language::Language>),
+) {
+    while configs.changed().await.is_ok() {
+        // Each further save restarts the wait.
+        while let Ok(Ok(())) = tokio::time::timeout(debounce, configs.changed()).await {}
+
+        let languages = configs
+            .borrow_and_update()
+            .as_ref()
+            .map(|c| c.general.spoken_languages.clone())
+            .filter(|l| !l.is_empty() && *l != current);
+
+        if let Some(languages) = languages {
+            current = languages.clone();
+            on_change(languages);
+        }
+    }
+}
+
+// Resolves to an error as soon as `token` is cancelled, dropping `fut` without finishing it.
+pub(crate) async fn until_cancelled<T>(
+    token: &CancellationToken,
+    fut: impl Future<Output = T>,
+) -> Result<T, ActorProcessingErr> {
+    token
+        .run_until_cancelled(fut)
+        .await
+        .ok_or_else(|| "cancelled".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::NoopActor;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_during_start() {
+        let token = CancellationToken::new();
+        let server_started = Arc::new(AtomicBool::new(false));
+
+        // Stands in for a slow server start, which leaves a long-running task behind once done.
+        let start_server = {
+            let server_started = server_started.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                server_started.store(true, Ordering::SeqCst);
+                tokio::spawn(std::future::pending::<()>())
+            }
+        };
+
+        tokio::spawn({
+            let token = token.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                token.cancel();
+            }
+        });
+
+        let started = tokio::time::Instant::now();
+        let result = until_cancelled(&token, start_server).await;
+
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_millis(200));
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!server_started.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_load_config_retries_until_user_is_ready() {
+        // No user id for the first two reads, as if the DB were still initializing.
+        let user_reads = std::sync::atomic::AtomicUsize::new(0);
+        let user_id = || {
+            let read = user_reads.fetch_add(1, Ordering::SeqCst);
+            async move { Ok::<_, tauri_plugin_db::Error>((read >= 2).then(|| "user".to_string())) }
+        };
+        let get_config = |user_id: String| async move {
+            Ok::<_, tauri_plugin_db::Error>(Some(hypr_db_user::Config {
+                id: "config".to_string(),
+                user_id,
+                general: hypr_db_user::ConfigGeneral {
+                    save_recordings: Some(false),
+                    ..Default::default()
+                },
+                notification: Default::default(),
+                ai: Default::default(),
+            }))
+        };
+
+        let config = load_config(user_id, get_config).await.unwrap().unwrap();
+        assert_eq!(user_reads.load(Ordering::SeqCst), 3);
+        assert_eq!(config.user_id, "user");
+        assert_eq!(config.general.save_recordings, Some(false));
+
+        // Never ready: a proper error instead of a panic, which pre_start turns into defaults.
+        let started = tokio::time::Instant::now();
+        let result = load_config(
+            || async { Ok::<_, tauri_plugin_db::Error>(None) },
+            |_| async { Ok::<_, tauri_plugin_db::Error>(None) },
+        )
+        .await;
+        assert!(matches!(result, Err(crate::Error::NoneUser)));
+        assert_eq!(started.elapsed(), Duration::from_millis(1500));
+
+        // A user without a config is read once, not retried.
+        let config_reads = std::sync::atomic::AtomicUsize::new(0);
+        let result = load_config(
+            || async { Ok::<_, tauri_plugin_db::Error>(Some("user".to_string())) },
+            |_| {
+                config_reads.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<_, tauri_plugin_db::Error>(None) }
+            },
+        )
+        .await;
+        assert!(matches!(result, Ok(None)));
+        assert_eq!(config_reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spoken_languages_reload_is_debounced() {
+        let (tx, rx) = tokio::sync::watch::channel(None);
+        let (changed_tx, mut changed_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let config = |languages: &[hypr_language::ISO639]| hypr_db_user::Config {
+            id: "config".to_string(),
+            user_id: "user".to_string(),
+            general: hypr_db_user::ConfigGeneral {
+                spoken_languages: languages.iter().map(|l| (*l).into()).collect(),
+                ..Default::default()
+            },
+            notification: Default::default(),
+            ai: Default::default(),
+        };
+
+        let debounce = Duration::from_secs(1);
+        tokio::spawn(watch_spoken_languages(
+            rx,
+            vec![hypr_language::ISO639::En.into()],
+            debounce,
+            move |languages| changed_tx.send(languages).unwrap(),
+        ));
+
+        // Two quick saves while picking languages, then nothing.
+        let started = tokio::time::Instant::now();
+        tx.send_replace(Some(config(&[hypr_language::ISO639::Ko])));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        tx.send_replace(Some(config(&[
+            hypr_language::ISO639::Ko,
+            hypr_language::ISO639::En,
+        ])));
+
+        let languages = changed_rx.recv().await.unwrap();
+        assert_eq!(
+            languages,
+            vec![
+                hypr_language::ISO639::Ko.into(),
+                hypr_language::ISO639::En.into()
+            ]
+        );
+        assert_eq!(started.elapsed(), Duration::from_millis(300) + debounce);
+
+        // Saving other settings doesn't reconnect.
+        tx.send_replace(Some(config(&[
+            hypr_language::ISO639::Ko,
+            hypr_language::ISO639::En,
+        ])));
+        tokio::time::sleep(debounce * 2).await;
+        assert!(changed_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_active_is_the_latest_running_session() {
+        let first = format!("first-{}", uuid::Uuid::new_v4());
+        let second = format!("second-{}", uuid::Uuid::new_v4());
+
+        let mut sessions = vec![];
+        for session_id in [&first, &second] {
+            let (actor, handle) = Actor::spawn(
+                Some(SessionActor::name(session_id)),
+                NoopActor::<SessionMsg>::default(),
+                (),
+            )
+            .await
+            .unwrap();
+            sessions.push((actor, handle));
+        }
+
+        let active_name = || SessionActor::active().and_then(|a| a.get_name());
+        assert_eq!(active_name(), Some(SessionActor::name(&second)));
+
+        let (actor, handle) = sessions.pop().unwrap();
+        actor.stop(None);
+        handle.await.unwrap();
+        assert_eq!(active_name(), Some(SessionActor::name(&first)));
+
+        let (actor, handle) = sessions.pop().unwrap();
+        actor.stop(None);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_actor_status_reports_dead_children() {
+        let session_id = "actor_status_session";
+        let mut children = HashMap::new();
+        for kind in ChildKind::ALL {
+            // Stands in for each of the session's children, registered under their real names.
+            let (actor, handle) = Actor::spawn(
+                Some(kind.actor_name(session_id)),
+                NoopActor::<()>::default(),
+                (),
+            )
+            .await
+            .unwrap();
+            children.insert(kind, (actor, handle));
+        }
+
+        let status = actor_status(session_id, &HashMap::new());
+        assert_eq!(status.len(), 4);
+        assert!(status.values().all(|s| s.alive));
+        assert_eq!(status["recorder"].state.as_deref(), Some("Running"));
+
+        let (recorder, handle) = children.remove(&ChildKind::Recorder).unwrap();
+        recorder.kill();
+        handle.await.unwrap();
+
+        // What the session records from the supervision event.
+        let exits = HashMap::from([(ChildKind::Recorder, "killed".to_string())]);
+        let status = actor_status(session_id, &exits);
+        assert_eq!(
+            status["recorder"],
+            ChildStatus {
+                alive: false,
+                state: Some("killed".to_string()),
+            }
+        );
+        for key in ["source", "processor", "listener"] {
+            assert!(status[key].alive, "{key} should be up");
+        }
+
+        assert_eq!(
+            child_kind(session_id, &RecorderActor::name(session_id)),
+            Some(ChildKind::Recorder)
+        );
+        assert_eq!(child_kind(session_id, "other"), None);
+
+        for (actor, handle) in children.into_values() {
+            actor.stop(None);
+            handle.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_without_live_transcription() {
+        assert_eq!(optional_children(true, false), vec![ChildKind::Recorder]);
+        assert_eq!(
+            optional_children(true, true),
+            vec![ChildKind::Listener, ChildKind::Recorder]
+        );
+        assert_eq!(optional_children(false, true), vec![ChildKind::Listener]);
+
+        // A real recorder, and stand-ins registered under the other children's names.
+        struct TestChildren {
+            session_id: String,
+            app_dir: std::path::PathBuf,
+            stubs: Vec<ActorRef<()>>,
+            recorder: Option<(ActorRef<RecMsg>, concurrency::JoinHandle<()>)>,
+        }
+
+        impl SpawnChild for TestChildren {
+            async fn spawn_child(&mut self, kind: ChildKind) -> Result<(), ActorProcessingErr> {
+                if kind == ChildKind::Recorder {
+                    self.recorder = Some(
+                        Actor::spawn(
+                            Some(RecorderActor::name(&self.session_id)),
+                            RecorderActor,
+                            RecArgs::for_test(&self.app_dir, &self.session_id),
+                        )
+                        .await?,
+                    );
+                    return Ok(());
+                }
+
+                let name = kind.actor_name(&self.session_id);
+                let (actor, _) = Actor::spawn(Some(name), NoopActor::<()>::default(), ()).await?;
+                self.stubs.push(actor);
+                Ok(())
+            }
+        }
+
+        let session_id = format!("record_only-{}", uuid::Uuid::new_v4());
+        let app_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let mut children = TestChildren {
+            session_id: session_id.clone(),
+            app_dir: app_dir.clone(),
+            stubs: vec![],
+            recorder: None,
+        };
+        SessionActor::start_all_actors(&mut children, true, false)
+            .await
+            .unwrap();
+
+        assert!(ActorRef::<ListenerMsg>::where_is(ListenerActor::name(&session_id)).is_none());
+        let status = actor_status(&session_id, &HashMap::new());
+        assert!(!status["listener"].alive);
+        for key in ["source", "processor", "recorder"] {
+            assert!(status[key].alive, "{key} should be up");
+        }
+
+        let (recorder, handle) = children.recorder.take().unwrap();
+        recorder.cast(RecMsg::Audio(vec![0.1; 16000])).unwrap();
+        recorder.drain_and_wait(None).await.unwrap();
+        handle.await.unwrap();
+        for stub in children.stubs {
+            stub.stop(None);
+        }
+
+        let segments = crate::actors::recording_segments(app_dir.join(&session_id));
+        assert_eq!(segments.len(), 1);
+        assert!(std::fs::metadata(&segments[0]).unwrap().len() > 0);
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+
+    #[test]
+    fn test_paused_listener_restarts_only_on_resume() {
+        let session_id = "session";
+        let listener = ListenerActor::name(session_id);
+        let recorder = RecorderActor::name(session_id);
+
+        // A listener that stopped on its own, e.g. a dropped connection, comes back.
+        assert_eq!(
+            on_child_terminated(session_id, &listener, None, false),
+            ChildExit::RestartListener
+        );
+
+        // Paused: the stop was asked for, so it stays down.
+        assert_eq!(
+            on_child_terminated(session_id, &listener, Some("restart"), true),
+            ChildExit::Ignore
+        );
+
+        // Anything else still ends the session, paused or not.
+        assert_eq!(
+            on_child_terminated(session_id, &recorder, None, true),
+            ChildExit::StopAndWait
+        );
+        assert_eq!(
+            on_child_terminated(
+                session_id,
+                &recorder,
+                Some(MAX_RECORDING_DURATION_REASON),
+                true
+            ),
+            ChildExit::Stop
+        );
+
+        // Resumed: the next unexpected stop is handled like before the pause.
+        assert_eq!(
+            on_child_terminated(session_id, &listener, None, false),
+            ChildExit::RestartListener
+        );
+    }
+}
@@ -1,41 +1,63 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 
 use futures_util::StreamExt;
 use ractor::{registry, Actor, ActorName, ActorProcessingErr, ActorRef, RpcReplyPort};
 use tokio_util::sync::CancellationToken;
 
-use crate::actors::{AudioChunk, ProcMsg, ProcessorActor};
+use crate::actors::{AudioChunk, ProcMsg, ProcessorActor, SessionActor, SessionMsg};
+use crate::Channel;
 use hypr_audio::{
     is_using_headphone, AudioInput, DeviceEvent, DeviceMonitor, DeviceMonitorHandle,
-    ResampledAsyncSource,
+    ExactChunksExt, ResampledAsyncSource,
 };
 
 // We previously used AEC; it has been removed.  Keep this constant to preserve chunking size.
 const AEC_BLOCK_SIZE: usize = 512;
 const SAMPLE_RATE: u32 = 16000;
 
+pub const MAX_CHANNEL_GAIN: f32 = 2.0;
+pub const DEFAULT_MUTE_RAMP_MS: u64 = 50;
+
 pub enum SourceMsg {
     SetMicMute(bool),
     GetMicMute(RpcReplyPort<bool>),
     SetSpkMute(bool),
     GetSpkMute(RpcReplyPort<bool>),
-    SetMicDevice(Option<String>),
+    // A pinned device is kept when the OS default input changes.
+    SetMicDevice {
+        device: Option<String>,
+        pinned: bool,
+    },
+    DefaultInputChanged(String),
     GetMicDevice(RpcReplyPort<Option<String>>),
+    SetGain(Channel, f32),
 }
 
 pub struct SourceArgs {
+    pub session_id: String,
     pub mic_device: Option<String>,
     pub token: CancellationToken,
     pub onboarding: bool,
+    // How long muting fades out and unmuting fades in. 0 switches at once.
+    pub mute_ramp_ms: u64,
 }
 
 pub struct SourceState {
+    session_id: String,
     mic_device: Option<String>,
+    mic_pinned: bool,
+    // The pinned mic is gone, so only the speaker is captured until another device is picked.
+    mic_lost: bool,
     token: CancellationToken,
     onboarding: bool,
     mic_muted: Arc<AtomicBool>,
     spk_muted: Arc<AtomicBool>,
+    mic_gain: ChannelGain,
+    spk_gain: ChannelGain,
+    // Whether the loop captures one mixed stream, which only the mic's gain applies to.
+    mixed: bool,
+    mute_ramp_samples: usize,
     run_task: Option<tokio::task::JoinHandle<()>>,
     stream_cancel_token: Option<CancellationToken>,
     _device_monitor_handle: Option<DeviceMonitorHandle>,
@@ -46,8 +68,8 @@ pub struct SourceState {
 pub struct SourceActor;
 
 impl SourceActor {
-    pub fn name() -> ActorName {
-        "source".into()
+    pub fn name(session_id: &str) -> ActorName {
+        format!("source:{}", session_id)
     }
 }
 
@@ -91,7 +113,7 @@ impl Actor for SourceActor {
                                     Err(RecvTimeoutError::Timeout) => {
                                         let new_device = AudioInput::get_default_device_name();
                                         let _ = myself_clone
-                                            .cast(SourceMsg::SetMicDevice(Some(new_device)));
+                                            .cast(SourceMsg::DefaultInputChanged(new_device));
                                         break;
                                     }
                                     Err(RecvTimeoutError::Disconnected) => return,
@@ -111,11 +133,18 @@ impl Actor for SourceActor {
         tracing::info!(mic_device = ?mic_device);
 
         let mut st = SourceState {
+            session_id: args.session_id,
             mic_device,
+            mic_pinned: false,
+            mic_lost: false,
             token: args.token,
             onboarding: args.onboarding,
             mic_muted: Arc::new(AtomicBool::new(false)),
             spk_muted: Arc::new(AtomicBool::new(false)),
+            mic_gain: ChannelGain::default(),
+            spk_gain: ChannelGain::default(),
+            mixed: false,
+            mute_ramp_samples: (args.mute_ramp_ms * SAMPLE_RATE as u64 / 1000) as usize,
             run_task: None,
             stream_cancel_token: None,
             _device_monitor_handle: Some(device_monitor_handle),
@@ -150,23 +179,58 @@ impl Actor for SourceActor {
                     let _ = reply.send(st.spk_muted.load(Ordering::Relaxed));
                 }
             }
+            SourceMsg::SetGain(channel, gain) => match channel {
+                Channel::Mic => st.mic_gain.set(gain),
+                Channel::Speaker => {
+                    // Still kept, for when the loop restarts with a separate speaker stream.
+                    if st.mixed {
+                        tracing::warn!("speaker_gain_ignored_in_mixed_mode");
+                    }
+                    st.spk_gain.set(gain);
+                }
+            },
             SourceMsg::GetMicDevice(reply) => {
                 if !reply.is_closed() {
                     let _ = reply.send(st.mic_device.clone());
                 }
             }
-            SourceMsg::SetMicDevice(dev) => {
-                st.mic_device = dev;
-
-                if let Some(cancel_token) = st.stream_cancel_token.take() {
-                    cancel_token.cancel();
-                }
+            SourceMsg::SetMicDevice { device, pinned } => {
+                st.mic_device = device;
+                st.mic_pinned = pinned && st.mic_device.is_some();
+                st.mic_lost = false;
 
-                if let Some(t) = st.run_task.take() {
-                    t.abort();
-                }
+                stop_source_loop(st);
                 start_source_loop(&myself, st).await?;
             }
+            SourceMsg::DefaultInputChanged(default_device) => {
+                match on_default_input_changed(
+                    st.mic_device.as_deref(),
+                    st.mic_pinned,
+                    default_device,
+                    &AudioInput::list_mic_devices(),
+                ) {
+                    DefaultInputAction::Follow(device) => {
+                        st.mic_device = Some(device);
+                        st.mic_lost = false;
+
+                        stop_source_loop(st);
+                        start_source_loop(&myself, st).await?;
+                    }
+                    DefaultInputAction::Keep => {}
+                    // The speaker keeps being captured, with the mic silent.
+                    DefaultInputAction::PinnedDeviceLost(device) => {
+                        tracing::warn!(device = %device, "pinned_mic_device_lost");
+                        st.mic_lost = true;
+                        stop_source_loop(st);
+                        start_source_loop(&myself, st).await?;
+
+                        if let Some(cell) = registry::where_is(SessionActor::name(&st.session_id)) {
+                            let actor: ActorRef<SessionMsg> = cell.into();
+                            actor.cast(SessionMsg::MicDeviceLost(device))?;
+                        }
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -177,14 +241,149 @@ impl Actor for SourceActor {
         _myself: ActorRef<Self::Msg>,
         st: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
-        if let Some(cancel_token) = st.stream_cancel_token.take() {
-            cancel_token.cancel();
+        stop_source_loop(st);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum DefaultInputAction {
+    Follow(String),
+    Keep,
+    PinnedDeviceLost(String),
+}
+
+// Unpinned sources follow the OS default input. Pinned ones stay on their device while it is
+// still connected.
+fn on_default_input_changed(
+    mic_device: Option<&str>,
+    pinned: bool,
+    default_device: String,
+    available: &[String],
+) -> DefaultInputAction {
+    match mic_device {
+        Some(device) if pinned => {
+            if available.iter().any(|d| d == device) {
+                DefaultInputAction::Keep
+            } else {
+                DefaultInputAction::PinnedDeviceLost(device.to_string())
+            }
         }
-        if let Some(task) = st.run_task.take() {
-            task.abort();
+        _ => DefaultInputAction::Follow(default_device),
+    }
+}
+
+// Linear gain, shared with the source loop. Stored as `f32` bits so it can be updated atomically.
+#[derive(Clone)]
+struct ChannelGain(Arc<AtomicU32>);
+
+impl Default for ChannelGain {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU32::new(1.0_f32.to_bits())))
+    }
+}
+
+impl ChannelGain {
+    fn set(&self, gain: f32) {
+        let gain = if gain.is_finite() {
+            gain.clamp(0.0, MAX_CHANNEL_GAIN)
+        } else {
+            1.0
+        };
+        self.0.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+// Fades a channel out on mute and back in on unmute, as switching at once makes an audible pop.
+struct MuteRamp {
+    // From 0.0 (muted) to 1.0.
+    level: f32,
+    step: f32,
+}
+
+impl MuteRamp {
+    fn new(ramp_samples: usize, muted: bool) -> Self {
+        Self {
+            level: if muted { 0.0 } else { 1.0 },
+            step: 1.0 / ramp_samples.max(1) as f32,
         }
+    }
 
-        Ok(())
+    fn apply(&mut self, data: &mut [f32], muted: bool) {
+        let target = if muted { 0.0 } else { 1.0 };
+
+        if self.level == target {
+            if muted {
+                data.fill(0.0);
+            }
+            return;
+        }
+
+        for s in data.iter_mut() {
+            self.level = if muted {
+                (self.level - self.step).max(0.0)
+            } else {
+                (self.level + self.step).min(1.0)
+            };
+            *s *= self.level;
+        }
+    }
+}
+
+// Applied before the processor's AGC.
+fn prepare_chunk(mut data: Vec<f32>, ramp: &mut MuteRamp, muted: bool, gain: f32) -> Vec<f32> {
+    ramp.apply(&mut data, muted);
+
+    if gain != 1.0 {
+        data.iter_mut()
+            .for_each(|s| *s = (*s * gain).clamp(-1.0, 1.0));
+    }
+    data
+}
+
+// Chunks of both streams, tagged by channel, until both have ended. The speaker stream ends right
+// away where capturing it is not supported, and either can fail mid-session. From then on the
+// other stream's chunks come with silence for the dead channel, so the processor still pairs them.
+fn dual_source(
+    mic: impl futures_util::Stream<Item = Vec<f32>>,
+    spk: impl futures_util::Stream<Item = Vec<f32>>,
+) -> impl futures_util::Stream<Item = (Channel, Vec<f32>)> {
+    let ended = |channel| futures_util::stream::once(async move { (channel, None) });
+
+    futures_util::stream::select(
+        mic.map(|data| (Channel::Mic, Some(data)))
+            .chain(ended(Channel::Mic)),
+        spk.map(|data| (Channel::Speaker, Some(data)))
+            .chain(ended(Channel::Speaker)),
+    )
+    .scan(Vec::<Channel>::new(), |ended, (channel, data)| {
+        let chunks = match data {
+            Some(data) => {
+                let silence = ended.first().map(|&dead| (dead, vec![0.0; data.len()]));
+                std::iter::once((channel, data)).chain(silence).collect()
+            }
+            None => {
+                tracing::warn!(channel = ?channel, "source_stream_ended");
+                ended.push(channel);
+                vec![]
+            }
+        };
+        futures_util::future::ready(Some(chunks))
+    })
+    .flat_map(futures_util::stream::iter)
+}
+
+fn stop_source_loop(st: &mut SourceState) {
+    if let Some(cancel_token) = st.stream_cancel_token.take() {
+        cancel_token.cancel();
+    }
+    if let Some(task) = st.run_task.take() {
+        task.abort();
     }
 }
 
@@ -196,18 +395,33 @@ async fn start_source_loop(
     let token = st.token.clone();
     let mic_muted = st.mic_muted.clone();
     let spk_muted = st.spk_muted.clone();
+    let mic_gain = st.mic_gain.clone();
+    let spk_gain = st.spk_gain.clone();
     let mic_device = st.mic_device.clone();
+    let ramp_samples = st.mute_ramp_samples;
+    let mic_lost = st.mic_lost;
+    let processor_name = ProcessorActor::name(&st.session_id);
+
+    // Lets the processor pick up the AGC gain saved for this mic.
+    if let Some(cell) = registry::where_is(processor_name.clone()) {
+        let actor: ActorRef<ProcMsg> = cell.into();
+        let device = mic_device
+            .clone()
+            .unwrap_or_else(AudioInput::get_default_device_name);
+        let _ = actor.cast(ProcMsg::MicDevice(device));
+    }
 
     let stream_cancel_token = CancellationToken::new();
     st.stream_cancel_token = Some(stream_cancel_token.clone());
 
     #[cfg(target_os = "macos")]
-    let use_mixed = !st.onboarding && !is_using_headphone();
+    let use_mixed = !st.onboarding && !st.mic_lost && !is_using_headphone();
 
     #[cfg(not(target_os = "macos"))]
     let use_mixed = false;
 
     tracing::info!(use_mixed = use_mixed);
+    st.mixed = use_mixed;
 
     let handle = if use_mixed {
         #[cfg(target_os = "macos")]
@@ -216,11 +430,15 @@ async fn start_source_loop(
                 let mixed_stream = {
                     let mut mixed_input = AudioInput::from_mic(mic_device).unwrap();
                     ResampledAsyncSource::new(mixed_input.stream(), SAMPLE_RATE)
-                        .chunks(AEC_BLOCK_SIZE)
+                        .exact_chunks(AEC_BLOCK_SIZE)
                 };
 
                 tokio::pin!(mixed_stream);
 
+                let both_muted =
+                    || mic_muted.load(Ordering::Relaxed) && spk_muted.load(Ordering::Relaxed);
+                let mut ramp = MuteRamp::new(ramp_samples, both_muted());
+
                 loop {
                     tokio::select! {
                         _ = token.cancelled() => {
@@ -235,14 +453,12 @@ async fn start_source_loop(
                         mixed_next = mixed_stream.next() => {
                             if let Some(data) = mixed_next {
                                 // TODO: should be able to mute each stream
-                                let output_data = if mic_muted.load(Ordering::Relaxed) && spk_muted.load(Ordering::Relaxed) {
-                                    vec![0.0; data.len()]
-                                } else {
-                                    data
-                                };
-                                let msg = ProcMsg::Mixed(AudioChunk{ data: output_data });
+                                // A single device, so the mic's gain is the one that applies.
+                                let data =
+                                    prepare_chunk(data, &mut ramp, both_muted(), mic_gain.get());
+                                let msg = ProcMsg::Mixed(AudioChunk{ data });
 
-                                let Some(cell) = registry::where_is(ProcessorActor::name()) else {
+                                let Some(cell) = registry::where_is(processor_name.clone()) else {
                                     tracing::warn!("processor_actor_not_found");
                                     continue;
                                 };
@@ -265,20 +481,29 @@ async fn start_source_loop(
         }
     } else {
         tokio::spawn(async move {
-            let mic_stream = {
+            // An ended mic stream leaves the speaker's chunks paired with silence.
+            let mic_stream = if mic_lost {
+                futures_util::stream::empty().boxed()
+            } else {
                 let mut mic_input = hypr_audio::AudioInput::from_mic(mic_device).unwrap();
-                ResampledAsyncSource::new(mic_input.stream(), SAMPLE_RATE).chunks(AEC_BLOCK_SIZE)
+                ResampledAsyncSource::new(mic_input.stream(), SAMPLE_RATE)
+                    .exact_chunks(AEC_BLOCK_SIZE)
+                    .boxed()
             };
             tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
             let spk_stream = {
                 let mut spk_input = hypr_audio::AudioInput::from_speaker();
-                ResampledAsyncSource::new(spk_input.stream(), SAMPLE_RATE).chunks(AEC_BLOCK_SIZE)
+                ResampledAsyncSource::new(spk_input.stream(), SAMPLE_RATE)
+                    .exact_chunks(AEC_BLOCK_SIZE)
             };
-            tokio::pin!(mic_stream);
-            tokio::pin!(spk_stream);
+            let chunks = dual_source(mic_stream, spk_stream);
+            tokio::pin!(chunks);
+
+            let mut mic_ramp = MuteRamp::new(ramp_samples, mic_muted.load(Ordering::Relaxed));
+            let mut spk_ramp = MuteRamp::new(ramp_samples, spk_muted.load(Ordering::Relaxed));
 
             loop {
-                let Some(cell) = registry::where_is(ProcessorActor::name()) else {
+                let Some(cell) = registry::where_is(processor_name.clone()) else {
                     tracing::warn!("processor_actor_not_found");
                     continue;
                 };
@@ -286,43 +511,38 @@ async fn start_source_loop(
 
                 tokio::select! {
                     _ = token.cancelled() => {
-                        drop(mic_stream);
-                        drop(spk_stream);
+                        drop(chunks);
                         myself2.stop(None);
                         return;
                     }
                     _ = stream_cancel_token.cancelled() => {
-                        drop(mic_stream);
-                        drop(spk_stream);
+                        drop(chunks);
                         return;
                     }
-                    mic_next = mic_stream.next() => {
-                        if let Some(data) = mic_next {
-                            let output_data = if mic_muted.load(Ordering::Relaxed) {
-                                vec![0.0; data.len()]
-                            } else {
-                                data
-                            };
-
-                            let msg = ProcMsg::Mic(AudioChunk{ data: output_data });
-                            let _ = proc.cast(msg);
-                        } else {
-                            break;
-                        }
-                    }
-                    spk_next = spk_stream.next() => {
-                        if let Some(data) = spk_next {
-                            let output_data = if spk_muted.load(Ordering::Relaxed) {
-                                vec![0.0; data.len()]
-                            } else {
-                                data
-                            };
-
-                            let msg = ProcMsg::Speaker(AudioChunk{ data: output_data });
-                            let _ = proc.cast(msg);
-                        } else {
+                    next = chunks.next() => {
+                        let Some((channel, data)) = next else {
                             break;
-                        }
+                        };
+
+                        let msg = match channel {
+                            Channel::Mic => ProcMsg::Mic(AudioChunk {
+                                data: prepare_chunk(
+                                    data,
+                                    &mut mic_ramp,
+                                    mic_muted.load(Ordering::Relaxed),
+                                    mic_gain.get(),
+                                ),
+                            }),
+                            Channel::Speaker => ProcMsg::Speaker(AudioChunk {
+                                data: prepare_chunk(
+                                    data,
+                                    &mut spk_ramp,
+                                    spk_muted.load(Ordering::Relaxed),
+                                    spk_gain.get(),
+                                ),
+                            }),
+                        };
+                        let _ = proc.cast(msg);
                     }
                 }
             }
@@ -332,3 +552,125 @@ async fn start_source_loop(
     st.run_task = Some(handle);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_device_ignores_default_changes() {
+        let available = vec!["Built-in Mic".to_string(), "Headphones".to_string()];
+
+        // Pinned to A, the default moves to B: stay on A.
+        assert_eq!(
+            on_default_input_changed(
+                Some("Built-in Mic"),
+                true,
+                "Headphones".to_string(),
+                &available
+            ),
+            DefaultInputAction::Keep
+        );
+
+        // Unpinned sources still follow the default.
+        assert_eq!(
+            on_default_input_changed(
+                Some("Built-in Mic"),
+                false,
+                "Headphones".to_string(),
+                &available
+            ),
+            DefaultInputAction::Follow("Headphones".to_string())
+        );
+
+        // A is unplugged: pause rather than quietly switching to B.
+        assert_eq!(
+            on_default_input_changed(
+                Some("Built-in Mic"),
+                true,
+                "Headphones".to_string(),
+                &available[1..]
+            ),
+            DefaultInputAction::PinnedDeviceLost("Built-in Mic".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mic_keeps_flowing_after_speaker_ends() {
+        let mic = futures_util::stream::iter((0..10).map(|i| vec![i as f32 + 1.0; 4]));
+        let spk = futures_util::stream::iter((0..3).map(|_| vec![0.5; 4]));
+
+        let chunks = dual_source(mic, spk).collect::<Vec<_>>().await;
+
+        let mic_chunks = chunks
+            .iter()
+            .filter(|(channel, _)| *channel == Channel::Mic)
+            .map(|(_, data)| data[0])
+            .collect::<Vec<_>>();
+        assert_eq!(mic_chunks, (1..=10).map(|i| i as f32).collect::<Vec<_>>());
+
+        // Once the speaker is gone, mic chunks come with silence in its place.
+        let speaker = chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, (channel, _))| *channel == Channel::Speaker)
+            .collect::<Vec<_>>();
+        assert!(speaker[..3].iter().all(|(_, (_, data))| data[0] == 0.5));
+        assert!(speaker.len() >= 9);
+        for (i, (_, data)) in &speaker[3..] {
+            assert_eq!(data, &vec![0.0; 4]);
+            assert_eq!(chunks[i - 1].0, Channel::Mic);
+        }
+        assert_eq!(chunks.last().unwrap().0, Channel::Speaker);
+    }
+
+    #[test]
+    fn test_speaker_gain_halves_amplitude() {
+        let gain = ChannelGain::default();
+        gain.set(0.5);
+
+        let chunk = (0..AEC_BLOCK_SIZE)
+            .map(|i| (i as f32 * 0.1).sin() * 0.8)
+            .collect::<Vec<_>>();
+        let peak = |xs: &[f32]| xs.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+
+        let mut ramp = MuteRamp::new(0, false);
+        let out = prepare_chunk(chunk.clone(), &mut ramp, false, gain.get());
+        assert!((peak(&out) - peak(&chunk) / 2.0).abs() < 1e-6);
+
+        // Muting still wins over gain, and gain is kept within range.
+        assert_eq!(
+            peak(&prepare_chunk(chunk.clone(), &mut ramp, true, gain.get())),
+            0.0
+        );
+        gain.set(10.0);
+        assert_eq!(gain.get(), MAX_CHANNEL_GAIN);
+    }
+
+    #[test]
+    fn test_mute_ramps_instead_of_stepping() {
+        // Two chunks to fade.
+        let mut ramp = MuteRamp::new(AEC_BLOCK_SIZE * 2, false);
+        let mut chunks = vec![];
+        for muted in [false, true, true, true, false, false, false] {
+            chunks.push(prepare_chunk(
+                vec![0.5; AEC_BLOCK_SIZE],
+                &mut ramp,
+                muted,
+                1.0,
+            ));
+        }
+        let samples = chunks.concat();
+
+        // No jump between neighbouring samples is bigger than one ramp step.
+        let max_step = 0.5 / (AEC_BLOCK_SIZE * 2) as f32 + 1e-6;
+        assert!(samples.windows(2).all(|w| (w[1] - w[0]).abs() <= max_step));
+
+        assert!(chunks[0].iter().all(|s| *s == 0.5));
+        // Fading out: not silent yet, but already quieter.
+        assert!(chunks[1][0] > 0.49 && chunks[1][AEC_BLOCK_SIZE - 1] < 0.26);
+        assert!(chunks[3].iter().all(|s| *s == 0.0));
+        assert!(chunks[4][0] > 0.0 && chunks[4][AEC_BLOCK_SIZE - 1] < 0.26);
+        assert!(chunks[6].iter().all(|s| *s == 0.5));
+    }
+}
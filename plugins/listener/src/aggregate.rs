@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+
+// Coalesces consecutive dual-channel chunks of 16-bit PCM into larger frames, so cloud backends
+// get fewer, bigger websocket messages.
+pub struct AudioAggregator {
+    frame_bytes: usize,
+    mic: BytesMut,
+    spk: BytesMut,
+}
+
+impl AudioAggregator {
+    pub fn new(window: Duration, sample_rate: u32) -> Self {
+        let frame_bytes = (window.as_secs_f64() * sample_rate as f64) as usize * 2;
+
+        Self {
+            frame_bytes,
+            mic: BytesMut::with_capacity(frame_bytes),
+            spk: BytesMut::with_capacity(frame_bytes),
+        }
+    }
+
+    // Returns a frame once at least a window's worth of audio has been pushed.
+    pub fn push(&mut self, mic: &[u8], spk: &[u8]) -> Option<(Bytes, Bytes)> {
+        self.mic.extend_from_slice(mic);
+        self.spk.extend_from_slice(spk);
+
+        (self.mic.len() >= self.frame_bytes)
+            .then(|| (self.mic.split().freeze(), self.spk.split().freeze()))
+    }
+
+    // Returns what's left of an unfilled window, so the audio just before a stop isn't lost.
+    pub fn flush(&mut self) -> Option<(Bytes, Bytes)> {
+        (!self.mic.is_empty() || !self.spk.is_empty())
+            .then(|| (self.mic.split().freeze(), self.spk.split().freeze()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_fill_the_window() {
+        // 512 samples (32ms) per chunk, as the sources produce them.
+        let chunk = |i: i16, offset: i16| {
+            (0..512)
+                .flat_map(|n| (i * 512 + n + offset).to_le_bytes())
+                .collect::<Vec<_>>()
+        };
+
+        let mut aggregator = AudioAggregator::new(Duration::from_millis(128), 16000);
+        let frames = (0..10)
+            .filter_map(|i| aggregator.push(&chunk(i, 0), &chunk(i, 1)))
+            .collect::<Vec<_>>();
+
+        // 10 chunks make two full frames, and the rest waits for more.
+        assert_eq!(frames.len(), 2);
+        for (n, (mic, spk)) in frames.iter().enumerate() {
+            let samples = |bytes: &Bytes| {
+                bytes
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                    .collect::<Vec<_>>()
+            };
+
+            // 128ms at 16kHz.
+            assert_eq!(samples(mic).len(), 2048);
+            assert_eq!(samples(spk).len(), 2048);
+
+            let first = n as i16 * 2048;
+            assert_eq!(samples(mic), (first..first + 2048).collect::<Vec<_>>());
+            assert_eq!(samples(spk), (first + 1..first + 2049).collect::<Vec<_>>());
+        }
+
+        // The two chunks left over come out as a shorter frame on flush, and only once.
+        let (mic, spk) = aggregator.flush().unwrap();
+        assert_eq!(mic.len(), 1024 * 2);
+        assert_eq!(spk.len(), 1024 * 2);
+        assert_eq!(&mic[..2], 4096i16.to_le_bytes());
+        assert_eq!(aggregator.flush(), None);
+        assert!(aggregator.push(&chunk(10, 0), &chunk(10, 1)).is_none());
+    }
+}
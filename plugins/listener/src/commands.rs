@@ -25,8 +25,9 @@ pub async fn get_current_microphone_device<R: tauri::Runtime>(
 pub async fn set_microphone_device<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     device_name: String,
+    pinned: bool,
 ) -> Result<(), String> {
-    app.set_microphone_device(device_name)
+    app.set_microphone_device(device_name, pinned)
         .await
         .map_err(|e| e.to_string())
 }
@@ -125,6 +126,55 @@ pub async fn set_speaker_muted<R: tauri::Runtime>(
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn pause_transcription<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<(), String> {
+    app.pause_transcription().await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_transcription<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<(), String> {
+    app.resume_transcription().await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_transcribe_live<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    enabled: bool,
+) -> Result<(), String> {
+    app.set_transcribe_live(enabled).await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_channel_gain<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    channel: crate::Channel,
+    gain: f32,
+) -> Result<(), String> {
+    app.set_channel_gain(channel, gain).await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_restore_punctuation<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    enabled: bool,
+) -> Result<(), String> {
+    app.set_restore_punctuation(enabled).await;
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn start_session<R: tauri::Runtime>(
@@ -142,6 +192,59 @@ pub async fn stop_session<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> Result
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn purge_session<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<(), String> {
+    app.purge_session(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn merge_sessions<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    primary_id: String,
+    secondary_id: String,
+) -> Result<hypr_db_user::Session, String> {
+    app.merge_sessions(primary_id, secondary_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn start_mic_test<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    device_name: Option<String>,
+    channel: tauri::ipc::Channel<crate::MicLevel>,
+) -> Result<(), String> {
+    app.start_mic_test(device_name, channel)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_mic_test<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> Result<(), String> {
+    app.stop_mic_test().await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_pre_roll_seconds<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    seconds: Option<u64>,
+) -> Result<(), String> {
+    app.set_pre_roll_seconds(seconds)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_state<R: tauri::Runtime>(
@@ -149,3 +252,153 @@ pub async fn get_state<R: tauri::Runtime>(
 ) -> Result<crate::fsm::State, String> {
     Ok(app.get_state().await)
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn is_recording<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<bool, String> {
+    Ok(app.is_recording(session_id).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_transcript_snapshot<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<Option<crate::TranscriptSnapshot>, String> {
+    Ok(app.get_transcript_snapshot(session_id).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_plain_transcript<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    Ok(app.get_plain_transcript(session_id).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_actor_status<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<Option<crate::ActorStatusMap>, String> {
+    Ok(app.get_actor_status(session_id).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn export_subtitles<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+    format: crate::SubtitleFormat,
+) -> Result<String, String> {
+    app.export_subtitles(session_id, format)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_active_stt_info<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<crate::SttInfo, String> {
+    app.get_active_stt_info().await.map_err(|e| e.to_string())
+}
+
+// `None` when the backend is free or its price unknown.
+#[tauri::command]
+#[specta::specta]
+pub async fn estimate_stt_cost<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    duration_seconds: u64,
+) -> Result<Option<crate::CostEstimate>, String> {
+    app.estimate_stt_cost(std::time::Duration::from_secs(duration_seconds))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recording_meta<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<Option<crate::RecordingMeta>, String> {
+    app.get_recording_meta(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn retranscribe_missing<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<Vec<owhisper_interface::Word2>, String> {
+    app.retranscribe_missing(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn enhance_recording_audio<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+    config: crate::AudioEnhanceConfig,
+) -> Result<String, String> {
+    app.enhance_recording_audio(session_id, config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn find_duplicate_recordings<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<Vec<Vec<String>>, String> {
+    app.find_duplicate_recordings()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_uploaded_file<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    path: String,
+    languages: Vec<hypr_language::Language>,
+) -> Result<String, String> {
+    app.transcribe_uploaded_file(path, languages)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recording_prefs<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<crate::RecordingPrefs, String> {
+    app.get_recording_prefs().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_recording_prefs<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    prefs: crate::RecordingPrefs,
+) -> Result<(), String> {
+    app.set_recording_prefs(prefs).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_recording_formats() -> Result<Vec<crate::FormatInfo>, String> {
+    Ok(crate::SUPPORTED_RECORDING_FORMATS
+        .iter()
+        .map(|f| f.info())
+        .collect())
+}
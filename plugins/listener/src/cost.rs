@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+// How often the running tally goes out, in audio sent rather than wall-clock time.
+const COST_EVENT_INTERVAL: Duration = Duration::from_secs(30);
+
+// What a metered STT backend charges, in whatever currency the user entered it in.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, specta::Type)]
+pub struct SttPricing {
+    pub price_per_minute: f64,
+    // Most a single meeting should cost. No cap when unset.
+    pub budget_cap: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, specta::Type)]
+pub struct CostEstimate {
+    pub audio_seconds: f64,
+    pub cost: f64,
+    pub within_budget: bool,
+}
+
+impl SttPricing {
+    /// Projected cost of sending `duration` of audio, and whether it stays within the cap.
+    pub fn estimate(&self, duration: Duration) -> CostEstimate {
+        let cost = duration.as_secs_f64() / 60.0 * self.price_per_minute;
+
+        CostEstimate {
+            audio_seconds: duration.as_secs_f64(),
+            cost,
+            within_budget: self.budget_cap.is_none_or(|cap| cost <= cap),
+        }
+    }
+}
+
+// Length of `bytes` of 16-bit mono PCM.
+pub fn pcm_duration(bytes: usize, sample_rate: u32) -> Duration {
+    Duration::from_micros((bytes / 2) as u64 * 1_000_000 / sample_rate as u64)
+}
+
+// Running tally of the audio a listener has sent. Both channels go out in the same message, so a
+// second of dual-channel audio counts once.
+#[derive(Debug, Default)]
+pub struct CostMeter {
+    // Unknown until a backend is selected, and `None` for free ones.
+    pricing: Option<SttPricing>,
+    sent: Duration,
+    reported: Duration,
+}
+
+impl CostMeter {
+    pub fn set_pricing(&mut self, pricing: Option<SttPricing>) {
+        self.pricing = pricing;
+    }
+
+    pub fn estimate(&self) -> Option<CostEstimate> {
+        self.pricing.map(|pricing| pricing.estimate(self.sent))
+    }
+
+    // Returns the tally once another `COST_EVENT_INTERVAL` of audio has gone out.
+    pub fn add(&mut self, duration: Duration) -> Option<CostEstimate> {
+        self.sent += duration;
+
+        if self.sent - self.reported < COST_EVENT_INTERVAL {
+            return None;
+        }
+        self.reported = self.sent;
+        self.estimate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_meter_tally() {
+        let pricing = SttPricing {
+            price_per_minute: 0.0043,
+            budget_cap: Some(0.01),
+        };
+
+        let estimate = pricing.estimate(Duration::from_secs(60 * 60));
+        assert!((estimate.cost - 0.258).abs() < 1e-9);
+        assert!(!estimate.within_budget);
+
+        let mut meter = CostMeter::default();
+        // 20ms chunks of 16kHz audio, sent before the backend is known.
+        let chunk = pcm_duration(320 * 2, 16000);
+        assert_eq!(chunk, Duration::from_millis(20));
+        for _ in 0..50 {
+            assert_eq!(meter.add(chunk), None);
+        }
+        assert_eq!(meter.estimate(), None);
+
+        meter.set_pricing(Some(pricing));
+        let reports = (0..50 * 119)
+            .filter_map(|_| meter.add(chunk))
+            .collect::<Vec<_>>();
+
+        // Two minutes in all, reported every 30 seconds.
+        assert_eq!(reports.len(), 4);
+        let tally = meter.estimate().unwrap();
+        assert!((tally.audio_seconds - 120.0).abs() < 1e-9);
+        assert!((tally.cost - 0.0086).abs() < 1e-9);
+        assert!(tally.within_budget);
+        assert_eq!(reports.last(), Some(&tally));
+    }
+}
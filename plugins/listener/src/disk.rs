@@ -0,0 +1,92 @@
+use std::path::Path;
+
+pub const DEFAULT_MIN_FREE_DISK_MB: u64 = 500;
+pub const LOW_DISK_SPACE_REASON: &str = "low_disk_space";
+
+// Free space is checked once per this much recorded audio, at 16kHz.
+const CHECK_INTERVAL_SAMPLES: u64 = 16000 * 30;
+
+// Watches the free space on the recording volume, so a full disk doesn't corrupt the WAV.
+#[derive(Debug, Clone, Copy)]
+pub struct DiskGuard {
+    min_free_bytes: u64,
+    free_space: fn(&Path) -> Option<u64>,
+    samples_since_check: u64,
+    warned: bool,
+}
+
+impl DiskGuard {
+    pub fn new(min_free_mb: u64) -> Self {
+        Self::with_free_space(min_free_mb, available_space)
+    }
+
+    fn with_free_space(min_free_mb: u64, free_space: fn(&Path) -> Option<u64>) -> Self {
+        Self {
+            min_free_bytes: min_free_mb * 1024 * 1024,
+            free_space,
+            samples_since_check: 0,
+            warned: false,
+        }
+    }
+
+    // Bytes left on the volume holding `dir`, if below the threshold.
+    // Unknown free space is never treated as low.
+    pub fn low_space(&self, dir: &Path) -> Option<u64> {
+        (self.free_space)(dir).filter(|available| *available < self.min_free_bytes)
+    }
+
+    // Called as audio is written. Returns the bytes left the first time they run low.
+    pub fn check(&mut self, dir: &Path, samples: u64) -> Option<u64> {
+        if self.warned {
+            return None;
+        }
+
+        self.samples_since_check += samples;
+        if self.samples_since_check < CHECK_INTERVAL_SAMPLES {
+            return None;
+        }
+        self.samples_since_check = 0;
+
+        let available = self.low_space(dir)?;
+        self.warned = true;
+        Some(available)
+    }
+}
+
+// Free bytes on the volume holding `path`, going by the longest matching mount point.
+pub fn available_space(path: &Path) -> Option<u64> {
+    let path = path.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_disk_space() {
+        let dir = std::env::temp_dir();
+
+        let guard = DiskGuard::with_free_space(500, |_| Some(100 * 1024 * 1024));
+        assert_eq!(guard.low_space(&dir), Some(100 * 1024 * 1024));
+
+        let guard = DiskGuard::with_free_space(500, |_| Some(10 * 1024 * 1024 * 1024));
+        assert_eq!(guard.low_space(&dir), None);
+
+        let guard = DiskGuard::with_free_space(500, |_| None);
+        assert_eq!(guard.low_space(&dir), None);
+
+        // While recording: checked once per interval, and warned about only once.
+        let mut guard = DiskGuard::with_free_space(500, |_| Some(100 * 1024 * 1024));
+        assert_eq!(guard.check(&dir, CHECK_INTERVAL_SAMPLES - 1), None);
+        assert_eq!(guard.check(&dir, 1), Some(100 * 1024 * 1024));
+        assert_eq!(guard.check(&dir, CHECK_INTERVAL_SAMPLES), None);
+    }
+}
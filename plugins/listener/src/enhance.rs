@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use ractor::ActorProcessingErr;
+
+use crate::{
+    actors::{read_recording, write_recording, DcBlocker, DEFAULT_DC_BLOCKER_CUTOFF_HZ},
+    pre_roll::SAMPLE_RATE,
+    AudioEnhanceConfig,
+};
+
+// Cleaned copies go in their own directory, so they are never taken for part of the recording.
+pub const ENHANCED_DIR: &str = "enhanced";
+
+// 20ms at the recording's 16kHz.
+const FRAME_SAMPLES: usize = 320;
+// Frames within 6dB of the noise floor are taken for noise and turned down by 20dB.
+const GATE_THRESHOLD_RATIO: f32 = 2.0;
+const GATE_ATTENUATION: f32 = 0.1;
+// The quietest tenth of the recording is assumed to be background noise.
+const NOISE_FLOOR_PERCENTILE: f32 = 0.1;
+
+// -1 dBFS, released over roughly 60ms.
+const LIMITER_CEILING: f32 = 0.89;
+const LIMITER_RELEASE: f32 = 0.001;
+
+/// Runs `samples` through the same chain as the live processor, plus the offline-only stages.
+pub fn enhance_samples(samples: &mut [f32], config: &AudioEnhanceConfig) {
+    DcBlocker::new(DEFAULT_DC_BLOCKER_CUTOFF_HZ, SAMPLE_RATE).process(samples);
+
+    if config.denoise {
+        denoise(samples);
+    }
+
+    if config.agc {
+        let mut agc = hypr_agc::Agc::default();
+        for chunk in samples.chunks_mut(FRAME_SAMPLES) {
+            agc.process(chunk);
+        }
+    }
+
+    if config.limiter {
+        limit(samples);
+    }
+}
+
+/// Writes a cleaned copy of the recording in `session_dir`, encoded at `quality`, and returns
+/// its path. The original segments are left as they are.
+pub fn enhance_recording(
+    session_dir: impl AsRef<Path>,
+    config: &AudioEnhanceConfig,
+    quality: f32,
+) -> Result<PathBuf, ActorProcessingErr> {
+    let session_dir = session_dir.as_ref();
+
+    let mut samples = read_recording(session_dir)?;
+    enhance_samples(&mut samples, config);
+
+    let enhanced_dir = session_dir.join(ENHANCED_DIR);
+    write_recording(&enhanced_dir, &samples, quality)?;
+
+    crate::actors::recording_segments(&enhanced_dir)
+        .into_iter()
+        .next()
+        .ok_or_else(|| "enhanced_recording_missing".into())
+}
+
+fn rms(frame: &[f32]) -> f32 {
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32).sqrt()
+}
+
+// A noise gate with the threshold taken from the recording itself, which only works offline.
+fn denoise(samples: &mut [f32]) {
+    let levels = samples.chunks(FRAME_SAMPLES).map(rms).collect::<Vec<_>>();
+    if levels.is_empty() {
+        return;
+    }
+
+    let mut sorted = levels.clone();
+    sorted.sort_by(f32::total_cmp);
+    let floor = sorted[(sorted.len() as f32 * NOISE_FLOOR_PERCENTILE) as usize];
+    let threshold = floor * GATE_THRESHOLD_RATIO;
+
+    // The gain moves across each frame rather than jumping, which would click.
+    let mut gain = 1.0;
+    for (frame, level) in samples.chunks_mut(FRAME_SAMPLES).zip(levels) {
+        let target = if level <= threshold {
+            GATE_ATTENUATION
+        } else {
+            1.0
+        };
+        let step = (target - gain) / frame.len() as f32;
+
+        for sample in frame.iter_mut() {
+            gain += step;
+            *sample *= gain;
+        }
+    }
+}
+
+fn limit(samples: &mut [f32]) {
+    let mut gain = 1.0_f32;
+
+    for sample in samples.iter_mut() {
+        let target = (LIMITER_CEILING / sample.abs()).min(1.0);
+        gain = (gain + (1.0 - gain) * LIMITER_RELEASE).min(target);
+        *sample *= gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic white noise in [-amplitude, amplitude].
+    fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enhance_reduces_noise_floor() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let quality = crate::RecordingPrefs::default().quality;
+
+        // Half a second of tone over noise, then half a second of noise only, six times.
+        let half = SAMPLE_RATE as usize / 2;
+        let mut samples = noise(half * 12, 0.02);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            if (i / half) % 2 == 0 {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                *sample += 0.2 * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            }
+        }
+        write_recording(&dir, &samples, quality).unwrap();
+
+        let original_path = crate::actors::recording_segments(&dir)[0].clone();
+        let original = std::fs::read(&original_path).unwrap();
+
+        let enhanced_path =
+            enhance_recording(&dir, &AudioEnhanceConfig::default(), quality).unwrap();
+        assert_eq!(
+            enhanced_path.parent(),
+            Some(dir.join(ENHANCED_DIR).as_path())
+        );
+
+        // The middle of each noise-only stretch, and of each tone.
+        let level = |samples: &[f32], tone: bool| {
+            let windows = (0..12)
+                .filter(|i| (i % 2 == 0) == tone)
+                .map(|i| rms(&samples[i * half + half / 4..i * half + half * 3 / 4]))
+                .collect::<Vec<_>>();
+            windows.iter().sum::<f32>() / windows.len() as f32
+        };
+
+        let before = read_recording(&dir).unwrap();
+        let after = read_recording(dir.join(ENHANCED_DIR)).unwrap();
+        assert_eq!(after.len(), before.len());
+
+        let (noise_before, noise_after) = (level(&before, false), level(&after, false));
+        assert!(
+            noise_after < noise_before / 4.0,
+            "{noise_after} vs {noise_before}"
+        );
+        assert!(
+            noise_after / level(&after, true) < noise_before / level(&before, true) / 4.0,
+            "the tone should be kept"
+        );
+        assert!(after.iter().all(|s| s.abs() <= LIMITER_CEILING + 0.01));
+
+        // The recording itself is untouched, and still the only one found.
+        assert_eq!(std::fs::read(&original_path).unwrap(), original);
+        assert_eq!(crate::actors::recording_segments(&dir), vec![original_path]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
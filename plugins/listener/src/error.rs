@@ -7,10 +7,14 @@ pub enum Error {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
+    TauriError(#[from] tauri::Error),
+    #[error(transparent)]
     HyprAudioError(#[from] hypr_audio::Error),
     #[error(transparent)]
     CpalDevicesError(#[from] hypr_audio::cpal::DevicesError),
     #[error(transparent)]
+    StoreError(#[from] tauri_plugin_store2::Error),
+    #[error(transparent)]
     DatabaseError(#[from] tauri_plugin_db::Error),
     #[error(transparent)]
     LocalSttError(#[from] tauri_plugin_local_stt::Error),
@@ -18,10 +22,22 @@ pub enum Error {
     ConnectorError(#[from] tauri_plugin_connector::Error),
     #[error("no session")]
     NoneSession,
+    #[error("no user")]
+    NoneUser,
     #[error("start session failed")]
     StartSessionFailed,
     #[error("stop session failed")]
     StopSessionFailed,
+    #[error("session is still being recorded")]
+    SessionInProgress,
+    #[error("no recording for the session")]
+    NoneRecording,
+    #[error("invalid session id: {0}")]
+    InvalidSessionId(String),
+    #[error("transcription failed: {0}")]
+    TranscriptionFailed(String),
+    #[error("unsupported audio file: {0}")]
+    UnsupportedAudioFile(String),
 }
 
 impl Serialize for Error {
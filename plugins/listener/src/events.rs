@@ -19,37 +19,129 @@ common_event_derives! {
         FinalWords { words: HashMap<usize, Vec<owhisper_interface::Word2>>},
         #[serde(rename = "partialWords")]
         PartialWords { words: HashMap<usize, Vec<owhisper_interface::Word2>>},
+        // Sent instead of `finalWords`/`partialWords` when incremental word events are enabled.
+        #[serde(rename = "wordsPatch")]
+        WordsPatch { partial_ops: Vec<crate::WordOp>, final_ops: Vec<crate::WordOp> },
+        // `mic`/`speaker` are peak levels from 0 to 100, the `_dbfs` ones RMS levels in dBFS.
         #[serde(rename = "audioAmplitude")]
-        AudioAmplitude { mic: u16, speaker: u16 },
+        AudioAmplitude { mic: u16, speaker: u16, mic_dbfs: f32, speaker_dbfs: f32 },
         #[serde(rename = "micMuted")]
         MicMuted { value: bool },
         #[serde(rename = "speakerMuted")]
         SpeakerMuted { value: bool },
+        #[serde(rename = "recordingsDirFallback")]
+        RecordingsDirFallback { requested: String, fallback: String },
+        #[serde(rename = "recordingLimitApproaching")]
+        RecordingLimitApproaching { remaining_seconds: u64 },
+        #[serde(rename = "lowDiskSpace")]
+        LowDiskSpace { available_bytes: u64 },
+        // Writing the recording fell too far behind, so audio is being left out of it.
+        #[serde(rename = "recorderSlow")]
+        RecorderSlow {},
+        // Sustained full-scale samples in the raw input. The device's gain is likely too high.
+        #[serde(rename = "inputClipping")]
+        InputClipping { channel: crate::Channel },
+        #[serde(rename = "sttBackendSelected")]
+        SttBackendSelected { backend: crate::SttInfo },
+        // Running cost of the audio sent to a metered backend. Only sent when its price is known.
+        #[serde(rename = "costEstimate")]
+        CostEstimate { estimate: crate::CostEstimate },
+        // The backend keeps falling behind, so audio is being dropped. Counts all dropped chunks.
+        #[serde(rename = "sttBackendSlow")]
+        SttBackendSlow { dropped_chunks: u64 },
+        // The pinned mic is gone. The speaker is still captured and transcribed, with the mic
+        // silent until another device is picked.
+        #[serde(rename = "micDeviceUnavailable")]
+        MicDeviceUnavailable { device: String },
+        // The listener stopped because the backend closed the stream.
+        #[serde(rename = "transcriptionCompleted")]
+        TranscriptionCompleted {},
+        #[serde(rename = "transcriptionFailed")]
+        TranscriptionFailed { reason: String },
+        // Neither audio nor responses came through for the stream timeout. `kind` tells whether
+        // audio stopped arriving, or the backend stopped responding to it.
+        #[serde(rename = "transcriptionTimedOut")]
+        TranscriptionTimedOut {
+            kind: crate::actors::StreamTimeoutKind,
+        },
     }
 }
 
 impl From<(&[f32], &[f32])> for SessionEvent {
     fn from((mic_chunk, speaker_chunk): (&[f32], &[f32])) -> Self {
-        let mic = (mic_chunk
-            .iter()
-            .map(|&x| x.abs())
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(0.0)
-            * 100.0) as u16;
-
-        let speaker = (speaker_chunk
-            .iter()
-            .map(|&x| x.abs())
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(0.0)
-            * 100.0) as u16;
-
-        Self::AudioAmplitude { mic, speaker }
+        Self::AudioAmplitude {
+            mic: peak_level(mic_chunk),
+            speaker: peak_level(speaker_chunk),
+            mic_dbfs: dbfs(mic_chunk),
+            speaker_dbfs: dbfs(speaker_chunk),
+        }
     }
 }
 
+// Meters bottom out here; silence, including an empty chunk, reads as this.
+pub const DBFS_FLOOR: f32 = -60.0;
+
+// RMS level of the chunk relative to full scale, from `DBFS_FLOOR` to 0.
+pub(crate) fn dbfs(chunk: &[f32]) -> f32 {
+    if chunk.is_empty() {
+        return DBFS_FLOOR;
+    }
+
+    let rms = (chunk.iter().map(|&x| x * x).sum::<f32>() / chunk.len() as f32).sqrt();
+    if rms == 0.0 {
+        return DBFS_FLOOR;
+    }
+    (20.0 * rms.log10()).clamp(DBFS_FLOOR, 0.0)
+}
+
+// Peak absolute sample of the chunk, scaled to 0..=100.
+pub(crate) fn peak_level(chunk: &[f32]) -> u16 {
+    (chunk
+        .iter()
+        .map(|&x| x.abs())
+        .max_by(|a, b| a.partial_cmp(b).unwrap())
+        .unwrap_or(0.0)
+        * 100.0) as u16
+}
+
+// Root mean square of the chunk, on the same scale as `peak_level`.
+pub(crate) fn rms_level(chunk: &[f32]) -> u16 {
+    if chunk.is_empty() {
+        return 0;
+    }
+
+    let mean_square = chunk.iter().map(|&x| x * x).sum::<f32>() / chunk.len() as f32;
+    (mean_square.sqrt() * 100.0) as u16
+}
+
 impl From<(&Vec<f32>, &Vec<f32>)> for SessionEvent {
     fn from((mic_chunk, speaker_chunk): (&Vec<f32>, &Vec<f32>)) -> Self {
         Self::from((mic_chunk.as_slice(), speaker_chunk.as_slice()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dbfs() {
+        // A sine's RMS is its amplitude over sqrt(2): -6.02 dB for half scale, minus 3.01 dB.
+        let sine = |amplitude: f32| {
+            (0..16000)
+                .map(|i| (i as f32 / 16000.0 * 440.0 * std::f32::consts::TAU).sin() * amplitude)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(
+            (dbfs(&sine(0.5)) - -9.03).abs() < 0.05,
+            "{}",
+            dbfs(&sine(0.5))
+        );
+        assert!((dbfs(&sine(1.0)) - -3.01).abs() < 0.05);
+
+        assert_eq!(dbfs(&sine(0.0001)), DBFS_FLOOR);
+        assert_eq!(dbfs(&[0.0; 512]), DBFS_FLOOR);
+        assert_eq!(dbfs(&[]), DBFS_FLOOR);
+    }
+}
@@ -0,0 +1,160 @@
+use owhisper_interface::{SpeakerIdentity, Word2};
+
+// A cue is closed when it gets longer than this, or at a pause longer than `MAX_CUE_GAP_S`.
+const MAX_CUE_DURATION_S: f64 = 5.0;
+const MAX_CUE_GAP_S: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum SubtitleFormat {
+    #[serde(rename = "srt")]
+    Srt,
+    #[serde(rename = "vtt")]
+    Vtt,
+}
+
+struct Cue {
+    start_s: f64,
+    end_s: f64,
+    speaker: Option<SpeakerIdentity>,
+    text: Vec<String>,
+}
+
+// Timed against the recording, so the subtitles line up with the exported audio.
+// Words without recording-relative times can't be placed and are skipped.
+pub fn to_subtitles(words: &[Word2], format: SubtitleFormat) -> String {
+    let cues = to_cues(words);
+
+    let body = cues
+        .iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            let timing = format!(
+                "{} --> {}",
+                format_timestamp(cue.start_s, format),
+                format_timestamp(cue.end_s, format)
+            );
+
+            match format {
+                SubtitleFormat::Srt => format!("{}\n{}\n{}\n", i + 1, timing, cue.text.join(" ")),
+                SubtitleFormat::Vtt => format!("{}\n{}\n", timing, cue.text.join(" ")),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match format {
+        SubtitleFormat::Srt => body,
+        SubtitleFormat::Vtt => format!("WEBVTT\n\n{}", body),
+    }
+}
+
+// One line per turn, e.g. `Me: hello there`, in the order the words come in. Words without a
+// speaker get no label.
+pub fn to_plain_text(words: &[Word2]) -> String {
+    let mut turns: Vec<(Option<SpeakerIdentity>, Vec<String>)> = vec![];
+
+    for word in words {
+        match turns.last_mut() {
+            Some((speaker, text)) if *speaker == word.speaker => text.push(word.text.clone()),
+            _ => turns.push((word.speaker.clone(), vec![word.text.clone()])),
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(speaker, text)| match speaker {
+            Some(SpeakerIdentity::Assigned { label, .. }) => {
+                format!("{}: {}", label, text.join(" "))
+            }
+            Some(SpeakerIdentity::Unassigned { index }) => {
+                format!("Speaker {}: {}", index, text.join(" "))
+            }
+            None => text.join(" "),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn to_cues(words: &[Word2]) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = vec![];
+
+    for word in words {
+        let (Some(start_s), Some(end_s)) = (word.recording_start_s, word.recording_end_s) else {
+            continue;
+        };
+
+        match cues.last_mut() {
+            Some(cue)
+                if cue.speaker == word.speaker
+                    && start_s - cue.end_s <= MAX_CUE_GAP_S
+                    && end_s - cue.start_s <= MAX_CUE_DURATION_S =>
+            {
+                cue.end_s = cue.end_s.max(end_s);
+                cue.text.push(word.text.clone());
+            }
+            _ => cues.push(Cue {
+                start_s,
+                end_s,
+                speaker: word.speaker.clone(),
+                text: vec![word.text.clone()],
+            }),
+        }
+    }
+
+    cues
+}
+
+fn format_timestamp(seconds: f64, format: SubtitleFormat) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    let (hours, minutes, secs, ms) = (
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        total_ms % 1000,
+    );
+
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        hours, minutes, secs, separator, ms
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_s: f64, end_s: f64) -> Word2 {
+        Word2 {
+            text: text.to_string(),
+            // Wall-clock based; must not leak into the subtitles.
+            start_ms: Some(1_700_000_000_000 + (start_s * 1000.0) as u64),
+            end_ms: Some(1_700_000_000_000 + (end_s * 1000.0) as u64),
+            recording_start_s: Some(start_s),
+            recording_end_s: Some(end_s),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_subtitles_use_recording_time() {
+        let words = vec![
+            word("hello", 1.0, 1.4),
+            word("there", 1.5, 2.0),
+            word("again", 3.5, 3.9),
+        ];
+
+        assert_eq!(
+            to_subtitles(&words, SubtitleFormat::Srt),
+            "1\n00:00:01,000 --> 00:00:02,000\nhello there\n\n2\n00:00:03,500 --> 00:00:03,900\nagain\n"
+        );
+        assert_eq!(
+            to_subtitles(&words, SubtitleFormat::Vtt),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nhello there\n\n00:00:03.500 --> 00:00:03.900\nagain\n"
+        );
+    }
+}
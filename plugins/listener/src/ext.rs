@@ -1,8 +1,9 @@
-use std::future::Future;
+use std::{collections::HashMap, future::Future};
 
 use futures_util::StreamExt;
 use ractor::{call_t, concurrency, registry, Actor, ActorRef};
 
+use tauri_plugin_store2::StorePluginExt;
 use tauri_specta::Event;
 
 #[cfg(target_os = "macos")]
@@ -12,11 +13,14 @@ use {
 };
 
 use crate::{
-    actors::{SessionActor, SessionArgs, SessionMsg},
-    SessionEvent,
+    actors::{ListenerActor, RecorderActor, SessionActor, SessionArgs, SessionMsg},
+    CostEstimate, MicLevel, RecordingMeta, RecordingPrefs, SessionEvent, StoreKey, SttInfo,
+    SubtitleFormat, TranscriptSnapshot,
 };
 
 pub trait ListenerPluginExt<R: tauri::Runtime> {
+    fn listener_store(&self) -> tauri_plugin_store2::ScopedStore<R, crate::StoreKey>;
+
     fn list_microphone_devices(&self) -> impl Future<Output = Result<Vec<String>, crate::Error>>;
     fn get_current_microphone_device(
         &self,
@@ -24,6 +28,7 @@ pub trait ListenerPluginExt<R: tauri::Runtime> {
     fn set_microphone_device(
         &self,
         device_name: impl Into<String>,
+        pinned: bool,
     ) -> impl Future<Output = Result<(), crate::Error>>;
 
     fn check_microphone_access(&self) -> impl Future<Output = Result<bool, crate::Error>>;
@@ -37,13 +42,91 @@ pub trait ListenerPluginExt<R: tauri::Runtime> {
     fn get_speaker_muted(&self) -> impl Future<Output = bool>;
     fn set_mic_muted(&self, muted: bool) -> impl Future<Output = ()>;
     fn set_speaker_muted(&self, muted: bool) -> impl Future<Output = ()>;
+    fn pause_transcription(&self) -> impl Future<Output = ()>;
+    fn resume_transcription(&self) -> impl Future<Output = ()>;
+    fn set_transcribe_live(&self, enabled: bool) -> impl Future<Output = ()>;
+    fn set_channel_gain(&self, channel: crate::Channel, gain: f32) -> impl Future<Output = ()>;
+    fn set_restore_punctuation(&self, enabled: bool) -> impl Future<Output = ()>;
 
     fn get_state(&self) -> impl Future<Output = crate::fsm::State>;
+    fn is_recording(&self, session_id: impl AsRef<str>) -> impl Future<Output = bool>;
+    fn get_transcript_snapshot(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> impl Future<Output = Option<TranscriptSnapshot>>;
+    fn get_plain_transcript(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> impl Future<Output = Option<String>>;
+    fn get_actor_status(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> impl Future<Output = Option<crate::ActorStatusMap>>;
+    fn export_subtitles(
+        &self,
+        session_id: impl Into<String>,
+        format: SubtitleFormat,
+    ) -> impl Future<Output = Result<String, crate::Error>>;
+    fn get_active_stt_info(&self) -> impl Future<Output = Result<SttInfo, crate::Error>>;
+    fn estimate_stt_cost(
+        &self,
+        duration: std::time::Duration,
+    ) -> impl Future<Output = Result<Option<CostEstimate>, crate::Error>>;
+    fn get_recording_meta(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Option<RecordingMeta>, crate::Error>>;
+    fn find_duplicate_recordings(
+        &self,
+    ) -> impl Future<Output = Result<Vec<Vec<String>>, crate::Error>>;
+    fn retranscribe_missing(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<owhisper_interface::Word2>, crate::Error>>;
+    fn enhance_recording_audio(
+        &self,
+        session_id: impl Into<String>,
+        config: crate::AudioEnhanceConfig,
+    ) -> impl Future<Output = Result<String, crate::Error>>;
+    fn transcribe_uploaded_file(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+        languages: Vec<hypr_language::Language>,
+    ) -> impl Future<Output = Result<String, crate::Error>>;
     fn stop_session(&self) -> impl Future<Output = ()>;
     fn start_session(&self, id: impl Into<String>) -> impl Future<Output = ()>;
+    fn purge_session(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn merge_sessions(
+        &self,
+        primary_id: impl Into<String>,
+        secondary_id: impl Into<String>,
+    ) -> impl Future<Output = Result<hypr_db_user::Session, crate::Error>>;
+    fn start_mic_test(
+        &self,
+        device_name: Option<String>,
+        channel: tauri::ipc::Channel<MicLevel>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn stop_mic_test(&self) -> impl Future<Output = ()>;
+    fn set_pre_roll_seconds(
+        &self,
+        seconds: Option<u64>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+
+    fn get_recording_prefs(&self) -> Result<RecordingPrefs, crate::Error>;
+    fn set_recording_prefs(&self, prefs: RecordingPrefs) -> Result<(), crate::Error>;
+
+    fn get_agc_gain(&self, device: &str) -> Result<Option<f32>, crate::Error>;
+    fn set_agc_gain(&self, device: &str, gain: f32) -> Result<(), crate::Error>;
 }
 
 impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
+    fn listener_store(&self) -> tauri_plugin_store2::ScopedStore<R, crate::StoreKey> {
+        self.scoped_store(crate::PLUGIN_NAME).unwrap()
+    }
+
     #[tracing::instrument(skip_all)]
     async fn list_microphone_devices(&self) -> Result<Vec<String>, crate::Error> {
         Ok(hypr_audio::AudioInput::list_mic_devices())
@@ -51,9 +134,7 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
 
     #[tracing::instrument(skip_all)]
     async fn get_current_microphone_device(&self) -> Result<Option<String>, crate::Error> {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
-
+        if let Some(actor) = SessionActor::active() {
             match call_t!(actor, SessionMsg::GetMicDeviceName, 100) {
                 Ok(device_name) => Ok(device_name),
                 Err(_) => Ok(None),
@@ -67,10 +148,13 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
     async fn set_microphone_device(
         &self,
         device_name: impl Into<String>,
+        pinned: bool,
     ) -> Result<(), crate::Error> {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
-            let _ = actor.cast(SessionMsg::ChangeMicDevice(Some(device_name.into())));
+        if let Some(actor) = SessionActor::active() {
+            let _ = actor.cast(SessionMsg::ChangeMicDevice(
+                Some(device_name.into()),
+                pinned,
+            ));
         }
 
         Ok(())
@@ -188,7 +272,7 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
 
     #[tracing::instrument(skip_all)]
     async fn get_state(&self) -> crate::fsm::State {
-        if let Some(_) = registry::where_is(SessionActor::name()) {
+        if SessionActor::active().is_some() {
             crate::fsm::State::RunningActive
         } else {
             crate::fsm::State::Inactive
@@ -196,10 +280,40 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
     }
 
     #[tracing::instrument(skip_all)]
-    async fn get_mic_muted(&self) -> bool {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
+    async fn is_recording(&self, session_id: impl AsRef<str>) -> bool {
+        SessionActor::is_recording(session_id.as_ref())
+    }
 
+    #[tracing::instrument(skip_all)]
+    async fn get_transcript_snapshot(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> Option<TranscriptSnapshot> {
+        let cell = registry::where_is(SessionActor::name(session_id.as_ref()))?;
+        let actor: ActorRef<SessionMsg> = cell.into();
+
+        call_t!(actor, SessionMsg::GetTranscriptSnapshot, 500).ok()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_plain_transcript(&self, session_id: impl AsRef<str>) -> Option<String> {
+        let cell = registry::where_is(SessionActor::name(session_id.as_ref()))?;
+        let actor: ActorRef<SessionMsg> = cell.into();
+
+        call_t!(actor, SessionMsg::GetPlainTranscript, 1000).ok()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_actor_status(&self, session_id: impl AsRef<str>) -> Option<crate::ActorStatusMap> {
+        let cell = registry::where_is(SessionActor::name(session_id.as_ref()))?;
+        let actor: ActorRef<SessionMsg> = cell.into();
+
+        call_t!(actor, SessionMsg::GetActorStatus, 100).ok()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_mic_muted(&self) -> bool {
+        if let Some(actor) = SessionActor::active() {
             match call_t!(actor, SessionMsg::GetMicMute, 100) {
                 Ok(muted) => muted,
                 Err(_) => false,
@@ -211,9 +325,7 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
 
     #[tracing::instrument(skip_all)]
     async fn get_speaker_muted(&self) -> bool {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
-
+        if let Some(actor) = SessionActor::active() {
             match call_t!(actor, SessionMsg::GetSpeakerMute, 100) {
                 Ok(muted) => muted,
                 Err(_) => false,
@@ -225,49 +337,566 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
 
     #[tracing::instrument(skip_all)]
     async fn set_mic_muted(&self, muted: bool) {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
+        if let Some(actor) = SessionActor::active() {
             let _ = actor.cast(SessionMsg::SetMicMute(muted));
         }
     }
 
     #[tracing::instrument(skip_all)]
     async fn set_speaker_muted(&self, muted: bool) {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
+        if let Some(actor) = SessionActor::active() {
             let _ = actor.cast(SessionMsg::SetSpeakerMute(muted));
         }
     }
 
+    #[tracing::instrument(skip_all)]
+    async fn pause_transcription(&self) {
+        if let Some(actor) = SessionActor::active() {
+            let _ = actor.cast(SessionMsg::PauseTranscription);
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn resume_transcription(&self) {
+        if let Some(actor) = SessionActor::active() {
+            let _ = actor.cast(SessionMsg::ResumeTranscription);
+        }
+    }
+
+    // Only for the running session, which keeps recording either way. `transcribe_live` in the
+    // config decides how the next one starts, and `retranscribe_missing` fills in the rest later.
+    #[tracing::instrument(skip_all)]
+    async fn set_transcribe_live(&self, enabled: bool) {
+        if enabled {
+            self.resume_transcription().await;
+        } else {
+            self.pause_transcription().await;
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn set_channel_gain(&self, channel: crate::Channel, gain: f32) {
+        if let Some(actor) = SessionActor::active() {
+            let _ = actor.cast(SessionMsg::SetChannelGain { channel, gain });
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn set_restore_punctuation(&self, enabled: bool) {
+        if let Some(actor) = SessionActor::active() {
+            let _ = actor.cast(SessionMsg::SetRestorePunctuation(enabled));
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn export_subtitles(
+        &self,
+        session_id: impl Into<String>,
+        format: SubtitleFormat,
+    ) -> Result<String, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let session = self
+            .db_get_session(session_id)
+            .await?
+            .ok_or(crate::Error::NoneSession)?;
+
+        Ok(crate::to_subtitles(&session.words, format))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_active_stt_info(&self) -> Result<SttInfo, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+        use tauri_plugin_local_stt::LocalSttPluginExt;
+
+        let conn = self.get_connection().await?;
+        let price_per_minute = match self.db_user_id().await? {
+            Some(user_id) => self
+                .db_get_config(user_id)
+                .await?
+                .and_then(|c| c.ai.stt_price_per_minute),
+            None => None,
+        };
+        let mut info = SttInfo::from(&tauri_plugin_local_stt::Connection {
+            price_per_minute: conn.price_per_minute.or(price_per_minute),
+            ..conn
+        });
+
+        // Local servers don't take a model parameter; they run the selected local model.
+        if info.is_local && info.model.is_none() {
+            info.model = self.get_local_model().ok().map(|m| m.to_string());
+        }
+
+        Ok(info)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn estimate_stt_cost(
+        &self,
+        duration: std::time::Duration,
+    ) -> Result<Option<CostEstimate>, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let Some(price_per_minute) = self.get_active_stt_info().await?.price_per_minute else {
+            return Ok(None);
+        };
+        let budget_cap = match self.db_user_id().await? {
+            Some(user_id) => self
+                .db_get_config(user_id)
+                .await?
+                .and_then(|c| c.ai.stt_budget_cap),
+            None => None,
+        };
+
+        Ok(Some(
+            crate::SttPricing {
+                price_per_minute,
+                budget_cap,
+            }
+            .estimate(duration),
+        ))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_recording_meta(
+        &self,
+        session_id: impl Into<String>,
+    ) -> Result<Option<RecordingMeta>, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let session_id = session_id.into();
+
+        let bases = self.db_recording_bases().await?;
+        Ok(hypr_recording::find_session_dir(&bases, &session_id)
+            .and_then(crate::actors::read_recording_meta))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn find_duplicate_recordings(&self) -> Result<Vec<Vec<String>>, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let mut recordings: Vec<RecordingMeta> = Vec::new();
+        for base in self.db_recording_bases().await? {
+            let Ok(entries) = std::fs::read_dir(&base) else {
+                continue;
+            };
+
+            for meta in entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| crate::actors::read_recording_meta(e.path()))
+            {
+                if !recordings.iter().any(|r| r.session_id == meta.session_id) {
+                    recordings.push(meta);
+                }
+            }
+        }
+
+        let groups =
+            tokio::task::spawn_blocking(move || crate::fingerprint::group_duplicates(&recordings))
+                .await
+                .map_err(std::io::Error::other)?;
+        Ok(groups)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn retranscribe_missing(
+        &self,
+        session_id: impl Into<String>,
+    ) -> Result<Vec<owhisper_interface::Word2>, crate::Error> {
+        use crate::retranscribe::{fill_gaps, find_gaps, recording_offset_ms, transcribe_samples};
+        use tauri_plugin_db::DatabasePluginExt;
+        use tauri_plugin_local_stt::LocalSttPluginExt;
+
+        let session_id = session_id.into();
+
+        // Its words are still coming in.
+        if SessionActor::is_recording(&session_id) {
+            return Err(crate::Error::SessionInProgress);
+        }
+
+        let mut session = self
+            .db_get_session(&session_id)
+            .await?
+            .ok_or(crate::Error::NoneSession)?;
+
+        let bases = self.db_recording_bases().await?;
+        let session_dir = hypr_recording::find_session_dir(&bases, &session_id)
+            .ok_or(crate::Error::NoneRecording)?;
+        let offset_ms = recording_offset_ms(&session).ok_or(crate::Error::NoneRecording)?;
+
+        let samples = tokio::task::spawn_blocking(move || {
+            crate::actors::read_recording(&session_dir)
+                .map_err(|e| std::io::Error::other(e.to_string()))
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        let duration_ms = samples.len() as u64 * 1000 / crate::pre_roll::SAMPLE_RATE as u64;
+
+        let words = std::mem::take(&mut session.words)
+            .into_iter()
+            .map(|w| match w.recording_start_s {
+                Some(_) => w,
+                None => crate::manager::with_recording_time(w, offset_ms),
+            })
+            .collect::<Vec<_>>();
+        let gaps = find_gaps(&words, duration_ms);
+        if gaps.is_empty() {
+            return Ok(words);
+        }
+        tracing::info!(gaps = ?gaps, "retranscribe_missing");
+
+        let config = self.db_get_config(&session.user_id).await?;
+        let languages = config.as_ref().map_or_else(
+            || vec![hypr_language::ISO639::En.into()],
+            |c| c.general.spoken_languages.clone(),
+        );
+        let conn = self.get_connection().await?;
+        let conn = tauri_plugin_local_stt::Connection {
+            sample_rate: conn
+                .sample_rate
+                .or(config.as_ref().and_then(|c| c.ai.stt_sample_rate)),
+            ..conn
+        };
+
+        session.words = fill_gaps(words, &samples, &gaps, offset_ms, |audio| {
+            transcribe_samples(&conn, &languages, audio)
+        })
+        .await?;
+        self.db_upsert_session(session.clone()).await?;
+
+        Ok(session.words)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn enhance_recording_audio(
+        &self,
+        session_id: impl Into<String>,
+        config: crate::AudioEnhanceConfig,
+    ) -> Result<String, crate::Error> {
+        let session_id = session_id.into();
+
+        // Its last segment is still being written.
+        if SessionActor::is_recording(&session_id) {
+            return Err(crate::Error::SessionInProgress);
+        }
+
+        let bases = self.db_recording_bases().await?;
+        let session_dir = hypr_recording::find_session_dir(&bases, &session_id)
+            .ok_or(crate::Error::NoneRecording)?;
+        let quality = self.get_recording_prefs()?.quality;
+
+        let path = tokio::task::spawn_blocking(move || {
+            crate::enhance::enhance_recording(&session_dir, &config, quality)
+                .map_err(|e| std::io::Error::other(e.to_string()))
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn transcribe_uploaded_file(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+        languages: Vec<hypr_language::Language>,
+    ) -> Result<String, crate::Error> {
+        use crate::retranscribe::transcribe_samples;
+        use tauri_plugin_db::DatabasePluginExt;
+        use tauri_plugin_local_stt::LocalSttPluginExt;
+
+        let path = path.into();
+        let user_id = self.db_user_id().await?.ok_or(crate::Error::NoneUser)?;
+        let config = self.db_get_config(&user_id).await?;
+
+        let languages = match languages.is_empty() {
+            false => languages,
+            true => config.as_ref().map_or_else(
+                || vec![hypr_language::ISO639::En.into()],
+                |c| c.general.spoken_languages.clone(),
+            ),
+        };
+        let conn = self.get_connection().await?;
+        let conn = tauri_plugin_local_stt::Connection {
+            sample_rate: conn
+                .sample_rate
+                .or(config.as_ref().and_then(|c| c.ai.stt_sample_rate)),
+            ..conn
+        };
+
+        let (recordings_dir, _) = crate::actors::resolve_recordings_dir(
+            self.db_recordings_dir().await?.as_deref(),
+            &self.path().app_data_dir()?,
+        );
+
+        let quality = self.get_recording_prefs()?.quality;
+
+        let session =
+            crate::upload::import_file(&path, &recordings_dir, quality, user_id, |audio| {
+                transcribe_samples(&conn, &languages, audio)
+            })
+            .await?;
+        self.db_upsert_session(session.clone()).await?;
+
+        Ok(session.id)
+    }
+
     #[tracing::instrument(skip_all)]
     async fn start_session(&self, session_id: impl Into<String>) {
         let state = self.state::<crate::SharedState>();
-        let guard = state.lock().await;
+
+        let session_id = session_id.into();
+        let token = tokio_util::sync::CancellationToken::new();
+        let done = tokio_util::sync::CancellationToken::new();
+
+        // The session needs the microphone the test may be holding.
+        self.stop_mic_test().await;
+
+        let pre_roll = {
+            let capture = state.lock().await.pre_roll.take();
+            match capture {
+                Some(capture) => capture.finish().await,
+                None => vec![],
+            }
+        };
+
+        // The lock is not held while starting, so that `stop_session` can cancel it.
+        let app = {
+            let mut guard = state.lock().await;
+            guard.pending_starts.push(crate::PendingStart {
+                session_id: session_id.clone(),
+                token: token.clone(),
+                done: done.clone(),
+            });
+            guard.app.clone()
+        };
 
         let _ = Actor::spawn(
-            Some(SessionActor::name()),
+            Some(SessionActor::name(&session_id)),
             SessionActor,
             SessionArgs {
-                app: guard.app.clone(),
-                session_id: session_id.into(),
+                app,
+                session_id: session_id.clone(),
+                token,
+                pre_roll,
             },
         )
         .await;
+
+        let mut guard = state.lock().await;
+        guard.pending_starts.retain(|start| start.session_id != session_id);
+        done.cancel();
+
+        // The session failed to start, so nothing else needs the microphone.
+        if SessionActor::active().is_none() {
+            resume_pre_roll(&mut guard);
+        }
     }
 
     #[tracing::instrument(skip_all)]
     async fn stop_session(&self) {
-        if let Some(cell) = registry::where_is(SessionActor::name()) {
-            let actor: ActorRef<SessionMsg> = cell.into();
+        // Stops the latest session, like `SessionActor::active`. One still starting can not handle
+        // `stop` until its `pre_start` returns, so its start is cancelled instead.
+        {
+            let state = self.state::<crate::SharedState>();
+            let guard = state.lock().await;
+
+            if let Some(start) = guard.pending_starts.last() {
+                tracing::info!(session_id = %start.session_id, "pending_start_cancelled");
+                start.token.cancel();
+                return;
+            }
+        }
 
+        if let Some(actor) = SessionActor::active() {
             if let Ok(_) = actor
                 .stop_and_wait(None, Some(concurrency::Duration::from_secs(3)))
                 .await
             {
                 let state = self.state::<crate::SharedState>();
-                let guard = state.lock().await;
+                let mut guard = state.lock().await;
                 SessionEvent::Inactive {}.emit(&guard.app).unwrap();
+                resume_pre_roll(&mut guard);
             }
         }
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn purge_session(&self, session_id: impl Into<String>) -> Result<(), crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let session_id = session_id.into();
+
+        // Recordings may be in either location, depending on the config at recording time.
+        let session_dirs = self
+            .db_recording_bases()
+            .await?
+            .into_iter()
+            .map(|base| hypr_recording::session_dir(base, &session_id))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| crate::Error::InvalidSessionId(session_id.clone()))?;
+
+        let cancelled_starts = {
+            let state = self.state::<crate::SharedState>();
+            let guard = state.lock().await;
+
+            guard
+                .pending_starts
+                .iter()
+                .filter(|start| start.session_id == session_id)
+                .map(|start| {
+                    start.token.cancel();
+                    start.done.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // An aborted start stops the actors it already started, which may still be writing.
+        for done in cancelled_starts {
+            tokio::time::timeout(std::time::Duration::from_secs(3), done.cancelled())
+                .await
+                .map_err(|_| crate::Error::StopSessionFailed)?;
+        }
+
+        // Stop the recording first, so nothing is written after its files are deleted.
+        if let Some(cell) = registry::where_is(SessionActor::name(&session_id)) {
+            let actor: ActorRef<SessionMsg> = cell.into();
+            actor
+                .stop_and_wait(None, Some(concurrency::Duration::from_secs(3)))
+                .await
+                .map_err(|_| crate::Error::StopSessionFailed)?;
+
+            let state = self.state::<crate::SharedState>();
+            let mut guard = state.lock().await;
+            SessionEvent::Inactive {}.emit(&guard.app).unwrap();
+            resume_pre_roll(&mut guard);
+        }
+
+        for session_dir in session_dirs {
+            crate::actors::remove_recordings(session_dir)?;
+        }
+
+        self.db_purge_session(&session_id).await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn merge_sessions(
+        &self,
+        primary_id: impl Into<String>,
+        secondary_id: impl Into<String>,
+    ) -> Result<hypr_db_user::Session, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let (primary_id, secondary_id) = (primary_id.into(), secondary_id.into());
+
+        // A recording still being written can't be merged, nor a transcript still coming in.
+        let in_progress = |id: &str| {
+            registry::where_is(RecorderActor::name(id)).is_some()
+                || registry::where_is(ListenerActor::name(id)).is_some()
+        };
+        if in_progress(&primary_id) || in_progress(&secondary_id) {
+            return Err(crate::Error::SessionInProgress);
+        }
+
+        let quality = self.get_recording_prefs()?.quality;
+        Ok(self
+            .db_merge_sessions(primary_id, secondary_id, quality)
+            .await?)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn start_mic_test(
+        &self,
+        device_name: Option<String>,
+        channel: tauri::ipc::Channel<MicLevel>,
+    ) -> Result<(), crate::Error> {
+        self.stop_mic_test().await;
+
+        let test = crate::mic_test::MicTest::start(device_name, move |level| {
+            let _ = channel.send(level);
+        })?;
+
+        let state = self.state::<crate::SharedState>();
+        state.lock().await.mic_test = Some(test);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn stop_mic_test(&self) {
+        let state = self.state::<crate::SharedState>();
+        let test = state.lock().await.mic_test.take();
+
+        if let Some(test) = test {
+            test.stop().await;
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn set_pre_roll_seconds(&self, seconds: Option<u64>) -> Result<(), crate::Error> {
+        let state = self.state::<crate::SharedState>();
+
+        let previous = {
+            let mut guard = state.lock().await;
+            guard.pre_roll_seconds = seconds.filter(|s| *s > 0);
+            guard.pre_roll.take()
+        };
+        if let Some(capture) = previous {
+            capture.finish().await;
+        }
+
+        // A running session picks it up once it stops.
+        if SessionActor::active().is_some() {
+            return Ok(());
+        }
+
+        let mut guard = state.lock().await;
+        if let Some(seconds) = guard.pre_roll_seconds {
+            guard.pre_roll = Some(crate::pre_roll::PreRollCapture::start(seconds)?);
+        }
+
+        Ok(())
+    }
+
+    fn get_recording_prefs(&self) -> Result<RecordingPrefs, crate::Error> {
+        Ok(self
+            .listener_store()
+            .get(StoreKey::RecordingPrefs)?
+            .unwrap_or_default())
+    }
+
+    // Applies from the next session on; a running recorder keeps what it started with.
+    fn set_recording_prefs(&self, prefs: RecordingPrefs) -> Result<(), crate::Error> {
+        self.listener_store().set(StoreKey::RecordingPrefs, prefs)?;
+        Ok(())
+    }
+
+    fn get_agc_gain(&self, device: &str) -> Result<Option<f32>, crate::Error> {
+        let gains: Option<HashMap<String, f32>> = self.listener_store().get(StoreKey::AgcGains)?;
+        Ok(gains.and_then(|gains| gains.get(device).copied()))
+    }
+
+    fn set_agc_gain(&self, device: &str, gain: f32) -> Result<(), crate::Error> {
+        let store = self.listener_store();
+        let mut gains: HashMap<String, f32> = store.get(StoreKey::AgcGains)?.unwrap_or_default();
+        gains.insert(device.to_string(), gain);
+        store.set(StoreKey::AgcGains, gains)?;
+        Ok(())
+    }
+}
+
+// Restarts the pre-roll capture if it is enabled, once no session is using the microphone.
+fn resume_pre_roll(state: &mut crate::State) {
+    if state.pre_roll.is_some() {
+        return;
+    }
+
+    if let Some(seconds) = state.pre_roll_seconds {
+        match crate::pre_roll::PreRollCapture::start(seconds) {
+            Ok(capture) => state.pre_roll = Some(capture),
+            Err(e) => tracing::error!(error = ?e, "pre_roll_start_failed"),
+        }
+    }
 }
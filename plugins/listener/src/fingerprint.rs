@@ -0,0 +1,273 @@
+// Coarse enough to survive different mics and codecs, fine enough to tell meetings apart.
+const FRAME_MS: u32 = 250;
+// Energy below this is treated as silence, so noise floors don't tell recordings apart.
+const SILENCE_DB: f32 = -80.0;
+// Two devices rarely start recording at the same moment.
+const MAX_OFFSET_FRAMES: usize = 60_000 / FRAME_MS as usize;
+// Less shared audio than this says too little either way.
+const MIN_OVERLAP_FRAMES: usize = 30_000 / FRAME_MS as usize;
+// Keeps comparing long recordings cheap; ten minutes of envelope is plenty.
+const MAX_COMPARED_FRAMES: usize = 600_000 / FRAME_MS as usize;
+
+// Correlation of the two energy envelopes, at their best alignment. The same meeting recorded
+// twice scores well above this, even from different devices; different meetings stay far below.
+pub const DUPLICATE_SIMILARITY: f64 = 0.9;
+
+// Loudness of a recording over time, one level per frame, in half-dB steps above `SILENCE_DB`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct AudioFingerprint {
+    pub frame_ms: u32,
+    // Hex, one byte per frame.
+    pub envelope: String,
+}
+
+impl AudioFingerprint {
+    fn levels(&self) -> Vec<f64> {
+        // Read back from disk, so a non-ASCII envelope is skipped rather than split mid-char.
+        self.envelope
+            .as_bytes()
+            .chunks_exact(2)
+            .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+            .map(f64::from)
+            .collect()
+    }
+}
+
+pub struct FingerprintBuilder {
+    frame_len: usize,
+    sum_squares: f64,
+    samples: usize,
+    envelope: String,
+}
+
+impl FingerprintBuilder {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            frame_len: (sample_rate as usize * FRAME_MS as usize / 1000).max(1),
+            sum_squares: 0.0,
+            samples: 0,
+            envelope: String::new(),
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.sum_squares += (sample as f64).powi(2);
+            self.samples += 1;
+
+            if self.samples == self.frame_len {
+                let rms = (self.sum_squares / self.samples as f64).sqrt();
+                let db = (20.0 * rms.max(1e-10).log10()) as f32;
+                let level = ((db - SILENCE_DB) * 2.0).clamp(0.0, 255.0) as u8;
+                self.envelope.push_str(&format!("{:02x}", level));
+
+                self.sum_squares = 0.0;
+                self.samples = 0;
+            }
+        }
+    }
+
+    // A trailing partial frame is left out.
+    pub fn finish(self) -> AudioFingerprint {
+        AudioFingerprint {
+            frame_ms: FRAME_MS,
+            envelope: self.envelope,
+        }
+    }
+}
+
+// Pearson correlation of the envelopes, at the offset where they line up best. Gain differences
+// between devices don't matter, only how loudness moves. 0.0 if they can't be compared.
+pub fn similarity(a: &AudioFingerprint, b: &AudioFingerprint) -> f64 {
+    if a.frame_ms != b.frame_ms {
+        return 0.0;
+    }
+
+    let (a, b) = (a.levels(), b.levels());
+    let mut best = 0.0_f64;
+
+    for offset in -(MAX_OFFSET_FRAMES as isize)..=MAX_OFFSET_FRAMES as isize {
+        let (a, b) = if offset >= 0 {
+            (a.get(offset as usize..), Some(&b[..]))
+        } else {
+            (Some(&a[..]), b.get(offset.unsigned_abs()..))
+        };
+        let (Some(a), Some(b)) = (a, b) else {
+            continue;
+        };
+
+        let len = a.len().min(b.len()).min(MAX_COMPARED_FRAMES);
+        if len < MIN_OVERLAP_FRAMES {
+            continue;
+        }
+        best = best.max(correlation(&a[..len], &b[..len]));
+    }
+
+    best
+}
+
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+
+    // Flat envelopes, like pure silence, match anything flat; that says nothing.
+    if var_a == 0.0 || var_b == 0.0 {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+// Groups of two or more session ids whose recordings are duplicates of each other, directly or
+// through another member of the group. Recordings without a fingerprint are left out.
+pub fn group_duplicates(recordings: &[crate::RecordingMeta]) -> Vec<Vec<String>> {
+    let mut group_of = (0..recordings.len()).collect::<Vec<_>>();
+
+    for i in 0..recordings.len() {
+        for j in i + 1..recordings.len() {
+            if group_of[i] != group_of[j] && are_duplicates(&recordings[i], &recordings[j]) {
+                let (from, to) = (group_of[j], group_of[i]);
+                group_of
+                    .iter_mut()
+                    .filter(|g| **g == from)
+                    .for_each(|g| *g = to);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    for group in 0..recordings.len() {
+        let members = (0..recordings.len())
+            .filter(|&i| group_of[i] == group)
+            .map(|i| recordings[i].session_id.clone())
+            .collect::<Vec<_>>();
+        if members.len() > 1 {
+            groups.push(members);
+        }
+    }
+    groups
+}
+
+fn are_duplicates(a: &crate::RecordingMeta, b: &crate::RecordingMeta) -> bool {
+    // Copies of one meeting were recorded at the same time, so recordings that don't overlap are
+    // ruled out before comparing their envelopes.
+    if let (Some(a_start), Some(a_end), Some(b_start), Some(b_end)) =
+        (a.started_at, a.ended_at, b.started_at, b.ended_at)
+    {
+        if a_end < b_start || b_end < a_start {
+            return false;
+        }
+    }
+
+    match (&a.fingerprint, &b.fingerprint) {
+        (Some(a), Some(b)) => similarity(a, b) >= DUPLICATE_SIMILARITY,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    // Speech-like audio: a tone whose loudness changes every half second.
+    fn meeting(seed: u64, seconds: usize) -> Vec<f32> {
+        let mut state = seed;
+        let loudness = (0..seconds * 2)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as f32 / (1u64 << 31) as f32
+            })
+            .collect::<Vec<_>>();
+
+        (0..seconds * SAMPLE_RATE as usize)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                loudness[i * 2 / SAMPLE_RATE as usize] * (t * 220.0 * std::f32::consts::TAU).sin()
+            })
+            .collect()
+    }
+
+    fn recording(session_id: &str, samples: &[f32]) -> crate::RecordingMeta {
+        crate::RecordingMeta {
+            session_id: session_id.to_string(),
+            title: String::new(),
+            started_at: None,
+            ended_at: None,
+            sample_rate: SAMPLE_RATE,
+            duration_ms: samples.len() as u64 * 1000 / SAMPLE_RATE as u64,
+            speakers: vec![],
+            word_count: 0,
+            fingerprint: Some(fingerprint(samples)),
+        }
+    }
+
+    fn fingerprint(samples: &[f32]) -> AudioFingerprint {
+        let mut builder = FingerprintBuilder::new(SAMPLE_RATE);
+        // In chunks that don't line up with frames, as segments are decoded.
+        samples.chunks(1000).for_each(|chunk| builder.push(chunk));
+        builder.finish()
+    }
+
+    #[test]
+    fn test_duplicate_recordings() {
+        let original = meeting(1, 90);
+
+        // The same meeting from a second device: started 3s later, quieter, with some hiss.
+        let mut noise = 7u64;
+        let second_device = original[3 * SAMPLE_RATE as usize..]
+            .iter()
+            .map(|s| {
+                noise = noise.wrapping_mul(6364136223846793005).wrapping_add(1);
+                s * 0.5 + ((noise >> 40) as f32 / (1u64 << 24) as f32 - 0.5) * 0.01
+            })
+            .collect::<Vec<_>>();
+
+        let other_meeting = meeting(2, 90);
+        let silence = vec![0.0; 90 * SAMPLE_RATE as usize];
+
+        let a = fingerprint(&original);
+        let b = fingerprint(&second_device);
+        let c = fingerprint(&other_meeting);
+        assert_eq!(a.envelope.len(), 90 * 1000 / FRAME_MS as usize * 2);
+
+        assert!(
+            similarity(&a, &b) >= DUPLICATE_SIMILARITY,
+            "{}",
+            similarity(&a, &b)
+        );
+        assert!(
+            similarity(&a, &c) < DUPLICATE_SIMILARITY,
+            "{}",
+            similarity(&a, &c)
+        );
+
+        let groups = group_duplicates(&[
+            recording("a", &original),
+            recording("c", &other_meeting),
+            recording("b", &second_device),
+            recording("silence-1", &silence),
+            recording("silence-2", &silence),
+        ]);
+        assert_eq!(groups, vec![vec!["a".to_string(), "b".to_string()]]);
+    }
+
+    #[test]
+    fn test_corrupt_envelope_is_skipped() {
+        let fingerprint = AudioFingerprint {
+            frame_ms: FRAME_MS,
+            envelope: "0aé0bzz".to_string(),
+        };
+        assert_eq!(fingerprint.levels(), vec![10.0, 11.0]);
+    }
+}
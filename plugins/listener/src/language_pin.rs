@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+use hypr_language::{Language, ISO639};
+use owhisper_interface::StreamResponse;
+
+// Detections below this are treated as noise rather than an actual switch of language.
+pub const DEFAULT_LANGUAGE_CONFIDENCE_THRESHOLD: f64 = 0.5;
+
+/// Keeps the transcript on one language while the STT is unsure what it's hearing, so short or
+/// noisy segments don't flip it mid-sentence.
+#[derive(Debug, Clone)]
+pub struct LanguagePin {
+    fallback: Option<Language>,
+    current: Option<Language>,
+    threshold: f64,
+}
+
+impl LanguagePin {
+    pub fn new(fallback: Option<Language>) -> Self {
+        Self {
+            fallback,
+            current: None,
+            threshold: DEFAULT_LANGUAGE_CONFIDENCE_THRESHOLD,
+        }
+    }
+
+    /// The last confidently detected language, or the fallback before there is one.
+    pub fn current(&self) -> Option<&Language> {
+        self.current.as_ref().or(self.fallback.as_ref())
+    }
+
+    /// Language to attribute to a segment the STT detected as `detected`. Confidence is `None`
+    /// when the STT didn't detect the language itself, which is taken as certain.
+    pub fn resolve(&mut self, detected: &str, confidence: Option<f64>) -> Option<Language> {
+        let language = ISO639::from_str(detected).ok().map(Language::from);
+
+        match (language, confidence) {
+            (Some(language), Some(c)) if c >= self.threshold => {
+                self.current = Some(language.clone());
+                Some(language)
+            }
+            (Some(language), None) => {
+                self.current = Some(language.clone());
+                Some(language)
+            }
+            (language, _) => self.current().cloned().or(language),
+        }
+    }
+
+    /// Rewrites the languages of a transcript response to the pinned one.
+    pub fn apply(&mut self, response: &mut StreamResponse) {
+        let StreamResponse::TranscriptResponse { channel, .. } = response else {
+            return;
+        };
+
+        for alternative in channel.alternatives.iter_mut() {
+            let Some(detected) = alternative.languages.first() else {
+                continue;
+            };
+            let Some(language) = self.resolve(detected, alternative.language_confidence) else {
+                continue;
+            };
+
+            let code = language.iso639().code().to_string();
+            alternative.languages = vec![code.clone()];
+            for word in alternative.words.iter_mut() {
+                word.language = Some(code.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_confidence_detections_stay_pinned() {
+        let en = Language::from(ISO639::En);
+        let ko = Language::from(ISO639::Ko);
+
+        let mut pin = LanguagePin::new(Some(en.clone()));
+        assert_eq!(pin.resolve("ko", Some(0.3)), Some(en.clone()));
+        assert_eq!(pin.resolve("ja", Some(0.2)), Some(en.clone()));
+
+        assert_eq!(pin.resolve("ko", Some(0.9)), Some(ko.clone()));
+        for detected in ["en", "ja", "en", "zh"] {
+            assert_eq!(pin.resolve(detected, Some(0.4)), Some(ko.clone()));
+        }
+        assert_eq!(pin.current(), Some(&ko));
+
+        assert_eq!(pin.resolve("en", Some(0.8)), Some(en.clone()));
+        assert_eq!(pin.resolve("en", None), Some(en));
+
+        let mut unpinned = LanguagePin::new(None);
+        assert_eq!(unpinned.resolve("ko", Some(0.1)), Some(ko));
+    }
+}
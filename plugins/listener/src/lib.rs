@@ -2,16 +2,42 @@ use tauri::Manager;
 use tokio::sync::Mutex;
 
 mod actors;
+mod aggregate;
 mod commands;
+mod cost;
+mod disk;
+mod enhance;
 mod error;
 mod events;
+mod export;
 mod ext;
+mod fingerprint;
 pub mod fsm;
+mod language_pin;
 mod manager;
+mod mic_test;
+mod pre_roll;
+mod punctuation;
+mod redemption;
+mod resample;
+mod retranscribe;
+mod sink;
+mod store;
+mod types;
+mod upload;
+mod word_patch;
 
+pub use cost::{CostEstimate, SttPricing};
 pub use error::*;
 pub use events::*;
+pub use export::*;
 pub use ext::*;
+pub use fingerprint::AudioFingerprint;
+pub use manager::{to_conversation, MIC_LABEL, SPEAKER_LABEL};
+pub use mic_test::MicLevel;
+pub use store::*;
+pub use types::*;
+pub use word_patch::WordOp;
 
 const PLUGIN_NAME: &str = "listener";
 
@@ -19,11 +45,25 @@ pub type SharedState = Mutex<State>;
 
 pub struct State {
     app: tauri::AppHandle,
+    // Sessions whose actors are still being set up, so a quick stop can abort them. Oldest first.
+    pending_starts: Vec<PendingStart>,
+    mic_test: Option<mic_test::MicTest>,
+    // Off unless the user opted in, since it listens while no session is running.
+    pre_roll_seconds: Option<u64>,
+    pre_roll: Option<pre_roll::PreRollCapture>,
+}
+
+struct PendingStart {
+    session_id: String,
+    // Aborts the start.
+    token: tokio_util::sync::CancellationToken,
+    // Cancelled once the start has returned, which for an aborted one is after its teardown.
+    done: tokio_util::sync::CancellationToken,
 }
 
 impl State {
     pub async fn get_state(&self) -> fsm::State {
-        if let Some(_) = ractor::registry::where_is(actors::SessionActor::name()) {
+        if actors::SessionActor::active().is_some() {
             crate::fsm::State::RunningActive
         } else {
             crate::fsm::State::Inactive
@@ -48,9 +88,34 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::set_mic_muted::<tauri::Wry>,
             commands::get_speaker_muted::<tauri::Wry>,
             commands::set_speaker_muted::<tauri::Wry>,
+            commands::pause_transcription::<tauri::Wry>,
+            commands::resume_transcription::<tauri::Wry>,
+            commands::set_transcribe_live::<tauri::Wry>,
+            commands::set_channel_gain::<tauri::Wry>,
+            commands::set_restore_punctuation::<tauri::Wry>,
             commands::start_session::<tauri::Wry>,
             commands::stop_session::<tauri::Wry>,
+            commands::purge_session::<tauri::Wry>,
+            commands::merge_sessions::<tauri::Wry>,
+            commands::start_mic_test::<tauri::Wry>,
+            commands::stop_mic_test::<tauri::Wry>,
+            commands::set_pre_roll_seconds::<tauri::Wry>,
             commands::get_state::<tauri::Wry>,
+            commands::is_recording::<tauri::Wry>,
+            commands::get_transcript_snapshot::<tauri::Wry>,
+            commands::export_subtitles::<tauri::Wry>,
+            commands::get_active_stt_info::<tauri::Wry>,
+            commands::estimate_stt_cost::<tauri::Wry>,
+            commands::get_recording_meta::<tauri::Wry>,
+            commands::find_duplicate_recordings::<tauri::Wry>,
+            commands::retranscribe_missing::<tauri::Wry>,
+            commands::transcribe_uploaded_file::<tauri::Wry>,
+            commands::get_recording_prefs::<tauri::Wry>,
+            commands::set_recording_prefs::<tauri::Wry>,
+            commands::list_recording_formats,
+            commands::get_plain_transcript::<tauri::Wry>,
+            commands::get_actor_status::<tauri::Wry>,
+            commands::enhance_recording_audio::<tauri::Wry>,
         ])
         .events(tauri_specta::collect_events![SessionEvent])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
@@ -66,7 +131,13 @@ pub fn init() -> tauri::plugin::TauriPlugin<tauri::Wry> {
 
             let app_handle = app.app_handle().clone();
 
-            let state: SharedState = Mutex::new(State { app: app_handle });
+            let state: SharedState = Mutex::new(State {
+                app: app_handle,
+                pending_starts: Default::default(),
+                mic_test: None,
+                pre_roll_seconds: None,
+                pre_roll: None,
+            });
 
             app.manage(state);
             Ok(())
@@ -2,10 +2,71 @@ use std::collections::HashMap;
 
 pub type WordsByChannel = HashMap<usize, Vec<owhisper_interface::Word>>;
 
+// Two words on different channels are treated as the same utterance when their text matches
+// and their time ranges overlap by at least this fraction of the shorter word.
+const OVERLAP_MIN_RATIO: f64 = 0.5;
+// Zero-length words (start == end) are common; widen them by this much (in seconds) before comparing.
+const OVERLAP_EPSILON_S: f64 = 0.02;
+// How long (in seconds) finalized words are remembered for cross-channel comparison.
+const FINAL_TAIL_WINDOW_S: f64 = 10.0;
+// How far apart partial word boundaries may jitter between responses and still be one boundary.
+pub const DEFAULT_PARTIAL_MERGE_TOLERANCE: std::time::Duration =
+    std::time::Duration::from_millis(50);
+
+// How the tokens of a response are joined into words. Backends often split words into tokens
+// ("don", "'t"), and what needs joining depends on the language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordJoinRule {
+    // A token starting with `'` continues the previous word, as in contractions.
+    pub leading_apostrophe: bool,
+    // A word ending in `-` continues with the next token, as in "well-" + "known".
+    pub trailing_hyphen: bool,
+    // For languages written without spaces between tokens (e.g. Korean). A token only starts a
+    // new word when the backend put whitespace before it. Responses without any such whitespace
+    // already come as whole words, and are left alone.
+    pub unspaced: bool,
+}
+
+impl Default for WordJoinRule {
+    fn default() -> Self {
+        Self {
+            leading_apostrophe: true,
+            trailing_hyphen: false,
+            unspaced: false,
+        }
+    }
+}
+
+impl WordJoinRule {
+    pub fn for_language(language: &hypr_language::Language) -> Self {
+        use hypr_language::ISO639;
+
+        match **language {
+            ISO639::Ko | ISO639::Ja | ISO639::Zh | ISO639::Th => Self {
+                leading_apostrophe: false,
+                trailing_hyphen: false,
+                unspaced: true,
+            },
+            _ => Self::default(),
+        }
+    }
+
+    fn joins(&self, previous: &str, token: &str, spaced: bool) -> bool {
+        (self.leading_apostrophe && token.starts_with('\''))
+            || (self.trailing_hyphen && previous.ends_with('-'))
+            || (self.unspaced && !spaced)
+    }
+}
+
 #[derive(Default)]
 pub struct TranscriptManagerBuilder {
     manager_offset: Option<u64>,
     partial_words_by_channel: Option<WordsByChannel>,
+    final_tail_by_channel: Option<WordsByChannel>,
+    dedupe_overlaps: bool,
+    include_events: Option<bool>,
+    word_join_rule: Option<WordJoinRule>,
+    partial_merge_tolerance: Option<std::time::Duration>,
 }
 
 impl TranscriptManagerBuilder {
@@ -20,11 +81,64 @@ impl TranscriptManagerBuilder {
         self
     }
 
+    // The last finalized words of a previous manager, e.g. `final_tail_by_channel` of the one a
+    // restarted listener replaces. Words the backend finalizes again are then dropped.
+    pub fn with_existing_final_tail(mut self, m: impl Into<WordsByChannel>) -> Self {
+        self.final_tail_by_channel = Some(m.into());
+        self
+    }
+
+    // When the same words show up on more than one channel, keep only the higher-confidence one.
+    pub fn with_confidence_dedupe(mut self, enabled: bool) -> Self {
+        self.dedupe_overlaps = enabled;
+        self
+    }
+
+    // Non-speech tokens like `[laughter]` are kept by default, tagged as `WordKind::Event` once
+    // converted to `Word2`. Disabling this drops them instead.
+    pub fn with_events(mut self, enabled: bool) -> Self {
+        self.include_events = Some(enabled);
+        self
+    }
+
+    pub fn with_word_join_rule(mut self, rule: WordJoinRule) -> Self {
+        self.word_join_rule = Some(rule);
+        self
+    }
+
+    // Existing partial words ending up to this much after new partials start (or starting this
+    // much before they end) are kept next to them, instead of being replaced. Zero by default.
+    pub fn with_partial_merge_tolerance(mut self, tolerance: std::time::Duration) -> Self {
+        self.partial_merge_tolerance = Some(tolerance);
+        self
+    }
+
     pub fn build(self) -> TranscriptManager {
+        let final_tail_by_channel: WordsByChannel = self
+            .final_tail_by_channel
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(channel_idx, mut words)| {
+                if let Some(last_end) = words.iter().map(|w| w.end).reduce(f64::max) {
+                    words.retain(|w| w.end >= last_end - FINAL_TAIL_WINDOW_S);
+                }
+                (channel_idx, words)
+            })
+            .collect();
+
         TranscriptManager {
             id: uuid::Uuid::new_v4(),
             partial_words_by_channel: self.partial_words_by_channel.unwrap_or_default(),
             manager_offset: self.manager_offset.unwrap_or(0),
+            dedupe_overlaps: self.dedupe_overlaps,
+            include_events: self.include_events.unwrap_or(true),
+            replayed_tail_by_channel: final_tail_by_channel.clone(),
+            final_tail_by_channel,
+            word_join_rule: self.word_join_rule.unwrap_or_default(),
+            partial_merge_tolerance_s: self
+                .partial_merge_tolerance
+                .unwrap_or_default()
+                .as_secs_f64(),
         }
     }
 }
@@ -33,6 +147,15 @@ pub struct TranscriptManager {
     pub id: uuid::Uuid,
     pub partial_words_by_channel: WordsByChannel,
     pub manager_offset: u64,
+    dedupe_overlaps: bool,
+    include_events: bool,
+    // Finalized words of the last `FINAL_TAIL_WINDOW_S` seconds.
+    pub final_tail_by_channel: WordsByChannel,
+    // The tail this manager was seeded with. Until the backend finalizes past it, words
+    // overlapping it are audio heard again after a restart.
+    replayed_tail_by_channel: WordsByChannel,
+    word_join_rule: WordJoinRule,
+    partial_merge_tolerance_s: f64,
 }
 
 impl TranscriptManager {
@@ -41,7 +164,7 @@ impl TranscriptManager {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Diff {
     pub partial_words: HashMap<usize, Vec<owhisper_interface::Word>>,
     pub final_words: HashMap<usize, Vec<owhisper_interface::Word>>,
@@ -106,14 +229,16 @@ impl TranscriptManager {
                     .clone()
                     .into_iter()
                     .filter_map(|mut w| {
+                        let spaced = w.word.starts_with(char::is_whitespace);
                         w.word = w.word.trim().to_string();
                         if w.word.is_empty() {
                             None
                         } else {
-                            Some(w)
+                            Some((w, spaced))
                         }
                     })
-                    .map(|mut w| {
+                    .filter(|(w, _)| self.include_events || w.kind().is_speech())
+                    .map(|(mut w, spaced)| {
                         if w.speaker.is_none() {
                             let speaker = channel_index.first().unwrap().clone();
                             w.speaker = Some(speaker);
@@ -124,25 +249,63 @@ impl TranscriptManager {
 
                         w.start = start_ms / 1000.0;
                         w.end = end_ms / 1000.0;
-                        w
+                        (w, spaced)
                     })
                     .collect::<Vec<_>>();
 
+                let rule = WordJoinRule {
+                    unspaced: self.word_join_rule.unspaced && ws.iter().any(|(_, spaced)| *spaced),
+                    ..self.word_join_rule
+                };
+
                 let mut i = 1;
                 while i < ws.len() {
-                    if ws[i].word.starts_with('\'') {
-                        let current_word = ws[i].word.clone();
-                        let current_end = ws[i].end;
-                        ws[i - 1].word.push_str(&current_word);
-                        ws[i - 1].end = current_end;
-                        ws.remove(i);
+                    if rule.joins(&ws[i - 1].0.word, &ws[i].0.word, ws[i].1) {
+                        let (current, _) = ws.remove(i);
+                        ws[i - 1].0.word.push_str(&current.word);
+                        ws[i - 1].0.end = current.end;
                     } else {
                         i += 1;
                     }
                 }
 
-                ws
+                ws.into_iter().map(|(w, _)| w).collect::<Vec<_>>()
             };
+
+            // A backend hearing audio again, e.g. replayed after a reconnect, finalizes it again.
+            let words = words
+                .into_iter()
+                .filter(|w| {
+                    !self
+                        .replayed_tail_by_channel
+                        .get(&channel_idx)
+                        .is_some_and(|tail| tail.iter().any(|other| is_overlap(w, other)))
+                })
+                .collect::<Vec<_>>();
+
+            let words = if self.dedupe_overlaps {
+                self.resolve_overlaps(channel_idx, words)
+            } else {
+                words
+            };
+
+            if is_final {
+                if let Some(last_end) = words.last().map(|w| w.end) {
+                    let replay_done = self
+                        .replayed_tail_by_channel
+                        .get(&channel_idx)
+                        .and_then(|tail| tail.iter().map(|w| w.end).reduce(f64::max))
+                        .is_some_and(|replay_end| last_end > replay_end);
+                    if replay_done {
+                        self.replayed_tail_by_channel.remove(&channel_idx);
+                    }
+
+                    let tail = self.final_tail_by_channel.entry(channel_idx).or_default();
+                    tail.extend(words.iter().cloned());
+                    tail.retain(|w| w.end >= last_end - FINAL_TAIL_WINDOW_S);
+                }
+            }
+
             // needed for deepgram
             if words.is_empty() {
                 return Diff {
@@ -182,7 +345,7 @@ impl TranscriptManager {
                         merged.extend(
                             channel_partial_words
                                 .iter()
-                                .filter(|w| w.end <= first_start)
+                                .filter(|w| w.end <= first_start + self.partial_merge_tolerance_s)
                                 .cloned(),
                         );
                     }
@@ -191,7 +354,7 @@ impl TranscriptManager {
                         merged.extend(
                             channel_partial_words
                                 .iter()
-                                .filter(|w| w.start >= last_end)
+                                .filter(|w| w.start >= last_end - self.partial_merge_tolerance_s)
                                 .cloned(),
                         );
                     }
@@ -212,6 +375,51 @@ impl TranscriptManager {
         }
     }
 
+    // Words already emitted as final can not be retracted, so a final word on another channel
+    // only ever causes the incoming word to be dropped. Partial words on other channels are
+    // removed when the incoming word has higher confidence.
+    fn resolve_overlaps(
+        &mut self,
+        channel_idx: usize,
+        words: Vec<owhisper_interface::Word>,
+    ) -> Vec<owhisper_interface::Word> {
+        words
+            .into_iter()
+            .filter(|w| {
+                let beaten_by_final = self
+                    .final_tail_by_channel
+                    .iter()
+                    .filter(|(idx, _)| **idx != channel_idx)
+                    .flat_map(|(_, ws)| ws.iter())
+                    .any(|other| is_overlap(w, other) && other.confidence >= w.confidence);
+
+                if beaten_by_final {
+                    return false;
+                }
+
+                let mut beaten_by_partial = false;
+                for (_, partials) in self
+                    .partial_words_by_channel
+                    .iter_mut()
+                    .filter(|(idx, _)| **idx != channel_idx)
+                {
+                    partials.retain(|other| {
+                        if !is_overlap(w, other) {
+                            return true;
+                        }
+                        if other.confidence > w.confidence {
+                            beaten_by_partial = true;
+                            return true;
+                        }
+                        false
+                    });
+                }
+
+                !beaten_by_partial
+            })
+            .collect()
+    }
+
     fn log(id: uuid::Uuid, response: &owhisper_interface::StreamResponse) {
         use std::fs::OpenOptions;
         use std::io::Write;
@@ -228,6 +436,114 @@ impl TranscriptManager {
     }
 }
 
+fn is_overlap(a: &owhisper_interface::Word, b: &owhisper_interface::Word) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+
+    if normalize(&a.word) != normalize(&b.word) {
+        return false;
+    }
+
+    let (a_start, a_end) = (a.start - OVERLAP_EPSILON_S, a.end + OVERLAP_EPSILON_S);
+    let (b_start, b_end) = (b.start - OVERLAP_EPSILON_S, b.end + OVERLAP_EPSILON_S);
+
+    let overlap = a_end.min(b_end) - a_start.max(b_start);
+    let shorter = (a_end - a_start).min(b_end - b_start);
+
+    overlap > 0.0 && overlap >= shorter * OVERLAP_MIN_RATIO
+}
+
+// `recording_offset_ms` is the wall-clock time (in ms) of the recording's first sample.
+pub fn with_recording_time(
+    mut word: owhisper_interface::Word2,
+    recording_offset_ms: u64,
+) -> owhisper_interface::Word2 {
+    let to_recording_s = |ms: u64| ms.saturating_sub(recording_offset_ms) as f64 / 1000.0;
+
+    word.recording_start_s = word.start_ms.map(to_recording_s);
+    word.recording_end_s = word.end_ms.map(to_recording_s);
+    word
+}
+
+// The manager tags every word with its channel as an unassigned speaker index, so that is used
+// to recover the channel. Words without one (e.g. already assigned to a person) go to channel 0.
+pub fn group_by_channel(
+    words: &[owhisper_interface::Word2],
+) -> HashMap<usize, Vec<owhisper_interface::Word2>> {
+    let mut grouped: HashMap<usize, Vec<owhisper_interface::Word2>> = HashMap::new();
+
+    for word in words {
+        let channel_idx = match word.speaker {
+            Some(owhisper_interface::SpeakerIdentity::Unassigned { index }) => index as usize,
+            _ => 0,
+        };
+        grouped.entry(channel_idx).or_default().push(word.clone());
+    }
+
+    grouped
+}
+
+// Merges all channels into a single list, ordered by start time.
+pub fn flatten(
+    words: HashMap<usize, Vec<owhisper_interface::Word2>>,
+) -> Vec<owhisper_interface::Word2> {
+    let mut channels = words.into_iter().collect::<Vec<_>>();
+    channels.sort_by_key(|(channel_idx, _)| *channel_idx);
+
+    let mut flattened = channels
+        .into_iter()
+        .flat_map(|(_, words)| words)
+        .collect::<Vec<_>>();
+    // Stable, so words without a start time and ties keep their channel order.
+    flattened.sort_by_key(|w| w.start_ms.unwrap_or(0));
+
+    flattened
+}
+
+// Who is on each channel of the separate-stream path: the user on the mic, everyone else on the
+// speaker.
+pub const MIC_LABEL: &str = "Me";
+pub const SPEAKER_LABEL: &str = "Them";
+
+// Merges the channels into one conversation, ordered by start time, with each word labeled by
+// its channel. Words already assigned to a person keep their speaker.
+pub fn to_conversation(
+    words: HashMap<usize, Vec<owhisper_interface::Word2>>,
+) -> Vec<owhisper_interface::Word2> {
+    let labeled = words
+        .into_iter()
+        .map(|(channel_idx, words)| {
+            let (id, label) = match channel_idx {
+                0 => ("mic", MIC_LABEL),
+                _ => ("speaker", SPEAKER_LABEL),
+            };
+
+            let words = words
+                .into_iter()
+                .map(|mut word| {
+                    if !matches!(
+                        word.speaker,
+                        Some(owhisper_interface::SpeakerIdentity::Assigned { .. })
+                    ) {
+                        word.speaker = Some(owhisper_interface::SpeakerIdentity::Assigned {
+                            id: id.to_string(),
+                            label: label.to_string(),
+                        });
+                    }
+                    word
+                })
+                .collect();
+            (channel_idx, words)
+        })
+        .collect();
+
+    flatten(labeled)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,4 +606,401 @@ mod tests {
 
         test_transcript!(test_council_011320_2022003V, "council_011320_2022003V");
     }
+
+    fn response(
+        channel_idx: i32,
+        is_final: bool,
+        words: &[(&str, f64, f64, f64)],
+    ) -> owhisper_interface::StreamResponse {
+        let words = words
+            .iter()
+            .map(|(word, start, end, confidence)| owhisper_interface::Word {
+                word: word.to_string(),
+                start: *start,
+                end: *end,
+                confidence: *confidence,
+                speaker: None,
+                punctuated_word: None,
+                language: None,
+            })
+            .collect::<Vec<_>>();
+
+        owhisper_interface::StreamResponse::TranscriptResponse {
+            type_field: "Results".to_string(),
+            start: 0.0,
+            duration: 0.0,
+            is_final,
+            speech_final: is_final,
+            from_finalize: false,
+            channel: owhisper_interface::Channel {
+                alternatives: vec![owhisper_interface::Alternatives {
+                    transcript: words
+                        .iter()
+                        .map(|w| w.word.clone())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    words,
+                    confidence: 0.0,
+                    languages: vec![],
+                    language_confidence: None,
+                }],
+            },
+            metadata: owhisper_interface::Metadata::default(),
+            channel_index: vec![channel_idx, 2],
+        }
+    }
+
+    #[test]
+    fn test_confidence_dedupe_partial() {
+        let mut manager = TranscriptManager::builder()
+            .with_confidence_dedupe(true)
+            .build();
+
+        manager.append(response(0, false, &[("hello", 1.0, 1.4, 0.4)]));
+        let diff = manager.append(response(1, false, &[("Hello", 1.05, 1.4, 0.9)]));
+
+        assert_eq!(diff.partial_content().get(&0).unwrap(), "");
+        assert_eq!(diff.partial_content().get(&1).unwrap(), "Hello");
+
+        let diff = manager.append(response(0, false, &[("hello", 1.0, 1.4, 0.5)]));
+        assert_eq!(diff.partial_content().get(&0).unwrap(), "");
+        assert_eq!(diff.partial_content().get(&1).unwrap(), "Hello");
+    }
+
+    #[test]
+    fn test_confidence_dedupe_final() {
+        let mut manager = TranscriptManager::builder()
+            .with_confidence_dedupe(true)
+            .build();
+
+        let diff = manager.append(response(1, true, &[("world", 2.0, 2.3, 0.95)]));
+        assert_eq!(diff.final_content().get(&1).unwrap(), "world");
+
+        let diff = manager.append(response(0, true, &[("world", 2.0, 2.3, 0.6)]));
+        assert!(diff.final_content().is_empty());
+    }
+
+    #[test]
+    fn test_confidence_dedupe_disabled() {
+        let mut manager = TranscriptManager::builder().build();
+
+        manager.append(response(0, false, &[("hello", 1.0, 1.4, 0.4)]));
+        let diff = manager.append(response(1, false, &[("hello", 1.0, 1.4, 0.9)]));
+
+        assert_eq!(diff.partial_content().get(&0).unwrap(), "hello");
+        assert_eq!(diff.partial_content().get(&1).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_events_are_tagged() {
+        let words = [
+            ("that's", 1.0, 1.3, 0.9),
+            ("funny", 1.3, 1.6, 0.9),
+            ("[laughter]", 1.7, 2.5, 0.9),
+        ];
+
+        let mut manager = TranscriptManager::builder().build();
+        let diff = manager.append(response(0, true, &words));
+
+        let tagged = diff.final_words[&0]
+            .iter()
+            .cloned()
+            .map(owhisper_interface::Word2::from)
+            .map(|w| (w.text, w.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tagged,
+            vec![
+                ("that's".to_string(), owhisper_interface::WordKind::Speech),
+                ("funny".to_string(), owhisper_interface::WordKind::Speech),
+                (
+                    "[laughter]".to_string(),
+                    owhisper_interface::WordKind::Event
+                ),
+            ]
+        );
+
+        let mut manager = TranscriptManager::builder().with_events(false).build();
+        let diff = manager.append(response(0, true, &words));
+        assert_eq!(diff.final_content().get(&0).unwrap(), "that's funny");
+    }
+
+    #[test]
+    fn test_events_are_classified_on_punctuated_word() {
+        let mut laughter = response(
+            0,
+            true,
+            &[("okay", 1.0, 1.3, 0.9), ("laughter", 1.7, 2.5, 0.9)],
+        );
+        if let owhisper_interface::StreamResponse::TranscriptResponse { channel, .. } =
+            &mut laughter
+        {
+            channel.alternatives[0].words[1].punctuated_word = Some("[Laughter]".to_string());
+        }
+
+        let mut manager = TranscriptManager::builder().build();
+        let diff = manager.append(laughter.clone());
+        let tagged = diff.final_words[&0]
+            .iter()
+            .cloned()
+            .map(owhisper_interface::Word2::from)
+            .map(|w| (w.text, w.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tagged[1],
+            (
+                "[Laughter]".to_string(),
+                owhisper_interface::WordKind::Event
+            )
+        );
+
+        let mut manager = TranscriptManager::builder().with_events(false).build();
+        let diff = manager.append(laughter);
+        assert_eq!(diff.final_content().get(&0).unwrap(), "okay");
+    }
+
+    #[test]
+    fn test_final_tail_dedupes_after_restart() {
+        let mut manager = TranscriptManager::builder().build();
+        manager.append(response(
+            0,
+            true,
+            &[("we", 1.0, 1.2, 0.9), ("ship", 1.2, 1.5, 0.9)],
+        ));
+
+        // The listener restarts, and the backend hears the last seconds again.
+        let mut manager = TranscriptManager::builder()
+            .with_existing_final_tail(manager.final_tail_by_channel.clone())
+            .build();
+
+        let diff = manager.append(response(
+            0,
+            false,
+            &[("ship", 1.21, 1.5, 0.8), ("on", 1.6, 1.7, 0.8)],
+        ));
+        assert_eq!(diff.partial_content().get(&0).unwrap(), "on");
+
+        let diff = manager.append(response(
+            0,
+            true,
+            &[
+                ("we", 1.0, 1.2, 0.9),
+                ("ship", 1.2, 1.5, 0.9),
+                ("on", 1.6, 1.7, 0.9),
+                ("Friday", 1.7, 2.1, 0.9),
+            ],
+        ));
+        assert_eq!(diff.final_content().get(&0).unwrap(), "on Friday");
+
+        // Only the same channel's finals count; the other channel is left to confidence dedupe.
+        let diff = manager.append(response(1, true, &[("ship", 1.2, 1.5, 0.9)]));
+        assert_eq!(diff.final_content().get(&1).unwrap(), "ship");
+
+        // Past the seeded tail, the replay is over and finals go through as sent.
+        let diff = manager.append(response(0, true, &[("Friday", 1.7, 2.1, 0.9)]));
+        assert_eq!(diff.final_content().get(&0).unwrap(), "Friday");
+    }
+
+    #[test]
+    fn test_final_tail_without_seed_keeps_finals() {
+        let mut manager = TranscriptManager::builder().build();
+
+        let diff = manager.append(response(0, true, &[("no", 1.0, 1.2, 0.9)]));
+        assert_eq!(diff.final_content().get(&0).unwrap(), "no");
+
+        // A fresh manager has nothing replayed, so a final matching its own tail is kept.
+        let diff = manager.append(response(0, true, &[("no", 1.1, 1.3, 0.9)]));
+        assert_eq!(diff.final_content().get(&0).unwrap(), "no");
+    }
+
+    #[test]
+    fn test_jittered_partials_merge_stably() {
+        let mut manager = TranscriptManager::builder()
+            .with_partial_merge_tolerance(DEFAULT_PARTIAL_MERGE_TOLERANCE)
+            .build();
+
+        let updates: &[&[(&str, f64, f64, f64)]] = &[
+            &[("we", 1.0, 1.3, 0.9), ("ship", 1.3, 1.6, 0.9)],
+            // Starts a bit before "ship" ended last time.
+            &[("the", 1.58, 1.8, 0.9)],
+            // A revision of the last two words, again slightly off.
+            &[("the", 1.61, 1.79, 0.9), ("beta", 1.79, 2.2, 0.9)],
+            // A revision of the first word, ending a bit after "ship" starts.
+            &[("we", 1.0, 1.33, 0.9)],
+        ];
+
+        let mut contents = vec![];
+        for words in updates {
+            let diff = manager.append(response(0, false, words));
+            contents.push(diff.partial_content().get(&0).unwrap().clone());
+        }
+        assert_eq!(
+            contents,
+            vec![
+                "we ship",
+                "we ship the",
+                "we ship the beta",
+                "we ship the beta"
+            ]
+        );
+
+        // Without tolerance, the jitter drops words.
+        let mut manager = TranscriptManager::builder().build();
+        manager.append(response(0, false, updates[0]));
+        let diff = manager.append(response(0, false, updates[1]));
+        assert_eq!(diff.partial_content().get(&0).unwrap(), "we the");
+    }
+
+    #[test]
+    fn test_english_contractions_are_joined() {
+        let mut manager = TranscriptManager::builder()
+            .with_word_join_rule(WordJoinRule::for_language(
+                &hypr_language::ISO639::En.into(),
+            ))
+            .build();
+
+        let diff = manager.append(response(
+            0,
+            true,
+            &[
+                ("we", 1.0, 1.2, 0.9),
+                ("don", 1.2, 1.4, 0.9),
+                ("'t", 1.4, 1.5, 0.9),
+                ("ship", 1.5, 1.8, 0.9),
+                ("well-", 1.8, 2.0, 0.9),
+                ("known", 2.0, 2.3, 0.9),
+            ],
+        ));
+        assert_eq!(
+            diff.final_content().get(&0).unwrap(),
+            "we don't ship well- known"
+        );
+        let dont = &diff.final_words[&0][1];
+        assert_eq!((dont.start, dont.end), (1.2, 1.5));
+
+        let mut manager = TranscriptManager::builder()
+            .with_word_join_rule(WordJoinRule {
+                trailing_hyphen: true,
+                ..Default::default()
+            })
+            .build();
+        let diff = manager.append(response(
+            0,
+            true,
+            &[("well-", 1.8, 2.0, 0.9), ("known", 2.0, 2.3, 0.9)],
+        ));
+        assert_eq!(diff.final_content().get(&0).unwrap(), "well-known");
+    }
+
+    #[test]
+    fn test_unspaced_language_is_joined() {
+        let rule = WordJoinRule::for_language(&hypr_language::ISO639::Ko.into());
+
+        // Subword tokens, with leading whitespace where a new word starts.
+        let mut manager = TranscriptManager::builder()
+            .with_word_join_rule(rule)
+            .build();
+        let diff = manager.append(response(
+            0,
+            true,
+            &[
+                (" 안녕", 1.0, 1.2, 0.9),
+                ("하세요", 1.2, 1.6, 0.9),
+                (" 반갑", 1.7, 1.9, 0.9),
+                ("습니다", 1.9, 2.3, 0.9),
+            ],
+        ));
+        assert_eq!(
+            diff.final_content().get(&0).unwrap(),
+            "안녕하세요 반갑습니다"
+        );
+        let first = &diff.final_words[&0][0];
+        assert_eq!((first.start, first.end), (1.0, 1.6));
+
+        // Whole words, as some backends send them, stay apart.
+        let mut manager = TranscriptManager::builder()
+            .with_word_join_rule(rule)
+            .build();
+        let diff = manager.append(response(
+            0,
+            true,
+            &[("안녕하세요", 1.0, 1.6, 0.9), ("반갑습니다", 1.7, 2.3, 0.9)],
+        ));
+        assert_eq!(
+            diff.final_content().get(&0).unwrap(),
+            "안녕하세요 반갑습니다"
+        );
+    }
+
+    fn word2(text: &str, channel_idx: u8, start_ms: u64) -> owhisper_interface::Word2 {
+        owhisper_interface::Word2 {
+            text: text.to_string(),
+            speaker: Some(owhisper_interface::SpeakerIdentity::Unassigned { index: channel_idx }),
+            confidence: None,
+            start_ms: Some(start_ms),
+            end_ms: Some(start_ms + 300),
+            recording_start_s: None,
+            recording_end_s: None,
+            kind: owhisper_interface::WordKind::Speech,
+        }
+    }
+
+    #[test]
+    fn test_flatten_orders_by_start() {
+        let words = vec![
+            word2("hello", 0, 0),
+            word2("there", 0, 1000),
+            word2("hi", 1, 500),
+            word2("again", 1, 1500),
+        ];
+
+        let grouped = group_by_channel(&words);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[&0].len(), 2);
+        assert_eq!(grouped[&1].len(), 2);
+
+        let flattened = flatten(grouped);
+        assert_eq!(
+            flattened
+                .iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["hello", "hi", "there", "again"]
+        );
+    }
+
+    #[test]
+    fn test_conversation_labels_channels() {
+        let words = vec![
+            word2("so", 0, 0),
+            word2("yeah", 1, 300),
+            word2("the", 0, 600),
+            word2("right", 1, 700),
+            word2("launch", 0, 900),
+            word2("friday", 1, 2000),
+        ];
+
+        let conversation = to_conversation(group_by_channel(&words));
+        let lines = conversation
+            .iter()
+            .map(|w| match &w.speaker {
+                Some(owhisper_interface::SpeakerIdentity::Assigned { label, .. }) => {
+                    format!("{}: {}", label, w.text)
+                }
+                other => panic!("{:?}", other),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Me: so",
+                "Them: yeah",
+                "Me: the",
+                "Them: right",
+                "Me: launch",
+                "Them: friday",
+            ]
+        );
+    }
 }
@@ -0,0 +1,113 @@
+use futures_util::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+use hypr_audio::{AudioInput, ResampledAsyncSource};
+
+use crate::events::{peak_level, rms_level};
+
+const SAMPLE_RATE: u32 = 16000;
+// One level update every 50ms.
+const CHUNK_SIZE: usize = SAMPLE_RATE as usize / 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, specta::Type)]
+pub struct MicLevel {
+    pub rms: u16,
+    pub peak: u16,
+}
+
+impl From<&[f32]> for MicLevel {
+    fn from(chunk: &[f32]) -> Self {
+        Self {
+            rms: rms_level(chunk),
+            peak: peak_level(chunk),
+        }
+    }
+}
+
+// Reads a microphone on its own, outside of any session, only to report its input level.
+pub struct MicTest {
+    token: CancellationToken,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MicTest {
+    pub fn start(
+        device_name: Option<String>,
+        on_level: impl FnMut(MicLevel) + Send + 'static,
+    ) -> Result<Self, crate::Error> {
+        let mut input = AudioInput::from_mic(device_name)?;
+        let stream = ResampledAsyncSource::new(input.stream(), SAMPLE_RATE).chunks(CHUNK_SIZE);
+
+        Ok(Self::spawn(stream, on_level))
+    }
+
+    fn spawn<S>(stream: S, on_level: impl FnMut(MicLevel) + Send + 'static) -> Self
+    where
+        S: Stream<Item = Vec<f32>> + Send + 'static,
+    {
+        let token = CancellationToken::new();
+        let task = tokio::spawn(run(stream, token.clone(), on_level));
+
+        Self { token, task }
+    }
+
+    // Returns once the stream is dropped, so the device is already released.
+    pub async fn stop(self) {
+        self.token.cancel();
+        let _ = self.task.await;
+    }
+}
+
+async fn run<S>(stream: S, token: CancellationToken, mut on_level: impl FnMut(MicLevel))
+where
+    S: Stream<Item = Vec<f32>>,
+{
+    futures_util::pin_mut!(stream);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            next = stream.next() => match next {
+                Some(chunk) => on_level(MicLevel::from(chunk.as_slice())),
+                None => break,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mic_test_levels() {
+        let (audio_tx, audio_rx) = tokio::sync::mpsc::channel::<Vec<f32>>(8);
+        let (level_tx, mut level_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let test = MicTest::spawn(
+            tokio_stream::wrappers::ReceiverStream::new(audio_rx),
+            move |level| {
+                let _ = level_tx.send(level);
+            },
+        );
+
+        audio_tx.send(vec![0.0; CHUNK_SIZE]).await.unwrap();
+        audio_tx
+            .send(
+                (0..CHUNK_SIZE)
+                    .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+                    .collect(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(level_rx.recv().await, Some(MicLevel { rms: 0, peak: 0 }));
+        assert_eq!(level_rx.recv().await, Some(MicLevel { rms: 50, peak: 50 }));
+
+        test.stop().await;
+
+        // The source was dropped by the time `stop` returned.
+        assert!(audio_tx.is_closed());
+        assert_eq!(level_rx.recv().await, None);
+    }
+}
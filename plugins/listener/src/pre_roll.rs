@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use futures_util::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+use hypr_audio::{AudioInput, ResampledAsyncSource};
+
+pub const SAMPLE_RATE: u32 = 16000;
+const CHUNK_SIZE: usize = 512;
+
+// Keeps only the most recent `capacity` samples.
+pub struct PreRollBuffer {
+    capacity: usize,
+    samples: VecDeque<f32>,
+}
+
+impl PreRollBuffer {
+    pub fn new(seconds: u64) -> Self {
+        let capacity = seconds as usize * SAMPLE_RATE as usize;
+
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[f32]) {
+        self.samples.extend(chunk);
+
+        let overflow = self.samples.len().saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+    }
+
+    // Oldest sample first.
+    pub fn take(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+}
+
+// Listens to the microphone while no session is running, so a new recording can start
+// with the audio from right before it was started. Only runs when the user opted in.
+pub struct PreRollCapture {
+    buffer: Arc<Mutex<PreRollBuffer>>,
+    token: CancellationToken,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl PreRollCapture {
+    pub fn start(seconds: u64) -> Result<Self, crate::Error> {
+        let mut input = AudioInput::from_mic(None)?;
+        let stream = ResampledAsyncSource::new(input.stream(), SAMPLE_RATE).chunks(CHUNK_SIZE);
+
+        Ok(Self::spawn(stream, seconds))
+    }
+
+    fn spawn<S>(stream: S, seconds: u64) -> Self
+    where
+        S: Stream<Item = Vec<f32>> + Send + 'static,
+    {
+        let buffer = Arc::new(Mutex::new(PreRollBuffer::new(seconds)));
+        let token = CancellationToken::new();
+        let task = tokio::spawn(run(stream, token.clone(), buffer.clone()));
+
+        Self {
+            buffer,
+            token,
+            task,
+        }
+    }
+
+    // Releases the microphone before returning, so the session can open it.
+    pub async fn finish(self) -> Vec<f32> {
+        self.token.cancel();
+        let _ = self.task.await;
+
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.take()
+    }
+}
+
+async fn run<S>(stream: S, token: CancellationToken, buffer: Arc<Mutex<PreRollBuffer>>)
+where
+    S: Stream<Item = Vec<f32>>,
+{
+    futures_util::pin_mut!(stream);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            next = stream.next() => match next {
+                Some(chunk) => buffer.lock().unwrap().push(&chunk),
+                None => break,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pre_roll_keeps_last_window() {
+        let (audio_tx, audio_rx) = tokio::sync::mpsc::channel::<Vec<f32>>(8);
+
+        let capture =
+            PreRollCapture::spawn(tokio_stream::wrappers::ReceiverStream::new(audio_rx), 1);
+
+        for i in 0..3 {
+            audio_tx
+                .send(vec![i as f32; SAMPLE_RATE as usize / 2])
+                .await
+                .unwrap();
+        }
+        // Lets the capture task catch up before it is stopped.
+        while audio_tx.capacity() < audio_tx.max_capacity() {
+            tokio::task::yield_now().await;
+        }
+        tokio::task::yield_now().await;
+
+        let samples = capture.finish().await;
+        assert!(audio_tx.is_closed());
+
+        assert_eq!(samples.len(), SAMPLE_RATE as usize);
+        assert!(samples[..SAMPLE_RATE as usize / 2]
+            .iter()
+            .all(|s| *s == 1.0));
+        assert!(samples[SAMPLE_RATE as usize / 2..]
+            .iter()
+            .all(|s| *s == 2.0));
+    }
+}
@@ -0,0 +1,199 @@
+use hypr_language::{Language, ISO639};
+use owhisper_interface::{Word2, WordKind};
+
+// A pause this long between two words is taken as the end of a sentence.
+const SENTENCE_PAUSE_MS: u64 = 700;
+
+const EN_QUESTION_WORDS: &[&str] = &[
+    "who", "what", "when", "where", "why", "how", "which", "is", "are", "do", "does", "did", "can",
+    "could", "would", "will", "should",
+];
+
+// What ends a sentence, or `None` for languages these rules don't cover.
+fn full_stop(language: &Language) -> Option<&'static str> {
+    match **language {
+        ISO639::Ja | ISO639::Zh => Some("。"),
+        ISO639::Th => None,
+        _ => Some("."),
+    }
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.ends_with(['.', '?', '!', '。', '？', '！'])
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Capitalizes sentences and ends them with punctuation, for backends that send neither.
+///
+/// Words before `from` were restored earlier and are only read for context, except that the one
+/// right before `from` may still get its full stop. Of those, only the sentence still open is
+/// looked at, so restoring batch after batch doesn't re-walk the transcript. The last word is
+/// left open, as the sentence may go on in the next batch. Only text changes, so timings stay as
+/// they are.
+pub fn restore_punctuation(words: &mut [Word2], from: usize, language: &Language) {
+    let Some(full_stop) = full_stop(language) else {
+        return;
+    };
+
+    // Backends that punctuate do it on their own.
+    let from = from.min(words.len());
+    if words[from..]
+        .iter()
+        .any(|w| w.text.ends_with(['.', '?', '!', ',', '。', '、']))
+    {
+        return;
+    }
+
+    // Earlier sentences were closed when restored, so the open one starts after the last of them.
+    let open = words[..from.saturating_sub(1)]
+        .iter()
+        .rposition(|w| w.kind == WordKind::Speech && ends_sentence(&w.text))
+        .map_or(0, |i| i + 1);
+
+    let speech = (open..words.len())
+        .filter(|&i| words[i].kind == WordKind::Speech)
+        .collect::<Vec<_>>();
+    let mut sentence_start = None;
+
+    for (n, &i) in speech.iter().enumerate() {
+        let prev = n.checked_sub(1).map(|p| speech[p]);
+
+        let paused = prev.is_some_and(|p| match (words[p].end_ms, words[i].start_ms) {
+            (Some(end), Some(start)) => start.saturating_sub(end) >= SENTENCE_PAUSE_MS,
+            _ => false,
+        });
+        let starts_sentence = match prev {
+            None => true,
+            Some(p) => paused || ends_sentence(&words[p].text),
+        };
+
+        if let Some(p) = prev.filter(|&p| paused && p + 1 >= from) {
+            if !ends_sentence(&words[p].text) {
+                let question = **language == ISO639::En
+                    && sentence_start.is_some_and(|s: usize| {
+                        EN_QUESTION_WORDS.contains(&words[s].text.to_lowercase().as_str())
+                    });
+                words[p]
+                    .text
+                    .push_str(if question { "?" } else { full_stop });
+            }
+        }
+
+        if i >= from {
+            if starts_sentence {
+                words[i].text = capitalize(&words[i].text);
+            }
+            if **language == ISO639::En {
+                let lower = words[i].text.to_lowercase();
+                if lower == "i" || lower.starts_with("i'") {
+                    words[i].text = capitalize(&words[i].text);
+                }
+            }
+        }
+
+        if starts_sentence {
+            sentence_start = Some(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_ms: u64, end_ms: u64) -> Word2 {
+        Word2 {
+            text: text.to_string(),
+            start_ms: Some(start_ms),
+            end_ms: Some(end_ms),
+            ..Default::default()
+        }
+    }
+
+    fn texts(words: &[Word2]) -> Vec<&str> {
+        words.iter().map(|w| w.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_restore_punctuation() {
+        let en = ISO639::En.into();
+        let mut words = vec![
+            word("we", 0, 200),
+            word("ship", 250, 500),
+            word("friday", 550, 900),
+            word("can", 2000, 2200),
+            word("i", 2250, 2300),
+            word("help", 2350, 2600),
+            word("sure", 4000, 4300),
+        ];
+        let timings = words
+            .iter()
+            .map(|w| (w.start_ms, w.end_ms))
+            .collect::<Vec<_>>();
+
+        restore_punctuation(&mut words, 0, &en);
+        assert_eq!(
+            texts(&words),
+            vec!["We", "ship", "friday.", "Can", "I", "help?", "Sure"]
+        );
+        assert_eq!(
+            words
+                .iter()
+                .map(|w| (w.start_ms, w.end_ms))
+                .collect::<Vec<_>>(),
+            timings
+        );
+
+        // The next batch closes the sentence left open, and leaves the rest alone.
+        words.extend([word("thanks", 5000, 5300), word("bye", 5350, 5600)]);
+        restore_punctuation(&mut words, 7, &en);
+        assert_eq!(
+            texts(&words),
+            vec!["We", "ship", "friday.", "Can", "I", "help?", "Sure.", "Thanks", "bye"]
+        );
+
+        // Punctuated by the backend already.
+        let mut punctuated = vec![word("we", 0, 200), word("ship.", 250, 500)];
+        restore_punctuation(&mut punctuated, 0, &en);
+        assert_eq!(texts(&punctuated), vec!["we", "ship."]);
+
+        let mut japanese = vec![word("はい", 0, 300), word("そうです", 1500, 1900)];
+        restore_punctuation(&mut japanese, 0, &ISO639::Ja.into());
+        assert_eq!(texts(&japanese), vec!["はい。", "そうです"]);
+    }
+
+    #[test]
+    fn test_restore_punctuation_in_batches() {
+        let en = ISO639::En.into();
+        let words = (0..40)
+            .map(|i| {
+                let start = i * 300 + (i / 5) * 1000;
+                let text = if i % 5 == 0 { "what" } else { "now" };
+                word(text, start, start + 250)
+            })
+            .collect::<Vec<_>>();
+
+        let mut whole = words.clone();
+        restore_punctuation(&mut whole, 0, &en);
+
+        // Restoring as the words arrive, a few at a time, gives the same transcript.
+        let mut batched = vec![];
+        for batch in words.chunks(3) {
+            let from = batched.len();
+            batched.extend_from_slice(batch);
+            restore_punctuation(&mut batched, from, &en);
+        }
+        assert_eq!(texts(&batched), texts(&whole));
+        assert_eq!(
+            texts(&whole)[..6],
+            ["What", "now", "now", "now", "now?", "What"]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use hypr_language::{Language, ISO639};
+
+// Onboarding plays back short, scripted lines, so each is finalized right away.
+const ONBOARDING_REDEMPTION_TIME_MS: u64 = 60;
+const DEFAULT_REDEMPTION_TIME_MS: u64 = 400;
+
+// How long the backend waits in silence before ending an utterance. Speakers of these languages
+// tend to pause longer between phrases of the same sentence.
+fn default_redemption_time_ms(language: &Language) -> u64 {
+    match **language {
+        ISO639::Ko | ISO639::Ja | ISO639::Zh => 600,
+        _ => DEFAULT_REDEMPTION_TIME_MS,
+    }
+}
+
+/// Redemption time for a stream in `languages`, going by the first one. `overrides` are keyed by
+/// ISO 639-1 code and win over the defaults.
+pub fn redemption_time_ms(
+    languages: &[Language],
+    onboarding: bool,
+    overrides: &HashMap<String, u32>,
+) -> u64 {
+    if onboarding {
+        return ONBOARDING_REDEMPTION_TIME_MS;
+    }
+
+    let Some(language) = languages.first() else {
+        return DEFAULT_REDEMPTION_TIME_MS;
+    };
+
+    overrides
+        .get(language.code())
+        .map(|ms| *ms as u64)
+        .unwrap_or_else(|| default_redemption_time_ms(language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redemption_time_per_language() {
+        let en = vec![Language::from(ISO639::En)];
+        let ko = vec![Language::from(ISO639::Ko), Language::from(ISO639::En)];
+        let none = HashMap::new();
+
+        assert_eq!(redemption_time_ms(&en, false, &none), 400);
+        assert_eq!(redemption_time_ms(&ko, false, &none), 600);
+        assert_eq!(redemption_time_ms(&[], false, &none), 400);
+        assert_eq!(redemption_time_ms(&ko, true, &none), 60);
+
+        let overrides = HashMap::from([("ko".to_string(), 800)]);
+        assert_eq!(redemption_time_ms(&ko, false, &overrides), 800);
+        assert_eq!(redemption_time_ms(&en, false, &overrides), 400);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use hypr_audio::{AsyncSource, ResampledAsyncSource};
+use owhisper_interface::{ControlMessage, MixedMessage};
+
+// Samples handed over a chunk at a time. Runs dry between chunks instead of ending.
+struct ChunkSource {
+    pending: Arc<Mutex<VecDeque<f32>>>,
+    sample_rate: u32,
+}
+
+impl AsyncSource for ChunkSource {
+    fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+        futures_util::stream::poll_fn(move |_| match self.pending.lock().unwrap().pop_front() {
+            Some(sample) => Poll::Ready(Some(sample)),
+            None => Poll::Pending,
+        })
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+// Converts a continuous 16-bit PCM stream between rates, chunk by chunk, with the resampler used
+// for capture. Its position carries over between chunks, so their boundaries don't click.
+pub struct StreamResampler {
+    pending: Arc<Mutex<VecDeque<f32>>>,
+    resampled: ResampledAsyncSource<ChunkSource>,
+}
+
+impl StreamResampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        let pending = Arc::new(Mutex::new(VecDeque::new()));
+        let source = ChunkSource {
+            pending: pending.clone(),
+            sample_rate: from_rate,
+        };
+
+        Self {
+            pending,
+            resampled: ResampledAsyncSource::new(source, to_rate),
+        }
+    }
+
+    pub fn process(&mut self, samples: &[i16]) -> Vec<i16> {
+        self.pending
+            .lock()
+            .unwrap()
+            .extend(samples.iter().map(|s| *s as f32));
+
+        // Pending once it needs samples from the next chunk.
+        let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+        let mut out = Vec::with_capacity(samples.len());
+        while let Poll::Ready(Some(sample)) = self.resampled.poll_next_unpin(&mut cx) {
+            out.push(sample.round() as i16);
+        }
+        out
+    }
+}
+
+// Resamples each channel of the dual-channel audio sent to the STT backend.
+pub struct DualResampler {
+    mic: StreamResampler,
+    spk: StreamResampler,
+}
+
+impl DualResampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            mic: StreamResampler::new(from_rate, to_rate),
+            spk: StreamResampler::new(from_rate, to_rate),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        msg: MixedMessage<(Bytes, Bytes), ControlMessage>,
+    ) -> MixedMessage<(Bytes, Bytes), ControlMessage> {
+        match msg {
+            MixedMessage::Audio((mic, spk)) => MixedMessage::Audio((
+                resample_bytes(&mut self.mic, &mic),
+                resample_bytes(&mut self.spk, &spk),
+            )),
+            control => control,
+        }
+    }
+}
+
+fn resample_bytes(resampler: &mut StreamResampler, data: &[u8]) -> Bytes {
+    let samples = data
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect::<Vec<_>>();
+
+    resampler
+        .process(&samples)
+        .into_iter()
+        .flat_map(i16::to_le_bytes)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_resampler_keeps_rate_across_chunks() {
+        let input = (0..16000).map(|i| (i % 100) as i16).collect::<Vec<_>>();
+
+        let mut down = StreamResampler::new(16000, 8000);
+        let out = input
+            .chunks(333)
+            .flat_map(|chunk| down.process(chunk))
+            .collect::<Vec<_>>();
+        assert!((7999..=8000).contains(&out.len()));
+        assert_eq!(&out[..3], &[0, 1, 3]);
+
+        let mut up = StreamResampler::new(16000, 48000);
+        let out = input
+            .chunks(512)
+            .flat_map(|chunk| up.process(chunk))
+            .collect::<Vec<_>>();
+        assert!((47997..=48000).contains(&out.len()));
+    }
+}
@@ -0,0 +1,224 @@
+use std::future::Future;
+use std::ops::Range;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use owhisper_interface::{ControlMessage, MixedMessage, Word2};
+
+use crate::{
+    manager::{TranscriptManager, WordJoinRule},
+    pre_roll::SAMPLE_RATE,
+};
+
+// Longer than the pauses of a normal conversation, so only audio that never got transcribed
+// (e.g. the STT server was down) is sent again.
+const MIN_GAP_MS: u64 = 10_000;
+// 100ms chunks, sent as fast as the backend takes them.
+const CHUNK_SAMPLES: usize = SAMPLE_RATE as usize / 10;
+// How long the backend may go quiet before the gap is considered done.
+const RESPONSE_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Wall-clock time of the recording's first sample, as the words were timed against it.
+// Falls back to when the recording started, for words that only have wall-clock times.
+pub fn recording_offset_ms(session: &hypr_db_user::Session) -> Option<u64> {
+    session
+        .words
+        .iter()
+        .find_map(|w| {
+            Some(
+                w.start_ms?
+                    .saturating_sub((w.recording_start_s? * 1000.0) as u64),
+            )
+        })
+        .or_else(|| {
+            session
+                .record_start
+                .map(|t| t.timestamp_millis().max(0) as u64)
+        })
+}
+
+// Stretches of the recording without words for at least `MIN_GAP_MS`, in ms from its start.
+// Words without a recording time are not counted.
+pub fn find_gaps(words: &[Word2], duration_ms: u64) -> Vec<Range<u64>> {
+    let mut spans = words
+        .iter()
+        .filter_map(|w| {
+            let start = (w.recording_start_s? * 1000.0) as u64;
+            let end = (w.recording_end_s? * 1000.0) as u64;
+            Some((start, end.max(start)))
+        })
+        .collect::<Vec<_>>();
+    spans.sort_unstable();
+
+    let mut gaps = Vec::new();
+    let mut covered_until = 0;
+
+    for (start, end) in spans.into_iter().chain([(duration_ms, duration_ms)]) {
+        let start = start.min(duration_ms);
+        if start >= covered_until + MIN_GAP_MS {
+            gaps.push(covered_until..start);
+        }
+        covered_until = covered_until.max(end);
+    }
+
+    gaps
+}
+
+// Transcribes each gap of `samples` on its own and merges the words into `words`, in order.
+//
+// `transcribe` gets a gap's audio and returns words timed from the gap's start. They are moved
+// to the gap's place on both the recording's timeline and the wall clock, which starts at
+// `recording_offset_ms`.
+pub async fn fill_gaps<F, Fut>(
+    mut words: Vec<Word2>,
+    samples: &[f32],
+    gaps: &[Range<u64>],
+    recording_offset_ms: u64,
+    mut transcribe: F,
+) -> Result<Vec<Word2>, crate::Error>
+where
+    F: FnMut(Vec<f32>) -> Fut,
+    Fut: Future<Output = Result<Vec<Word2>, crate::Error>>,
+{
+    let to_sample = |ms: u64| ((ms * SAMPLE_RATE as u64 / 1000) as usize).min(samples.len());
+
+    for gap in gaps {
+        let audio = &samples[to_sample(gap.start)..to_sample(gap.end)];
+        if audio.is_empty() {
+            continue;
+        }
+
+        for mut word in transcribe(audio.to_vec()).await? {
+            let start_ms = word.start_ms.map(|ms| ms + gap.start);
+            let end_ms = word.end_ms.map(|ms| ms + gap.start);
+
+            word.recording_start_s = start_ms.map(|ms| ms as f64 / 1000.0);
+            word.recording_end_s = end_ms.map(|ms| ms as f64 / 1000.0);
+            word.start_ms = start_ms.map(|ms| ms + recording_offset_ms);
+            word.end_ms = end_ms.map(|ms| ms + recording_offset_ms);
+            words.push(word);
+        }
+    }
+
+    words.sort_by(|a, b| {
+        a.recording_start_s
+            .partial_cmp(&b.recording_start_s)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(words)
+}
+
+// Streams `samples` (16kHz) to `conn` as the mic channel, and returns the final words, timed
+// from the start of `samples`.
+pub async fn transcribe_samples(
+    conn: &tauri_plugin_local_stt::Connection,
+    languages: &[hypr_language::Language],
+    samples: Vec<f32>,
+) -> Result<Vec<Word2>, crate::Error> {
+    let redemption_time_ms =
+        crate::redemption::redemption_time_ms(languages, false, &Default::default());
+    let client = crate::actors::build_client(conn, languages, redemption_time_ms);
+
+    let audio = samples
+        .chunks(CHUNK_SAMPLES)
+        .map(|chunk| {
+            let mic = hypr_audio_utils::f32_to_i16_bytes(chunk.iter().copied());
+            let spk = Bytes::from(vec![0u8; mic.len()]);
+            MixedMessage::Audio((mic, spk))
+        })
+        .chain([MixedMessage::Control(ControlMessage::Finalize)])
+        .collect::<Vec<_>>();
+    let audio = crate::actors::resampled_stream(
+        futures_util::stream::iter(audio),
+        crate::SttInfo::from(conn).sample_rate,
+    );
+
+    let (stream, _handle) = client
+        .from_realtime_audio(audio)
+        .await
+        .map_err(|e| crate::Error::TranscriptionFailed(e.to_string()))?;
+    tokio::pin!(stream);
+
+    let mut manager = TranscriptManager::builder()
+        .with_manager_offset(0)
+        .with_word_join_rule(
+            languages
+                .first()
+                .map(WordJoinRule::for_language)
+                .unwrap_or_default(),
+        )
+        .build();
+    let mut words = Vec::new();
+
+    while let Ok(Some(response)) = tokio::time::timeout(RESPONSE_IDLE_TIMEOUT, stream.next()).await
+    {
+        let response = response.map_err(|e| crate::Error::TranscriptionFailed(e.to_string()))?;
+        let mut diff = manager.append(response);
+        // The speaker channel is silence.
+        words.extend(
+            diff.final_words
+                .remove(&0)
+                .unwrap_or_default()
+                .into_iter()
+                .map(Word2::from),
+        );
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_s: f64, end_s: f64) -> Word2 {
+        Word2 {
+            text: text.to_string(),
+            start_ms: Some(1_000_000 + (start_s * 1000.0) as u64),
+            end_ms: Some(1_000_000 + (end_s * 1000.0) as u64),
+            recording_start_s: Some(start_s),
+            recording_end_s: Some(end_s),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_middle_is_filled() {
+        // 30s recording; someone speaks from 12s to 13s, while the backend was down.
+        let mut samples = vec![0.0_f32; 30 * SAMPLE_RATE as usize];
+        samples[12 * SAMPLE_RATE as usize..13 * SAMPLE_RATE as usize].fill(0.5);
+
+        let words = vec![
+            word("hello", 1.0, 2.0),
+            word("again", 4.0, 5.0),
+            word("bye", 27.0, 28.0),
+        ];
+
+        let gaps = find_gaps(&words, 30_000);
+        assert_eq!(gaps, vec![5_000..27_000]);
+
+        // Stands in for the backend: hears speech wherever the audio isn't silent.
+        let filled = fill_gaps(words, &samples, &gaps, 1_000_000, |audio| async move {
+            let start = audio.iter().position(|s| *s != 0.0).unwrap();
+            let end = audio.iter().rposition(|s| *s != 0.0).unwrap() + 1;
+            Ok(vec![Word2 {
+                text: "recovered".to_string(),
+                start_ms: Some(start as u64 * 1000 / SAMPLE_RATE as u64),
+                end_ms: Some(end as u64 * 1000 / SAMPLE_RATE as u64),
+                ..Default::default()
+            }])
+        })
+        .await
+        .unwrap();
+
+        let texts = filled.iter().map(|w| w.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["hello", "again", "recovered", "bye"]);
+
+        let recovered = &filled[2];
+        assert_eq!(recovered.recording_start_s, Some(12.0));
+        assert_eq!(recovered.recording_end_s, Some(13.0));
+        assert_eq!(recovered.start_ms, Some(1_012_000));
+        assert_eq!(recovered.end_ms, Some(1_013_000));
+    }
+}
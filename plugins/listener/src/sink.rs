@@ -0,0 +1,31 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::manager::Diff;
+
+// Appends each `Diff` as a JSON line, so external tools can tail the transcript as it arrives.
+pub struct TranscriptSink {
+    writer: BufWriter<File>,
+}
+
+impl TranscriptSink {
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn write(&mut self, diff: &Diff) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, diff)?;
+        self.writer.write_all(b"\n")?;
+        // Flushed per line, so tailing readers see it right away.
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,10 @@
+use tauri_plugin_store2::ScopedStoreKey;
+
+#[derive(serde::Deserialize, specta::Type, PartialEq, Eq, Hash, strum::Display)]
+pub enum StoreKey {
+    RecordingPrefs,
+    // Where each mic's AGC settled, by device name.
+    AgcGains,
+}
+
+impl ScopedStoreKey for StoreKey {}
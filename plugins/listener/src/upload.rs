@@ -0,0 +1,186 @@
+use std::future::Future;
+use std::path::Path;
+
+use owhisper_interface::Word2;
+use rodio::Source;
+
+use crate::{
+    actors::{recording_fingerprint, write_recording, write_recording_meta},
+    pre_roll::SAMPLE_RATE,
+    resample::StreamResampler,
+    retranscribe::fill_gaps,
+    RecordingMeta,
+};
+
+/// Decodes an audio file in any format rodio reads, downmixed to mono at the recording's 16kHz.
+pub fn decode_file(path: &Path) -> Result<Vec<f32>, crate::Error> {
+    let unsupported = |reason: String| {
+        crate::Error::UnsupportedAudioFile(format!("{}: {}", path.display(), reason))
+    };
+
+    let file = std::fs::File::open(path)?;
+    let source = rodio::Decoder::new(std::io::BufReader::new(file))
+        .map_err(|e| unsupported(e.to_string()))?;
+    let channels = source.channels().max(1) as usize;
+    let sample_rate = source.sample_rate();
+
+    let mono = source
+        .collect::<Vec<i16>>()
+        .chunks(channels)
+        .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32) as i16)
+        .collect::<Vec<_>>();
+    if mono.is_empty() {
+        return Err(unsupported("no audio".to_string()));
+    }
+
+    let mono = match sample_rate {
+        SAMPLE_RATE => mono,
+        rate => StreamResampler::new(rate, SAMPLE_RATE).process(&mono),
+    };
+    Ok(hypr_audio_utils::i16_to_f32_samples(&mono))
+}
+
+/// Builds a session for the audio file at `path`, transcribed in one go by `transcribe`, and
+/// keeps a copy of the audio, encoded at `quality`, as its recording under `recordings_dir`.
+///
+/// The recording is taken to have ended at the time of the import. Nothing is written if the
+/// file can't be decoded or transcribed.
+pub async fn import_file<F, Fut>(
+    path: &Path,
+    recordings_dir: &Path,
+    quality: f32,
+    user_id: String,
+    transcribe: F,
+) -> Result<hypr_db_user::Session, crate::Error>
+where
+    F: FnMut(Vec<f32>) -> Fut,
+    Fut: Future<Output = Result<Vec<Word2>, crate::Error>>,
+{
+    let samples = {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || decode_file(&path))
+            .await
+            .map_err(std::io::Error::other)??
+    };
+    let duration_ms = samples.len() as u64 * 1000 / SAMPLE_RATE as u64;
+
+    let now = chrono::Utc::now();
+    let record_start = now - chrono::Duration::milliseconds(duration_ms as i64);
+    let offset_ms = record_start.timestamp_millis().max(0) as u64;
+
+    let words = fill_gaps(vec![], &samples, &[0..duration_ms], offset_ms, transcribe).await?;
+
+    let session = hypr_db_user::Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: now,
+        visited_at: now,
+        user_id,
+        calendar_event_id: None,
+        title: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        raw_memo_html: String::new(),
+        enhanced_memo_html: None,
+        conversations: vec![],
+        words,
+        record_start: Some(record_start),
+        record_end: Some(now),
+        pre_meeting_memo_html: None,
+        partial_words: vec![],
+    };
+
+    let session_dir = recordings_dir.join(&session.id);
+    write_recording(&session_dir, &samples, quality)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let fingerprint = tokio::task::spawn_blocking({
+        let session_dir = session_dir.clone();
+        move || recording_fingerprint(&session_dir)
+    })
+    .await
+    .ok()
+    .flatten();
+    let meta = RecordingMeta {
+        fingerprint,
+        ..RecordingMeta::from_session(&session, duration_ms)
+    };
+    write_recording_meta(&session_dir, &meta)?;
+
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_import_wav() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let quality = crate::RecordingPrefs::default().quality;
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // 2s of stereo 44.1kHz, with a tone in the second half.
+        let path = dir.join("Weekly sync.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..2 * 44100 {
+            let sample = if i < 44100 {
+                0
+            } else {
+                ((i as f32 / 44100.0 * 440.0 * std::f32::consts::TAU).sin() * 8000.0) as i16
+            };
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        // Stands in for the backend: hears a word wherever the audio isn't silent.
+        let session = import_file(
+            &path,
+            &dir,
+            quality,
+            "user".to_string(),
+            |audio| async move {
+                let start = audio.iter().position(|s| s.abs() > 0.01).unwrap();
+                Ok(vec![Word2 {
+                    text: "hello".to_string(),
+                    start_ms: Some(start as u64 * 1000 / SAMPLE_RATE as u64),
+                    end_ms: Some(audio.len() as u64 * 1000 / SAMPLE_RATE as u64),
+                    ..Default::default()
+                }])
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(session.title, "Weekly sync");
+        assert_eq!(session.words.len(), 1);
+        let word = &session.words[0];
+        assert!((word.recording_start_s.unwrap() - 1.0).abs() < 0.01);
+        assert!((word.recording_end_s.unwrap() - 2.0).abs() < 0.01);
+        // Wall-clock times count from the start of the recording.
+        let record_start_ms = session.record_start.unwrap().timestamp_millis() as u64;
+        assert!((word.start_ms.unwrap() - record_start_ms).abs_diff(1000) < 10);
+
+        // The copy is a regular 16kHz recording of the session.
+        let recording = crate::actors::read_recording(dir.join(&session.id)).unwrap();
+        assert!((recording.len() as i64 - 32000).abs() < 100);
+        let meta = crate::actors::read_recording_meta(dir.join(&session.id)).unwrap();
+        assert_eq!(meta.word_count, 1);
+
+        let not_audio = dir.join("notes.txt");
+        std::fs::write(&not_audio, "not audio").unwrap();
+        let result = import_file(&not_audio, &dir, quality, "user".to_string(), |_| async {
+            Ok(vec![])
+        })
+        .await;
+        assert!(matches!(result, Err(crate::Error::UnsupportedAudioFile(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use owhisper_interface::Word2;
+
+// One edit to a channel's word list. Applying a batch in order turns the previous list into the
+// current one.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+#[serde(tag = "op")]
+pub enum WordOp {
+    #[serde(rename = "add")]
+    Add {
+        channel: usize,
+        index: usize,
+        word: Word2,
+    },
+    #[serde(rename = "replace")]
+    Replace {
+        channel: usize,
+        index: usize,
+        word: Word2,
+    },
+    #[serde(rename = "remove")]
+    Remove { channel: usize, index: usize },
+}
+
+// Remembers what the frontend has already been sent, so only the changes go out.
+#[derive(Debug, Default, Clone)]
+pub struct WordPatcher {
+    partial_words: HashMap<usize, Vec<Word2>>,
+    final_counts: HashMap<usize, usize>,
+}
+
+impl WordPatcher {
+    // `partial_words` is the full current partial list per channel; `final_words` holds only the
+    // newly finalized words, which are appended after everything finalized before.
+    pub fn patch(
+        &mut self,
+        partial_words: &HashMap<usize, Vec<Word2>>,
+        final_words: &HashMap<usize, Vec<Word2>>,
+    ) -> (Vec<WordOp>, Vec<WordOp>) {
+        let mut channels = partial_words
+            .keys()
+            .chain(self.partial_words.keys())
+            .copied()
+            .collect::<Vec<_>>();
+        channels.sort_unstable();
+        channels.dedup();
+
+        let mut partial_ops = Vec::new();
+        for channel in channels {
+            let prev = self.partial_words.get(&channel).map_or(&[][..], |w| w);
+            let next = partial_words.get(&channel).map_or(&[][..], |w| w);
+            partial_ops.extend(diff_words(channel, prev, next));
+        }
+        self.partial_words = partial_words.clone();
+
+        let mut final_channels = final_words.keys().copied().collect::<Vec<_>>();
+        final_channels.sort_unstable();
+
+        let mut final_ops = Vec::new();
+        for channel in final_channels {
+            let count = self.final_counts.entry(channel).or_default();
+            for word in &final_words[&channel] {
+                final_ops.push(WordOp::Add {
+                    channel,
+                    index: *count,
+                    word: word.clone(),
+                });
+                *count += 1;
+            }
+        }
+
+        (partial_ops, final_ops)
+    }
+}
+
+// Replaces what changed in the shared prefix, then appends or trims the tail. Removals go from
+// the end, so earlier indices stay valid while applying.
+pub fn diff_words(channel: usize, prev: &[Word2], next: &[Word2]) -> Vec<WordOp> {
+    let mut ops = prev
+        .iter()
+        .zip(next)
+        .enumerate()
+        .filter(|(_, (p, n))| p != n)
+        .map(|(index, (_, word))| WordOp::Replace {
+            channel,
+            index,
+            word: word.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    ops.extend(
+        next.iter()
+            .enumerate()
+            .skip(prev.len())
+            .map(|(index, word)| WordOp::Add {
+                channel,
+                index,
+                word: word.clone(),
+            }),
+    );
+    ops.extend(
+        (next.len()..prev.len())
+            .rev()
+            .map(|index| WordOp::Remove { channel, index }),
+    );
+
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(words: &mut HashMap<usize, Vec<Word2>>, ops: &[WordOp]) {
+        for op in ops {
+            match op.clone() {
+                WordOp::Add {
+                    channel,
+                    index,
+                    word,
+                } => words.entry(channel).or_default().insert(index, word),
+                WordOp::Replace {
+                    channel,
+                    index,
+                    word,
+                } => words.entry(channel).or_default()[index] = word,
+                WordOp::Remove { channel, index } => {
+                    words.entry(channel).or_default().remove(index);
+                }
+            }
+        }
+    }
+
+    fn words(texts: &[&str]) -> Vec<Word2> {
+        texts
+            .iter()
+            .map(|t| Word2 {
+                text: t.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_ops_reconstruct_full_lists() {
+        // (partial words per channel, newly finalized words per channel) for each response.
+        let responses = vec![
+            (HashMap::from([(0, words(&["hel"]))]), HashMap::new()),
+            (
+                HashMap::from([(0, words(&["hello", "wor"])), (1, words(&["yes"]))]),
+                HashMap::new(),
+            ),
+            (
+                HashMap::from([(0, words(&["world"])), (1, words(&["yes", "i"]))]),
+                HashMap::from([(0, words(&["hello"]))]),
+            ),
+            (
+                HashMap::from([(0, vec![]), (1, words(&["i", "agree"]))]),
+                HashMap::from([(0, words(&["world"])), (1, words(&["yes"]))]),
+            ),
+            (
+                HashMap::from([(1, vec![])]),
+                HashMap::from([(1, words(&["i", "agree"]))]),
+            ),
+        ];
+
+        let mut patcher = WordPatcher::default();
+        let mut full_final = HashMap::new();
+        let (mut patched_partial, mut patched_final) = (HashMap::new(), HashMap::new());
+
+        for (partial_words, final_words) in responses {
+            let mut full_partial = partial_words.clone();
+            for (channel, words) in &final_words {
+                full_final
+                    .entry(*channel)
+                    .or_insert_with(Vec::new)
+                    .extend(words.clone());
+            }
+
+            let (partial_ops, final_ops) = patcher.patch(&partial_words, &final_words);
+            apply(&mut patched_partial, &partial_ops);
+            apply(&mut patched_final, &final_ops);
+
+            patched_partial.retain(|_, w: &mut Vec<Word2>| !w.is_empty());
+            full_partial.retain(|_, w: &mut Vec<Word2>| !w.is_empty());
+            assert_eq!(patched_partial, full_partial);
+            assert_eq!(patched_final, full_final);
+        }
+
+        assert_eq!(
+            patched_final[&0]
+                .iter()
+                .map(|w| &w.text)
+                .collect::<Vec<_>>(),
+            ["hello", "world"]
+        );
+        assert_eq!(
+            patched_final[&1]
+                .iter()
+                .map(|w| &w.text)
+                .collect::<Vec<_>>(),
+            ["yes", "i", "agree"]
+        );
+    }
+
+    #[test]
+    fn test_diff_words_removes_from_the_end() {
+        let ops = diff_words(0, &words(&["a", "b", "c"]), &words(&["a"]));
+        assert_eq!(
+            ops,
+            vec![
+                WordOp::Remove {
+                    channel: 0,
+                    index: 2
+                },
+                WordOp::Remove {
+                    channel: 0,
+                    index: 1
+                },
+            ]
+        );
+    }
+}
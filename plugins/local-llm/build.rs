@@ -16,6 +16,13 @@ const COMMANDS: &[&str] = &[
     "set_current_model_selection",
     "generate_title",
     "generate_tags",
+    "get_compute_backend",
+    "benchmark_model",
+    "preview_prompt",
+    "reenhance",
+    "cancel_generation",
+    "get_fallback_model",
+    "set_fallback_model",
 ];
 
 fn main() {
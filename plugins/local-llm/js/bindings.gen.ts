@@ -16,6 +16,9 @@ async listSupportedModel() : Promise<ModelInfo[]> {
 async isServerRunning() : Promise<boolean> {
     return await TAURI_INVOKE("plugin:local-llm|is_server_running");
 },
+async getComputeBackend() : Promise<ComputeBackend | null> {
+    return await TAURI_INVOKE("plugin:local-llm|get_compute_backend");
+},
 async isModelDownloaded(model: SupportedModel) : Promise<boolean> {
     return await TAURI_INVOKE("plugin:local-llm|is_model_downloaded", { model });
 },
@@ -52,11 +55,29 @@ async getCurrentModelSelection() : Promise<ModelSelection> {
 async setCurrentModelSelection(model: ModelSelection) : Promise<null> {
     return await TAURI_INVOKE("plugin:local-llm|set_current_model_selection", { model });
 },
-async generateTitle(ctx: Partial<{ [key in string]: JsonValue }>) : Promise<string> {
-    return await TAURI_INVOKE("plugin:local-llm|generate_title", { ctx });
+async getFallbackModel() : Promise<ModelSelection | null> {
+    return await TAURI_INVOKE("plugin:local-llm|get_fallback_model");
+},
+async setFallbackModel(model: ModelSelection | null) : Promise<null> {
+    return await TAURI_INVOKE("plugin:local-llm|set_fallback_model", { model });
+},
+async generateTitle(ctx: Partial<{ [key in string]: JsonValue }>, maxWords: number | null, sentenceCase: boolean | null) : Promise<string> {
+    return await TAURI_INVOKE("plugin:local-llm|generate_title", { ctx, maxWords, sentenceCase });
+},
+async generateTags(ctx: Partial<{ [key in string]: JsonValue }>, minConfidence: number | null, maxTags: number | null) : Promise<string[]> {
+    return await TAURI_INVOKE("plugin:local-llm|generate_tags", { ctx, minConfidence, maxTags });
+},
+async benchmarkModel() : Promise<BenchmarkResult> {
+    return await TAURI_INVOKE("plugin:local-llm|benchmark_model");
+},
+async previewPrompt(template: Template, ctx: Partial<{ [key in string]: JsonValue }>) : Promise<PromptPreview> {
+    return await TAURI_INVOKE("plugin:local-llm|preview_prompt", { template, ctx });
+},
+async reenhance(sessionId: string, templateId: string | null, model: ModelSelection, channel: TAURI_CHANNEL<EnhanceEvent>) : Promise<string> {
+    return await TAURI_INVOKE("plugin:local-llm|reenhance", { sessionId, templateId, model, channel });
 },
-async generateTags(ctx: Partial<{ [key in string]: JsonValue }>) : Promise<string[]> {
-    return await TAURI_INVOKE("plugin:local-llm|generate_tags", { ctx });
+async cancelGeneration(taskId: string) : Promise<boolean> {
+    return await TAURI_INVOKE("plugin:local-llm|cancel_generation", { taskId });
 }
 }
 
@@ -75,12 +96,17 @@ llmEvent: "plugin:local-llm:llm-event"
 
 /** user-defined types **/
 
+export type BenchmarkResult = { load_ms: number; prompt_tokens_per_sec: number; gen_tokens_per_sec: number }
+export type ComputeBackend = "gpu" | "cpu"
 export type CustomModelInfo = { path: string; name: string }
+export type EnhanceEvent = { type: "sectionStart"; name: string } | { type: "textDelta"; text: string } | { type: "sectionEnd" }
 export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
-export type LLMEvent = { progress: number }
+export type LLMEvent = { progress: number } | { generationStarted: { task_id: string; task: string } } | { generationFinished: { task_id: string; model: string } }
 export type ModelInfo = { key: SupportedModel; name: string; description: string; size_bytes: number }
 export type ModelSelection = { type: "Predefined"; content: { key: SupportedModel } } | { type: "Custom"; content: { path: string } }
+export type PromptPreview = { system: string; user: string }
 export type SupportedModel = "Llama3p2_3bQ4" | "Gemma3_4bQ4" | "HyprLLM"
+export type Template = "enhance.system" | "enhance.user" | "create_title.system" | "create_title.user" | "suggest_tags.system" | "suggest_tags.user" | "chat.system" | "chat.user" | "auto_generate_tags.system" | "auto_generate_tags.user" | "postprocess_transcript.system" | "postprocess_transcript.user"
 export type TAURI_CHANNEL<TSend> = null
 
 /** tauri-specta globals **/
@@ -41,6 +41,14 @@ pub async fn is_server_running<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> b
     app.is_server_running().await
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_compute_backend<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Option<hypr_llama::ComputeBackend> {
+    app.get_compute_backend().await
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn is_model_downloaded<R: tauri::Runtime>(
@@ -143,13 +151,41 @@ pub async fn set_current_model_selection<R: tauri::Runtime>(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_fallback_model<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<Option<ModelSelection>, String> {
+    app.get_fallback_model().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_fallback_model<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    model: Option<ModelSelection>,
+) -> Result<(), String> {
+    app.set_fallback_model(model)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn generate_title<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     ctx: serde_json::Map<String, serde_json::Value>,
+    max_words: Option<u8>,
+    sentence_case: Option<bool>,
 ) -> Result<String, String> {
-    app.generate_title(ctx).await.map_err(|e| e.to_string())
+    let style = hypr_llm::TitleStyle {
+        max_words,
+        sentence_case: sentence_case.unwrap_or(false),
+    };
+
+    app.generate_title(ctx, style)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -157,6 +193,56 @@ pub async fn generate_title<R: tauri::Runtime>(
 pub async fn generate_tags<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     ctx: serde_json::Map<String, serde_json::Value>,
+    min_confidence: Option<f32>,
+    max_tags: Option<u32>,
 ) -> Result<Vec<String>, String> {
-    app.generate_tags(ctx).await.map_err(|e| e.to_string())
+    let filter = hypr_llm::TagFilter {
+        min_confidence,
+        max_tags: max_tags.map(|n| n as usize),
+    };
+
+    app.generate_tags(ctx, filter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn benchmark_model<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<hypr_llm::BenchmarkResult, String> {
+    app.benchmark_model().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_prompt<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    template: hypr_llm::Template,
+    ctx: serde_json::Map<String, serde_json::Value>,
+) -> Result<hypr_llm::PromptPreview, String> {
+    app.preview_prompt(template, ctx).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn reenhance<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+    template_id: Option<String>,
+    model: ModelSelection,
+    channel: Channel<hypr_llm::EnhanceEvent>,
+) -> Result<String, String> {
+    app.reenhance(session_id, template_id, model, channel)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_generation<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    task_id: String,
+) -> Result<bool, String> {
+    Ok(app.cancel_generation(task_id))
 }
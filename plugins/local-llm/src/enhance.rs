@@ -0,0 +1,151 @@
+use std::future::Future;
+
+use hypr_db_user::{Config, Human, Session, Template};
+
+pub type EnhanceContext = serde_json::Map<String, serde_json::Value>;
+
+// About what the local model's context leaves for the transcript, as the editor uses.
+const LOCAL_TRANSCRIPT_TOKEN_BUDGET: u64 = 10000;
+
+// Enhances an already recorded session again, with `template` (the default structure if `None`)
+// and whichever model `generate` runs. The session itself is left untouched.
+pub async fn reenhance_session<F, Fut>(
+    session: &Session,
+    template: Option<&Template>,
+    config: &Config,
+    participants: &[Human],
+    generate: F,
+) -> Result<String, crate::Error>
+where
+    F: FnOnce(EnhanceContext, Option<Vec<String>>) -> Fut,
+    Fut: Future<Output = Result<String, crate::Error>>,
+{
+    let ctx = enhance_context(session, template, config, participants);
+    let sections = template.map(|t| t.sections.iter().map(|s| s.title.clone()).collect());
+
+    generate(ctx, sections).await
+}
+
+// Same inputs the editor renders the enhance templates with.
+fn enhance_context(
+    session: &Session,
+    template: Option<&Template>,
+    config: &Config,
+    participants: &[Human],
+) -> EnhanceContext {
+    let mut ctx = serde_json::json!({
+        "type": "HyprLocal",
+        "config": config,
+        "editor": session.raw_memo_html,
+        "words": serde_json::to_string(&session.words).unwrap_or_default(),
+        "redactPii": config.ai.redact_pii,
+        "transcriptTokenBudget": LOCAL_TRANSCRIPT_TOKEN_BUDGET,
+        "participants": participants,
+    })
+    .as_object()
+    .cloned()
+    .unwrap_or_default();
+
+    if config.ai.strip_filler_words {
+        let language = config
+            .general
+            .spoken_languages
+            .first()
+            .map_or_else(|| "en".to_string(), |l| l.iso639().code().to_string());
+        ctx.insert(
+            "fillerWords".into(),
+            serde_json::json!({ "mode": "strip", "language": language }),
+        );
+    }
+
+    if let Some(template) = template {
+        ctx.insert("templateInfo".into(), serde_json::json!(template));
+        ctx.insert(
+            "customInstruction".into(),
+            template.description.clone().into(),
+        );
+    }
+
+    ctx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hypr_db_user::TemplateSection;
+    use owhisper_interface::{SpeakerIdentity, Word2};
+
+    fn seeded_session() -> Session {
+        let words = ["we", "ship", "the", "beta", "on", "friday"]
+            .iter()
+            .map(|text| Word2 {
+                text: text.to_string(),
+                speaker: Some(SpeakerIdentity::Unassigned { index: 0 }),
+                ..Default::default()
+            })
+            .collect();
+
+        Session {
+            id: "session-1".to_string(),
+            user_id: "user-1".to_string(),
+            title: "Launch sync".to_string(),
+            raw_memo_html: "beta launch".to_string(),
+            enhanced_memo_html: Some("# Original\n- Kept as is".to_string()),
+            words,
+            ..Session::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reenhance_with_template() {
+        let session = seeded_session();
+        let template = Template {
+            id: "template-1".to_string(),
+            user_id: "user-1".to_string(),
+            title: "Decisions".to_string(),
+            description: "Only list decisions.".to_string(),
+            sections: vec![TemplateSection {
+                title: "Decisions".to_string(),
+                description: "What was decided".to_string(),
+            }],
+            tags: vec![],
+            context_option: None,
+        };
+
+        // Stands in for the model: checks what it was asked, then answers.
+        let note = reenhance_session(
+            &session,
+            Some(&template),
+            &Config {
+                id: "config-1".to_string(),
+                user_id: "user-1".to_string(),
+                general: Default::default(),
+                notification: Default::default(),
+                ai: Default::default(),
+            },
+            &[],
+            |ctx, sections| async move {
+                assert_eq!(ctx["redactPii"], false);
+                assert_eq!(ctx["transcriptTokenBudget"], LOCAL_TRANSCRIPT_TOKEN_BUDGET);
+
+                let prompt =
+                    hypr_llm::preview_prompt(hypr_llm::Template::EnhanceUser, &ctx).unwrap();
+                assert!(prompt.user.contains("we ship the beta on friday"));
+                assert!(prompt.user.contains("beta launch"));
+                assert!(prompt.system.contains("What was decided"));
+                assert_eq!(sections, Some(vec!["Decisions".to_string()]));
+
+                Ok("# Decisions\n- Ship the beta on Friday".to_string())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(note, "# Decisions\n- Ship the beta on Friday");
+        assert_eq!(
+            session.enhanced_memo_html.as_deref(),
+            Some("# Original\n- Kept as is")
+        );
+    }
+}
@@ -17,11 +17,19 @@ pub enum Error {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
+    TauriError(#[from] tauri::Error),
+    #[error(transparent)]
     StoreError(#[from] tauri_plugin_store2::Error),
+    #[error(transparent)]
+    DatabaseError(#[from] tauri_plugin_db::Error),
     #[error("Model not downloaded")]
     ModelNotDownloaded,
     #[error("server already running")]
     ServerAlreadyRunning,
+    #[error("session not found")]
+    SessionNotFound,
+    #[error("template not found")]
+    TemplateNotFound,
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -35,4 +35,11 @@ pub fn on_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: &tauri::Run
 pub enum LLMEvent {
     #[serde(rename = "progress")]
     Progress(f64),
+    // Pass `task_id` to `cancel_generation` to stop it.
+    #[serde(rename = "generationStarted")]
+    GenerationStarted { task_id: String, task: String },
+    // `model` is the fallback model if the configured one ran out of memory, and empty if the
+    // task was answered without a model.
+    #[serde(rename = "generationFinished")]
+    GenerationFinished { task_id: String, model: String },
 }
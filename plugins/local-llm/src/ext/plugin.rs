@@ -14,6 +14,7 @@ pub trait LocalLlmPluginExt<R: Runtime> {
     fn api_base(&self) -> impl Future<Output = Option<String>>;
 
     fn is_server_running(&self) -> impl Future<Output = bool>;
+    fn get_compute_backend(&self) -> impl Future<Output = Option<hypr_llama::ComputeBackend>>;
     fn start_server(&self) -> impl Future<Output = Result<String, crate::Error>>;
     fn stop_server(&self) -> impl Future<Output = Result<(), crate::Error>>;
 
@@ -29,6 +30,12 @@ pub trait LocalLlmPluginExt<R: Runtime> {
     fn get_current_model_selection(&self) -> Result<crate::ModelSelection, crate::Error>;
     fn set_current_model_selection(&self, model: crate::ModelSelection)
         -> Result<(), crate::Error>;
+    fn get_fallback_model(&self) -> Result<Option<crate::ModelSelection>, crate::Error>;
+    fn set_fallback_model(
+        &self,
+        model: Option<crate::ModelSelection>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn model_manager(&self, model_path: PathBuf) -> crate::ModelManager;
 
     fn download_model(
         &self,
@@ -94,6 +101,17 @@ impl<R: Runtime, T: Manager<R>> LocalLlmPluginExt<R> for T {
         s.server.is_some()
     }
 
+    #[tracing::instrument(skip_all)]
+    async fn get_compute_backend(&self) -> Option<hypr_llama::ComputeBackend> {
+        let model_manager = {
+            let state = self.state::<crate::SharedState>();
+            let s = state.lock().await;
+            s.server.as_ref().map(|server| server.model_manager.clone())
+        };
+
+        model_manager?.backend().await
+    }
+
     #[tracing::instrument(skip_all)]
     async fn download_model(
         &self,
@@ -188,9 +206,7 @@ impl<R: Runtime, T: Manager<R>> LocalLlmPluginExt<R> for T {
         let current_selection = self.get_current_model_selection()?;
         let model_path = current_selection.file_path(&self.models_dir());
 
-        let model_manager = crate::ModelManager::builder()
-            .model_path(model_path)
-            .build();
+        let model_manager = self.model_manager(model_path);
         let state = self.state::<crate::SharedState>();
 
         let handle = self.app_handle().clone();
@@ -326,4 +342,46 @@ impl<R: Runtime, T: Manager<R>> LocalLlmPluginExt<R> for T {
         store.set(crate::StoreKey::ModelSelection, model)?;
         Ok(())
     }
+
+    #[tracing::instrument(skip_all)]
+    fn get_fallback_model(&self) -> Result<Option<crate::ModelSelection>, crate::Error> {
+        let store = self.local_llm_store();
+        let model = store.get::<Option<crate::ModelSelection>>(crate::StoreKey::FallbackModel)?;
+        Ok(model.flatten())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn set_fallback_model(
+        &self,
+        model: Option<crate::ModelSelection>,
+    ) -> Result<(), crate::Error> {
+        let store = self.local_llm_store();
+        store.set(crate::StoreKey::FallbackModel, model)?;
+
+        // Picked up by the server on its next start; the built-in model is rebuilt right away.
+        let builtin_model = self.model_manager(crate::builtin_model_path(self.app_handle())?);
+        let state = self.state::<crate::SharedState>();
+        state.lock().await.builtin_model = builtin_model;
+
+        Ok(())
+    }
+
+    fn model_manager(&self, model_path: PathBuf) -> crate::ModelManager {
+        let fallback = self
+            .get_fallback_model()
+            .ok()
+            .flatten()
+            .map(|model| model.file_path(&self.models_dir()))
+            .filter(|path| *path != model_path && path.exists());
+
+        match fallback {
+            Some(fallback) => crate::ModelManager::builder()
+                .model_path(model_path)
+                .fallback_model_path(fallback)
+                .build(),
+            None => crate::ModelManager::builder()
+                .model_path(model_path)
+                .build(),
+        }
+    }
 }
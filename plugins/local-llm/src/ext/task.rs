@@ -1,51 +1,201 @@
 use std::future::Future;
 use tauri::{Manager, Runtime};
+use tauri_specta::Event;
 
 pub trait LocalLlmTaskExt<R: Runtime> {
     fn generate_title(
         &self,
         ctx: serde_json::Map<String, serde_json::Value>,
+        style: hypr_llm::TitleStyle,
     ) -> impl Future<Output = Result<String, crate::Error>>;
 
     fn generate_tags(
         &self,
         ctx: serde_json::Map<String, serde_json::Value>,
+        filter: hypr_llm::TagFilter,
     ) -> impl Future<Output = Result<Vec<String>, crate::Error>>;
 
     fn postprocess_transcript(
         &self,
         ctx: serde_json::Map<String, serde_json::Value>,
     ) -> impl Future<Output = Result<String, crate::Error>>;
+
+    fn benchmark_model(
+        &self,
+    ) -> impl Future<Output = Result<hypr_llm::BenchmarkResult, crate::Error>>;
+
+    fn preview_prompt(
+        &self,
+        template: hypr_llm::Template,
+        ctx: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<hypr_llm::PromptPreview, crate::Error>;
+
+    fn reenhance(
+        &self,
+        session_id: String,
+        template_id: Option<String>,
+        model: crate::ModelSelection,
+        channel: tauri::ipc::Channel<hypr_llm::EnhanceEvent>,
+    ) -> impl Future<Output = Result<String, crate::Error>>;
+
+    fn cancel_generation(&self, task_id: impl AsRef<str>) -> bool;
+}
+
+// Cloned out so the state lock isn't held for the whole generation, which would block
+// `set_fallback_model` until the task finishes.
+async fn builtin_model<R: Runtime>(app: &impl Manager<R>) -> crate::ModelManager {
+    app.state::<crate::SharedState>()
+        .lock()
+        .await
+        .builtin_model
+        .clone()
+}
+
+// Registers the generation and tells the UI its task id.
+fn start_generation<R: Runtime>(app: &impl Manager<R>, task: &str) -> crate::GenerationGuard {
+    let guard = app.state::<crate::GenerationTasks>().start();
+
+    let _ = crate::LLMEvent::GenerationStarted {
+        task_id: guard.id.clone(),
+        task: task.to_string(),
+    }
+    .emit(app.app_handle());
+
+    guard
+}
+
+// Tells the UI which model the task's output came from.
+fn finish_generation<R: Runtime>(app: &impl Manager<R>, task_id: &str, model: &str) {
+    tracing::info!(task_id, model, "generation_finished");
+
+    let _ = crate::LLMEvent::GenerationFinished {
+        task_id: task_id.to_string(),
+        model: model.to_string(),
+    }
+    .emit(app.app_handle());
 }
 
 impl<R: Runtime, T: Manager<R>> LocalLlmTaskExt<R> for T {
     async fn generate_title(
         &self,
         ctx: serde_json::Map<String, serde_json::Value>,
+        style: hypr_llm::TitleStyle,
     ) -> Result<String, crate::Error> {
-        let state = self.state::<crate::SharedState>();
-        let s = state.lock().await;
-        let v = hypr_llm::generate_title(&s.builtin_model, ctx).await?;
-        Ok(v)
+        let generation = start_generation(self, "generate_title");
+        let model = builtin_model(self).await;
+        let v = hypr_llm::generate_title(&model, ctx, &style, &generation.token).await?;
+        finish_generation(self, &generation.id, &v.model);
+        Ok(v.output)
     }
 
     async fn generate_tags(
         &self,
         ctx: serde_json::Map<String, serde_json::Value>,
+        filter: hypr_llm::TagFilter,
     ) -> Result<Vec<String>, crate::Error> {
-        let state = self.state::<crate::SharedState>();
-        let s = state.lock().await;
-        let v = hypr_llm::generate_tags(&s.builtin_model, ctx).await?;
-        Ok(v)
+        let generation = start_generation(self, "generate_tags");
+        let model = builtin_model(self).await;
+        let v = hypr_llm::generate_tags(&model, ctx, &filter, &generation.token).await?;
+        finish_generation(self, &generation.id, &v.model);
+        Ok(v.output.into_iter().map(|t| t.name).collect())
     }
 
     async fn postprocess_transcript(
         &self,
         ctx: serde_json::Map<String, serde_json::Value>,
     ) -> Result<String, crate::Error> {
-        let state = self.state::<crate::SharedState>();
-        let s = state.lock().await;
-        let v = hypr_llm::postprocess_transcript(&s.builtin_model, ctx).await?;
+        let generation = start_generation(self, "postprocess_transcript");
+        let model = builtin_model(self).await;
+        let v = hypr_llm::postprocess_transcript(&model, ctx, &generation.token).await?;
+        finish_generation(self, &generation.id, &v.model);
+        Ok(v.output)
+    }
+
+    async fn benchmark_model(&self) -> Result<hypr_llm::BenchmarkResult, crate::Error> {
+        let model = builtin_model(self).await;
+        let v = hypr_llm::benchmark_model(&model).await?;
+        tracing::info!(
+            load_ms = v.load_ms,
+            prompt_tokens_per_sec = v.prompt_tokens_per_sec,
+            gen_tokens_per_sec = v.gen_tokens_per_sec,
+            "benchmark_model"
+        );
         Ok(v)
     }
+
+    fn preview_prompt(
+        &self,
+        template: hypr_llm::Template,
+        ctx: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<hypr_llm::PromptPreview, crate::Error> {
+        Ok(hypr_llm::preview_prompt(template, &ctx)?)
+    }
+
+    async fn reenhance(
+        &self,
+        session_id: String,
+        template_id: Option<String>,
+        model: crate::ModelSelection,
+        channel: tauri::ipc::Channel<hypr_llm::EnhanceEvent>,
+    ) -> Result<String, crate::Error> {
+        use crate::LocalLlmPluginExt;
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let session = self
+            .db_get_session(&session_id)
+            .await?
+            .ok_or(crate::Error::SessionNotFound)?;
+        let template = match template_id {
+            Some(id) => Some(
+                self.db_get_template(id)
+                    .await?
+                    .ok_or(crate::Error::TemplateNotFound)?,
+            ),
+            None => None,
+        };
+        let config = self
+            .db_get_config(&session.user_id)
+            .await?
+            .ok_or(crate::Error::Other("config not found".to_string()))?;
+        let participants = self.db_session_participants(&session_id).await?;
+
+        let generation = start_generation(self, "reenhance");
+        let (task_id, token) = (&generation.id, &generation.token);
+
+        // The built-in model is already loaded; any other is loaded just for this run, with the
+        // same fallback, so the current model stays as it is.
+        let model_path = model.file_path(&self.models_dir());
+        let provider = if model_path == crate::builtin_model_path(self.app_handle())? {
+            builtin_model(self).await
+        } else {
+            self.model_manager(model_path)
+        };
+
+        crate::enhance::reenhance_session(
+            &session,
+            template.as_ref(),
+            &config,
+            &participants,
+            |ctx, sections| async move {
+                let v = hypr_llm::enhance(
+                    &provider,
+                    ctx,
+                    sections,
+                    |event| {
+                        let _ = channel.send(event);
+                    },
+                    token,
+                )
+                .await?;
+                finish_generation(self, task_id, &v.model);
+                Ok::<_, crate::Error>(v.output)
+            },
+        )
+        .await
+    }
+
+    fn cancel_generation(&self, task_id: impl AsRef<str>) -> bool {
+        self.state::<crate::GenerationTasks>()
+            .cancel(task_id.as_ref())
+    }
 }
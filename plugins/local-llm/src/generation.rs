@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio_util::sync::CancellationToken;
+
+// Running generations by task id, so the UI can stop them.
+#[derive(Clone, Default)]
+pub struct GenerationTasks(Arc<Mutex<HashMap<String, CancellationToken>>>);
+
+impl GenerationTasks {
+    pub fn start(&self) -> GenerationGuard {
+        let id = uuid::Uuid::new_v4().to_string();
+        let token = CancellationToken::new();
+        self.0.lock().unwrap().insert(id.clone(), token.clone());
+
+        GenerationGuard {
+            id,
+            token,
+            tasks: self.clone(),
+        }
+    }
+
+    // Whether a task with that id was running.
+    pub fn cancel(&self, task_id: &str) -> bool {
+        match self.0.lock().unwrap().remove(task_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_running(&self, task_id: &str) -> bool {
+        self.0.lock().unwrap().contains_key(task_id)
+    }
+}
+
+// Unregisters the task once the generation is over, however it ended.
+pub struct GenerationGuard {
+    pub id: String,
+    pub token: CancellationToken,
+    tasks: GenerationTasks,
+}
+
+impl Drop for GenerationGuard {
+    fn drop(&mut self) {
+        self.tasks.0.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancel_generation() {
+        let tasks = GenerationTasks::default();
+        let guard = tasks.start();
+        let task_id = guard.id.clone();
+        assert!(tasks.is_running(&task_id));
+
+        // Never ends on its own, like a runaway summary.
+        let stream = futures_util::stream::unfold(0, |n| async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Some((hypr_llama::Response::TextDelta(format!("{} ", n)), n + 1))
+        });
+        let generation =
+            tokio::spawn(async move { hypr_llm::collect_text(stream, &guard.token).await });
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert!(tasks.cancel(&task_id));
+
+        let output = tokio::time::timeout(Duration::from_millis(10), generation)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(output, "0 1 2 ");
+        assert!(!tasks.is_running(&task_id));
+        assert!(!tasks.cancel(&task_id));
+
+        // Finished tasks are gone too.
+        let guard = tasks.start();
+        let task_id = guard.id.clone();
+        drop(guard);
+        assert!(!tasks.is_running(&task_id));
+    }
+}
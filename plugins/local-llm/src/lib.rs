@@ -7,9 +7,11 @@ use tokio::sync::Mutex;
 use hypr_llm::ModelManager;
 
 mod commands;
+mod enhance;
 mod error;
 mod events;
 mod ext;
+mod generation;
 mod model;
 mod server;
 mod store;
@@ -20,6 +22,7 @@ mod lmstudio;
 pub use error::*;
 use events::*;
 pub use ext::*;
+pub use generation::*;
 pub use model::*;
 pub use server::*;
 pub use store::*;
@@ -37,6 +40,18 @@ pub struct State {
     pub builtin_model: ModelManager,
 }
 
+// Bundled with the app, and used for the built-in tasks.
+fn builtin_model_path<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<std::path::PathBuf, tauri::Error> {
+    if cfg!(debug_assertions) {
+        app.path()
+            .resolve("resources/llm.gguf", BaseDirectory::Resource)
+    } else {
+        app.path().resolve("llm.gguf", BaseDirectory::Resource)
+    }
+}
+
 fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
     tauri_specta::Builder::<R>::new()
         .plugin_name(PLUGIN_NAME)
@@ -45,6 +60,7 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::models_dir::<Wry>,
             commands::list_supported_model,
             commands::is_server_running::<Wry>,
+            commands::get_compute_backend::<Wry>,
             commands::is_model_downloaded::<Wry>,
             commands::is_model_downloading::<Wry>,
             commands::download_model::<Wry>,
@@ -57,8 +73,14 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::list_custom_models::<Wry>,
             commands::get_current_model_selection::<Wry>,
             commands::set_current_model_selection::<Wry>,
+            commands::get_fallback_model::<Wry>,
+            commands::set_fallback_model::<Wry>,
             commands::generate_title::<Wry>,
             commands::generate_tags::<Wry>,
+            commands::benchmark_model::<Wry>,
+            commands::preview_prompt::<Wry>,
+            commands::reenhance::<Wry>,
+            commands::cancel_generation::<Wry>,
         ])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
 }
@@ -90,22 +112,19 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             }
 
             {
-                let model_path = if cfg!(debug_assertions) {
-                    app.path()
-                        .resolve("resources/llm.gguf", BaseDirectory::Resource)?
-                } else {
-                    app.path().resolve("llm.gguf", BaseDirectory::Resource)?
-                };
+                let model_path = builtin_model_path(app.app_handle())?;
 
                 let state = State {
                     api_base: None,
                     server: None,
                     download_task: HashMap::new(),
-                    builtin_model: ModelManager::builder().model_path(model_path).build(),
+                    builtin_model: app.model_manager(model_path),
                 };
                 app.manage(Arc::new(Mutex::new(state)));
             }
 
+            app.manage(GenerationTasks::default());
+
             Ok(())
         })
         .on_event(on_event)
@@ -194,7 +213,10 @@ mod test {
                         .into(),
                 ),
             ],
-            metadata: Some(serde_json::json!({ "grammar": hypr_gbnf::Grammar::Title })),
+            metadata: Some(serde_json::json!({ "grammar": hypr_gbnf::Grammar::Title {
+                max_words: None,
+                sentence_case: false,
+            } })),
             ..Default::default()
         }
     }
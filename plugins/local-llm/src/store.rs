@@ -5,6 +5,7 @@ pub enum StoreKey {
     Model,
     ModelSelection,
     DefaultModelMigrated,
+    FallbackModel,
 }
 
 impl ScopedStoreKey for StoreKey {}
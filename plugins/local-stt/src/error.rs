@@ -18,6 +18,8 @@ pub enum Error {
     StoreError(#[from] tauri_plugin_store2::Error),
     #[error("Model not downloaded")]
     ModelNotDownloaded,
+    #[error("Model not found at {}; download it again", .0.display())]
+    ModelNotFound(std::path::PathBuf),
     #[error("Server already running")]
     ServerAlreadyRunning,
     #[error("Server start failed")]
@@ -133,6 +133,9 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                     model: model.map(|m| m.to_string()),
                     base_url,
                     api_key,
+                    sample_rate: None,
+                    price_per_minute: None,
+                    opus: false,
                 })
             }
             Provider::Local => {
@@ -146,6 +149,9 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                             model: None,
                             base_url,
                             api_key,
+                            sample_rate: None,
+                            price_per_minute: None,
+                            opus: false,
                         })
                     }
                     SupportedSttModel::Am(_) => {
@@ -162,6 +168,9 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                                 model: None,
                                 base_url: api_base,
                                 api_key: Some(am_key),
+                                sample_rate: None,
+                                price_per_minute: None,
+                                opus: false,
                             },
                             None => {
                                 let api_base = self.start_server(Some(model)).await?;
@@ -169,6 +178,9 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                                     model: None,
                                     base_url: api_base,
                                     api_key: Some(am_key),
+                                    sample_rate: None,
+                                    price_per_minute: None,
+                                    opus: false,
                                 }
                             }
                         };
@@ -182,6 +194,9 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                                 model: None,
                                 base_url: api_base,
                                 api_key: None,
+                                sample_rate: None,
+                                price_per_minute: None,
+                                opus: false,
                             },
                             None => {
                                 let api_base = self.start_server(Some(model)).await?;
@@ -189,6 +204,9 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                                     model: None,
                                     base_url: api_base,
                                     api_key: None,
+                                    sample_rate: None,
+                                    price_per_minute: None,
+                                    opus: false,
                                 }
                             }
                         };
@@ -273,7 +291,7 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
                     },
                 )
                 .await
-                .map_err(|_| crate::Error::ServerStartFailed)?;
+                .map_err(internal::spawn_error)?;
 
                 let base_url = internal_health().await.map(|r| r.0).unwrap();
                 Ok(base_url)
@@ -29,6 +29,17 @@ pub struct InternalSTTState {
 
 pub struct InternalSTTActor;
 
+// Keeps errors like `Error::ModelNotFound` from `pre_start`, instead of a generic start failure.
+pub fn spawn_error(err: ractor::SpawnErr) -> crate::Error {
+    match err {
+        ractor::SpawnErr::StartupFailed(err) => match err.downcast::<crate::Error>() {
+            Ok(err) => *err,
+            Err(_) => crate::Error::ServerStartFailed,
+        },
+        _ => crate::Error::ServerStartFailed,
+    }
+}
+
 impl InternalSTTActor {
     pub fn name() -> ActorName {
         "internal_stt".into()
@@ -47,6 +58,11 @@ impl Actor for InternalSTTActor {
     ) -> Result<Self::State, ActorProcessingErr> {
         let model_path = args.model_cache_dir.join(args.model_type.file_name());
 
+        // Otherwise the first `/v1/listen` request fails with an opaque error.
+        if !model_path.exists() {
+            return Err(crate::Error::ModelNotFound(model_path).into());
+        }
+
         let whisper_service = HandleError::new(
             hypr_transcribe_whisper_local::TranscribeService::builder()
                 .model_path(model_path)
@@ -120,3 +136,29 @@ impl Actor for InternalSTTActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_missing_model() {
+        let model_cache_dir = std::env::temp_dir().join("hypr-missing-stt-models");
+
+        let err = Actor::spawn(
+            None,
+            InternalSTTActor,
+            InternalSTTArgs {
+                model_type: WhisperModel::QuantizedTiny,
+                model_cache_dir: model_cache_dir.clone(),
+            },
+        )
+        .await
+        .map_err(spawn_error)
+        .unwrap_err();
+
+        let model_path = model_cache_dir.join(WhisperModel::QuantizedTiny.file_name());
+        assert!(matches!(&err, crate::Error::ModelNotFound(path) if *path == model_path));
+        assert!(err.to_string().contains(&model_path.display().to_string()));
+    }
+}
@@ -1,6 +1,12 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Connection {
     pub model: Option<String>,
     pub base_url: String,
     pub api_key: Option<String>,
+    // Rate the backend wants its audio at. 16kHz when unset.
+    pub sample_rate: Option<u32>,
+    // What the backend charges per minute of audio, for cost estimates. Unknown when unset.
+    pub price_per_minute: Option<f64>,
+    // Whether the backend takes Opus, which is then sent instead of PCM when the rate allows it.
+    pub opus: bool,
 }
@@ -32,17 +32,9 @@ pub async fn audio_exist<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     session_id: String,
 ) -> Result<bool, String> {
-    let data_dir = app.path().app_data_dir().unwrap();
-    let session_dir = data_dir.join(session_id);
+    let session_dirs = session_dirs(&app, &session_id).await;
 
-    ["audio.wav", "audio.ogg"]
-        .iter()
-        .map(|format| session_dir.join(format))
-        .try_fold(false, |acc, path| {
-            std::fs::exists(path)
-                .map(|exists| acc || exists)
-                .map_err(|e| e.to_string())
-        })
+    Ok(session_dirs.iter().any(|dir| !audio_files(dir).is_empty()))
 }
 
 #[tauri::command]
@@ -51,11 +43,62 @@ pub async fn audio_delete<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     session_id: String,
 ) -> Result<(), String> {
+    for session_dir in session_dirs(&app, &session_id).await {
+        for path in audio_files(&session_dir) {
+            std::fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// Recordings live under the configured recordings directory, but may also be in app_data_dir
+// when the configured one was unavailable at record time (or for older sessions).
+async fn session_dirs<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    session_id: &str,
+) -> Vec<std::path::PathBuf> {
+    use tauri_plugin_db::DatabasePluginExt;
+
+    let mut dirs = vec![];
+
+    if let Ok(Some(recordings_dir)) = app.db_recordings_dir().await {
+        dirs.push(recordings_dir.join(session_id));
+    }
+
     let data_dir = app.path().app_data_dir().unwrap();
-    let audio_path = data_dir.join(session_id).join("audio.wav");
+    let default_dir = data_dir.join(session_id);
+    if !dirs.contains(&default_dir) {
+        dirs.push(default_dir);
+    }
 
-    std::fs::remove_file(audio_path).map_err(|e| e.to_string())?;
-    Ok(())
+    dirs
+}
+
+// Matches both `audio.{wav,ogg}` and rotated segments like `audio.000.ogg`.
+fn audio_files(session_dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(session_dir) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            let is_audio = path
+                .extension()
+                .is_some_and(|ext| ext == "wav" || ext == "ogg");
+
+            let segment_ok = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| match stem.split_once('.') {
+                    Some((base, idx)) => base == "audio" && idx.chars().all(|c| c.is_ascii_digit()),
+                    None => stem == "audio",
+                });
+
+            is_audio && segment_ok
+        })
+        .collect()
 }
 
 #[tauri::command]
@@ -64,8 +107,11 @@ pub async fn audio_open<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     session_id: String,
 ) -> Result<(), String> {
-    let data_dir = app.path().app_data_dir().unwrap();
-    let session_dir = data_dir.join(session_id);
+    let session_dirs = session_dirs(&app, &session_id).await;
+    let session_dir = session_dirs
+        .iter()
+        .find(|dir| !audio_files(dir).is_empty())
+        .unwrap_or(&session_dirs[0]);
 
     app.opener()
         .open_path(session_dir.to_string_lossy(), None::<&str>)
@@ -80,11 +126,10 @@ pub async fn delete_session_folder<R: tauri::Runtime>(
     app: tauri::AppHandle<R>,
     session_id: String,
 ) -> Result<(), String> {
-    let data_dir = app.path().app_data_dir().unwrap();
-    let session_dir = data_dir.join(session_id);
-
-    if session_dir.exists() {
-        std::fs::remove_dir_all(session_dir).map_err(|e| e.to_string())?;
+    for session_dir in session_dirs(&app, &session_id).await {
+        if session_dir.exists() {
+            std::fs::remove_dir_all(session_dir).map_err(|e| e.to_string())?;
+        }
     }
 
     Ok(())
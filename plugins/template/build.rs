@@ -1,4 +1,4 @@
-const COMMANDS: &[&str] = &["render"];
+const COMMANDS: &[&str] = &["render", "validate_template"];
 
 fn main() {
     tauri_plugin::Builder::new(COMMANDS).build();
@@ -9,6 +9,9 @@
 export const commands = {
 async render(name: Template, ctx: Partial<{ [key in string]: JsonValue }>) : Promise<string> {
     return await TAURI_INVOKE("plugin:template|render", { name, ctx });
+},
+async validateTemplate(source: string, ctx: Partial<{ [key in string]: JsonValue }>) : Promise<Validation> {
+    return await TAURI_INVOKE("plugin:template|validate_template", { source, ctx });
 }
 }
 
@@ -22,9 +25,10 @@ async render(name: Template, ctx: Partial<{ [key in string]: JsonValue }>) : Pro
 
 /** user-defined types **/
 
-export type Grammar = { task: "enhance"; sections: string[] | null } | { task: "title" } | { task: "tags" } | { task: "email-to-name" }
+export type Grammar = { task: "enhance"; sections: string[] | null } | { task: "title"; max_words?: number | null; sentence_case?: boolean } | { task: "tags" } | { task: "email-to-name" }
 export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
 export type Template = "enhance.system" | "enhance.user" | "create_title.system" | "create_title.user" | "suggest_tags.system" | "suggest_tags.user" | "chat.system" | "chat.user" | "auto_generate_tags.system" | "auto_generate_tags.user" | "postprocess_transcript.system" | "postprocess_transcript.user"
+export type Validation = { rendered: string; missing_variables: string[] }
 
 /** tauri-specta globals **/
 
@@ -9,3 +9,13 @@ pub async fn render<R: tauri::Runtime>(
 ) -> Result<String, String> {
     app.render(name, ctx)
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn validate_template<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    source: String,
+    ctx: serde_json::Map<String, serde_json::Value>,
+) -> Result<hypr_template::Validation, String> {
+    app.validate_template(&source, ctx)
+}
@@ -4,6 +4,11 @@ pub trait TemplatePluginExt<R: tauri::Runtime> {
         name: hypr_template::Template,
         ctx: serde_json::Map<String, serde_json::Value>,
     ) -> Result<String, String>;
+    fn validate_template(
+        &self,
+        source: &str,
+        ctx: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<hypr_template::Validation, String>;
 }
 
 impl<R: tauri::Runtime, T: tauri::Manager<R>> TemplatePluginExt<R> for T {
@@ -17,4 +22,13 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> TemplatePluginExt<R> for T {
             .map(|s| s.trim().to_string())
             .map_err(|e| e.to_string())
     }
+
+    #[tracing::instrument(skip_all)]
+    fn validate_template(
+        &self,
+        source: &str,
+        ctx: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<hypr_template::Validation, String> {
+        hypr_template::validate(source, &ctx).map_err(|e| e.to_string())
+    }
 }